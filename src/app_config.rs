@@ -22,6 +22,20 @@ pub(crate) fn get_or_prompt_for_github_token() -> Result<String, StepError> {
     })
 }
 
+pub(crate) fn get_or_prompt_for_smtp_password() -> Result<String, StepError> {
+    std::env::var("SMTP_PASSWORD")
+        .or_else(|_| load_value_or_prompt("smtp_password", "No SMTP password found, input here"))
+}
+
+pub(crate) fn get_or_prompt_for_sentry_token() -> Result<String, StepError> {
+    std::env::var("SENTRY_AUTH_TOKEN").or_else(|_| {
+        load_value_or_prompt(
+            "sentry_token",
+            "No Sentry token found, generate one with `project:releases` scope from https://sentry.io/settings/account/api/auth-tokens/ and input here",
+        )
+    })
+}
+
 pub(crate) fn load_value_or_prompt(key: &str, prompt: &str) -> Result<String, StepError> {
     let app_dirs = AppDirs::new(Some("knope"), true).expect("Could not open config path");
     let config_path = app_dirs.config_dir.join(key);
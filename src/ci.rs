@@ -0,0 +1,134 @@
+/// A CI service which knope can get details about the current build from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Ci {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Jenkins,
+    CircleCI,
+}
+
+impl Ci {
+    /// Detect the CI service running knope from its environment variables.
+    pub(crate) fn detect(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        if var("GITHUB_RUN_ID").is_some() {
+            Some(Ci::GitHub)
+        } else if var("CI_JOB_URL").is_some() {
+            Some(Ci::GitLab)
+        } else if var("BITBUCKET_BUILD_NUMBER").is_some() {
+            Some(Ci::Bitbucket)
+        } else if var("JENKINS_URL").is_some() {
+            Some(Ci::Jenkins)
+        } else if var("CIRCLECI").is_some() {
+            Some(Ci::CircleCI)
+        } else {
+            None
+        }
+    }
+
+    /// The branch being built. CI services often check out a detached HEAD, so this is the only
+    /// way to know which branch it came from.
+    pub(crate) fn branch(self, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+        match self {
+            // `GITHUB_HEAD_REF` is the source branch of a pull request, and isn't set otherwise.
+            Ci::GitHub => var("GITHUB_HEAD_REF")
+                .filter(|branch| !branch.is_empty())
+                .or_else(|| var("GITHUB_REF_NAME")),
+            Ci::GitLab => {
+                var("CI_MERGE_REQUEST_SOURCE_BRANCH_NAME").or_else(|| var("CI_COMMIT_REF_NAME"))
+            }
+            Ci::Bitbucket => var("BITBUCKET_BRANCH"),
+            // The Git plugin sets `GIT_BRANCH` to something like `origin/main`.
+            Ci::Jenkins => var("BRANCH_NAME").or_else(|| {
+                var("GIT_BRANCH").map(|branch| {
+                    branch
+                        .strip_prefix("origin/")
+                        .map(String::from)
+                        .unwrap_or(branch)
+                })
+            }),
+            Ci::CircleCI => var("CIRCLE_BRANCH"),
+        }
+    }
+
+    /// The URL of the current build.
+    pub(crate) fn build_url(self, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+        match self {
+            Ci::GitHub => Some(format!(
+                "{server}/{repository}/actions/runs/{run_id}",
+                server = var("GITHUB_SERVER_URL")?,
+                repository = var("GITHUB_REPOSITORY")?,
+                run_id = var("GITHUB_RUN_ID")?
+            )),
+            Ci::GitLab => var("CI_JOB_URL"),
+            Ci::Bitbucket => Some(format!(
+                "https://bitbucket.org/{repository}/addon/pipelines/home#!/results/{build}",
+                repository = var("BITBUCKET_REPO_FULL_NAME")?,
+                build = var("BITBUCKET_BUILD_NUMBER")?
+            )),
+            Ci::Jenkins => var("BUILD_URL"),
+            Ci::CircleCI => var("CIRCLE_BUILD_URL"),
+        }
+    }
+}
+
+/// Whether knope can prompt for input. It never can in CI, where nobody is around to answer, so
+/// prompts fail right away instead of waiting forever.
+pub(crate) fn is_interactive(var: impl Fn(&str) -> Option<String>) -> bool {
+    Ci::detect(&var).is_none() && var("CI").map_or(true, |ci| ci == "false")
+}
+
+#[cfg(test)]
+mod test_ci {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn vars(pairs: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<&str, &str> = pairs.iter().copied().collect();
+        move |key| vars.get(key).map(ToString::to_string)
+    }
+
+    #[test]
+    fn bitbucket() {
+        let var = vars(&[
+            ("BITBUCKET_BUILD_NUMBER", "42"),
+            ("BITBUCKET_BRANCH", "123-some-feature"),
+            ("BITBUCKET_REPO_FULL_NAME", "knope-dev/knope"),
+        ]);
+        let ci = Ci::detect(&var);
+        assert_eq!(ci, Some(Ci::Bitbucket));
+        let ci = ci.unwrap();
+        assert_eq!(ci.branch(&var), Some(String::from("123-some-feature")));
+        assert_eq!(
+            ci.build_url(&var),
+            Some(String::from(
+                "https://bitbucket.org/knope-dev/knope/addon/pipelines/home#!/results/42"
+            ))
+        );
+    }
+
+    #[test]
+    fn jenkins_git_branch() {
+        let var = vars(&[
+            ("JENKINS_URL", "https://jenkins.example.com/"),
+            ("GIT_BRANCH", "origin/PROJ-12-fix"),
+        ]);
+        assert_eq!(Ci::detect(&var), Some(Ci::Jenkins));
+        assert_eq!(Ci::Jenkins.branch(&var), Some(String::from("PROJ-12-fix")));
+    }
+
+    #[test]
+    fn circleci() {
+        let var = vars(&[("CIRCLECI", "true"), ("CIRCLE_BRANCH", "main")]);
+        assert_eq!(Ci::detect(&var), Some(Ci::CircleCI));
+        assert_eq!(Ci::CircleCI.branch(&var), Some(String::from("main")));
+    }
+
+    #[test]
+    fn interactive() {
+        assert!(is_interactive(vars(&[])));
+        assert!(!is_interactive(vars(&[("CI", "true")])));
+        assert!(!is_interactive(vars(&[("JENKINS_URL", "https://jenkins")])));
+    }
+}
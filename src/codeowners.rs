@@ -0,0 +1,253 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use git2::Repository;
+use itertools::Itertools;
+
+use crate::step::StepError;
+
+/// The places GitHub looks for a CODEOWNERS file, in the order it checks them.
+const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// The reviewers to request for a pull request, according to CODEOWNERS.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct Owners {
+    /// GitHub users (without the `@`).
+    pub(crate) users: Vec<String>,
+    /// Slugs of teams in the `owner` organization.
+    pub(crate) teams: Vec<String>,
+}
+
+/// The owners (from the CODEOWNERS file of the current repo) of every file changed between `base`
+/// and HEAD. Teams from organizations other than `owner` can't review, so they're left out, as are
+/// owners given by email. With no CODEOWNERS file, there are no owners.
+pub(crate) fn owners_of_changes(base: &str, owner: &str) -> Result<Owners, StepError> {
+    let content = match LOCATIONS
+        .iter()
+        .find_map(|location| read_to_string(location).ok())
+    {
+        Some(content) => content,
+        None => return Ok(Owners::default()),
+    };
+    let rules = parse(&content);
+    let owners = changed_paths(base)?
+        .iter()
+        .filter_map(|path| owners_of(&rules, path))
+        .flatten()
+        .unique()
+        .collect_vec();
+    Ok(split_owners(&owners, owner))
+}
+
+/// The paths of every file changed on HEAD since it diverged from `base` (like
+/// `git diff --name-only base...HEAD`), preferring the remote-tracking branch of `base`.
+fn changed_paths(base: &str) -> Result<Vec<String>, StepError> {
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let base_commit = repo
+        .revparse_single(&format!("origin/{base}"))
+        .or_else(|_| repo.revparse_single(base))
+        .map_err(|_| StepError::BranchNotFound(base.to_string()))?
+        .peel_to_commit()?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let merge_base = repo.merge_base(base_commit.id(), head_commit.id())?;
+    let diff = repo.diff_tree_to_tree(
+        Some(&repo.find_commit(merge_base)?.tree()?),
+        Some(&head_commit.tree()?),
+        None,
+    )?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| {
+            delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .and_then(Path::to_str)
+                .map(String::from)
+        })
+        .collect())
+}
+
+/// A line of a CODEOWNERS file: a pattern and who owns the files it matches.
+#[derive(Debug, Eq, PartialEq)]
+struct Rule<'a> {
+    pattern: &'a str,
+    owners: Vec<&'a str>,
+}
+
+fn parse(content: &str) -> Vec<Rule<'_>> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next()?;
+            Some(Rule {
+                pattern,
+                owners: parts.collect(),
+            })
+        })
+        .collect()
+}
+
+/// The owners of `path` from the last rule which matches it, like GitHub does. A matching rule
+/// with no owners means that `path` has no owners.
+fn owners_of<'a>(rules: &[Rule<'a>], path: &str) -> Option<Vec<&'a str>> {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| matches(rule.pattern, path))
+        .map(|rule| rule.owners.clone())
+}
+
+/// Whether the CODEOWNERS `pattern` (which follows the rules of `.gitignore`) matches `path`.
+///
+/// A pattern containing a `/` (other than at the end) is relative to the root of the repo, any
+/// other pattern matches at any depth. A pattern matching a directory matches everything in it,
+/// unless it ends with a wildcard (so `docs/*` doesn't match `docs/src/index.md`).
+fn matches(pattern: &str, path: &str) -> bool {
+    let directory_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern_segments = pattern.trim_start_matches('/').split('/').collect_vec();
+    let contents = !pattern_segments
+        .last()
+        .map_or(false, |segment| segment.contains('*'));
+    let path_segments = path.split('/').collect_vec();
+    let starts = if anchored {
+        0..1
+    } else {
+        0..path_segments.len()
+    };
+    starts.into_iter().any(|start| {
+        let rest = &path_segments[start..];
+        if contents {
+            // Try the pattern against every directory containing the path too.
+            let ends = if directory_only {
+                1..rest.len()
+            } else {
+                1..rest.len() + 1
+            };
+            ends.into_iter()
+                .any(|end| matches_segments(&pattern_segments, &rest[..end]))
+        } else {
+            !directory_only && matches_segments(&pattern_segments, rest)
+        }
+    })
+}
+
+/// Whether every segment of `pattern` matches `path`, where a `**` segment matches any number of
+/// segments.
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            matches_segments(&pattern[1..], path)
+                || (!path.is_empty() && matches_segments(pattern, &path[1..]))
+        }
+        (Some(pattern_segment), Some(path_segment)) => {
+            matches_segment(pattern_segment.as_bytes(), path_segment.as_bytes())
+                && matches_segments(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Whether a single segment of a pattern (with `*` for any characters and `?` for one) matches
+/// a single segment of a path.
+fn matches_segment(pattern: &[u8], segment: &[u8]) -> bool {
+    match (pattern.first(), segment.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches_segment(&pattern[1..], segment)
+                || (!segment.is_empty() && matches_segment(pattern, &segment[1..]))
+        }
+        (Some(b'?'), Some(_)) => matches_segment(&pattern[1..], &segment[1..]),
+        (Some(expected), Some(actual)) if expected == actual => {
+            matches_segment(&pattern[1..], &segment[1..])
+        }
+        _ => false,
+    }
+}
+
+/// Sort `owners` into users and teams of the `owner` organization.
+fn split_owners(owners: &[&str], owner: &str) -> Owners {
+    let mut split = Owners::default();
+    for name in owners.iter().filter_map(|name| name.strip_prefix('@')) {
+        match name.split_once('/') {
+            Some((organization, team)) if organization.eq_ignore_ascii_case(owner) => {
+                split.teams.push(team.to_string());
+            }
+            Some(_) => {}
+            None => split.users.push(name.to_string()),
+        }
+    }
+    split
+}
+
+#[cfg(test)]
+mod test_codeowners {
+    use super::*;
+
+    const CODEOWNERS: &str = "\
+# Everything else
+*       @knope-dev/maintainers
+
+*.md    @docs-writer # Documentation
+/src/   @knope-dev/core @dtolnay
+docs/   @someone-else
+Cargo.lock
+";
+
+    #[test]
+    fn last_match_wins() {
+        let rules = parse(CODEOWNERS);
+        assert_eq!(owners_of(&rules, "README.md"), Some(vec!["@docs-writer"]));
+        assert_eq!(
+            owners_of(&rules, "src/lib.rs"),
+            Some(vec!["@knope-dev/core", "@dtolnay"])
+        );
+        assert_eq!(
+            owners_of(&rules, "build.rs"),
+            Some(vec!["@knope-dev/maintainers"])
+        );
+        assert_eq!(owners_of(&rules, "Cargo.lock"), Some(vec![]));
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(matches("*", "src/lib.rs"));
+        assert!(matches("*.rs", "src/lib.rs"));
+        assert!(matches("/src/", "src/releases/mod.rs"));
+        assert!(!matches("/src/", "tests/src/main.rs"));
+        assert!(matches("src/", "tests/src/main.rs"));
+        assert!(!matches("src/", "src"));
+        assert!(matches("docs/*", "docs/index.md"));
+        assert!(!matches("docs/*", "docs/src/index.md"));
+        assert!(matches("apps/", "web/apps/index.js"));
+        assert!(matches("docs/**/*.md", "docs/src/config/index.md"));
+        assert!(matches("**/logs", "build/logs/today.log"));
+        assert!(matches("README.?d", "README.md"));
+        assert!(!matches("/README.md", "docs/README.md"));
+    }
+
+    #[test]
+    fn split() {
+        let owners = split_owners(
+            &[
+                "@knope-dev/core",
+                "@dtolnay",
+                "@other-org/team",
+                "someone@example.com",
+            ],
+            "knope-dev",
+        );
+        assert_eq!(
+            owners,
+            Owners {
+                users: vec![String::from("dtolnay")],
+                teams: vec![String::from("core")],
+            }
+        );
+    }
+}
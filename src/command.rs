@@ -1,101 +1,533 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
-use execute::shell;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::git::branch_name_from_issue;
-use crate::releases::get_version;
+use crate::releases::{get_version, tag_name, Package};
 use crate::state::Release;
 use crate::step::StepError;
-use crate::{state, RunType, State};
+use crate::{state, template, RunType, State};
 
 /// Describes a value that you can replace an arbitrary string with when running a command.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) enum Variable {
     /// Uses the first supported version found in your project.
     Version,
     /// The generated branch name for the selected issue. Note that this means the workflow must
     /// already be in [`State::IssueSelected`] when this variable is used.
     IssueBranch,
+    /// Every selected issue (see the `multiple` option of [`crate::step::Step::SelectJiraIssue`]),
+    /// one per line like `PROJ-123: Summary`.
+    Issues,
+    /// The full changelog entry (for every package) created by [`crate::step::Step::PrepareRelease`].
+    ChangelogEntry,
+    /// A Markdown table listing every package that has a new version in this workflow.
+    VersionTable,
+    /// The name of a package as defined in `[packages.<name>]`. A [`crate::step::Step::Command`]
+    /// using this will run once for each package.
+    PackageName,
+    /// The directory containing a package's versioned files. A [`crate::step::Step::Command`]
+    /// using this will run once for each package.
+    PackagePath,
+    /// The version that a package had before it was bumped in this workflow.
+    PreviousVersion,
+    /// How much the version was bumped in this workflow compared to the last stable version:
+    /// `major`, `minor`, `patch`, `pre` for a pre-release, or `build` when only the build metadata
+    /// changed.
+    VersionBumpLevel,
+    /// The Git tag of the version that a package had before it was bumped in this workflow.
+    PreviousTag,
+}
+
+/// A shell that a [`crate::step::Step::Command`] can run in, instead of the default: `$SHELL` (or
+/// `sh`) on Unix and `cmd.exe` on Windows.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[allow(clippy::enum_variant_names)] // PowerShell is the name of the shell
+pub(crate) enum Shell {
+    Sh,
+    Bash,
+    Cmd,
+    /// Windows PowerShell (`powershell`).
+    PowerShell,
+    /// PowerShell 7+ (`pwsh`), which also runs on macOS and Linux.
+    Pwsh,
+}
+
+impl Shell {
+    fn command(self, command: &str) -> std::process::Command {
+        let (program, args): (&str, &[&str]) = match self {
+            Shell::Sh => ("sh", &["-c"]),
+            Shell::Bash => ("bash", &["-c"]),
+            Shell::Cmd => ("cmd.exe", &["/C"]),
+            Shell::PowerShell => ("powershell", &["-NoProfile", "-NonInteractive", "-Command"]),
+            Shell::Pwsh => ("pwsh", &["-NoProfile", "-NonInteractive", "-Command"]),
+        };
+        let mut shell_command = std::process::Command::new(program);
+        shell_command.args(args).arg(command);
+        shell_command
+    }
+}
+
+/// A variant of a [`crate::step::Step::Command`] which runs instead on Windows.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct WindowsCommand {
+    /// The command to run, with the same variables as the main command.
+    pub(crate) command: String,
+    /// The shell to run `command` in, if not `cmd.exe`.
+    pub(crate) shell: Option<Shell>,
+}
+
+impl Variable {
+    /// Whether this variable has a different value for each package.
+    fn is_per_package(&self) -> bool {
+        matches!(self, Variable::PackageName | Variable::PackagePath)
+    }
 }
 
 /// Run the command string `command` in the current shell after replacing the keys of `variables`
 /// with the values that the [`Variable`]s represent.
+///
+/// If any of the variables are per-package, the command is run once for each package instead.
+///
+/// The command runs in `shell`, or the default shell for this platform. If it is still running after
+/// `timeout` seconds, it is killed (along with any processes it started) and this fails.
 pub(crate) fn run_command(
     mut run_type: RunType,
-    mut command: String,
+    command: &str,
     variables: Option<HashMap<String, Variable>>,
+    shell: Option<Shell>,
+    timeout: Option<u64>,
 ) -> Result<RunType, StepError> {
-    let (state, dry_run_stdout) = match &mut run_type {
-        RunType::DryRun { state, stdout } => (state, Some(stdout)),
-        RunType::Real(state) => (state, None),
+    let (state, mut dry_run_stdout) = match &mut run_type {
+        RunType::DryRun { state, stdout } => (&*state, Some(stdout)),
+        RunType::Real(state) => (&*state, None),
+    };
+    let commands = match variables {
+        Some(variables) if variables.values().any(Variable::is_per_package) => {
+            if state.packages.is_empty() {
+                return Err(StepError::no_defined_packages_with_help());
+            }
+            state
+                .packages
+                .iter()
+                .map(|package| {
+                    replace_variables_for_package(command, variables.clone(), state, Some(package))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        Some(variables) => vec![replace_variables(command, variables, state)?],
+        None => vec![template::render_expressions(command, &HashMap::new())?],
     };
-    if let Some(variables) = variables {
-        command = replace_variables(command, variables, state)?;
+    for command in commands {
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(stdout, "Would run {command}")?;
+            continue;
+        }
+        run_shell(&command, shell, timeout.map(Duration::from_secs))?;
+    }
+    Ok(run_type)
+}
+
+/// How often to check whether a running command has finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a command has to exit after being asked to stop before it is killed.
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Run `command` in `shell` (or the current shell), with its output going straight to the terminal.
+///
+/// If knope is interrupted (e.g., with CTRL-C) while the command is running, the signal is
+/// forwarded to the command and knope waits for it to stop.
+///
+/// With a `timeout`, the command runs in its own process group, so if it takes longer than that,
+/// every process it started is stopped too instead of being left running. That group is in the
+/// background, so a command with a `timeout` can't read from the terminal.
+fn run_shell(
+    command: &str,
+    shell: Option<Shell>,
+    timeout: Option<Duration>,
+) -> Result<(), StepError> {
+    let mut command = shell.map_or_else(|| execute::shell(command), |shell| shell.command(command));
+    command
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    let isolated = timeout.is_some();
+    if isolated {
+        process_group::isolate(&mut command);
+    }
+    let start = Instant::now();
+    let mut child = command.spawn()?;
+    let _interrupt_guard = process_group::interrupt_guard();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(StepError::CommandError(status))
+            };
+        }
+        if process_group::interrupted() {
+            process_group::interrupt(&child, isolated);
+            stop(&mut child, isolated)?;
+            return Err(StepError::CommandInterrupted);
+        }
+        if let Some(timeout) = timeout {
+            if start.elapsed() >= timeout {
+                process_group::terminate(&child, isolated);
+                stop(&mut child, isolated)?;
+                return Err(StepError::CommandTimeout(timeout.as_secs()));
+            }
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+/// Run `command` in `shell` (or the current shell) and return what it printed, for commands whose
+/// output knope uses. Only stdout is captured, so any errors or progress still go to the terminal.
+pub(crate) fn command_output(command: &str, shell: Option<Shell>) -> Result<String, StepError> {
+    let mut command = shell.map_or_else(|| execute::shell(command), |shell| shell.command(command));
+    let output = command
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(StepError::CommandError(output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Wait for `child`, which has been asked to stop, killing it (and everything it started, if it's
+/// `isolated`) if it doesn't within [`GRACE_PERIOD`].
+fn stop(child: &mut Child, isolated: bool) -> Result<(), StepError> {
+    let start = Instant::now();
+    while start.elapsed() < GRACE_PERIOD {
+        if child.try_wait()?.is_some() {
+            return Ok(());
+        }
+        sleep(POLL_INTERVAL);
+    }
+    process_group::kill(child, isolated);
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+mod process_group {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use signal_hook::consts::TERM_SIGNALS;
+    use signal_hook::flag;
+
+    /// Put the process that `command` spawns in a new process group, whose ID is its PID.
+    pub(super) fn isolate(command: &mut Command) {
+        command.process_group(0);
+    }
+
+    pub(super) fn interrupt(child: &Child, isolated: bool) {
+        signal(child, isolated, "-INT");
+    }
+
+    pub(super) fn terminate(child: &Child, isolated: bool) {
+        signal(child, isolated, "-TERM");
+    }
+
+    pub(super) fn kill(child: &Child, isolated: bool) {
+        signal(child, isolated, "-KILL");
+    }
+
+    /// Send `signal` to `child` with `kill`. If it's `isolated`, the signal goes to its whole
+    /// process group instead, so anything it started gets it too.
+    fn signal(child: &Child, isolated: bool, signal: &str) {
+        let target = if isolated {
+            format!("-{}", child.id())
+        } else {
+            child.id().to_string()
+        };
+        // The process may already be gone, which is fine.
+        let _status = Command::new("kill").args([signal, "--", &target]).status();
+    }
+
+    /// Whether knope was asked to stop (e.g., with CTRL-C) while the current command was running.
+    pub(super) fn interrupted() -> bool {
+        SIGNALS.with(|signals| {
+            signals
+                .as_ref()
+                .map_or(false, |signals| signals.interrupted.load(Ordering::SeqCst))
+        })
+    }
+
+    /// Catch termination signals until the returned guard is dropped, instead of letting them stop
+    /// knope right away. Commands with a timeout run in their own process group, so they don't get
+    /// the signal from the terminal—knope has to forward it and wait for them to stop first.
+    pub(super) fn interrupt_guard() -> InterruptGuard {
+        SIGNALS.with(|signals| {
+            if let Some(signals) = signals {
+                signals.interrupted.store(false, Ordering::SeqCst);
+                signals.idle.store(false, Ordering::SeqCst);
+            }
+        });
+        InterruptGuard
+    }
+
+    pub(super) struct InterruptGuard;
+
+    impl Drop for InterruptGuard {
+        fn drop(&mut self) {
+            SIGNALS.with(|signals| {
+                if let Some(signals) = signals {
+                    signals.idle.store(true, Ordering::SeqCst);
+                }
+            });
+        }
     }
-    if let Some(stdout) = dry_run_stdout {
-        writeln!(stdout, "Would run {}", command)?;
-        return Ok(run_type);
+
+    struct Signals {
+        /// Set when a termination signal arrives.
+        interrupted: Arc<AtomicBool>,
+        /// While this is set, termination signals have their default effect (stopping knope).
+        idle: Arc<AtomicBool>,
+    }
+
+    impl Signals {
+        fn register() -> std::io::Result<Self> {
+            let signals = Self {
+                interrupted: Arc::new(AtomicBool::new(false)),
+                idle: Arc::new(AtomicBool::new(true)),
+            };
+            for signal in TERM_SIGNALS {
+                flag::register_conditional_default(*signal, Arc::clone(&signals.idle))?;
+                flag::register(*signal, Arc::clone(&signals.interrupted))?;
+            }
+            Ok(signals)
+        }
     }
-    let status = shell(command).status()?;
-    if status.success() {
-        return Ok(run_type);
+
+    thread_local! {
+        /// Signal handlers can only be registered once without leaking them, so every command run
+        /// from this thread shares them.
+        static SIGNALS: Option<Signals> = Signals::register().ok();
     }
-    Err(StepError::CommandError(status))
+}
+
+/// Without process groups, only the command itself can be stopped.
+#[cfg(not(unix))]
+mod process_group {
+    use std::process::{Child, Command};
+
+    pub(super) fn isolate(_command: &mut Command) {}
+
+    pub(super) fn interrupt(_child: &Child, _isolated: bool) {}
+
+    pub(super) fn terminate(_child: &Child, _isolated: bool) {}
+
+    pub(super) fn kill(child: &mut Child, _isolated: bool) {
+        // The child may already have exited, which is fine.
+        let _result = child.kill();
+    }
+
+    pub(super) fn interrupted() -> bool {
+        false
+    }
+
+    pub(super) fn interrupt_guard() {}
 }
 
 /// Replace declared variables in the command string and return command.
-fn replace_variables(
-    mut command: String,
+pub(crate) fn replace_variables(
+    command: &str,
+    variables: HashMap<String, Variable>,
+    state: &State,
+) -> Result<String, StepError> {
+    replace_variables_for_package(command, variables, state, None)
+}
+
+/// Replace declared variables in the command string, using `package` for any package-specific
+/// variables. If there is no `package`, the only defined package is used.
+///
+/// Any `{{ expression }}` in the command is evaluated as well, see [`template::render_expressions`].
+pub(crate) fn replace_variables_for_package(
+    command: &str,
     variables: HashMap<String, Variable>,
     state: &State,
+    package: Option<&Package>,
 ) -> Result<String, StepError> {
+    template::render_expressions(command, &variable_values(variables, state, package)?)
+}
+
+/// The value of each of `variables`, keyed by the text it replaces.
+fn variable_values(
+    variables: HashMap<String, Variable>,
+    state: &State,
+    package: Option<&Package>,
+) -> Result<HashMap<String, String>, StepError> {
+    let mut values = HashMap::with_capacity(variables.len());
     for (var_name, var_type) in variables {
-        match var_type {
+        let value = match var_type {
             Variable::Version => {
-                let package = if state.packages.len() == 1 {
-                    &state.packages[0]
-                } else if state.packages.is_empty() {
-                    return Err(StepError::no_defined_packages_with_help());
-                } else {
-                    return Err(StepError::TooManyPackages);
-                };
-                let release = if state.releases.is_empty() {
-                    command = command.replace(
-                        &var_name,
-                        &get_version(package.clone())?.latest_version().to_string(),
-                    );
-                    continue;
-                } else {
-                    &state.releases[0]
-                };
-                match release {
-                    Release::Prepared(release) => {
-                        command = command.replace(&var_name, &release.version.to_string());
-                    }
-                    Release::Bumped {
-                        version,
-                        package_name: _package_name,
-                    } => {
-                        command = command.replace(&var_name, &version.to_string());
+                let package = single_package(state, package)?;
+                match package_release(state, package) {
+                    Some(Release::Prepared(release)) => release.version.to_string(),
+                    Some(Release::Bumped { version, .. }) => version.to_string(),
+                    None => get_version(package.clone())?.latest_version().to_string(),
+                }
+            }
+            Variable::PreviousVersion => {
+                let package = single_package(state, package)?;
+                match package_release(state, package) {
+                    Some(Release::Prepared(release)) => release.previous_version.to_string(),
+                    Some(Release::Bumped {
+                        previous_version, ..
+                    }) => previous_version.to_string(),
+                    None => return Err(StepError::ReleaseNotPrepared),
+                }
+            }
+            Variable::VersionBumpLevel => {
+                let package = single_package(state, package)?;
+                match package_release(state, package) {
+                    Some(Release::Prepared(release)) => release.bump_level.to_string(),
+                    Some(Release::Bumped { bump_level, .. }) => bump_level.to_string(),
+                    None => return Err(StepError::ReleaseNotPrepared),
+                }
+            }
+            Variable::PreviousTag => {
+                let package = single_package(state, package)?;
+                match package_release(state, package) {
+                    Some(Release::Prepared(release)) => {
+                        tag_name(&release.previous_version, &release.tag_prefix)
                     }
+                    Some(Release::Bumped {
+                        previous_version, ..
+                    }) => tag_name(previous_version, &package.tag_prefix()),
+                    None => return Err(StepError::ReleaseNotPrepared),
                 }
             }
+            Variable::PackageName => {
+                let package = single_package(state, package)?;
+                package.name.clone().ok_or(StepError::UnnamedPackage)?
+            }
+            Variable::PackagePath => {
+                let package = single_package(state, package)?;
+                package_path(package).to_string_lossy().to_string()
+            }
             Variable::IssueBranch => match &state.issue {
                 state::Issue::Initial => return Err(StepError::NoIssueSelected),
-                state::Issue::Selected(issue) => {
-                    command = command.replace(&var_name, &branch_name_from_issue(issue));
-                }
+                state::Issue::Selected(issue) => branch_name_from_issue(issue),
+                state::Issue::SelectedMany(_) => return Err(StepError::MultipleIssuesSelected),
             },
-        }
+            Variable::Issues => match &state.issue {
+                state::Issue::Initial => return Err(StepError::NoIssueSelected),
+                state::Issue::Selected(issue) => issue.to_string(),
+                state::Issue::SelectedMany(issues) => issues.iter().join("\n"),
+            },
+            Variable::ChangelogEntry => changelog_entry(&state.releases)?,
+            Variable::VersionTable => version_table(&state.releases)?,
+        };
+        values.insert(var_name, value);
+    }
+    Ok(values)
+}
+
+/// Get the package that package-specific variables refer to.
+fn single_package<'a>(
+    state: &'a State,
+    package: Option<&'a Package>,
+) -> Result<&'a Package, StepError> {
+    if let Some(package) = package {
+        return Ok(package);
+    }
+    match state.packages.as_slice() {
+        [package] => Ok(package),
+        [] => Err(StepError::no_defined_packages_with_help()),
+        _ => Err(StepError::TooManyPackages),
+    }
+}
+
+/// The release of `package` created earlier in this workflow, if any.
+fn package_release<'a>(state: &'a State, package: &Package) -> Option<&'a Release> {
+    state.releases.iter().find(|release| {
+        let package_name = match release {
+            Release::Prepared(release) => &release.package_name,
+            Release::Bumped { package_name, .. } => package_name,
+        };
+        *package_name == package.name
+    })
+}
+
+/// The directory containing a package, based on its first versioned file (or its changelog).
+fn package_path(package: &Package) -> PathBuf {
+    package
+        .versioned_files
+        .first()
+        .map(|versioned_file| versioned_file.path.as_path())
+        .or_else(|| {
+            package
+                .changelog
+                .as_ref()
+                .map(|changelog| changelog.path.as_path())
+        })
+        .and_then(Path::parent)
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+}
+
+/// Combine the changelog entries of every prepared release, labeling each with its package name
+/// when there is one.
+fn changelog_entry(releases: &[Release]) -> Result<String, StepError> {
+    if releases.is_empty() {
+        return Err(StepError::ReleaseNotPrepared);
+    }
+    let entries = releases
+        .iter()
+        .map(|release| match release {
+            Release::Prepared(release) => Ok(match &release.package_name {
+                Some(name) => format!("# {name}\n\n{}", release.changelog),
+                None => release.changelog.clone(),
+            }),
+            Release::Bumped { .. } => Err(StepError::ReleaseNotPrepared),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries.join("\n"))
+}
+
+/// A Markdown table of every package and the version it's being released as.
+fn version_table(releases: &[Release]) -> Result<String, StepError> {
+    if releases.is_empty() {
+        return Err(StepError::ReleaseNotPrepared);
     }
-    Ok(command)
+    let rows = releases.iter().map(|release| {
+        let (package_name, version) = match release {
+            Release::Prepared(release) => (&release.package_name, &release.version),
+            Release::Bumped {
+                version,
+                package_name,
+                ..
+            } => (package_name, version),
+        };
+        format!(
+            "| {} | {version} |\n",
+            package_name.as_deref().unwrap_or("package")
+        )
+    });
+    Ok(format!(
+        "| Package | Version |\n| --- | --- |\n{}",
+        rows.collect::<String>()
+    ))
 }
 
 #[cfg(test)]
 mod test_run_command {
     use tempfile::NamedTempFile;
 
+    use crate::config::Versioning;
     use crate::State;
 
     use super::*;
@@ -105,8 +537,17 @@ mod test_run_command {
         let file = NamedTempFile::new().unwrap();
         let command = format!("cat {}", file.path().to_str().unwrap());
         let result = run_command(
-            RunType::Real(State::new(None, None, Vec::new())),
-            command.clone(),
+            RunType::Real(State::new(
+                None,
+                None,
+                None,
+                None,
+                Versioning::default(),
+                Vec::new(),
+            )),
+            &command,
+            None,
+            None,
             None,
         );
 
@@ -115,8 +556,17 @@ mod test_run_command {
         file.close().unwrap();
 
         let result = run_command(
-            RunType::Real(State::new(None, None, Vec::new())),
-            command,
+            RunType::Real(State::new(
+                None,
+                None,
+                None,
+                None,
+                Versioning::default(),
+                Vec::new(),
+            )),
+            &command,
+            None,
+            None,
             None,
         );
         assert!(result.is_err());
@@ -125,8 +575,9 @@ mod test_run_command {
 
 #[cfg(test)]
 mod test_replace_variables {
+    use crate::config::Versioning;
     use crate::issues::Issue;
-    use crate::releases::{Package, Release};
+    use crate::releases::{BumpLevel, CommitRange, Package, Release};
     use crate::state;
     use semver::Version;
     use std::path::PathBuf;
@@ -137,8 +588,7 @@ mod test_replace_variables {
         vec![Package {
             versioned_files: vec![PathBuf::from("Cargo.toml").try_into().unwrap()],
             changelog: Some(PathBuf::from("CHANGELOG.md").try_into().unwrap()),
-            name: None,
-            scopes: None,
+            ..Package::default()
         }]
     }
 
@@ -157,12 +607,17 @@ mod test_replace_variables {
             jira_config: None,
             github: state::GitHub::New,
             github_config: None,
+            smtp_config: None,
+            sentry_config: None,
+            versioning: Versioning::default(),
             issue: state::Issue::Selected(issue),
+            refresh_issues: false,
+            protected_branches: Vec::new(),
             releases: Vec::new(),
             packages: packages(),
         };
 
-        let command = replace_variables(command, variables, &state).unwrap();
+        let command = replace_variables(&command, variables, &state).unwrap();
 
         assert_eq!(
             command,
@@ -181,9 +636,9 @@ mod test_replace_variables {
         let command = "blah $$ other blah".to_string();
         let mut variables = HashMap::new();
         variables.insert("$$".to_string(), Variable::Version);
-        let state = State::new(None, None, packages());
+        let state = State::new(None, None, None, None, Versioning::default(), packages());
 
-        let command = replace_variables(command, variables, &state).unwrap();
+        let command = replace_variables(&command, variables, &state).unwrap();
 
         assert_eq!(
             command,
@@ -201,15 +656,19 @@ mod test_replace_variables {
         let command = "blah $$ other blah".to_string();
         let mut variables = HashMap::new();
         variables.insert("$$".to_string(), Variable::Version);
-        let mut state = State::new(None, None, packages());
+        let mut state = State::new(None, None, None, None, Versioning::default(), packages());
         let version = Version::new(1, 2, 3);
         state.releases.push(state::Release::Prepared(Release {
             version: version.clone(),
+            previous_version: Version::new(1, 2, 2),
+            bump_level: BumpLevel::Patch,
             changelog: "".to_string(),
             package_name: None,
+            tag_prefix: String::from("v"),
+            commits: CommitRange::default(),
         }));
 
-        let command = replace_variables(command, variables, &state).unwrap();
+        let command = replace_variables(&command, variables, &state).unwrap();
 
         assert_eq!(command, format!("blah {} other blah", version,));
     }
@@ -228,13 +687,193 @@ mod test_replace_variables {
             jira_config: None,
             github: state::GitHub::New,
             github_config: None,
+            smtp_config: None,
+            sentry_config: None,
+            versioning: Versioning::default(),
             issue: state::Issue::Selected(issue),
+            refresh_issues: false,
+            protected_branches: Vec::new(),
             releases: Vec::new(),
             packages: Vec::new(),
         };
 
-        let command = replace_variables(command, variables, &state).unwrap();
+        let command = replace_variables(&command, variables, &state).unwrap();
 
         assert_eq!(command, format!("blah {} other blah", expected_branch_name));
     }
+
+    #[test]
+    fn replace_issues() {
+        let command = "echo \"$issues\"".to_string();
+        let mut variables = HashMap::new();
+        variables.insert("$issues".to_string(), Variable::Issues);
+        let mut state = State::new(None, None, None, None, Versioning::default(), Vec::new());
+        state.issue = state::Issue::SelectedMany(vec![
+            Issue {
+                key: "PROJ-1".to_string(),
+                summary: "First".to_string(),
+            },
+            Issue {
+                key: "PROJ-2".to_string(),
+                summary: "Second".to_string(),
+            },
+        ]);
+
+        let command = replace_variables(&command, variables, &state).unwrap();
+
+        assert_eq!(command, "echo \"PROJ-1: First\nPROJ-2: Second\"");
+        let mut variables = HashMap::new();
+        variables.insert("$$".to_string(), Variable::IssueBranch);
+        assert!(matches!(
+            replace_variables("$$", variables, &state),
+            Err(StepError::MultipleIssuesSelected)
+        ));
+    }
+
+    #[test]
+    fn replace_changelog_entry_and_version_table() {
+        let command = "$table\n$changelog".to_string();
+        let mut variables = HashMap::new();
+        variables.insert("$table".to_string(), Variable::VersionTable);
+        variables.insert("$changelog".to_string(), Variable::ChangelogEntry);
+        let mut state = State::new(None, None, None, None, Versioning::default(), Vec::new());
+        for (name, version) in [
+            ("first", Version::new(1, 2, 3)),
+            ("second", Version::new(2, 0, 0)),
+        ] {
+            state.releases.push(state::Release::Prepared(Release {
+                changelog: format!("## {version}\n"),
+                version,
+                previous_version: Version::new(1, 0, 0),
+                bump_level: BumpLevel::Minor,
+                package_name: Some(name.to_string()),
+                tag_prefix: format!("{name}/v"),
+                commits: CommitRange::default(),
+            }));
+        }
+
+        let command = replace_variables(&command, variables, &state).unwrap();
+
+        assert_eq!(
+            command,
+            "| Package | Version |\n\
+            | --- | --- |\n\
+            | first | 1.2.3 |\n\
+            | second | 2.0.0 |\n\n\
+            # first\n\n## 1.2.3\n\n\
+            # second\n\n## 2.0.0\n"
+        );
+    }
+
+    #[test]
+    fn changelog_entry_requires_prepared_release() {
+        let mut variables = HashMap::new();
+        variables.insert("$changelog".to_string(), Variable::ChangelogEntry);
+        let mut state = State::new(None, None, None, None, Versioning::default(), Vec::new());
+        state.releases.push(state::Release::Bumped {
+            version: Version::new(1, 2, 3),
+            previous_version: Version::new(1, 2, 2),
+            bump_level: BumpLevel::Patch,
+            package_name: None,
+        });
+
+        let result = replace_variables("$changelog", variables, &state);
+
+        assert!(matches!(result, Err(StepError::ReleaseNotPrepared)));
+    }
+
+    #[test]
+    fn replace_package_name_and_path() {
+        let command = "cd $path && publish $name $version".to_string();
+        let mut variables = HashMap::new();
+        variables.insert("$path".to_string(), Variable::PackagePath);
+        variables.insert("$name".to_string(), Variable::PackageName);
+        variables.insert("$version".to_string(), Variable::Version);
+        let package = Package {
+            versioned_files: vec![PathBuf::from("Cargo.toml").try_into().unwrap()],
+            name: Some(String::from("knope")),
+            ..Package::default()
+        };
+        let mut state = State::new(
+            None,
+            None,
+            None,
+            None,
+            Versioning::default(),
+            vec![package.clone(), Package::default()],
+        );
+        state.releases.push(state::Release::Bumped {
+            version: Version::new(1, 2, 3),
+            previous_version: Version::new(1, 2, 2),
+            bump_level: BumpLevel::Patch,
+            package_name: Some(String::from("knope")),
+        });
+
+        let command =
+            replace_variables_for_package(&command, variables, &state, Some(&package)).unwrap();
+
+        assert_eq!(command, "cd . && publish knope 1.2.3");
+    }
+
+    #[test]
+    fn replace_previous_version_and_bump_level() {
+        let command = "from $previous ($level)".to_string();
+        let mut variables = HashMap::new();
+        variables.insert("$previous".to_string(), Variable::PreviousVersion);
+        variables.insert("$level".to_string(), Variable::VersionBumpLevel);
+        let mut state = State::new(None, None, None, None, Versioning::default(), packages());
+        state.releases.push(state::Release::Bumped {
+            version: Version::new(2, 0, 0),
+            previous_version: Version::new(1, 2, 3),
+            bump_level: BumpLevel::Major,
+            package_name: None,
+        });
+
+        let command = replace_variables(&command, variables, &state).unwrap();
+
+        assert_eq!(command, "from 1.2.3 (major)");
+    }
+
+    #[test]
+    fn previous_version_requires_release() {
+        let mut variables = HashMap::new();
+        variables.insert("$previous".to_string(), Variable::PreviousVersion);
+        let state = State::new(None, None, None, None, Versioning::default(), packages());
+
+        let result = replace_variables("$previous", variables, &state);
+
+        assert!(matches!(result, Err(StepError::ReleaseNotPrepared)));
+    }
+
+    #[test]
+    fn package_name_requires_name() {
+        let mut variables = HashMap::new();
+        variables.insert("$name".to_string(), Variable::PackageName);
+        let state = State::new(None, None, None, None, Versioning::default(), packages());
+
+        let result = replace_variables("$name", variables, &state);
+
+        assert!(matches!(result, Err(StepError::UnnamedPackage)));
+    }
+}
+
+#[cfg(test)]
+mod test_package_path {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn nested_versioned_file() {
+        let package = Package {
+            versioned_files: vec![PathBuf::from("tests/detect/Cargo.toml").try_into().unwrap()],
+            ..Package::default()
+        };
+        assert_eq!(package_path(&package), PathBuf::from("tests/detect"));
+    }
+
+    #[test]
+    fn no_files() {
+        assert_eq!(package_path(&Package::default()), PathBuf::from("."));
+    }
 }
@@ -4,10 +4,10 @@ use std::path::PathBuf;
 
 use miette::{IntoDiagnostic, Result, WrapErr};
 use serde::{Deserialize, Serialize};
-use velcro::{hash_map, vec};
+use velcro::hash_map;
 
-use crate::releases::find_packages;
-use crate::step::{PrepareRelease, Step, StepError};
+use crate::releases::{find_packages, tag_only_package};
+use crate::step::{ConfiguredStep, PrepareRelease, ReleaseOptions, Step, StepError};
 use crate::workflow::Workflow;
 use crate::{command, git, releases};
 
@@ -19,16 +19,34 @@ pub(crate) struct Config {
     /// A single package to update via PrepareRelease or BumpVersion. Mutually exclusive with `packages`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     package: Option<Package>,
+    /// A project graph (like Nx's) to read more `packages` from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) project_graph: Option<ProjectGraph>,
+    /// Whether multiple packages are released together with one version or each on their own
+    #[serde(default, skip_serializing_if = "Versioning::is_independent")]
+    pub(crate) versioning: Versioning,
     /// The list of defined workflows that are selectable
     pub(crate) workflows: Vec<Workflow>,
     /// Optional configuration for Jira
     pub(crate) jira: Option<Jira>,
     /// Optional configuration to talk to GitHub
     pub(crate) github: Option<GitHub>,
+    /// Optional configuration for sending emails
+    pub(crate) smtp: Option<Smtp>,
+    /// Optional configuration to register releases with Sentry
+    pub(crate) sentry: Option<Sentry>,
+    /// Branches (which may contain `*` wildcards) that steps shouldn't modify without confirmation
+    pub(crate) protected_branches: Option<Vec<String>>,
+    /// Times when release steps refuse to run, unless `--override-freeze` is passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) release_freezes: Option<Vec<ReleaseFreeze>>,
+    /// Whether to check GitHub for a newer release of knope after running a workflow
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) check_for_updates: bool,
 }
 
 impl Config {
-    const CONFIG_PATH: &'static str = "knope.toml";
+    pub(crate) const CONFIG_PATH: &'static str = "knope.toml";
 
     /// Create a Config from a TOML file.
     ///
@@ -57,14 +75,49 @@ impl Config {
     }
 
     pub(crate) fn packages(&self) -> Result<Vec<releases::Package>, StepError> {
-        match (self.packages.clone(), self.package.clone()) {
+        let mut configured_packages = self.packages.clone();
+        let mut dependencies = BTreeMap::new();
+        if let Some(project_graph) = &self.project_graph {
+            let packages = match configured_packages.take() {
+                None => BTreeMap::new(),
+                Some(Packages::Multiple(packages)) => packages,
+                Some(Packages::Deprecated(_)) => return Err(StepError::ConflictingPackages),
+            };
+            let (packages, graph_dependencies) =
+                packages_from_graph(releases::projects(project_graph)?, packages);
+            configured_packages = (!packages.is_empty()).then_some(Packages::Multiple(packages));
+            dependencies = graph_dependencies;
+        }
+        match (configured_packages, self.package.clone()) {
             (None, None) => Ok(Vec::new()),
             (Some(..), Some(..)) => Err(StepError::ConflictingPackages),
             (None, Some(package)) => Ok(vec![releases::Package::new(package, None)?]),
-            (Some(Packages::Multiple(packages)), None) => packages
-                .into_iter()
-                .map(|(name, package)| releases::Package::new(package, Some(name)))
-                .collect(),
+            (Some(Packages::Multiple(packages)), None) => {
+                let mut packages = packages
+                    .into_iter()
+                    .map(|(name, mut package)| -> Result<_, StepError> {
+                        if self.versioning == Versioning::Fixed && package.tag_prefix.is_none() {
+                            // All packages share one version, so they also share one tag.
+                            package.tag_prefix = Some(releases::default_tag_prefix(None));
+                        }
+                        let dependencies = dependencies.remove(&name);
+                        let mut package = releases::Package::new(package, Some(name))?;
+                        package.dependencies = dependencies;
+                        Ok(package)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                // Packages which share a version (like Cargo workspace members which inherit it)
+                // also share a tag, just like with fixed versioning.
+                for index in 0..packages.len() {
+                    let shares_version = packages.iter().enumerate().any(|(other_index, other)| {
+                        other_index != index && other.shares_version_with(&packages[index])
+                    });
+                    if shares_version && packages[index].tag_prefix.is_none() {
+                        packages[index].tag_prefix = Some(releases::default_tag_prefix(None));
+                    }
+                }
+                Ok(packages)
+            }
             (Some(Packages::Deprecated(packages)), None) => {
                 println!("WARNING: The [[packages]] syntax is deprecated, use [package] instead. Run knope --upgrade to do this automatically.");
                 packages
@@ -90,7 +143,7 @@ impl Config {
             Some(Packages::Deprecated(packages)) => {
                 println!("Upgrading deprecated [[packages]] syntax to [package]");
                 upgraded = true;
-                let [package] = packages;
+                let [package] = *packages;
                 self.package = Some(package);
             }
             None => {}
@@ -105,86 +158,313 @@ impl Config {
     }
 }
 
+/// Add a package for every project in the graph to `packages`, returning them along with the
+/// dependencies of each project.
+///
+/// A package which is already configured with the same name as a project keeps its settings, but
+/// gets the `package.json` and `paths` of the project if it doesn't set them itself. Projects
+/// without a `package.json` are only included if they're configured.
+fn packages_from_graph(
+    projects: Vec<releases::Project>,
+    mut packages: BTreeMap<String, Package>,
+) -> (BTreeMap<String, Package>, BTreeMap<String, Vec<String>>) {
+    let mut dependencies = BTreeMap::new();
+    for project in projects {
+        let configured = packages.contains_key(&project.name);
+        let package = packages.entry(project.name.clone()).or_default();
+        if package.versioned_files.is_empty() {
+            let package_json = project.root.join("package.json");
+            if package_json.exists() {
+                package
+                    .versioned_files
+                    .push(VersionedFile::Path(package_json));
+            }
+        }
+        if package.versioned_files.is_empty() && !configured {
+            packages.remove(&project.name);
+            continue;
+        }
+        if package.paths.is_none() {
+            package.paths = Some(vec![project.root.to_string_lossy().into_owned()]);
+        }
+        dependencies.insert(project.name, project.dependencies);
+    }
+    (packages, dependencies)
+}
+
+/// How the versions of multiple packages relate to each other.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum Versioning {
+    /// Every package is versioned, tagged, and released on its own.
+    #[default]
+    Independent,
+    /// Every package always has the same version, and they are released together under one tag.
+    Fixed,
+}
+
+impl Versioning {
+    #[allow(clippy::trivially_copy_pass_by_ref)] // Required by serde
+    fn is_independent(&self) -> bool {
+        *self == Versioning::Independent
+    }
+}
+
 /// All of the different ways packages can be defined in `knope.toml`.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(untagged)]
 pub(crate) enum Packages {
     Multiple(BTreeMap<String, Package>),
-    Deprecated([Package; 1]),
+    Deprecated(Box<[Package; 1]>),
 }
 
 /// Represents a single package in `knope.toml`.
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub(crate) struct Package {
-    /// The files which define the current version of the package.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub(crate) versioned_files: Vec<PathBuf>,
+    /// The files which define the current version of the package. If there are none, the version
+    /// comes from Git tags alone.
+    #[serde(default)]
+    pub(crate) versioned_files: Vec<VersionedFile>,
     /// The path to the `CHANGELOG.md` file (if any) to be updated when running [`crate::Step::PrepareRelease`].
     pub(crate) changelog: Option<PathBuf>,
     /// Optional scopes that can be used to filter commits when running [`crate::Step::PrepareRelease`].
     pub(crate) scopes: Option<Vec<String>>,
+    /// Optional locales (e.g., `de`) which get their own copy of `changelog` for translators.
+    pub(crate) changelog_locales: Option<Vec<String>>,
+    /// Optional changelog sections beyond features and fixes, keyed by conventional commit type.
+    pub(crate) changelog_sections: Option<Vec<ChangelogSection>>,
+    /// Optional limits on how each note in the changelog is formatted.
+    pub(crate) changelog_format: Option<ChangelogFormat>,
+    /// Optional prefix for this package's Git tags, used instead of `v` or `<name>/v`.
+    pub(crate) tag_prefix: Option<String>,
+    /// Optional paths (which may contain globs) that a commit must change to apply to this package.
+    pub(crate) paths: Option<Vec<String>>,
+    /// Optional paths (which may contain globs) whose changes don't count toward `paths`.
+    pub(crate) exclude_paths: Option<Vec<String>>,
+    /// Whether to leave out commits without a scope, which otherwise apply to every package.
+    pub(crate) ignore_unscoped: Option<bool>,
+    /// The path to a `Cargo.lock` which should be updated whenever the version changes.
+    pub(crate) cargo_lock: Option<PathBuf>,
+    /// What to do with the `+buildnumber` of a `pubspec.yaml` version when bumping it.
+    pub(crate) build_number: Option<BuildNumber>,
+    /// The path to an `AppStream` `metainfo.xml` which gets a `<release>` for every new version.
+    pub(crate) metainfo: Option<PathBuf>,
+    /// A Debian `changelog` which gets an entry for every new version, alongside `changelog`.
+    pub(crate) debian_changelog: Option<DebianChangelog>,
+    /// How the version is written in `versioned_files`, if it isn't `MAJOR.MINOR.PATCH`.
+    pub(crate) version_scheme: Option<VersionScheme>,
+    /// The order that pre-releases are promoted through (e.g., `alpha`, `beta`, `rc`) on their
+    /// way to a stable release.
+    pub(crate) prerelease_channels: Option<Vec<String>>,
+    /// How changes bump the version while the major version is 0.
+    pub(crate) major_version_zero: Option<MajorVersionZero>,
 }
 
-/// Generate a brand new config file for the project in the current directory.
-pub(crate) fn generate() -> Result<()> {
-    let variables = hash_map! {
-        String::from("$version"): command::Variable::Version,
-    };
-    let mut github = None;
-
-    let release_steps = match git::get_first_remote() {
-        Some(remote) if remote.contains("github.com") => {
-            let parts = remote.split('/').collect::<Vec<_>>();
-            let owner = parts[parts.len() - 2];
-            let owner = owner
-                .strip_prefix("git@github.com:")
-                .unwrap_or(owner)
-                .to_string();
-
-            let repo = parts[parts.len() - 1];
-            let repo = repo.strip_suffix(".git").unwrap_or(repo).to_string();
-            github = Some(GitHub { owner, repo });
-            vec![
-                Step::Command {
-                    command: String::from(
-                        "git commit -m \"chore: prepare release $version\" && git push",
-                    ),
-                    variables: Some(variables),
-                },
-                Step::Release,
-            ]
+/// An entry in the `versioned_files` of a [`Package`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum VersionedFile {
+    /// A file in one of the supported formats, like `Cargo.toml`.
+    Path(PathBuf),
+    /// Any other file, where the version is found by a `pattern` like `version: {version}`.
+    Pattern { path: PathBuf, pattern: String },
+    /// Any other TOML, JSON, or YAML file, where the version is at a `key` like
+    /// `tool.mytool.version`.
+    Key { path: PathBuf, key: String },
+    /// A file in one of the supported formats with a different name, where `format` is the usual
+    /// name (like `VERSION`).
+    Format { path: PathBuf, format: String },
+    /// Any other file (like a `README.md`), where every version between comments like
+    /// `<!-- version-start -->` and `<!-- version-end -->` is the version, for `markers = "version"`.
+    Markers { path: PathBuf, markers: String },
+}
+
+impl VersionedFile {
+    pub(crate) fn path(&self) -> &PathBuf {
+        match self {
+            VersionedFile::Path(path)
+            | VersionedFile::Pattern { path, .. }
+            | VersionedFile::Key { path, .. }
+            | VersionedFile::Format { path, .. }
+            | VersionedFile::Markers { path, .. } => path,
         }
-        _ => vec![
-            Step::Command {
-                command: String::from("git commit -m \"chore: prepare release $version\""),
-                variables: Some(variables),
-            },
-            Step::Release,
-            Step::Command {
-                command: String::from("git push && git push --tags"),
-                variables: None,
-            },
-        ],
-    };
+    }
+}
+
+/// What happens to the build number (e.g., `+7` in `1.2.3+7`) of a `pubspec.yaml` version when
+/// the version is bumped.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum BuildNumber {
+    /// Keep the build number as it is.
+    #[default]
+    Preserve,
+    /// Add one to the build number, starting at 1 if there isn't one.
+    Increment,
+}
+
+/// How a [`Package`] is bumped while its major version is 0, when anything may change at any time.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum MajorVersionZero {
+    /// Every bump is one component smaller: breaking changes bump the minor component and
+    /// features bump the patch component.
+    #[default]
+    Shift,
+    /// Only breaking changes are smaller, bumping the minor component like features do.
+    BreakingMinor,
+    /// Bump like any other major version, so a breaking change releases 1.0.0.
+    Standard,
+}
+
+/// Controls the formatting of each note (list item) that is added to a changelog.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct ChangelogFormat {
+    /// Wrap notes onto multiple lines so that no line is longer than this (if possible).
+    pub(crate) wrap: Option<usize>,
+    /// Truncate notes longer than this, linking to the full commit when possible.
+    pub(crate) max_length: Option<usize>,
+    /// Icons (e.g., `✨`) to put before each note, keyed by conventional commit type. The key
+    /// `breaking` is used for breaking changes.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) icons: BTreeMap<String, String>,
+}
+
+/// A changelog section (e.g., `Security`) and the conventional commit types which are listed in it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct ChangelogSection {
+    /// The name of the section, used as the `###` header in the changelog.
+    pub(crate) name: String,
+    /// The conventional commit types (e.g., `security`) which belong in this section.
+    pub(crate) types: Vec<String>,
+}
 
+/// Where and how to add an entry to a Debian `changelog` (usually `debian/changelog`) for every new
+/// version.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct DebianChangelog {
+    /// The path to the changelog, which is created if it doesn't exist.
+    pub(crate) path: PathBuf,
+    /// The name of the source package, defaults to the one in the latest entry.
+    pub(crate) package: Option<String>,
+    /// The distribution to release to, defaults to `unstable`.
+    pub(crate) distribution: Option<String>,
+    /// How important it is to upgrade, defaults to `medium`.
+    pub(crate) urgency: Option<String>,
+    /// The maintainer (like `Name <email>`) for the trailer line, defaults to the one in the latest
+    /// entry.
+    pub(crate) maintainer: Option<String>,
+}
+
+/// The `version_scheme` of a [`Package`], for versions like `1.2.3.4`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct VersionScheme {
+    /// The version with a `{name}` for each component, like `{major}.{minor}.{patch}.{build}`.
+    pub(crate) template: String,
+    /// The component that the `Major` rule bumps, defaults to `major`.
+    pub(crate) major: Option<String>,
+    /// The component that the `Minor` rule bumps, defaults to `minor`.
+    pub(crate) minor: Option<String>,
+    /// The component that the `Patch` rule bumps, defaults to `patch`.
+    pub(crate) patch: Option<String>,
+}
+
+/// Generate a brand new config file for the project in the current directory.
+pub(crate) fn generate() -> Result<()> {
+    let github = github_from_remote();
     let config = Config {
         workflows: vec![Workflow {
             name: String::from("release"),
-            steps: vec![
-                Step::PrepareRelease(PrepareRelease {
-                    prerelease_label: None,
-                }),
-                ..release_steps,
-            ],
+            steps: release_steps(github.is_some(), false),
         }],
         jira: None,
         github,
-        package: find_packages(),
+        smtp: None,
+        sentry: None,
+        protected_branches: None,
+        release_freezes: None,
+        check_for_updates: false,
+        package: Some(find_packages().unwrap_or_else(tag_only_package)),
+        versioning: Versioning::default(),
         packages: None,
+        project_graph: None,
     };
     config.write_out()
 }
 
+/// Determine the GitHub config from the first Git remote, if it's on GitHub.
+pub(crate) fn github_from_remote() -> Option<GitHub> {
+    let remote = git::get_first_remote().filter(|remote| remote.contains("github.com"))?;
+    let parts = remote.split('/').collect::<Vec<_>>();
+    let owner = parts[parts.len() - 2];
+    let owner = owner
+        .strip_prefix("git@github.com:")
+        .unwrap_or(owner)
+        .to_string();
+
+    let repo = parts[parts.len() - 1];
+    let repo = repo.strip_suffix(".git").unwrap_or(repo).to_string();
+    Some(GitHub { owner, repo })
+}
+
+/// The steps of a typical release workflow. With `github`, the release is created on GitHub.
+/// Otherwise, it's only tagged and pushed.
+///
+/// The `Version` variable can't be used with multiple packages, so a `monorepo` uses a commit
+/// message without it.
+pub(crate) fn release_steps(github: bool, monorepo: bool) -> Vec<ConfiguredStep> {
+    let (commit_message, variables) = if monorepo {
+        ("chore: prepare releases", None)
+    } else {
+        (
+            "chore: prepare release $version",
+            Some(hash_map! {
+                String::from("$version"): command::Variable::Version,
+            }),
+        )
+    };
+    let commit = format!("git commit -m \"{commit_message}\"");
+    let prepare_release = Step::PrepareRelease(PrepareRelease {
+        prerelease_label: None,
+        prerelease_start: None,
+        first_parent: None,
+        record: None,
+    });
+
+    if github {
+        vec![
+            prepare_release.into(),
+            Step::Command {
+                command: format!("{commit} && git push"),
+                variables,
+                shell: None,
+                windows: None,
+                timeout: None,
+            }
+            .into(),
+            Step::Release(ReleaseOptions::default()).into(),
+        ]
+    } else {
+        vec![
+            prepare_release.into(),
+            Step::Command {
+                command: commit,
+                variables,
+                shell: None,
+                windows: None,
+                timeout: None,
+            }
+            .into(),
+            Step::Release(ReleaseOptions::default()).into(),
+            Step::Command {
+                command: String::from("git push && git push --tags"),
+                variables: None,
+                shell: None,
+                windows: None,
+                timeout: None,
+            }
+            .into(),
+        ]
+    }
+}
+
 /// Config required for steps that interact with Jira.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub(crate) struct Jira {
@@ -202,3 +482,71 @@ pub(crate) struct GitHub {
     /// The name of the repository in GitHub that this project is utilizing
     pub(crate) repo: String,
 }
+
+/// Details needed to send emails with [`crate::step::Step::SendEmail`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Smtp {
+    /// The hostname of the SMTP server
+    pub(crate) host: String,
+    /// The port of the SMTP server, defaults to 587
+    pub(crate) port: Option<u16>,
+    /// The address to send emails from, like `Release Bot <bot@example.com>`
+    pub(crate) from: String,
+    /// The username to log in to the SMTP server with, if it requires authentication
+    pub(crate) username: Option<String>,
+}
+
+/// Details needed to register releases with [`crate::step::Step::CreateSentryRelease`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Sentry {
+    /// The slug of the organization in Sentry
+    pub(crate) organization: String,
+    /// The slug of the project which releases belong to
+    pub(crate) project: String,
+    /// The URL of a self-hosted Sentry instance, defaults to `https://sentry.io`
+    pub(crate) url: Option<String>,
+}
+
+/// Where to read packages from in a JS monorepo, see [`releases::projects`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct ProjectGraph {
+    /// The tool which the project graph comes from
+    pub(crate) tool: ProjectGraphTool,
+    /// A project graph which was already exported from `tool`, instead of running it
+    pub(crate) file: Option<PathBuf>,
+}
+
+/// A monorepo tool which can export a project graph.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum ProjectGraphTool {
+    /// Read from `nx graph --file=stdout`
+    Nx,
+    /// Read from `turbo ls --output=json` and the `package.json` of each package
+    Turborepo,
+}
+
+/// A time when release steps refuse to run, see [`crate::freeze::check`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub(crate) struct ReleaseFreeze {
+    /// Why releases are frozen, shown when a release is refused
+    pub(crate) reason: Option<String>,
+    /// Days of the week which are frozen. If empty, every day (within `start` and `end`) is.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) days: Vec<Weekday>,
+    /// The first frozen date (in UTC), like `2023-12-20` or `12-20` for every year
+    pub(crate) start: Option<String>,
+    /// The last frozen date (in UTC), like `2024-01-02` or `01-02` for every year
+    pub(crate) end: Option<String>,
+}
+
+/// A day of the week, for [`ReleaseFreeze::days`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
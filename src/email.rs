@@ -0,0 +1,112 @@
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+
+use crate::app_config::get_or_prompt_for_smtp_password;
+use crate::config::Smtp;
+use crate::step::{SendEmail, StepError};
+use crate::RunType;
+
+/// The port for SMTP submission over implicit TLS. Every other port is expected to use STARTTLS.
+const IMPLICIT_TLS_PORT: u16 = 465;
+
+/// The implementation of [`crate::step::Step::SendEmail`].
+pub(crate) fn send_email(run_type: RunType, send_email: SendEmail) -> Result<RunType, StepError> {
+    let (state, dry_run_stdout) = run_type.decompose();
+    let smtp_config = state
+        .smtp_config
+        .clone()
+        .ok_or(StepError::SmtpNotConfigured)?;
+    let SendEmail { to, subject, body } = send_email;
+    let subject = subject.render(&state)?;
+    let body = body.render(&state)?;
+    let message = build_message(&smtp_config.from, &to, subject.clone(), body.clone())?;
+
+    if let Some(mut stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would send an email to {} with subject {subject} and body:\n{body}",
+            to.join(", ")
+        )?;
+        return Ok(RunType::DryRun { state, stdout });
+    }
+
+    transport(&smtp_config)?.send(&message)?;
+    println!("Sent email to {}", to.join(", "));
+    Ok(RunType::Real(state))
+}
+
+fn build_message(
+    from: &str,
+    to: &[String],
+    subject: String,
+    body: String,
+) -> Result<Message, StepError> {
+    let mut builder = Message::builder()
+        .from(parse_mailbox(from)?)
+        .subject(subject);
+    for recipient in to {
+        builder = builder.to(parse_mailbox(recipient)?);
+    }
+    builder.body(body).map_err(StepError::EmailError)
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, StepError> {
+    address
+        .parse()
+        .map_err(|_| StepError::InvalidEmailAddress(address.to_string()))
+}
+
+fn transport(smtp_config: &Smtp) -> Result<SmtpTransport, StepError> {
+    let port = smtp_config.port.unwrap_or(587);
+    let builder = if port == IMPLICIT_TLS_PORT {
+        SmtpTransport::relay(&smtp_config.host)?
+    } else {
+        SmtpTransport::starttls_relay(&smtp_config.host)?
+    }
+    .port(port);
+    let builder = if let Some(username) = &smtp_config.username {
+        builder.credentials(Credentials::new(
+            username.clone(),
+            get_or_prompt_for_smtp_password()?,
+        ))
+    } else {
+        builder
+    };
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod test_build_message {
+    use super::*;
+
+    #[test]
+    fn multiple_recipients() {
+        let message = build_message(
+            "Release Bot <bot@knope.dev>",
+            &[String::from("one@knope.dev"), String::from("two@knope.dev")],
+            String::from("Released 1.2.3"),
+            String::from("Hello"),
+        )
+        .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("From: \"Release Bot\" <bot@knope.dev>"));
+        assert!(formatted.contains("To: one@knope.dev, two@knope.dev"));
+        assert!(formatted.contains("Subject: Released 1.2.3"));
+    }
+
+    #[test]
+    fn invalid_address() {
+        let result = build_message(
+            "bot@knope.dev",
+            &[String::from("not an address")],
+            String::new(),
+            String::new(),
+        );
+
+        assert!(
+            matches!(result, Err(StepError::InvalidEmailAddress(address)) if address == "not an address")
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use crate::config::{ReleaseFreeze, Weekday};
+use crate::step::StepError;
+use crate::template::{civil_date, now};
+
+/// Every [`Weekday`], starting from Monday.
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+/// Refuse to release if today (in UTC) is in any of `freezes`.
+///
+/// # Errors
+///
+/// 1. [`StepError::ReleaseFrozen`] with the reason of the first freeze which applies
+/// 2. [`StepError::InvalidReleaseFreeze`] if a freeze has an invalid date
+pub(crate) fn check(freezes: &[ReleaseFreeze]) -> Result<(), StepError> {
+    let days = now() / 86_400;
+    let (year, month, day) = civil_date(days);
+    let today = Date {
+        year: Some(year),
+        month,
+        day,
+    };
+    // The Unix epoch was a Thursday
+    let weekday = WEEKDAYS[usize::try_from((days + 3) % 7).unwrap_or_default()];
+    for freeze in freezes {
+        if is_frozen(freeze, today, weekday)? {
+            return Err(StepError::ReleaseFrozen(describe(freeze)));
+        }
+    }
+    Ok(())
+}
+
+/// A date in a [`ReleaseFreeze`], which is for every year if there's no `year`.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Date {
+    year: Option<u64>,
+    month: u64,
+    day: u64,
+}
+
+impl Date {
+    /// Parse a date like `2023-12-24` or `12-24`.
+    fn parse(date: &str) -> Result<Self, StepError> {
+        let invalid = || StepError::InvalidReleaseFreeze(format!("{date} is not a valid date"));
+        let parts = date
+            .split('-')
+            .map(str::parse::<u64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        let (year, month, day) = match parts.as_slice() {
+            [year, month, day] => (Some(*year), *month, *day),
+            [month, day] => (None, *month, *day),
+            _ => return Err(invalid()),
+        };
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+        Ok(Self { year, month, day })
+    }
+}
+
+fn is_frozen(freeze: &ReleaseFreeze, today: Date, weekday: Weekday) -> Result<bool, StepError> {
+    if !freeze.days.is_empty() && !freeze.days.contains(&weekday) {
+        return Ok(false);
+    }
+    let (start, end) = match (&freeze.start, &freeze.end) {
+        (None, None) => return Ok(true),
+        (Some(start), Some(end)) => (Date::parse(start)?, Date::parse(end)?),
+        _ => {
+            return Err(StepError::InvalidReleaseFreeze(String::from(
+                "start and end must be set together",
+            )))
+        }
+    };
+    match (start.year, end.year) {
+        (Some(_), Some(_)) => Ok(start <= today && today <= end),
+        (None, None) => {
+            let today = (today.month, today.day);
+            let (start, end) = ((start.month, start.day), (end.month, end.day));
+            if start <= end {
+                Ok(start <= today && today <= end)
+            } else {
+                // Wraps around the new year, like December to January
+                Ok(start <= today || today <= end)
+            }
+        }
+        _ => Err(StepError::InvalidReleaseFreeze(String::from(
+            "start and end must both have a year, or both not have one",
+        ))),
+    }
+}
+
+/// The `reason` of a freeze, or a description of when it is if there isn't one.
+fn describe(freeze: &ReleaseFreeze) -> String {
+    if let Some(reason) = &freeze.reason {
+        return reason.clone();
+    }
+    let mut description = String::from("releases are frozen");
+    if let (Some(start), Some(end)) = (&freeze.start, &freeze.end) {
+        let _ = write!(description, " from {start} to {end}");
+    }
+    if !freeze.days.is_empty() {
+        let days = freeze
+            .days
+            .iter()
+            .map(|day| format!("{day:?}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(description, " on {days}");
+    }
+    description
+}
+
+#[cfg(test)]
+mod test_is_frozen {
+    use super::*;
+
+    fn date(year: u64, month: u64, day: u64) -> Date {
+        Date {
+            year: Some(year),
+            month,
+            day,
+        }
+    }
+
+    fn range(start: &str, end: &str) -> ReleaseFreeze {
+        ReleaseFreeze {
+            start: Some(String::from(start)),
+            end: Some(String::from(end)),
+            ..ReleaseFreeze::default()
+        }
+    }
+
+    #[test]
+    fn days() {
+        let freeze = ReleaseFreeze {
+            days: vec![Weekday::Friday],
+            ..ReleaseFreeze::default()
+        };
+        let today = date(2023, 6, 2);
+        assert!(is_frozen(&freeze, today, Weekday::Friday).unwrap());
+        assert!(!is_frozen(&freeze, today, Weekday::Thursday).unwrap());
+    }
+
+    #[test]
+    fn dates() {
+        let freeze = range("2023-06-01", "2023-06-30");
+        assert!(is_frozen(&freeze, date(2023, 6, 1), Weekday::Thursday).unwrap());
+        assert!(is_frozen(&freeze, date(2023, 6, 30), Weekday::Friday).unwrap());
+        assert!(!is_frozen(&freeze, date(2023, 7, 1), Weekday::Saturday).unwrap());
+        assert!(!is_frozen(&freeze, date(2024, 6, 15), Weekday::Saturday).unwrap());
+    }
+
+    #[test]
+    fn every_year_across_new_year() {
+        let freeze = range("12-20", "01-02");
+        assert!(is_frozen(&freeze, date(2023, 12, 25), Weekday::Monday).unwrap());
+        assert!(is_frozen(&freeze, date(2024, 1, 2), Weekday::Tuesday).unwrap());
+        assert!(!is_frozen(&freeze, date(2024, 1, 3), Weekday::Wednesday).unwrap());
+        assert!(!is_frozen(&freeze, date(2023, 12, 19), Weekday::Tuesday).unwrap());
+    }
+
+    #[test]
+    fn days_within_dates() {
+        let freeze = ReleaseFreeze {
+            days: vec![Weekday::Friday],
+            ..range("06-01", "06-30")
+        };
+        assert!(is_frozen(&freeze, date(2023, 6, 2), Weekday::Friday).unwrap());
+        assert!(!is_frozen(&freeze, date(2023, 6, 3), Weekday::Saturday).unwrap());
+        assert!(!is_frozen(&freeze, date(2023, 7, 7), Weekday::Friday).unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let today = date(2023, 6, 2);
+        for freeze in [
+            range("2023-13-01", "2023-12-31"),
+            range("2023-06-01", "06-30"),
+            range("June 1", "June 30"),
+            ReleaseFreeze {
+                start: Some(String::from("06-01")),
+                ..ReleaseFreeze::default()
+            },
+        ] {
+            assert!(is_frozen(&freeze, today, Weekday::Friday).is_err());
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs::{create_dir_all, read_to_string, write};
+
+use crate::git::add_files;
+use crate::step::{GenerateFile, StepError};
+use crate::template::Template;
+use crate::RunType;
+
+/// The implementation of [`crate::step::Step::GenerateFile`].
+///
+/// Renders the template file with the current state (like the new version) and writes the result
+/// to `path`, staging it so that it's part of the release commit.
+pub(crate) fn generate_file(
+    run_type: RunType,
+    generate_file: GenerateFile,
+) -> Result<RunType, StepError> {
+    let (state, dry_run_stdout) = run_type.decompose();
+    let GenerateFile {
+        path,
+        template,
+        variables,
+    } = generate_file;
+    let template = read_to_string(&template).map_err(|_| StepError::FileNotFound(template))?;
+    let content = Template {
+        template,
+        variables,
+    }
+    .render(&state)?;
+
+    if let Some(mut stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would write the following to {}: \n{content}",
+            path.display()
+        )?;
+        return Ok(RunType::DryRun { state, stdout });
+    }
+
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    write(&path, content)?;
+    add_files(&[&path])?;
+    Ok(RunType::Real(state))
+}
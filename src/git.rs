@@ -1,13 +1,21 @@
-use std::path::PathBuf;
+use std::env;
+use std::fs::read_to_string;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::str::FromStr;
 
 use git2::build::CheckoutBuilder;
-use git2::{Branch, BranchType, Repository};
+use git2::{
+    AttrCheckFlags, Branch, BranchType, DiffOptions, Oid, Pathspec, PathspecFlags, Repository,
+    Revwalk, Status,
+};
 use log::{debug, error, trace, warn};
 
-use crate::issues::Issue;
-use crate::prompt::select;
-use crate::releases::{get_current_versions_from_tag, tag_name};
+use crate::ci::{is_interactive, Ci};
+use crate::issues::{self, Issue};
+use crate::prompt::{confirm, select};
+use crate::releases::{get_current_versions_from_tag, tag_name, CommitRange};
 use crate::state;
 use crate::step::StepError;
 use crate::RunType;
@@ -19,6 +27,7 @@ pub(crate) fn switch_branches(run_type: RunType) -> Result<RunType, StepError> {
     let issue = match &state.issue {
         state::Issue::Initial => return Err(StepError::NoIssueSelected),
         state::Issue::Selected(issue) => issue,
+        state::Issue::SelectedMany(_) => return Err(StepError::MultipleIssuesSelected),
     };
     let new_branch_name = branch_name_from_issue(issue);
     if let Some(mut stdout) = dry_run_stdout {
@@ -27,6 +36,12 @@ pub(crate) fn switch_branches(run_type: RunType) -> Result<RunType, StepError> {
             "Would switch to or create a branch named {}",
             new_branch_name
         )?;
+        if is_protected(&state.protected_branches, &new_branch_name) {
+            writeln!(
+                stdout,
+                "Would ask before switching to the protected branch {new_branch_name}"
+            )?;
+        }
         return Ok(RunType::DryRun { state, stdout });
     }
 
@@ -34,6 +49,7 @@ pub(crate) fn switch_branches(run_type: RunType) -> Result<RunType, StepError> {
     let branches = get_all_branches(&repo)?;
 
     if let Ok(existing) = repo.find_branch(&new_branch_name, BranchType::Local) {
+        check_protected_branch(&state.protected_branches, &new_branch_name, "switch to it")?;
         println!(
             "Found existing branch named {}, switching to it.",
             new_branch_name
@@ -42,6 +58,12 @@ pub(crate) fn switch_branches(run_type: RunType) -> Result<RunType, StepError> {
     } else {
         println!("Creating a new branch called {}", new_branch_name);
         let branch = select_branch(branches, "Which branch do you want to base off of?")?;
+        let base_name = branch.name()?.ok_or(StepError::BadGitBranchName)?;
+        check_protected_branch(
+            &state.protected_branches,
+            base_name,
+            &format!("base {new_branch_name} on it"),
+        )?;
         let new_branch = create_branch(&repo, &new_branch_name, &branch)?;
         switch_to_branch(&repo, &new_branch)?;
     }
@@ -50,11 +72,23 @@ pub(crate) fn switch_branches(run_type: RunType) -> Result<RunType, StepError> {
 }
 
 /// Rebase the current branch onto the selected one.
-pub(crate) fn rebase_branch(to: &str, mut run_type: RunType) -> Result<RunType, StepError> {
-    if let RunType::DryRun { stdout, .. } = &mut run_type {
+pub(crate) fn rebase_branch(to: &str, run_type: RunType) -> Result<RunType, StepError> {
+    let (state, dry_run_stdout) = run_type.decompose();
+    if let Some(mut stdout) = dry_run_stdout {
         writeln!(stdout, "Would rebase current branch onto {}", to)?;
-        return Ok(run_type);
+        if is_protected(&state.protected_branches, to) {
+            writeln!(
+                stdout,
+                "Would ask before modifying the protected branch {to}"
+            )?;
+        }
+        return Ok(RunType::DryRun { state, stdout });
     }
+    check_protected_branch(
+        &state.protected_branches,
+        to,
+        "add the commits of the current branch to it",
+    )?;
 
     let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
     let head = repo.head()?;
@@ -68,10 +102,118 @@ pub(crate) fn rebase_branch(to: &str, mut run_type: RunType) -> Result<RunType,
     println!("Rebased current branch onto {}", to);
     switch_to_branch(&repo, &target_branch)?;
     println!("Switched to branch {}, don't forget to push!", to);
-    Ok(run_type)
+    Ok(RunType::Real(state))
 }
 
-pub(crate) fn select_issue_from_current_branch(run_type: RunType) -> Result<RunType, StepError> {
+/// Make sure that `branch` may be modified, asking first if it matches one of
+/// `protected_branches`. Nobody can be asked when running non-interactively (like in CI), so the
+/// branch isn't modified at all.
+fn check_protected_branch(
+    protected_branches: &[String],
+    branch: &str,
+    action: &str,
+) -> Result<(), StepError> {
+    if !is_protected(protected_branches, branch) {
+        return Ok(());
+    }
+    if is_interactive(|key| env::var(key).ok())
+        && confirm(&format!(
+            "{branch} is a protected branch, are you sure you want to {action}?"
+        ))?
+    {
+        Ok(())
+    } else {
+        Err(StepError::ProtectedBranch(branch.to_string()))
+    }
+}
+
+/// Make sure that a step may commit and push to `branch` (if there is one), see
+/// [`check_protected_branch`]. With `dry_run_stdout`, only say whether it would ask.
+pub(crate) fn check_branch_before_push(
+    protected_branches: &[String],
+    branch: Option<&str>,
+    dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<(), StepError> {
+    let branch = if let Some(branch) = branch {
+        branch
+    } else {
+        return Ok(());
+    };
+    match dry_run_stdout {
+        Some(stdout) if is_protected(protected_branches, branch) => {
+            writeln!(
+                stdout,
+                "Would ask before modifying the protected branch {branch}"
+            )?;
+            Ok(())
+        }
+        Some(_) => Ok(()),
+        None => check_protected_branch(protected_branches, branch, "commit and push to it"),
+    }
+}
+
+/// Whether `branch` matches one of `protected_branches`, where `*` matches any characters (like
+/// `release/*` for `release/1.x`).
+fn is_protected(protected_branches: &[String], branch: &str) -> bool {
+    protected_branches
+        .iter()
+        .any(|pattern| matches_wildcards(pattern, branch))
+}
+
+fn matches_wildcards(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let mut rest = match text.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // No wildcards, so the whole pattern must match.
+        None => return rest.is_empty(),
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod test_is_protected {
+    use super::*;
+
+    #[test]
+    fn exact() {
+        let protected = [String::from("main")];
+        assert!(is_protected(&protected, "main"));
+        assert!(!is_protected(&protected, "main-2"));
+        assert!(!is_protected(&protected, "PROJ-1-main"));
+    }
+
+    #[test]
+    fn wildcards() {
+        let protected = [String::from("release/*"), String::from("*-stable")];
+        assert!(is_protected(&protected, "release/1.x"));
+        assert!(is_protected(&protected, "2.0-stable"));
+        assert!(!is_protected(&protected, "release"));
+        assert!(!is_protected(&protected, "42-release-notes"));
+    }
+
+    #[test]
+    fn overlapping() {
+        assert!(!matches_wildcards("a*ab", "ab"));
+        assert!(matches_wildcards("a*b*b", "abab"));
+    }
+}
+
+pub(crate) fn select_issue_from_current_branch(
+    run_type: RunType,
+    fetch: bool,
+) -> Result<RunType, StepError> {
     match run_type {
         RunType::DryRun {
             mut state,
@@ -81,23 +223,61 @@ pub(crate) fn select_issue_from_current_branch(run_type: RunType) -> Result<RunT
                 stdout,
                 "Would attempt to parse current branch name to select current issue"
             )?;
+            if fetch {
+                if state.jira_config.is_none() && state.github_config.is_none() {
+                    return Err(StepError::IssueTrackerNotConfigured);
+                }
+                writeln!(
+                    stdout,
+                    "Would fetch the issue's summary from the issue tracker"
+                )?;
+            }
             state.issue = state::Issue::Selected(Issue {
                 key: String::from("123"),
                 summary: String::from("Fake Issue"),
             });
             Ok(RunType::DryRun { state, stdout })
         }
-        RunType::Real(mut state) => {
+        RunType::Real(state) => {
             let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
-            let head = repo.head()?;
-            let ref_name = head.name().ok_or(StepError::NotOnAGitBranch)?;
-            let issue = select_issue_from_branch_name(ref_name)?;
+            let branch = current_branch(&repo)?;
+            let issue = select_issue_from_branch_name(&branch)?;
+            println!("Auto-selecting issue {} from ref {}", &issue.key, branch);
+            let (mut state, issue) = if fetch {
+                issues::fetch_issue(state, &issue.key)?
+            } else {
+                (state, issue)
+            };
             state.issue = state::Issue::Selected(issue);
             Ok(RunType::Real(state))
         }
     }
 }
 
+/// The name of the branch that HEAD is on. CI services often check out a detached HEAD, in which
+/// case the branch they're building is used instead.
+pub(crate) fn current_branch(repo: &Repository) -> Result<String, StepError> {
+    let head = repo.head()?;
+    if head.is_branch() {
+        return head
+            .shorthand()
+            .map(String::from)
+            .ok_or(StepError::NotOnAGitBranch);
+    }
+    let var = |key: &str| env::var(key).ok();
+    Ci::detect(var)
+        .and_then(|ci| ci.branch(var))
+        .ok_or(StepError::NotOnAGitBranch)
+}
+
+/// The branch that HEAD is on in the Git repo at `repository` (like a clone that a step commits
+/// to), if there is one.
+pub(crate) fn repository_branch(repository: &Path) -> Option<String> {
+    Repository::open(repository)
+        .ok()
+        .and_then(|repo| current_branch(&repo).ok())
+}
+
 /// Get the first remote of the Git repo, if any.
 pub(crate) fn get_first_remote() -> Option<String> {
     let repo = Repository::open(".").ok()?;
@@ -121,7 +301,6 @@ fn select_issue_from_branch_name(ref_name: &str) -> Result<Issue, StepError> {
         Err(StepError::BadGitBranchName)
     }?;
 
-    println!("Auto-selecting issue {} from ref {}", &key, ref_name);
     Ok(Issue { key, summary })
 }
 
@@ -165,6 +344,91 @@ mod test_select_issue_from_branch_name {
     }
 }
 
+/// Add the key of the issue that the current branch is for (as in
+/// [`select_issue_from_current_branch`]) to the commit message in `message_file` as a trailer,
+/// like `Issue: PROJ-123`, so that the commit is linked to the issue.
+///
+/// Nothing is added to merge or squash commits, to empty messages (so that they still abort the
+/// commit), on branches which aren't for an issue, or when the message already has the same
+/// trailer.
+pub(crate) fn add_issue_trailer(
+    message_file: &Path,
+    source: Option<&str>,
+    trailer: &str,
+) -> Result<(), StepError> {
+    if matches!(source, Some("merge" | "squash"))
+        || is_empty_message(&read_to_string(message_file)?)
+    {
+        return Ok(());
+    }
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let issue =
+        match current_branch(&repo).and_then(|branch| select_issue_from_branch_name(&branch)) {
+            Ok(issue) => issue,
+            Err(err) => {
+                debug!("Not adding an issue trailer: {err}");
+                return Ok(());
+            }
+        };
+    let status = Command::new("git")
+        .args([
+            "interpret-trailers",
+            "--in-place",
+            "--if-exists",
+            "addIfDifferent",
+        ])
+        .arg("--trailer")
+        .arg(format!("{trailer}: {}", issue_reference(&issue.key)))
+        .arg(message_file)
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(StepError::CommandError(status))
+    }
+}
+
+/// Whether a commit message has nothing in it other than comments, which Git removes. Anything
+/// after the scissors line of `git commit --verbose` is removed too.
+fn is_empty_message(message: &str) -> bool {
+    message
+        .lines()
+        .take_while(|line| !line.starts_with("# ------------------------ >8"))
+        .all(|line| line.starts_with('#') || line.trim().is_empty())
+}
+
+#[cfg(test)]
+mod test_is_empty_message {
+    #[test]
+    fn only_comments() {
+        let message = "\n# Please enter the commit message for your changes.\n#\n";
+        assert!(super::is_empty_message(message));
+    }
+
+    #[test]
+    fn verbose() {
+        let message =
+            "\n# ------------------------ >8 ------------------------\ndiff --git a/file b/file\n";
+        assert!(super::is_empty_message(message));
+    }
+
+    #[test]
+    fn with_message() {
+        let message = "feat: Add a thing\n\n# Please enter the commit message for your changes.\n";
+        assert!(!super::is_empty_message(message));
+    }
+}
+
+/// How commit messages refer to the issue with `key`: GitHub issues (which are only a number)
+/// like `#42`, and Jira issues by their key, like `PROJ-123`.
+fn issue_reference(key: &str) -> String {
+    if key.chars().all(|c| c.is_ascii_digit()) {
+        format!("#{key}")
+    } else {
+        key.to_string()
+    }
+}
+
 fn create_branch<'repo>(
     repo: &'repo Repository,
     name: &str,
@@ -194,16 +458,7 @@ fn select_branch<'repo>(
 }
 
 fn switch_to_branch(repo: &Repository, branch: &Branch) -> Result<(), StepError> {
-    let statuses = repo.statuses(None)?;
-    let uncommitted_changes = statuses.iter().any(|status| {
-        if let Ok(path) = String::from_utf8(Vec::from(status.path_bytes())) {
-            if matches!(repo.status_should_ignore(path.as_ref()), Ok(false)) {
-                return true;
-            }
-        }
-        false
-    });
-    if uncommitted_changes {
+    if has_uncommitted_changes(repo)? {
         return Err(StepError::UncommittedChanges);
     }
     let ref_name = branch.get().name().ok_or(StepError::BadGitBranchName)?;
@@ -213,6 +468,46 @@ fn switch_to_branch(repo: &Repository, branch: &Branch) -> Result<(), StepError>
     Ok(())
 }
 
+/// Whether there are any changes which switching branches would throw away.
+///
+/// libgit2 can't run the Git LFS filters, so every file tracked with LFS looks modified once it's
+/// been checked out. Those files are checked again with `git status`, which runs the filters.
+fn has_uncommitted_changes(repo: &Repository) -> Result<bool, StepError> {
+    let statuses = repo.statuses(None)?;
+    let mut lfs_paths = Vec::new();
+    for status in statuses.iter() {
+        let path = match status.path() {
+            Some(path) => path,
+            None => continue,
+        };
+        if !matches!(repo.status_should_ignore(Path::new(path)), Ok(false)) {
+            continue;
+        }
+        if status.status() == Status::WT_MODIFIED && is_lfs_file(repo, path) {
+            lfs_paths.push(path.to_string());
+        } else {
+            return Ok(true);
+        }
+    }
+    if lfs_paths.is_empty() {
+        return Ok(false);
+    }
+    debug!("Checking Git LFS files with git status: {lfs_paths:?}");
+    let output = Command::new("git")
+        .args(["status", "--porcelain", "--"])
+        .args(&lfs_paths)
+        .output()?;
+    Ok(!output.status.success() || !output.stdout.is_empty())
+}
+
+/// Whether the file at `path` is tracked with Git LFS, according to `.gitattributes`.
+fn is_lfs_file(repo: &Repository, path: &str) -> bool {
+    matches!(
+        repo.get_attr(Path::new(path), "filter", AttrCheckFlags::FILE_THEN_INDEX),
+        Ok(Some("lfs"))
+    )
+}
+
 fn get_all_branches(repo: &Repository) -> Result<Vec<Branch>, StepError> {
     Ok(repo
         .branches(Some(BranchType::Local))?
@@ -246,59 +541,151 @@ mod test_branch_name_from_issue {
     }
 }
 
+/// A commit's full message along with its ID.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CommitMessage {
+    pub(crate) id: String,
+    pub(crate) message: String,
+}
+
+/// Which files a commit must change for it to be included.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PathFilter<'a> {
+    /// If not empty, a commit must change a file matching one of these paths (which may contain
+    /// globs).
+    pub(crate) paths: &'a [String],
+    /// Changes to files matching one of these paths (which may contain globs) don't count.
+    pub(crate) exclude_paths: &'a [String],
+}
+
+impl PathFilter<'_> {
+    /// Whether every commit is included.
+    fn is_empty(&self) -> bool {
+        self.paths.is_empty() && self.exclude_paths.is_empty()
+    }
+}
+
 pub(crate) fn get_commit_messages_after_last_stable_version(
-    package_name: &Option<String>,
-) -> Result<Vec<String>, StepError> {
-    let target_version = get_current_versions_from_tag(package_name.as_deref())?
-        .map(|current_version| current_version.stable);
-    let reference = match &target_version {
-        Some(version) => {
-            let tag = tag_name(version, package_name);
-            debug!("Processing all commits since tag {tag}");
-            Some(format!("refs/tags/{tag}"))
-        }
-        None => {
-            warn!("No stable version tag found, processing all commits.");
-            None
-        }
+    tag_prefix: &str,
+    path_filter: PathFilter,
+    first_parent: bool,
+) -> Result<Vec<CommitMessage>, StepError> {
+    let target_version =
+        get_current_versions_from_tag(tag_prefix)?.map(|current_version| current_version.stable);
+    let tag = if let Some(version) = &target_version {
+        let tag = tag_name(version, tag_prefix);
+        debug!("Processing all commits since tag {tag}");
+        Some(tag)
+    } else {
+        warn!("No stable version tag found, processing all commits.");
+        None
     };
-    let repo = git_repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
-    let tag_ref = reference
-        .as_ref()
-        .map(|reference| repo.find_reference(reference))
-        .transpose()
-        .expect("Could not find Git reference that was previously seen.");
-    let tag_oid = tag_ref
-        .map(git_repository::Reference::into_fully_peeled_id)
-        .transpose()?;
-    if reference.is_some() && tag_oid.is_none() {
-        error!(
-            "Found tagged version {}, but could not parse it within Git",
-            reference.unwrap()
-        );
+    get_commit_messages_since_tag(tag.as_deref(), path_filter, first_parent)
+}
+
+/// Get the messages of every commit reachable from HEAD but not from `tag` (like
+/// `git log <tag>..HEAD`), or every commit if there is no `tag` or it doesn't exist.
+///
+/// Only commits which changed a file allowed by `path_filter` are included. If `first_parent` is
+/// set, only the first parent of each merge commit is followed, so commits from merged branches
+/// are left out (but the merge commits themselves are not).
+pub(crate) fn get_commit_messages_since_tag(
+    tag: Option<&str>,
+    path_filter: PathFilter,
+    first_parent: bool,
+) -> Result<Vec<CommitMessage>, StepError> {
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let reference = tag.map(|tag| format!("refs/tags/{tag}"));
+    let tag_oid = reference.as_ref().and_then(|reference| {
+        repo.find_reference(reference)
+            .and_then(|reference| reference.peel_to_commit())
+            .map(|commit| commit.id())
+            .ok()
+    });
+    if let (Some(reference), None) = (&reference, tag_oid) {
+        error!("Could not find {reference} within Git, processing all commits.");
     }
-    let commit = repo.head_commit()?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    if let Some(tag_oid) = tag_oid {
+        // Leave out everything that was already released, even if it's reachable from a merge.
+        revwalk.hide(tag_oid)?;
+    }
+    if first_parent {
+        revwalk.simplify_first_parent()?;
+    }
+    collect_commit_messages(&repo, revwalk, path_filter)
+}
+
+/// Get the messages of every commit in `range`, as recorded when a release was prepared, no matter
+/// where HEAD is now.
+pub(crate) fn get_commit_messages_in_range(
+    range: &CommitRange,
+    path_filter: PathFilter,
+) -> Result<Vec<CommitMessage>, StepError> {
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(Oid::from_str(&range.to)?)?;
+    if let Some(from) = &range.from {
+        revwalk.hide(Oid::from_str(from)?)?;
+    }
+    collect_commit_messages(&repo, revwalk, path_filter)
+}
+
+/// Collect the message of every commit in `revwalk` which changes a file allowed by `path_filter`.
+fn collect_commit_messages(
+    repo: &Repository,
+    revwalk: Revwalk,
+    path_filter: PathFilter,
+) -> Result<Vec<CommitMessage>, StepError> {
     let mut messages = vec![];
-    for item in commit.ancestors().all()?.error_on_missing_commit() {
-        let id = item?;
-        if let Some(tag_id) = tag_oid {
-            if id == tag_id {
-                break;
-            }
-        }
-        if let Some(commit) = repo
-            .find_object(id)
-            .ok()
-            .and_then(|object| object.try_into_commit().ok())
-        {
-            let message = commit.decode()?.message.to_string();
-            trace!("Checking commit message: {}", &message);
-            messages.push(message);
+    for id in revwalk {
+        let id = id?;
+        let id_string = id.to_string();
+        if !path_filter.is_empty() && !changes_paths(repo, &id_string, path_filter)? {
+            trace!("Skipping commit {id_string} which doesn't change any files in {path_filter:?}");
+            continue;
         }
+        let commit = repo.find_commit(id)?;
+        let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+        trace!("Checking commit message: {}", &message);
+        messages.push(CommitMessage {
+            id: id_string,
+            message,
+        });
     }
     Ok(messages)
 }
 
+/// Whether the commit `id` changed any file allowed by `path_filter` compared to its first parent.
+fn changes_paths(repo: &Repository, id: &str, path_filter: PathFilter) -> Result<bool, StepError> {
+    let commit = repo.find_commit(Oid::from_str(id)?)?;
+    let parent_tree = commit
+        .parents()
+        .next()
+        .map(|parent| parent.tree())
+        .transpose()?;
+    let mut options = DiffOptions::new();
+    for path in path_filter.paths {
+        options.pathspec(path);
+    }
+    let diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&commit.tree()?),
+        Some(&mut options),
+    )?;
+    if path_filter.exclude_paths.is_empty() {
+        return Ok(diff.deltas().next().is_some());
+    }
+    let excluded = Pathspec::new(path_filter.exclude_paths.iter().map(String::as_str))?;
+    Ok(diff.deltas().any(|delta| {
+        [delta.old_file().path(), delta.new_file().path()]
+            .into_iter()
+            .flatten()
+            .any(|path| !excluded.matches_path(path, PathspecFlags::DEFAULT))
+    }))
+}
+
 /// Add some files to Git to be committed later.
 pub(crate) fn add_files(file_names: &[&PathBuf]) -> Result<(), StepError> {
     let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
@@ -0,0 +1,260 @@
+use serde_json::{json, Value};
+
+/// Convert `markdown` (like a changelog entry) to the [Atlassian Document Format] which Jira uses
+/// for comments and descriptions, so that its formatting survives.
+///
+/// Headings, bullet and numbered lists, fenced code blocks, and paragraphs are supported, along
+/// with bold, italic, code, and links within them. Anything else is kept as plain text.
+///
+/// [Atlassian Document Format]: https://developer.atlassian.com/cloud/jira/platform/apis/document/structure/
+pub(crate) fn markdown_to_adf(markdown: &str) -> Value {
+    let mut content = Vec::new();
+    let mut lines = markdown.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if let Some(language) = trimmed.strip_prefix("```") {
+            let mut code = Vec::new();
+            for line in lines.by_ref() {
+                if line.trim_start().starts_with("```") {
+                    break;
+                }
+                code.push(line);
+            }
+            let mut block = json!({"type": "codeBlock", "content": text_nodes(&code.join("\n"))});
+            if !language.trim().is_empty() {
+                block["attrs"] = json!({"language": language.trim()});
+            }
+            content.push(block);
+        } else if let Some((level, text)) = heading(trimmed) {
+            content.push(json!({
+                "type": "heading",
+                "attrs": {"level": level},
+                "content": inline(text)
+            }));
+        } else if let Some((ordered, text)) = list_item(trimmed) {
+            let mut items = vec![text.to_string()];
+            while let Some(&next) = lines.peek() {
+                match list_item(next.trim()) {
+                    Some((next_ordered, text)) if next_ordered == ordered => {
+                        items.push(text.to_string());
+                    }
+                    // A list item which was wrapped onto multiple lines.
+                    None if next.starts_with(' ') && !next.trim().is_empty() => {
+                        if let Some(item) = items.last_mut() {
+                            item.push(' ');
+                            item.push_str(next.trim());
+                        }
+                    }
+                    Some(_) | None => break,
+                }
+                lines.next();
+            }
+            let items = items
+                .iter()
+                .map(|item| json!({"type": "listItem", "content": [paragraph(item)]}))
+                .collect::<Vec<_>>();
+            let list_type = if ordered { "orderedList" } else { "bulletList" };
+            content.push(json!({"type": list_type, "content": items}));
+        } else {
+            let mut text = trimmed.to_string();
+            while let Some(&next) = lines.peek() {
+                let next = next.trim();
+                if next.is_empty()
+                    || next.starts_with("```")
+                    || heading(next).is_some()
+                    || list_item(next).is_some()
+                {
+                    break;
+                }
+                text.push(' ');
+                text.push_str(next);
+                lines.next();
+            }
+            content.push(paragraph(&text));
+        }
+    }
+    json!({"type": "doc", "version": 1, "content": content})
+}
+
+/// The level and text of a heading like `## Features`.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let text = line[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, text.trim()))
+}
+
+/// Whether a list item (like `- Note` or `1. Note`) is ordered, along with its text.
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+    {
+        return Some((false, text.trim()));
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let text = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))?;
+    Some((true, text.trim()))
+}
+
+fn paragraph(text: &str) -> Value {
+    json!({"type": "paragraph", "content": inline(text)})
+}
+
+/// A single unformatted text node, or nothing for empty `text` (which ADF doesn't allow).
+fn text_nodes(text: &str) -> Vec<Value> {
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![json!({"type": "text", "text": text})]
+    }
+}
+
+/// Convert the inline formatting of `text` to text nodes with marks.
+fn inline(text: &str) -> Vec<Value> {
+    let mut nodes = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if let Some((element, remaining)) = inline_element(rest, plain.chars().next_back()) {
+            nodes.extend(text_nodes(&plain));
+            plain.clear();
+            nodes.extend(element);
+            rest = remaining;
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    nodes.extend(text_nodes(&plain));
+    nodes
+}
+
+/// Parse a code span, bold or italic text, or a link at the start of `text`, returning its nodes
+/// and the text after it. `previous` is the character before `text`, if known.
+fn inline_element(text: &str, previous: Option<char>) -> Option<(Vec<Value>, &str)> {
+    if let Some(rest) = text.strip_prefix('`') {
+        let end = rest.find('`').filter(|end| *end > 0)?;
+        let code = with_mark(text_nodes(&rest[..end]), "code");
+        return Some((code, &rest[end + 1..]));
+    }
+    if let Some(rest) = text.strip_prefix("**") {
+        let end = rest.find("**").filter(|end| *end > 0)?;
+        return Some((with_mark(inline(&rest[..end]), "strong"), &rest[end + 2..]));
+    }
+    if let Some(rest) = text.strip_prefix('*') {
+        let end = rest.find('*').filter(|end| *end > 0)?;
+        return Some((with_mark(inline(&rest[..end]), "em"), &rest[end + 1..]));
+    }
+    if let Some(rest) = text.strip_prefix('_') {
+        // Underscores inside of words (like `snake_case`) aren't emphasis.
+        if previous.map_or(false, char::is_alphanumeric) {
+            return None;
+        }
+        let end = rest.find('_').filter(|end| *end > 0)?;
+        let after = &rest[end + 1..];
+        if after.chars().next().map_or(false, char::is_alphanumeric) {
+            return None;
+        }
+        return Some((with_mark(inline(&rest[..end]), "em"), after));
+    }
+    if let Some(rest) = text.strip_prefix('[') {
+        let (label, after) = rest.split_once("](")?;
+        let (href, after) = after.split_once(')')?;
+        if label.is_empty() {
+            return None;
+        }
+        let mut nodes = inline(label);
+        for node in &mut nodes {
+            add_mark(node, json!({"type": "link", "attrs": {"href": href}}));
+        }
+        return Some((nodes, after));
+    }
+    None
+}
+
+fn with_mark(mut nodes: Vec<Value>, mark_type: &str) -> Vec<Value> {
+    for node in &mut nodes {
+        add_mark(node, json!({ "type": mark_type }));
+    }
+    nodes
+}
+
+fn add_mark(node: &mut Value, mark: Value) {
+    if let Some(Value::Array(marks)) = node.get_mut("marks") {
+        marks.push(mark);
+    } else {
+        node["marks"] = json!([mark]);
+    }
+}
+
+#[cfg(test)]
+mod test_markdown_to_adf {
+    use super::*;
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            markdown_to_adf("Released in 1.2.3"),
+            json!({"type": "doc", "version": 1, "content": [
+                {"type": "paragraph", "content": [{"type": "text", "text": "Released in 1.2.3"}]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn changelog() {
+        let markdown = "## 1.2.3\n\n### Features\n\n- Add **new** `thing`\n- A note which\n  was wrapped\n\nSee [the docs](https://knope.dev) for more_info.\n";
+        assert_eq!(
+            markdown_to_adf(markdown),
+            json!({"type": "doc", "version": 1, "content": [
+                {"type": "heading", "attrs": {"level": 2}, "content": [{"type": "text", "text": "1.2.3"}]},
+                {"type": "heading", "attrs": {"level": 3}, "content": [{"type": "text", "text": "Features"}]},
+                {"type": "bulletList", "content": [
+                    {"type": "listItem", "content": [{"type": "paragraph", "content": [
+                        {"type": "text", "text": "Add "},
+                        {"type": "text", "text": "new", "marks": [{"type": "strong"}]},
+                        {"type": "text", "text": " "},
+                        {"type": "text", "text": "thing", "marks": [{"type": "code"}]}
+                    ]}]},
+                    {"type": "listItem", "content": [{"type": "paragraph", "content": [
+                        {"type": "text", "text": "A note which was wrapped"}
+                    ]}]}
+                ]},
+                {"type": "paragraph", "content": [
+                    {"type": "text", "text": "See "},
+                    {"type": "text", "text": "the docs", "marks": [{"type": "link", "attrs": {"href": "https://knope.dev"}}]},
+                    {"type": "text", "text": " for more_info."}
+                ]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn code_block_and_ordered_list() {
+        let markdown = "1. First\n2. *Second*\n\n```toml\n[package]\n```\n";
+        assert_eq!(
+            markdown_to_adf(markdown),
+            json!({"type": "doc", "version": 1, "content": [
+                {"type": "orderedList", "content": [
+                    {"type": "listItem", "content": [{"type": "paragraph", "content": [
+                        {"type": "text", "text": "First"}
+                    ]}]},
+                    {"type": "listItem", "content": [{"type": "paragraph", "content": [
+                        {"type": "text", "text": "Second", "marks": [{"type": "em"}]}
+                    ]}]}
+                ]},
+                {"type": "codeBlock", "attrs": {"language": "toml"}, "content": [
+                    {"type": "text", "text": "[package]"}
+                ]}
+            ]})
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::fs::{create_dir_all, metadata, read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use log::debug;
+use platform_dirs::AppDirs;
+
+use crate::issues::Issue;
+use crate::step::StepError;
+
+/// How long a list of issues is reused before the API is queried again.
+const TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Get the list of issues for `key` from the cache if it was stored in the last few minutes,
+/// otherwise `fetch` it and store it for next time. Passing `refresh` always fetches.
+///
+/// The cache is only an optimization, so any problem using it falls back to `fetch`.
+pub(super) fn cached_issues<F>(key: &str, refresh: bool, fetch: F) -> Result<Vec<Issue>, StepError>
+where
+    F: FnOnce() -> Result<Vec<Issue>, StepError>,
+{
+    match AppDirs::new(Some("knope"), true) {
+        Some(app_dirs) => {
+            cached_issues_in(&app_dirs.cache_dir.join("issues"), key, TTL, refresh, fetch)
+        }
+        None => fetch(),
+    }
+}
+
+fn cached_issues_in<F>(
+    dir: &Path,
+    key: &str,
+    ttl: Duration,
+    refresh: bool,
+    fetch: F,
+) -> Result<Vec<Issue>, StepError>
+where
+    F: FnOnce() -> Result<Vec<Issue>, StepError>,
+{
+    let path = cache_path(dir, key);
+    if !refresh {
+        if let Some(issues) = read_fresh(&path, ttl) {
+            debug!("Using cached issues from {}", path.display());
+            return Ok(issues);
+        }
+    }
+    let issues = fetch()?;
+    let written = create_dir_all(dir)
+        .ok()
+        .and_then(|()| serde_json::to_string(&issues).ok())
+        .and_then(|content| write(&path, content).ok());
+    if written.is_none() {
+        debug!("Could not cache issues in {}", path.display());
+    }
+    Ok(issues)
+}
+
+/// The issues stored at `path`, if they were stored less than `ttl` ago.
+fn read_fresh(path: &Path, ttl: Duration) -> Option<Vec<Issue>> {
+    let age = metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+    if age >= ttl {
+        return None;
+    }
+    serde_json::from_str(&read_to_string(path).ok()?).ok()
+}
+
+/// A file name for `key`, which may contain characters like `/` from URLs.
+fn cache_path(dir: &Path, key: &str) -> PathBuf {
+    let file_name = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    dir.join(format!("{file_name}.json"))
+}
+
+#[cfg(test)]
+mod test_cached_issues {
+    use super::*;
+
+    fn issues() -> Vec<Issue> {
+        vec![Issue {
+            key: String::from("123"),
+            summary: String::from("Cached issue"),
+        }]
+    }
+
+    #[test]
+    fn reuses_fresh_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = cached_issues_in(dir.path(), "github/owner/repo", TTL, false, || Ok(issues()));
+        let second = cached_issues_in(dir.path(), "github/owner/repo", TTL, false, || {
+            panic!("Should not fetch when the cache is fresh")
+        });
+
+        assert_eq!(first.unwrap(), issues());
+        assert_eq!(second.unwrap(), issues());
+    }
+
+    #[test]
+    fn refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        cached_issues_in(dir.path(), "key", TTL, false, || Ok(issues())).unwrap();
+        let refreshed = cached_issues_in(dir.path(), "key", TTL, true, || Ok(Vec::new()));
+
+        assert_eq!(refreshed.unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn expired() {
+        let dir = tempfile::tempdir().unwrap();
+        cached_issues_in(dir.path(), "key", Duration::ZERO, false, || Ok(issues())).unwrap();
+        let expired = cached_issues_in(dir.path(), "key", Duration::ZERO, false, || Ok(Vec::new()));
+
+        assert_eq!(expired.unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn failed_fetch_is_not_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let failed = cached_issues_in(dir.path(), "key", TTL, false, || {
+            Err(StepError::ApiResponseError(None))
+        });
+        let retried = cached_issues_in(dir.path(), "key", TTL, false, || Ok(issues()));
+
+        assert!(failed.is_err());
+        assert_eq!(retried.unwrap(), issues());
+    }
+}
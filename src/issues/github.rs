@@ -1,4 +1,5 @@
 use crate::app_config::get_or_prompt_for_github_token;
+use crate::issues::cache::cached_issues;
 use crate::issues::Issue;
 use crate::step::StepError;
 use crate::{config, state};
@@ -22,10 +23,36 @@ struct ResponseIssue {
     title: String,
 }
 
+/// List the open issues with `labels`, which may come from the cache unless `refresh` is set.
 pub(crate) fn list_issues(
     github_config: &config::GitHub,
     github_state: state::GitHub,
     labels: Option<&[String]>,
+    refresh: bool,
+) -> Result<(state::GitHub, Vec<Issue>), StepError> {
+    let cache_key = format!(
+        "github {}/{} {}",
+        github_config.owner,
+        github_config.repo,
+        labels.unwrap_or_default().join(",")
+    );
+    let mut github_state = Some(github_state);
+    let issues = cached_issues(&cache_key, refresh, || {
+        let (new_state, issues) = query_issues(
+            github_config,
+            github_state.take().unwrap_or(state::GitHub::New),
+            labels,
+        )?;
+        github_state = Some(new_state);
+        Ok(issues)
+    })?;
+    Ok((github_state.unwrap_or(state::GitHub::New), issues))
+}
+
+fn query_issues(
+    github_config: &config::GitHub,
+    github_state: state::GitHub,
+    labels: Option<&[String]>,
 ) -> Result<(state::GitHub, Vec<Issue>), StepError> {
     let token = match github_state {
         state::GitHub::Initialized { token } => token,
@@ -55,6 +82,31 @@ pub(crate) fn list_issues(
     Ok((state::GitHub::Initialized { token }, issues))
 }
 
+/// Get the issue with `number`, for its current title.
+pub(crate) fn get_issue(
+    github_config: &config::GitHub,
+    github_state: state::GitHub,
+    number: &str,
+) -> Result<(state::GitHub, Issue), StepError> {
+    let token = match github_state {
+        state::GitHub::Initialized { token } => token,
+        state::GitHub::New => get_or_prompt_for_github_token()?,
+    };
+    let gh_issue: ResponseIssue = ureq::get(&format!(
+        "https://api.github.com/repos/{owner}/{repo}/issues/{number}",
+        owner = github_config.owner,
+        repo = github_config.repo
+    ))
+    .set("Authorization", &format!("bearer {token}"))
+    .call()?
+    .into_json()?;
+    let issue = Issue {
+        key: gh_issue.number.to_string(),
+        summary: gh_issue.title,
+    };
+    Ok((state::GitHub::Initialized { token }, issue))
+}
+
 fn decode_github_response(response: ureq::Response) -> Result<Vec<ResponseIssue>, StepError> {
     let json_value: serde_json::Value = response.into_json()?;
     let json_issues = json_value.pointer("/data/repository/issues/nodes");
@@ -65,3 +117,36 @@ fn decode_github_response(response: ureq::Response) -> Result<Vec<ResponseIssue>
         None => Err(StepError::ApiResponseError(None)),
     }
 }
+
+#[derive(serde::Deserialize)]
+struct IssueState {
+    state: String,
+    /// Only set when the "issue" is really a pull request.
+    pull_request: Option<serde_json::Value>,
+}
+
+/// Close the issue with `number` as completed, unless it's a pull request or already closed.
+/// Returns whether the issue was closed.
+pub(crate) fn close_issue(
+    github_config: &config::GitHub,
+    token: &str,
+    number: &str,
+) -> Result<bool, StepError> {
+    let url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/issues/{number}",
+        owner = github_config.owner,
+        repo = github_config.repo
+    );
+    let authorization = format!("bearer {token}");
+    let issue: IssueState = ureq::get(&url)
+        .set("Authorization", &authorization)
+        .call()?
+        .into_json()?;
+    if issue.pull_request.is_some() || issue.state == "closed" {
+        return Ok(false);
+    }
+    ureq::patch(&url)
+        .set("Authorization", &authorization)
+        .send_json(ureq::json!({"state": "closed", "state_reason": "completed"}))?;
+    Ok(true)
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 use crate::app_config::{get_or_prompt_for_email, get_or_prompt_for_jira_token};
 use crate::config::Jira;
+use crate::issues::adf::markdown_to_adf;
+use crate::issues::cache::cached_issues;
 use crate::issues::Issue;
 use crate::step::StepError;
 
@@ -11,9 +14,20 @@ struct SearchParams {
     fields: Vec<&'static str>,
 }
 
+/// The most issues Jira returns from one search.
+const MAX_RESULTS: usize = 100;
+
 #[derive(Deserialize, Debug)]
 struct IssueFields {
     summary: String,
+    /// Only present when requested.
+    #[serde(default)]
+    issuetype: Option<IssueType>,
+}
+
+#[derive(Deserialize, Debug)]
+struct IssueType {
+    name: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -36,11 +50,21 @@ fn get_auth() -> Result<String, StepError> {
     ))
 }
 
-pub(crate) fn get_issues(jira_config: &Jira, status: &str) -> Result<Vec<Issue>, StepError> {
-    let auth = get_auth()?;
+/// Get the issues in `status`, which may come from the cache unless `refresh` is set.
+pub(crate) fn get_issues(
+    jira_config: &Jira,
+    status: &str,
+    refresh: bool,
+) -> Result<Vec<Issue>, StepError> {
     let jql = format!("status = {} AND project = {}", status, jira_config.project);
     let url = format!("{}/rest/api/3/search", jira_config.url);
-    Ok(ureq::post(&url)
+    let cache_key = format!("jira {url} {jql}");
+    cached_issues(&cache_key, refresh, || search(&url, &jql))
+}
+
+fn search(url: &str, jql: &str) -> Result<Vec<Issue>, StepError> {
+    let auth = get_auth()?;
+    Ok(ureq::post(url)
         .set("Authorization", &auth)
         .send_json(ureq::json!({"jql": jql, "fields": ["summary"]}))?
         .into_json::<SearchResponse>()?
@@ -53,10 +77,67 @@ pub(crate) fn get_issues(jira_config: &Jira, status: &str) -> Result<Vec<Issue>,
         .collect())
 }
 
+/// Get the issue with `issue_key`, for its current summary.
+pub(crate) fn get_issue(jira_config: &Jira, issue_key: &str) -> Result<Issue, StepError> {
+    let auth = get_auth()?;
+    let jira_issue = ureq::get(&format!(
+        "{}/rest/api/3/issue/{}",
+        jira_config.url, issue_key
+    ))
+    .query("fields", "summary")
+    .set("Authorization", &auth)
+    .call()?
+    .into_json::<JiraIssue>()?;
+    Ok(Issue {
+        key: jira_issue.key,
+        summary: jira_issue.fields.summary,
+    })
+}
+
+/// Get the issues with `keys`, along with the name of each one's type (like `Bug`). Keys which
+/// don't exist are left out.
+pub(crate) fn get_issue_types(
+    jira_config: &Jira,
+    keys: &[String],
+) -> Result<Vec<(Issue, String)>, StepError> {
+    let auth = get_auth()?;
+    let url = format!("{}/rest/api/3/search", jira_config.url);
+    let mut issues = Vec::with_capacity(keys.len());
+    // Searched in batches, since Jira won't return more than `MAX_RESULTS` at a time.
+    for batch in keys.chunks(MAX_RESULTS) {
+        let response = ureq::post(&url)
+            .set("Authorization", &auth)
+            .send_json(ureq::json!({
+                "jql": format!("key in ({})", batch.join(", ")),
+                "fields": ["summary", "issuetype"],
+                "maxResults": MAX_RESULTS,
+                // Otherwise, a single missing key fails the whole search.
+                "validateQuery": "warn",
+            }))?
+            .into_json::<SearchResponse>()?;
+        issues.extend(response.issues.into_iter().map(|jira_issue| {
+            let issue_type = jira_issue
+                .fields
+                .issuetype
+                .map_or_else(|| String::from("Other"), |issue_type| issue_type.name);
+            (
+                Issue {
+                    key: jira_issue.key,
+                    summary: jira_issue.fields.summary,
+                },
+                issue_type,
+            )
+        }));
+    }
+    Ok(issues)
+}
+
 pub(crate) fn transition_issue(
     jira_config: &Jira,
     issue_key: &str,
     status: &str,
+    fields: Option<Map<String, Value>>,
+    comment: Option<String>,
 ) -> Result<(), StepError> {
     let auth = get_auth()?; // TODO: get auth once and store in state
     let url = format!(
@@ -74,7 +155,7 @@ pub(crate) fn transition_issue(
     let _response = agent
         .post(&url)
         .set("Authorization", &auth)
-        .send_json(ureq::json!({"transition": {"id": transition.id}}))?;
+        .send_json(PostTransitionBody::new(transition.id, fields, comment))?;
     Ok(())
 }
 
@@ -91,5 +172,100 @@ struct Transition {
 
 #[derive(Debug, Serialize)]
 struct PostTransitionBody {
-    transition: Transition,
+    transition: TransitionId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    update: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct TransitionId {
+    id: String,
+}
+
+impl PostTransitionBody {
+    /// Build the body of a transition. `comment` and any `description` in `fields` are Markdown,
+    /// which is converted to the Atlassian Document Format that Jira expects.
+    fn new(id: String, fields: Option<Map<String, Value>>, comment: Option<String>) -> Self {
+        let fields = fields.map(|mut fields| {
+            if let Some(description) = fields
+                .get("description")
+                .and_then(Value::as_str)
+                .map(markdown_to_adf)
+            {
+                fields.insert(String::from("description"), description);
+            }
+            fields
+        });
+        let update = comment.map(|comment| {
+            ureq::json!({
+                "comment": [{"add": {"body": markdown_to_adf(&comment)}}]
+            })
+        });
+        Self {
+            transition: TransitionId { id },
+            fields,
+            update,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_post_transition_body {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn transition_only() {
+        let body = PostTransitionBody::new(String::from("31"), None, None);
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            json!({"transition": {"id": "31"}})
+        );
+    }
+
+    #[test]
+    fn with_fields_and_comment() {
+        let mut fields = Map::new();
+        fields.insert(String::from("resolution"), json!({"name": "Done"}));
+        let body = PostTransitionBody::new(
+            String::from("31"),
+            Some(fields),
+            Some(String::from("Released in 1.2.3")),
+        );
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            json!({
+                "transition": {"id": "31"},
+                "fields": {"resolution": {"name": "Done"}},
+                "update": {"comment": [{"add": {"body": {
+                    "type": "doc",
+                    "version": 1,
+                    "content": [{"type": "paragraph", "content": [{"type": "text", "text": "Released in 1.2.3"}]}]
+                }}}]}
+            })
+        );
+    }
+
+    #[test]
+    fn markdown_description() {
+        let mut fields = Map::new();
+        fields.insert(String::from("description"), json!("- **Fixed** a bug"));
+        let body = PostTransitionBody::new(String::from("31"), Some(fields), None);
+        assert_eq!(
+            serde_json::to_value(body).unwrap(),
+            json!({
+                "transition": {"id": "31"},
+                "fields": {"description": {
+                    "type": "doc",
+                    "version": 1,
+                    "content": [{"type": "bulletList", "content": [{"type": "listItem", "content": [
+                        {"type": "paragraph", "content": [{"type": "text", "text": "Fixed", "marks": [{"type": "strong"}]}, {"type": "text", "text": " a bug"}]}
+                    ]}]}]
+                }}
+            })
+        );
+    }
 }
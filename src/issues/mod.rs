@@ -1,13 +1,28 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
 
-use crate::prompt::select;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::command::{replace_variables, Variable};
+use crate::prompt::{select, select_many};
 use crate::state::{self, RunType, State};
-use crate::step::StepError;
+use crate::step::{StepError, TransitionJiraIssue};
 
+mod adf;
+mod cache;
 mod github;
 mod jira;
+mod released;
+mod resolved;
+
+pub(super) use released::{references, transition_released_issues, Reference};
+pub(crate) use resolved::resolved_issues_section;
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub(crate) struct Issue {
     pub(crate) key: String,
     pub(crate) summary: String,
@@ -19,7 +34,11 @@ impl fmt::Display for Issue {
     }
 }
 
-pub(super) fn select_jira_issue(status: &str, run_type: RunType) -> Result<RunType, StepError> {
+pub(super) fn select_jira_issue(
+    status: &str,
+    multiple: bool,
+    run_type: RunType,
+) -> Result<RunType, StepError> {
     let (mut state, dry_run_stdout) = run_type.decompose();
     let jira_config = state
         .jira_config
@@ -32,26 +51,25 @@ pub(super) fn select_jira_issue(status: &str, run_type: RunType) -> Result<RunTy
             "Would query configured Jira instance for issues with status {}",
             status
         )?;
-        writeln!(
-            stdout,
-            "Would prompt user to select an issue and move workflow to IssueSelected state."
+        state.issue = fake_selection(
+            &mut stdout,
+            Issue {
+                key: "FAKE-123".to_string(),
+                summary: "Test issue".to_string(),
+            },
+            multiple,
         )?;
-        state.issue = state::Issue::Selected(Issue {
-            key: "FAKE-123".to_string(),
-            summary: "Test issue".to_string(),
-        });
         return Ok(RunType::DryRun { state, stdout });
     }
 
-    let issues = jira::get_issues(jira_config, status)?;
-    let issue = select(issues, "Select an Issue")?;
-    println!("Selected item : {}", &issue);
-    state.issue = state::Issue::Selected(issue);
+    let issues = jira::get_issues(jira_config, status, state.refresh_issues)?;
+    state.issue = select_issues(issues, multiple)?;
     Ok(RunType::Real(state))
 }
 
 pub(super) fn select_github_issue(
     labels: Option<&[String]>,
+    multiple: bool,
     run_type: RunType,
 ) -> Result<RunType, StepError> {
     match run_type {
@@ -74,14 +92,14 @@ pub(super) fn select_github_issue(
                     "Would query configured GitHub instance for issues with any labels"
                 )?;
             }
-            writeln!(
-                stdout,
-                "Would prompt user to select an issue and move workflow to IssueSelected state."
+            state.issue = fake_selection(
+                &mut stdout,
+                Issue {
+                    key: String::from("123"),
+                    summary: String::from("Test issue"),
+                },
+                multiple,
             )?;
-            state.issue = state::Issue::Selected(Issue {
-                key: String::from("123"),
-                summary: String::from("Test issue"),
-            });
             Ok(RunType::DryRun { state, stdout })
         }
         RunType::Real(state) => {
@@ -89,39 +107,206 @@ pub(super) fn select_github_issue(
                 .github_config
                 .as_ref()
                 .ok_or(StepError::GitHubNotConfigured)?;
-            let (github, issues) = github::list_issues(github_config, state.github, labels)?;
-            let issue = select(issues, "Select an Issue")?;
-            println!("Selected item : {}", &issue);
+            let (github, issues) =
+                github::list_issues(github_config, state.github, labels, state.refresh_issues)?;
             Ok(RunType::Real(State {
                 github,
-                issue: state::Issue::Selected(issue),
+                issue: select_issues(issues, multiple)?,
                 ..state
             }))
         }
     }
 }
 
-pub(super) fn transition_jira_issue(status: &str, run_type: RunType) -> Result<RunType, StepError> {
+/// Prompt the user to select one of `issues` or, if `multiple`, any number of them.
+fn select_issues(issues: Vec<Issue>, multiple: bool) -> Result<state::Issue, StepError> {
+    if multiple {
+        let issues = select_many(issues, "Select Issues")?;
+        for issue in &issues {
+            println!("Selected item : {issue}");
+        }
+        Ok(state::Issue::SelectedMany(issues))
+    } else {
+        let issue = select(issues, "Select an Issue")?;
+        println!("Selected item : {}", &issue);
+        Ok(state::Issue::Selected(issue))
+    }
+}
+
+/// Report what [`select_issues`] would do and pretend that `issue` was selected.
+fn fake_selection(
+    stdout: &mut Box<dyn Write>,
+    issue: Issue,
+    multiple: bool,
+) -> Result<state::Issue, StepError> {
+    if multiple {
+        writeln!(
+            stdout,
+            "Would prompt user to select issues and move workflow to IssueSelected state."
+        )?;
+        Ok(state::Issue::SelectedMany(vec![issue]))
+    } else {
+        writeln!(
+            stdout,
+            "Would prompt user to select an issue and move workflow to IssueSelected state."
+        )?;
+        Ok(state::Issue::Selected(issue))
+    }
+}
+
+/// Get the current summary of the issue with `key` from GitHub (for a number, like `42`) or from
+/// Jira (for a key like `PROJ-123`).
+pub(crate) fn fetch_issue(state: State, key: &str) -> Result<(State, Issue), StepError> {
+    if usize::from_str(key).is_ok() {
+        let github_config = state
+            .github_config
+            .as_ref()
+            .ok_or(StepError::GitHubNotConfigured)?;
+        let (github, issue) = github::get_issue(github_config, state.github, key)?;
+        Ok((State { github, ..state }, issue))
+    } else {
+        let jira_config = state
+            .jira_config
+            .as_ref()
+            .ok_or(StepError::JiraNotConfigured)?;
+        let issue = jira::get_issue(jira_config, key)?;
+        Ok((state, issue))
+    }
+}
+
+pub(super) fn transition_jira_issue(
+    transition: TransitionJiraIssue,
+    run_type: RunType,
+) -> Result<RunType, StepError> {
     let (state, dry_run_stdout) = run_type.decompose();
-    let issue = match &state.issue {
-        state::Issue::Selected(issue) => issue,
+    let issues = match &state.issue {
+        state::Issue::Selected(issue) => std::slice::from_ref(issue),
+        state::Issue::SelectedMany(issues) => issues.as_slice(),
         state::Issue::Initial => return Err(StepError::NoIssueSelected),
     };
     let jira_config = state
         .jira_config
         .as_ref()
         .ok_or(StepError::JiraNotConfigured)?;
+    let TransitionJiraIssue {
+        status,
+        fields,
+        comment,
+        variables,
+    } = transition;
+    let variables = variables.unwrap_or_default();
+    let fields = fields
+        .map(|fields| render_fields(fields, &variables, &state))
+        .transpose()?;
+    let comment = comment
+        .map(|comment| replace_variables(&comment, variables.clone(), &state))
+        .transpose()?;
 
     if let Some(mut stdout) = dry_run_stdout {
-        writeln!(
-            stdout,
-            "Would transition currently selected issue to status {}",
-            status
-        )?;
+        if let state::Issue::SelectedMany(issues) = &state.issue {
+            writeln!(
+                stdout,
+                "Would transition selected issues {} to status {status}",
+                issues.iter().map(|issue| issue.key.as_str()).join(", ")
+            )?;
+        } else {
+            writeln!(
+                stdout,
+                "Would transition currently selected issue to status {status}"
+            )?;
+        }
+        if let Some(fields) = &fields {
+            writeln!(stdout, "Would set fields {}", Value::Object(fields.clone()))?;
+        }
+        if let Some(comment) = &comment {
+            writeln!(stdout, "Would add comment {comment}")?;
+        }
         return Ok(RunType::DryRun { state, stdout });
     }
 
-    jira::transition_issue(jira_config, &issue.key, status)?;
-    println!("{} transitioned to {}", &issue.key, status);
+    for issue in issues {
+        jira::transition_issue(
+            jira_config,
+            &issue.key,
+            &status,
+            fields.clone(),
+            comment.clone(),
+        )?;
+        println!("{} transitioned to {}", &issue.key, status);
+    }
     Ok(RunType::Real(state))
 }
+
+type Fields = serde_json::Map<String, Value>;
+
+/// Replace `variables` in every string within `fields`, no matter how deeply nested.
+fn render_fields(
+    fields: Fields,
+    variables: &HashMap<String, Variable>,
+    state: &State,
+) -> Result<Fields, StepError> {
+    fields
+        .into_iter()
+        .map(|(key, value)| Ok((key, render_value(value, variables, state)?)))
+        .collect()
+}
+
+fn render_value(
+    value: Value,
+    variables: &HashMap<String, Variable>,
+    state: &State,
+) -> Result<Value, StepError> {
+    Ok(match value {
+        Value::String(string) => {
+            Value::String(replace_variables(&string, variables.clone(), state)?)
+        }
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|value| render_value(value, variables, state))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(render_fields(fields, variables, state)?),
+        other => other,
+    })
+}
+
+#[cfg(test)]
+mod test_render_fields {
+    use serde_json::json;
+
+    use crate::config::Versioning;
+
+    use super::*;
+
+    #[test]
+    fn nested_strings() {
+        let mut state = State::new(None, None, None, None, Versioning::default(), Vec::new());
+        state.issue = state::Issue::Selected(Issue {
+            key: String::from("ABC-123"),
+            summary: String::from("Something"),
+        });
+        let mut variables = HashMap::new();
+        variables.insert(String::from("$branch"), Variable::IssueBranch);
+        let fields = json!({
+            "resolution": {"name": "Done"},
+            "labels": ["released", "$branch"],
+            "customfield_10000": 5
+        });
+        let fields = match fields {
+            Value::Object(fields) => fields,
+            _ => unreachable!(),
+        };
+
+        let rendered = render_fields(fields, &variables, &state).unwrap();
+
+        assert_eq!(
+            Value::Object(rendered),
+            json!({
+                "resolution": {"name": "Done"},
+                "labels": ["released", "ABC-123-something"],
+                "customfield_10000": 5
+            })
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use itertools::Itertools;
+use log::{debug, warn};
+
+use crate::app_config::get_or_prompt_for_github_token;
+use crate::git::get_commit_messages_in_range;
+use crate::issues::{github, jira};
+use crate::releases::Package;
+use crate::state::GitHub::{Initialized, New};
+use crate::state::Release::{Bumped, Prepared};
+use crate::step::{StepError, TransitionReleasedIssues};
+use crate::{RunType, State};
+
+/// How many issues are transitioned before pausing, unless configured otherwise.
+const DEFAULT_BATCH_SIZE: usize = 10;
+/// How many seconds to pause between batches, unless configured otherwise.
+const DEFAULT_DELAY: u64 = 1;
+/// How many times a rate limited request is retried before giving up.
+const MAX_RETRIES: u32 = 3;
+/// How many seconds to wait before retrying a rate limited request which doesn't say how long.
+const DEFAULT_RETRY_AFTER: u64 = 60;
+
+/// The implementation of [`crate::step::Step::TransitionReleasedIssues`].
+///
+/// Moves every Jira issue referenced (like `PROJ-123`) by the commits of each release prepared in
+/// this workflow to `status`, and closes every referenced GitHub issue (like `#42`). Issues are
+/// handled in batches, pausing in between, and rate limited requests are retried.
+pub(crate) fn transition_released_issues(
+    run_type: RunType,
+    transition: &TransitionReleasedIssues,
+) -> Result<RunType, StepError> {
+    let (mut state, dry_run_stdout) = run_type.decompose();
+    let jira_config = match &transition.status {
+        Some(_) => Some(
+            state
+                .jira_config
+                .clone()
+                .ok_or(StepError::JiraNotConfigured)?,
+        ),
+        None => None,
+    };
+    let github_config = if transition.close_github_issues {
+        Some(
+            state
+                .github_config
+                .clone()
+                .ok_or(StepError::GitHubNotConfigured)?,
+        )
+    } else {
+        None
+    };
+    let references = released_references(
+        &state,
+        jira_config.as_ref().map(|jira| jira.project.as_str()),
+        github_config.is_some(),
+    )?;
+
+    if let Some(mut stdout) = dry_run_stdout {
+        for reference in &references {
+            match (reference, &transition.status) {
+                (Reference::Jira(key), Some(status)) => {
+                    writeln!(stdout, "Would transition {key} to status {status}")?;
+                }
+                (Reference::GitHub(number), _) => writeln!(
+                    stdout,
+                    "Would close GitHub issue #{number} unless it's a pull request or already closed"
+                )?,
+                (Reference::Jira(_), None) => {}
+            }
+        }
+        if references.is_empty() {
+            writeln!(stdout, "Would transition nothing, no issues are referenced")?;
+        }
+        return Ok(RunType::DryRun { state, stdout });
+    }
+
+    let token = if references
+        .iter()
+        .any(|reference| matches!(reference, Reference::GitHub(_)))
+    {
+        let token = match state.github {
+            Initialized { token } => token,
+            New => get_or_prompt_for_github_token()?,
+        };
+        state.github = Initialized {
+            token: token.clone(),
+        };
+        Some(token)
+    } else {
+        None
+    };
+    let batch_size = transition.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let delay = Duration::from_secs(transition.delay.unwrap_or(DEFAULT_DELAY));
+    for (index, batch) in references.chunks(batch_size).enumerate() {
+        if index > 0 {
+            sleep(delay);
+        }
+        for reference in batch {
+            match (reference, &jira_config, &transition.status, &github_config) {
+                (Reference::Jira(key), Some(jira_config), Some(status), _) => {
+                    match with_retries(|| {
+                        jira::transition_issue(jira_config, key, status, None, None)
+                    }) {
+                        Ok(()) => println!("{key} transitioned to {status}"),
+                        // Most likely, the issue is already in `status`.
+                        Err(StepError::InvalidJiraTransition) => {
+                            warn!("{key} can't be transitioned to {status}, skipping it");
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+                (Reference::GitHub(number), _, _, Some(github_config)) => {
+                    let token = token.as_deref().unwrap_or_default();
+                    if with_retries(|| github::close_issue(github_config, token, number))? {
+                        println!("Closed GitHub issue #{number}");
+                    } else {
+                        debug!("#{number} is a pull request or already closed, skipping it");
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(RunType::Real(state))
+}
+
+/// An issue referenced in a commit message.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Reference {
+    /// The key of a Jira issue, like `PROJ-123`.
+    Jira(String),
+    /// The number of a GitHub issue, like `42` for `#42`.
+    GitHub(String),
+}
+
+/// Every issue referenced by the commits of every prepared release, each only once, in the
+/// order they were first referenced.
+fn released_references(
+    state: &State,
+    jira_project: Option<&str>,
+    github: bool,
+) -> Result<Vec<Reference>, StepError> {
+    if state.releases.is_empty() {
+        return Err(StepError::ReleaseNotPrepared);
+    }
+    let mut all_references = Vec::new();
+    for release in &state.releases {
+        let release = match release {
+            Prepared(release) => release,
+            Bumped { .. } => return Err(StepError::ReleaseNotPrepared),
+        };
+        let path_filter = state
+            .packages
+            .iter()
+            .find(|package| package.name == release.package_name)
+            .map(Package::path_filter)
+            .unwrap_or_default();
+        // Oldest first, so issues are transitioned in the order they were worked on.
+        for commit in get_commit_messages_in_range(&release.commits, path_filter)?
+            .iter()
+            .rev()
+        {
+            all_references.extend(references(&commit.message, jira_project, github));
+        }
+    }
+    Ok(all_references.into_iter().unique().collect())
+}
+
+/// The issues referenced in `message`: keys of Jira issues in `jira_project` (if there is one) and,
+/// if `github` is set, GitHub issues in the same repository. References to other GitHub
+/// repositories (like `owner/repo#42`) are left out.
+pub(crate) fn references(
+    message: &str,
+    jira_project: Option<&str>,
+    github: bool,
+) -> Vec<Reference> {
+    let is_number = |word: &str| !word.is_empty() && word.chars().all(|c| c.is_ascii_digit());
+    message
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '#' | '/')))
+        .filter_map(|word| {
+            if let Some(number) = word.strip_prefix('#') {
+                return (github && is_number(number))
+                    .then(|| Reference::GitHub(number.to_string()));
+            }
+            let number = word.strip_prefix(jira_project?)?.strip_prefix('-')?;
+            is_number(number).then(|| Reference::Jira(word.to_string()))
+        })
+        .collect()
+}
+
+/// Run `request`, retrying it (up to [`MAX_RETRIES`] times) whenever it's rate limited, after
+/// waiting as long as the response asks.
+fn with_retries<T>(mut request: impl FnMut() -> Result<T, StepError>) -> Result<T, StepError> {
+    let mut retries = 0;
+    loop {
+        let (status, response) = match request() {
+            Err(StepError::ApiRequestError(ureq::Error::Status(status, response))) => {
+                (status, response)
+            }
+            result => return result,
+        };
+        let seconds = match retry_after(status, &response) {
+            Some(seconds) if retries < MAX_RETRIES => seconds,
+            _ => {
+                return Err(StepError::ApiRequestError(ureq::Error::Status(
+                    status, response,
+                )))
+            }
+        };
+        warn!("Rate limited, retrying in {seconds} seconds");
+        sleep(Duration::from_secs(seconds));
+        retries += 1;
+    }
+}
+
+/// How many seconds to wait before retrying a request which failed with `status`, or `None` if it
+/// wasn't rate limited. Jira and GitHub both send `Retry-After`, but GitHub's primary rate limit
+/// only says when it resets.
+fn retry_after(status: u16, response: &ureq::Response) -> Option<u64> {
+    if let Some(seconds) = response
+        .header("retry-after")
+        .and_then(|seconds| seconds.trim().parse().ok())
+    {
+        return Some(seconds);
+    }
+    if response.header("x-ratelimit-remaining") == Some("0") {
+        let reset = response.header("x-ratelimit-reset")?.parse::<u64>().ok()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        return Some(reset.saturating_sub(now) + 1);
+    }
+    (status == 429).then_some(DEFAULT_RETRY_AFTER)
+}
+
+#[cfg(test)]
+mod test_references {
+    use super::*;
+
+    #[test]
+    fn jira_and_github() {
+        let message = "feat: PROJ-12 Add a thing (#45)\n\nCloses #7, refs OTHER-3 and owner/repo#8\n\nIssue: PROJ-13\n";
+        assert_eq!(
+            references(message, Some("PROJ"), true),
+            vec![
+                Reference::Jira(String::from("PROJ-12")),
+                Reference::GitHub(String::from("45")),
+                Reference::GitHub(String::from("7")),
+                Reference::Jira(String::from("PROJ-13")),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_configured_trackers() {
+        let message = "fix: PROJ-12 and #45";
+        assert_eq!(
+            references(message, None, true),
+            vec![Reference::GitHub(String::from("45"))]
+        );
+        assert_eq!(
+            references(message, Some("PROJ"), false),
+            vec![Reference::Jira(String::from("PROJ-12"))]
+        );
+    }
+
+    #[test]
+    fn not_references() {
+        assert!(references("PROJ-12a SUBPROJ-3 #4b PROJ-", Some("PROJ"), true).is_empty());
+    }
+}
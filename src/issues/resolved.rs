@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use itertools::Itertools;
+
+use crate::config::Jira;
+use crate::git::{get_commit_messages_in_range, PathFilter};
+use crate::issues::{jira, references, Issue, Reference};
+use crate::releases::Release;
+use crate::step::StepError;
+
+/// The "Issues resolved" section which [`crate::step::Step::Release`] adds to the GitHub release
+/// of `release` when `jira_issues` is set: a link to every Jira issue referenced by the commits it
+/// was prepared from (those allowed by `path_filter`), grouped by the type of issue. `None` if no
+/// issues are referenced.
+///
+/// Jira isn't asked about the issues in a dry run, so nothing is added then.
+pub(crate) fn resolved_issues_section(
+    jira_config: &Jira,
+    release: &Release,
+    path_filter: PathFilter,
+    dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<Option<String>, StepError> {
+    // Oldest first, in the order the issues were worked on.
+    let keys = get_commit_messages_in_range(&release.commits, path_filter)?
+        .iter()
+        .rev()
+        .flat_map(|commit| references(&commit.message, Some(&jira_config.project), false))
+        .filter_map(|reference| match reference {
+            Reference::Jira(key) => Some(key),
+            Reference::GitHub(_) => None,
+        })
+        .unique()
+        .collect_vec();
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    if let Some(stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would add an Issues resolved section linking {} to the release",
+            keys.join(", ")
+        )?;
+        return Ok(None);
+    }
+    let issues = jira::get_issue_types(jira_config, &keys)?;
+    // In the order they were referenced, not the order Jira found them.
+    let issues = keys
+        .iter()
+        .filter_map(|key| issues.iter().find(|(issue, _)| &issue.key == key))
+        .collect_vec();
+    Ok(Some(issues_section(&jira_config.url, &issues)))
+}
+
+/// A Markdown section linking to each of `issues` (with the name of its type) in Jira at `url`.
+fn issues_section(url: &str, issues: &[&(Issue, String)]) -> String {
+    let mut by_type: BTreeMap<&str, Vec<&Issue>> = BTreeMap::new();
+    for (issue, issue_type) in issues {
+        by_type.entry(issue_type).or_default().push(issue);
+    }
+    let url = url.trim_end_matches('/');
+    let groups = by_type
+        .into_iter()
+        .map(|(issue_type, issues)| {
+            let links = issues
+                .iter()
+                .map(|issue| {
+                    format!(
+                        "- [{key}]({url}/browse/{key}) {summary}\n",
+                        key = issue.key,
+                        summary = issue.summary
+                    )
+                })
+                .join("");
+            format!("\n#### {issue_type}\n\n{links}")
+        })
+        .join("");
+    format!("### Issues resolved\n{groups}")
+}
+
+#[cfg(test)]
+mod test_issues_section {
+    use super::*;
+
+    fn issue(key: &str, summary: &str, issue_type: &str) -> (Issue, String) {
+        (
+            Issue {
+                key: String::from(key),
+                summary: String::from(summary),
+            },
+            String::from(issue_type),
+        )
+    }
+
+    #[test]
+    fn grouped_by_type() {
+        let issues = [
+            issue("PROJ-3", "Add a thing", "Story"),
+            issue("PROJ-1", "Fix a thing", "Bug"),
+            issue("PROJ-2", "Add another thing", "Story"),
+        ];
+        let issues = issues.iter().collect_vec();
+        assert_eq!(
+            issues_section("https://knope.atlassian.net/", &issues),
+            "### Issues resolved\n\
+            \n\
+            #### Bug\n\
+            \n\
+            - [PROJ-1](https://knope.atlassian.net/browse/PROJ-1) Fix a thing\n\
+            \n\
+            #### Story\n\
+            \n\
+            - [PROJ-3](https://knope.atlassian.net/browse/PROJ-3) Add a thing\n\
+            - [PROJ-2](https://knope.atlassian.net/browse/PROJ-2) Add another thing\n"
+        );
+    }
+}
@@ -4,25 +4,39 @@
 #![allow(clippy::multiple_crate_versions)] // Let cargo-deny handle this
 #![forbid(unsafe_code)]
 
-use std::io::stdout;
+use std::io::{stdout, Write};
+use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use miette::{miette, Result};
+use semver::Version;
 
 use prompt::select;
 
 use crate::config::Config;
+use crate::releases::{Audit, Yank, YankRelease};
 use crate::state::{RunType, State};
+use crate::workflow::Workflow;
 
 mod app_config;
+mod ci;
+mod codeowners;
 mod command;
 mod config;
+mod email;
+mod freeze;
+mod generate_file;
 mod git;
 mod issues;
+mod new_workflow;
 mod prompt;
+mod pull_request;
 mod releases;
+mod sentry;
 mod state;
 mod step;
+mod template;
+mod update;
 mod workflow;
 
 /// The main entry point for the application.
@@ -34,11 +48,33 @@ mod workflow;
 /// 3. Selected workflow not found
 /// 4. Passthrough errors of selected workflow
 pub fn run(cli: Cli) -> Result<()> {
+    if let Some(Tool::MergeChangelog { base, ours, theirs }) = &cli.tool {
+        return releases::merge_changelog(base, ours, theirs);
+    }
+
+    if let Some(Tool::PrepareCommitMsg {
+        message_file,
+        source,
+        trailer,
+    }) = &cli.tool
+    {
+        git::add_issue_trailer(message_file, source.as_deref(), trailer)?;
+        return Ok(());
+    }
+
     if cli.generate {
         println!("Generating a knope.toml file");
         return config::generate();
     }
 
+    if let Some(Tool::Detect) = &cli.tool {
+        return releases::detect();
+    }
+
+    if let Some(Tool::NewWorkflow) = &cli.tool {
+        return new_workflow::new_workflow();
+    }
+
     let preselected_workflow = cli.workflow;
 
     let mut config = Config::load()?;
@@ -53,43 +89,141 @@ pub fn run(cli: Cli) -> Result<()> {
         };
     }
 
-    if let Some(prerelease_label) = cli.prerelease_label {
-        config.set_prerelease_label(&prerelease_label);
+    if let Some(prerelease_label) = &cli.prerelease_label {
+        config.set_prerelease_label(prerelease_label);
+    }
+    let mut packages = config.packages()?;
+    releases::set_override_versions(&mut packages, &cli.override_version)?;
+    let mut state = State::new(
+        config.jira,
+        config.github,
+        config.smtp,
+        config.sentry,
+        config.versioning,
+        packages,
+    );
+    state.refresh_issues = cli.refresh;
+    state.protected_branches = config.protected_branches.unwrap_or_default();
+
+    if let Some(tool) = cli.tool {
+        let prerelease_label = cli
+            .prerelease_label
+            .as_deref()
+            .filter(|label| !label.is_empty());
+        return run_tool(tool, state, cli.dry_run, prerelease_label);
     }
-    let packages = config.packages()?;
-    let state = State::new(config.jira, config.github, packages);
 
     if cli.validate {
+        for warning in releases::tag_prefix_warnings(&state.packages, state.versioning) {
+            println!("WARNING: {warning}");
+        }
         workflow::validate(config.workflows, state)?;
         return Ok(());
     }
 
+    let workflow = select_workflow(config.workflows, preselected_workflow, &cli.skip, &cli.only)?;
+    workflow::lint(&workflow)?;
+    if workflow.releases() && !cli.override_freeze {
+        freeze::check(&config.release_freezes.unwrap_or_default())?;
+    }
+    workflow.check_requirements()?;
+
+    let state = if cli.dry_run {
+        RunType::DryRun {
+            state,
+            stdout: Box::new(stdout()),
+        }
+    } else {
+        RunType::Real(state)
+    };
+
+    workflow::run(workflow, state)?;
+    if config.check_for_updates {
+        update::check();
+    }
+    Ok(())
+}
+
+/// Find the workflow named `preselected_workflow` (or prompt for one), keep only the steps picked
+/// by `skip` and `only`, and drop the steps meant for other platforms.
+fn select_workflow(
+    workflows: Vec<Workflow>,
+    preselected_workflow: Option<String>,
+    skip: &[String],
+    only: &[String],
+) -> Result<Workflow> {
     let workflow_name = if let Some(workflow_name) = preselected_workflow {
         workflow_name
     } else {
-        let workflow_names: Vec<&str> = config
-            .workflows
+        let workflow_names: Vec<&str> = workflows
             .iter()
             .map(|workflow| workflow.name.as_str())
             .collect();
         select(workflow_names, "Select a workflow").map(String::from)?
     };
-    let workflow = config
-        .workflows
+    let mut workflow = workflows
         .into_iter()
         .find(|w| w.name == workflow_name)
         .ok_or_else(|| miette!("No workflow named {}", workflow_name))?;
+    workflow.select_steps(skip, only).map_err(|name| {
+        miette!(
+            "No step named or tagged {} in workflow {}",
+            name,
+            workflow_name
+        )
+    })?;
+    workflow.remove_other_platforms();
+    Ok(workflow)
+}
 
-    let state = if cli.dry_run {
-        RunType::DryRun {
-            state,
-            stdout: Box::new(stdout()),
+/// Run one of the [`Tool`]s which need the packages from `knope.toml`.
+fn run_tool(tool: Tool, state: State, dry_run: bool, prerelease_label: Option<&str>) -> Result<()> {
+    let dry_run_stdout = dry_run.then(|| Box::new(stdout()) as Box<dyn Write>);
+    match tool {
+        Tool::NextVersion => releases::next_version(&state, prerelease_label, &mut stdout())?,
+        Tool::RegenerateChangelog => releases::regenerate_changelog(&state, dry_run_stdout)?,
+        Tool::Yank {
+            version,
+            package,
+            draft,
+            prerelease,
+            remote,
+            revert,
+            yes,
+        } => {
+            let github_release = match (draft, prerelease) {
+                (true, _) => YankRelease::Draft,
+                (false, true) => YankRelease::Prerelease,
+                (false, false) => YankRelease::Delete,
+            };
+            let yank = Yank {
+                version,
+                package,
+                github_release,
+                remote,
+                revert,
+                yes,
+            };
+            releases::yank(&yank, state, dry_run_stdout)?;
         }
-    } else {
-        RunType::Real(state)
-    };
-
-    workflow::run(workflow, state)?;
+        Tool::Audit {
+            version,
+            package,
+            json,
+        } => {
+            let audit = Audit {
+                version,
+                package,
+                json,
+            };
+            releases::audit(&audit, &state, &mut stdout())?;
+        }
+        // These don't need `knope.toml`, so they've already been run.
+        Tool::MergeChangelog { .. }
+        | Tool::PrepareCommitMsg { .. }
+        | Tool::Detect
+        | Tool::NewWorkflow => {}
+    }
     Ok(())
 }
 
@@ -100,11 +234,14 @@ pub fn run(cli: Cli) -> Result<()> {
 #[clap(author, version, about, long_about = None)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Cli {
+    #[clap(subcommand)]
+    tool: Option<Tool>,
+
     /// Name a workflow to bypass the interactive select and just run it. If not provided,
     /// you'll be asked to select one.
     workflow: Option<String>,
 
-    #[clap(long)]
+    #[clap(long, global = true)]
     /// Pretend to run a workflow, outputting what _would_ happen without actually doing it.
     dry_run: bool,
 
@@ -112,10 +249,33 @@ pub struct Cli {
     /// Generate a new `knope.toml` file.
     generate: bool,
 
-    #[clap(long, env = "KNOPE_PRERELEASE_LABEL")]
-    /// Set the `prerelease_label` attribute of any `PrepareRelease` steps at runtime.
+    #[clap(long, env = "KNOPE_PRERELEASE_LABEL", global = true)]
+    /// Set the `prerelease_label` attribute of any `PrepareRelease` steps at runtime. An empty label
+    /// removes it instead, for a stable release.
     prerelease_label: Option<String>,
 
+    #[clap(long, value_name = "[PACKAGE=]VERSION", global = true)]
+    /// Bump to this version instead of the one that `PrepareRelease` or `BumpVersion` would
+    /// compute. Prefix it with a package name (like `knope=1.0.0`) to only override the version of
+    /// that package. Can be used multiple times.
+    override_version: Vec<String>,
+
+    #[clap(long, value_name = "NAME")]
+    /// Skip any step in the workflow with this `name` or tag. Can be used multiple times.
+    skip: Vec<String>,
+
+    #[clap(long, value_name = "NAME")]
+    /// Only run the steps in the workflow with this `name` or tag. Can be used multiple times.
+    only: Vec<String>,
+
+    #[clap(long)]
+    /// Query issue trackers again instead of using issues cached by a recent run.
+    refresh: bool,
+
+    #[clap(long)]
+    /// Release even if today is in one of the `release_freezes` in `knope.toml`.
+    override_freeze: bool,
+
     #[clap(long)]
     /// Upgrade to the latest `knope.toml` syntax from any deprecated (but still supported) syntax.
     upgrade: bool,
@@ -125,6 +285,90 @@ pub struct Cli {
     validate: bool,
 }
 
+/// Commands which are run by other tools, rather than being workflows.
+#[derive(Clone, Subcommand)]
+enum Tool {
+    /// Merge two versions of a changelog without conflicts, for use as a Git merge driver.
+    ///
+    /// Configure it with `git config merge.knope-changelog.driver "knope merge-changelog %O %A %B"`
+    /// and `CHANGELOG.md merge=knope-changelog` in `.gitattributes`.
+    MergeChangelog {
+        /// The common ancestor of both versions (`%O`).
+        base: PathBuf,
+        /// The current version (`%A`), where the result is written.
+        ours: PathBuf,
+        /// The version being merged in (`%B`).
+        theirs: PathBuf,
+    },
+    /// Add the issue that the current branch is for to a commit message as a trailer, for use as
+    /// Git's `prepare-commit-msg` (or `commit-msg`) hook.
+    ///
+    /// Install it with `knope prepare-commit-msg "$1" "$2"` in `.git/hooks/prepare-commit-msg`.
+    PrepareCommitMsg {
+        /// The file containing the commit message (`$1`).
+        message_file: PathBuf,
+        /// Where the commit message came from (`$2`), like `message` or `merge`.
+        source: Option<String>,
+        #[clap(long, default_value = "Issue")]
+        /// The key of the trailer, like `Refs` for `Refs: PROJ-123`.
+        trailer: String,
+    },
+    /// List every supported versioned file in the current directory along with its version, and
+    /// warn about files in the same directory which disagree.
+    ///
+    /// Doesn't need a `knope.toml`.
+    Detect,
+    /// Answer a few questions to add a new workflow to `knope.toml`.
+    NewWorkflow,
+    /// Print the next version of each package (and the rule from conventional commits that
+    /// produced it) without changing anything.
+    NextVersion,
+    /// Rebuild the changelog of each package from scratch, with an entry for every stable version
+    /// tagged in the Git history.
+    ///
+    /// Replaces everything after the title of the existing changelog.
+    RegenerateChangelog,
+    /// Pull a bad release: delete its GitHub release and its Git tag, and optionally revert it.
+    ///
+    /// Asks for confirmation before each change.
+    Yank {
+        /// The version to yank, like `1.2.3`.
+        version: Version,
+        #[clap(long, value_name = "NAME")]
+        /// The package the version belongs to, required when there are multiple packages.
+        package: Option<String>,
+        #[clap(long)]
+        /// Convert the GitHub release to a draft instead of deleting it.
+        draft: bool,
+        #[clap(long, conflicts_with = "draft")]
+        /// Mark the GitHub release as a pre-release instead of deleting it.
+        prerelease: bool,
+        #[clap(long, default_value = "origin")]
+        /// The Git remote to delete the tag from.
+        remote: String,
+        #[clap(long)]
+        /// Also revert the commit that the release was tagged on.
+        revert: bool,
+        #[clap(long)]
+        /// Make every change without asking for confirmation first.
+        yes: bool,
+    },
+    /// List the commits, authors, issues, pull requests, and approvals that went into a release,
+    /// as evidence for compliance reviews.
+    ///
+    /// Pull requests and approvals are only listed if GitHub is configured. Nothing is changed.
+    Audit {
+        /// The version to audit, like `1.2.3`.
+        version: Version,
+        #[clap(long, value_name = "NAME")]
+        /// The package the version belongs to, required when there are multiple packages.
+        package: Option<String>,
+        #[clap(long)]
+        /// Print JSON instead of Markdown.
+        json: bool,
+    },
+}
+
 #[cfg(test)]
 mod tests {
     use clap::CommandFactory;
@@ -0,0 +1,283 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use miette::{miette, IntoDiagnostic, Result};
+use serde::Serialize;
+
+use crate::config::{github_from_remote, release_steps, Config, GitHub, Jira};
+use crate::prompt::{get_input, select};
+use crate::step::{Step, TransitionJiraIssue};
+use crate::workflow::Workflow;
+
+/// The implementation of `knope new-workflow`.
+///
+/// Asks a few questions about the project, then appends a matching workflow (and any config it
+/// requires) to `knope.toml`.
+pub(crate) fn new_workflow() -> Result<()> {
+    let config = Config::load()?;
+    let answers = ask()?;
+    if config
+        .workflows
+        .iter()
+        .any(|workflow| workflow.name == answers.name())
+    {
+        return Err(miette!(
+            "A workflow named {} already exists",
+            answers.name()
+        ));
+    }
+
+    let jira = match (&answers, &config.jira) {
+        (
+            Answers::Issue {
+                tracker: Tracker::Jira { .. },
+                ..
+            },
+            None,
+        ) => Some(Jira {
+            url: get_input("What is the URL of your Jira instance?")?,
+            project: get_input("What is the key of your Jira project?")?,
+        }),
+        _ => None,
+    };
+    let github = if answers.uses_github() && config.github.is_none() {
+        Some(github_from_remote().map_or_else(prompt_for_github, Ok)?)
+    } else {
+        None
+    };
+
+    let addition = Addition {
+        jira,
+        github,
+        workflows: vec![answers.into_workflow()],
+    };
+    let contents = toml::to_string(&addition).into_diagnostic()?;
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(Config::CONFIG_PATH)
+        .into_diagnostic()?;
+    writeln!(file, "\n{contents}").into_diagnostic()?;
+    println!(
+        "Added workflow {} to {}",
+        addition.workflows[0].name,
+        Config::CONFIG_PATH
+    );
+    Ok(())
+}
+
+/// The parts of `knope.toml` to append.
+#[derive(Serialize)]
+struct Addition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jira: Option<Jira>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    github: Option<GitHub>,
+    workflows: Vec<Workflow>,
+}
+
+#[derive(Debug)]
+enum Answers {
+    /// A workflow to select an issue and start working on it.
+    Issue { name: String, tracker: Tracker },
+    /// A workflow to release every package.
+    Release {
+        name: String,
+        forge: Forge,
+        monorepo: bool,
+    },
+}
+
+#[derive(Debug)]
+enum Tracker {
+    Jira {
+        select_status: String,
+        transition_status: String,
+    },
+    GitHub,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Forge {
+    GitHub,
+    None,
+}
+
+const ISSUE_WORKFLOW: &str = "Start working on an issue";
+const RELEASE_WORKFLOW: &str = "Release";
+
+fn ask() -> Result<Answers> {
+    let name = get_input("What should the workflow be named?")?;
+    let kind = select(
+        vec![ISSUE_WORKFLOW, RELEASE_WORKFLOW],
+        "What should the workflow do?",
+    )?;
+    if kind == ISSUE_WORKFLOW {
+        let tracker = match select(vec!["Jira", "GitHub"], "Which issue tracker do you use?")? {
+            "Jira" => Tracker::Jira {
+                select_status: get_input("Which status should issues be selected from?")?,
+                transition_status: get_input("Which status should the selected issue move to?")?,
+            },
+            _ => Tracker::GitHub,
+        };
+        Ok(Answers::Issue { name, tracker })
+    } else {
+        let forge = match select(
+            vec!["GitHub", "None (only create Git tags)"],
+            "Where should releases be created?",
+        )? {
+            "GitHub" => Forge::GitHub,
+            _ => Forge::None,
+        };
+        let monorepo = select(
+            vec!["No", "Yes"],
+            "Is this a monorepo with multiple packages?",
+        )? == "Yes";
+        Ok(Answers::Release {
+            name,
+            forge,
+            monorepo,
+        })
+    }
+}
+
+fn prompt_for_github() -> Result<GitHub> {
+    Ok(GitHub {
+        owner: get_input("Which user or organization owns the GitHub repo?")?,
+        repo: get_input("What is the name of the GitHub repo?")?,
+    })
+}
+
+impl Answers {
+    fn name(&self) -> &str {
+        match self {
+            Answers::Issue { name, .. } | Answers::Release { name, .. } => name,
+        }
+    }
+
+    fn uses_github(&self) -> bool {
+        matches!(
+            self,
+            Answers::Issue {
+                tracker: Tracker::GitHub,
+                ..
+            } | Answers::Release {
+                forge: Forge::GitHub,
+                ..
+            }
+        )
+    }
+
+    fn into_workflow(self) -> Workflow {
+        match self {
+            Answers::Issue { name, tracker } => {
+                let mut steps = match tracker {
+                    Tracker::Jira {
+                        select_status,
+                        transition_status,
+                    } => vec![
+                        Step::SelectJiraIssue {
+                            status: select_status,
+                            multiple: false,
+                        }
+                        .into(),
+                        Step::TransitionJiraIssue(TransitionJiraIssue {
+                            status: transition_status,
+                            fields: None,
+                            comment: None,
+                            variables: None,
+                        })
+                        .into(),
+                    ],
+                    Tracker::GitHub => vec![Step::SelectGitHubIssue {
+                        labels: None,
+                        multiple: false,
+                    }
+                    .into()],
+                };
+                steps.push(Step::SwitchBranches.into());
+                Workflow { name, steps }
+            }
+            Answers::Release {
+                name,
+                forge,
+                monorepo,
+            } => Workflow {
+                name,
+                steps: release_steps(forge == Forge::GitHub, monorepo),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_new_workflow {
+    use super::*;
+
+    #[test]
+    fn jira_issue_workflow() {
+        let addition = Addition {
+            jira: Some(Jira {
+                url: String::from("https://knope.atlassian.net"),
+                project: String::from("KNOPE"),
+            }),
+            github: None,
+            workflows: vec![Answers::Issue {
+                name: String::from("start"),
+                tracker: Tracker::Jira {
+                    select_status: String::from("Backlog"),
+                    transition_status: String::from("In Progress"),
+                },
+            }
+            .into_workflow()],
+        };
+
+        assert_eq!(
+            toml::to_string(&addition).unwrap(),
+            r#"[jira]
+url = "https://knope.atlassian.net"
+project = "KNOPE"
+
+[[workflows]]
+name = "start"
+
+[[workflows.steps]]
+type = "SelectJiraIssue"
+status = "Backlog"
+
+[[workflows.steps]]
+type = "TransitionJiraIssue"
+status = "In Progress"
+
+[[workflows.steps]]
+type = "SwitchBranches"
+"#
+        );
+    }
+
+    #[test]
+    fn monorepo_release_workflow() {
+        let answers = Answers::Release {
+            name: String::from("release"),
+            forge: Forge::GitHub,
+            monorepo: true,
+        };
+        assert!(answers.uses_github());
+        let workflow = answers.into_workflow();
+
+        assert_eq!(
+            toml::to_string(&workflow).unwrap(),
+            r#"name = "release"
+
+[[steps]]
+type = "PrepareRelease"
+
+[[steps]]
+type = "Command"
+command = "git commit -m \"chore: prepare releases\" && git push"
+
+[[steps]]
+type = "Release"
+"#
+        );
+    }
+}
@@ -1,13 +1,16 @@
+use std::env;
 use std::fmt::Display;
 
 use console::Term;
 use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Input, Select};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
 use miette::Result;
 
+use crate::ci::is_interactive;
 use crate::step::StepError;
 
 pub(crate) fn select<T: Display>(mut items: Vec<T>, prompt: &str) -> Result<T, StepError> {
+    ensure_interactive(prompt)?;
     let selection = Select::with_theme(&ColorfulTheme::default())
         .items(&items)
         .default(0)
@@ -21,9 +24,49 @@ pub(crate) fn select<T: Display>(mut items: Vec<T>, prompt: &str) -> Result<T, S
     }
 }
 
+/// Like [`select`], but any number of `items` can be selected. Selecting none counts as not
+/// answering.
+pub(crate) fn select_many<T: Display>(items: Vec<T>, prompt: &str) -> Result<Vec<T>, StepError> {
+    ensure_interactive(prompt)?;
+    let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+        .items(&items)
+        .with_prompt(prompt)
+        .interact_on_opt(&Term::stdout())
+        .map_err(|e| StepError::UserInput(Some(e)))?;
+
+    match selection {
+        Some(indices) if !indices.is_empty() => Ok(items
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| indices.contains(index))
+            .map(|(_, item)| item)
+            .collect()),
+        _ => Err(StepError::UserInput(None)),
+    }
+}
+
 pub(crate) fn get_input(prompt: &str) -> Result<String, StepError> {
+    ensure_interactive(prompt)?;
     Input::with_theme(&ColorfulTheme::default())
         .with_prompt(prompt)
         .interact_text()
         .map_err(|e| StepError::UserInput(Some(e)))
 }
+
+pub(crate) fn confirm(prompt: &str) -> Result<bool, StepError> {
+    ensure_interactive(prompt)?;
+    Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(false)
+        .interact_on(&Term::stdout())
+        .map_err(|e| StepError::UserInput(Some(e)))
+}
+
+/// Fail right away when running in CI, where nobody is around to answer `prompt`.
+fn ensure_interactive(prompt: &str) -> Result<(), StepError> {
+    if is_interactive(|key| env::var(key).ok()) {
+        Ok(())
+    } else {
+        Err(StepError::NonInteractive(prompt.to_string()))
+    }
+}
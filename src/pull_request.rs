@@ -0,0 +1,420 @@
+use std::env;
+use std::io::sink;
+
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::get_or_prompt_for_github_token;
+use crate::codeowners::owners_of_changes;
+use crate::config::GitHub;
+use crate::git::current_branch;
+use crate::releases;
+use crate::state::GitHub::{Initialized, New};
+use crate::state::Release;
+use crate::step::{CreatePullRequest, PrepareRelease, StepError};
+use crate::RunType;
+
+/// Identifies the comment created by [`comment_release_preview`] so it can be updated later.
+const PREVIEW_MARKER: &str = "<!-- knope-release-preview -->";
+
+/// The implementation of [`crate::step::Step::CreatePullRequest`].
+///
+/// Opens a pull request on GitHub from the current branch into `base`, then routes it to any
+/// configured reviewers, assignees, and labels—along with the code owners of the changed files,
+/// if `codeowners` is set.
+pub(crate) fn create_pull_request(
+    run_type: RunType,
+    create_pull_request: CreatePullRequest,
+) -> Result<RunType, StepError> {
+    let (mut state, dry_run_stdout) = run_type.decompose();
+    let github_config = state
+        .github_config
+        .clone()
+        .ok_or(StepError::GitHubNotConfigured)?;
+    let CreatePullRequest {
+        base,
+        title,
+        body,
+        reviewers,
+        team_reviewers,
+        assignees,
+        labels,
+        codeowners,
+    } = create_pull_request;
+    let title = title.render(&state)?;
+    let body = body.render(&state)?;
+    let mut routing = Routing {
+        reviewers: reviewers.unwrap_or_default(),
+        team_reviewers: team_reviewers.unwrap_or_default(),
+        assignees: assignees.unwrap_or_default(),
+        labels: labels.unwrap_or_default(),
+    };
+    if codeowners {
+        let owners = owners_of_changes(&base, &github_config.owner)?;
+        for user in owners.users {
+            if !routing.reviewers.contains(&user) {
+                routing.reviewers.push(user);
+            }
+        }
+        for team in owners.teams {
+            if !routing.team_reviewers.contains(&team) {
+                routing.team_reviewers.push(team);
+            }
+        }
+    }
+
+    if let Some(mut stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would create a pull request on GitHub into {base} with title {title} and body:\n{body}"
+        )?;
+        for (description, values) in [
+            ("request reviews from", &routing.reviewers),
+            ("request reviews from teams", &routing.team_reviewers),
+            ("assign", &routing.assignees),
+            ("add labels", &routing.labels),
+        ] {
+            if !values.is_empty() {
+                writeln!(stdout, "Would {description} {}", values.join(", "))?;
+            }
+        }
+        return Ok(RunType::DryRun { state, stdout });
+    }
+
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let head = current_branch(&repo)?;
+
+    let token = match state.github {
+        Initialized { token } => token,
+        New => get_or_prompt_for_github_token()?,
+    };
+    let CreatedPullRequest { number, user } = open_pull_request(
+        &github_config,
+        &token,
+        &GitHubPullRequest {
+            title: &title,
+            head: &head,
+            base: &base,
+            body: &body,
+        },
+    )?;
+    println!("Created pull request #{number} {title}");
+    // GitHub refuses to request a review from the author, who may well be a code owner.
+    routing.reviewers.retain(|reviewer| *reviewer != user.login);
+    routing.apply(&Api::new(&github_config, &token), number)?;
+
+    state.github = Initialized { token };
+    Ok(RunType::Real(state))
+}
+
+/// Open `pull_request` in the GitHub repo of `github_config`.
+pub(crate) fn open_pull_request(
+    github_config: &GitHub,
+    token: &str,
+    pull_request: &GitHubPullRequest,
+) -> Result<CreatedPullRequest, StepError> {
+    let api = Api::new(github_config, token);
+    let response = ureq::post(&api.url("pulls"))
+        .set("Authorization", &api.authorization)
+        .send_json(pull_request)?;
+    if response.status() != 201 {
+        return Err(StepError::ApiResponseError(None));
+    }
+    Ok(response.into_json()?)
+}
+
+/// The implementation of [`crate::step::Step::CommentReleasePreview`].
+///
+/// Works out what [`crate::step::Step::PrepareRelease`] would do (without changing any files),
+/// then comments it on the pull request for the current branch—replacing any earlier preview.
+pub(crate) fn comment_release_preview(run_type: RunType) -> Result<RunType, StepError> {
+    let (mut state, dry_run_stdout) = run_type.decompose();
+    let github_config = state
+        .github_config
+        .clone()
+        .ok_or(StepError::GitHubNotConfigured)?;
+    let preview = RunType::DryRun {
+        state: state.clone(),
+        stdout: Box::new(sink()),
+    };
+    let prepare_release = PrepareRelease {
+        prerelease_label: None,
+        prerelease_start: None,
+        first_parent: None,
+        record: None,
+    };
+    let (preview, _) = releases::prepare_release(preview, &prepare_release)?.decompose();
+    let body = preview_body(&preview.releases);
+
+    if let Some(mut stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would comment on the pull request for the current branch with:\n{body}"
+        )?;
+        return Ok(RunType::DryRun { state, stdout });
+    }
+
+    let token = match state.github {
+        Initialized { token } => token,
+        New => get_or_prompt_for_github_token()?,
+    };
+    let api = Api::new(&github_config, &token);
+    let number = current_pull_request(&api, &github_config)?;
+    let comments: Vec<Comment> = ureq::get(&api.url(&format!("issues/{number}/comments")))
+        .query("per_page", "100")
+        .set("Authorization", &api.authorization)
+        .call()?
+        .into_json()?;
+    let existing = comments
+        .iter()
+        .find(|comment| comment.body.starts_with(PREVIEW_MARKER));
+    let request = if let Some(comment) = existing {
+        ureq::patch(&api.url(&format!("issues/comments/{}", comment.id)))
+    } else {
+        ureq::post(&api.url(&format!("issues/{number}/comments")))
+    };
+    request
+        .set("Authorization", &api.authorization)
+        .send_json(ureq::json!({ "body": body }))?;
+    println!("Commented a release preview on pull request #{number}");
+
+    state.github = Initialized { token };
+    Ok(RunType::Real(state))
+}
+
+/// The Markdown body of a release preview comment.
+fn preview_body(releases: &[Release]) -> String {
+    let releases = releases
+        .iter()
+        .filter_map(|release| match release {
+            Release::Prepared(release) => Some(release),
+            Release::Bumped { .. } => None,
+        })
+        .collect::<Vec<_>>();
+    if releases.is_empty() {
+        return format!(
+            "{PREVIEW_MARKER}\n## Release Preview\n\nMerging this pull request will not release anything.\n"
+        );
+    }
+    let rows = releases
+        .iter()
+        .map(|release| {
+            format!(
+                "| {} | {} | {} | {} |",
+                release.package_name.as_deref().unwrap_or("package"),
+                release.previous_version,
+                release.version,
+                release.bump_level
+            )
+        })
+        .collect::<Vec<_>>();
+    let changelogs = releases
+        .iter()
+        .map(|release| {
+            // Replace the version header with one that includes the package name, and nest the
+            // rest of the headers under it.
+            let notes = release
+                .changelog
+                .split_once('\n')
+                .map_or("", |(_, notes)| notes)
+                .lines()
+                .map(|line| {
+                    if line.starts_with('#') {
+                        format!("#{line}\n")
+                    } else {
+                        format!("{line}\n")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .concat();
+            format!(
+                "### {} {}\n{notes}",
+                release.package_name.as_deref().unwrap_or("package"),
+                release.version
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{PREVIEW_MARKER}\n## Release Preview\n\nMerging this pull request will release:\n\n\
+        | Package | Current Version | Next Version | Bump |\n| --- | --- | --- | --- |\n{}\n\n{}",
+        rows.join("\n"),
+        changelogs.join("\n")
+    )
+}
+
+/// The number of the pull request being checked in GitHub Actions, or else the open pull request
+/// from the current branch.
+fn current_pull_request(api: &Api, github_config: &GitHub) -> Result<u64, StepError> {
+    // Looks like `refs/pull/123/merge` for pull requests in GitHub Actions
+    let from_actions = env::var("GITHUB_REF").ok().and_then(|reference| {
+        reference
+            .strip_prefix("refs/pull/")
+            .and_then(|rest| rest.split('/').next())
+            .and_then(|number| number.parse().ok())
+    });
+    if let Some(number) = from_actions {
+        return Ok(number);
+    }
+
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let branch = current_branch(&repo)?;
+    let pull_requests: Vec<CreatedPullRequest> = ureq::get(&api.url("pulls"))
+        .query("head", &format!("{}:{branch}", github_config.owner))
+        .query("state", "open")
+        .set("Authorization", &api.authorization)
+        .call()?
+        .into_json()?;
+    pull_requests
+        .first()
+        .map(|pull_request| pull_request.number)
+        .ok_or(StepError::PullRequestNotFound)
+}
+
+/// The merged pull requests which `commit` is part of.
+pub(crate) fn merged_pull_requests(
+    github_config: &GitHub,
+    token: &str,
+    commit: &str,
+) -> Result<Vec<MergedPullRequest>, StepError> {
+    let api = Api::new(github_config, token);
+    let pull_requests: Vec<MergedPullRequest> =
+        ureq::get(&api.url(&format!("commits/{commit}/pulls")))
+            .set("Authorization", &api.authorization)
+            .call()?
+            .into_json()?;
+    Ok(pull_requests
+        .into_iter()
+        .filter(|pull_request| pull_request.merged_at.is_some())
+        .collect())
+}
+
+/// The users who approved the pull request `number`. Only the latest review of each user counts
+/// (other than comments), so an approval followed by a request for changes isn't an approval.
+pub(crate) fn approvers(
+    github_config: &GitHub,
+    token: &str,
+    number: u64,
+) -> Result<Vec<String>, StepError> {
+    let api = Api::new(github_config, token);
+    let reviews: Vec<Review> = ureq::get(&api.url(&format!("pulls/{number}/reviews")))
+        .query("per_page", "100")
+        .set("Authorization", &api.authorization)
+        .call()?
+        .into_json()?;
+    let mut latest: Vec<(String, String)> = Vec::new();
+    for review in reviews {
+        let login = match review.user {
+            Some(user) if review.state != "COMMENTED" => user.login,
+            // Deleted users and plain comments don't change anything.
+            _ => continue,
+        };
+        latest.retain(|(reviewer, _)| *reviewer != login);
+        latest.push((login, review.state));
+    }
+    Ok(latest
+        .into_iter()
+        .filter(|(_, state)| state == "APPROVED")
+        .map(|(login, _)| login)
+        .collect())
+}
+
+/// Who (and what) a new pull request should be routed to.
+struct Routing {
+    reviewers: Vec<String>,
+    team_reviewers: Vec<String>,
+    assignees: Vec<String>,
+    labels: Vec<String>,
+}
+
+impl Routing {
+    fn apply(&self, api: &Api, number: u64) -> Result<(), StepError> {
+        if !self.reviewers.is_empty() || !self.team_reviewers.is_empty() {
+            ureq::post(&api.url(&format!("pulls/{number}/requested_reviewers")))
+                .set("Authorization", &api.authorization)
+                .send_json(ureq::json!({
+                    "reviewers": self.reviewers,
+                    "team_reviewers": self.team_reviewers,
+                }))?;
+        }
+        if !self.assignees.is_empty() || !self.labels.is_empty() {
+            // Pull requests are issues as far as assignees and labels are concerned.
+            ureq::patch(&api.url(&format!("issues/{number}")))
+                .set("Authorization", &api.authorization)
+                .send_json(ureq::json!({
+                    "assignees": self.assignees,
+                    "labels": self.labels,
+                }))?;
+        }
+        Ok(())
+    }
+}
+
+/// Details for calling the REST API of the configured GitHub repo.
+struct Api {
+    base_url: String,
+    authorization: String,
+}
+
+impl Api {
+    fn new(github_config: &GitHub, token: &str) -> Self {
+        Self {
+            base_url: format!(
+                "https://api.github.com/repos/{owner}/{repo}",
+                owner = github_config.owner,
+                repo = github_config.repo,
+            ),
+            authorization: format!("token {token}"),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url)
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct GitHubPullRequest<'a> {
+    pub(crate) title: &'a str,
+    /// The branch to merge.
+    pub(crate) head: &'a str,
+    /// The branch to merge into.
+    pub(crate) base: &'a str,
+    pub(crate) body: &'a str,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CreatedPullRequest {
+    pub(crate) number: u64,
+    /// Who opened the pull request.
+    pub(crate) user: User,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct User {
+    pub(crate) login: String,
+}
+
+/// A pull request which was merged, as listed for one of its commits.
+#[derive(Deserialize)]
+pub(crate) struct MergedPullRequest {
+    pub(crate) number: u64,
+    pub(crate) title: String,
+    pub(crate) html_url: String,
+    /// Who opened the pull request.
+    pub(crate) user: User,
+    merged_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Review {
+    /// `None` if the user was deleted.
+    user: Option<User>,
+    /// Like `APPROVED`, `CHANGES_REQUESTED`, `COMMENTED`, or `DISMISSED`.
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct Comment {
+    id: u64,
+    body: String,
+}
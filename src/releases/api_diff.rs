@@ -0,0 +1,105 @@
+use std::fs::{read_to_string, write};
+use std::io::Write;
+
+use crate::command::{command_output, replace_variables_for_package};
+use crate::git::add_files;
+use crate::state::Release;
+use crate::step::{ApiDiff, StepError};
+use crate::RunType;
+
+/// The title of the section added to release notes, unless [`ApiDiff::section`] is set.
+const DEFAULT_SECTION: &str = "Public API changes";
+
+/// The implementation of [`crate::step::Step::ApiDiff`].
+///
+/// For every release prepared earlier in the workflow, runs `command` and adds whatever it prints
+/// as a new section at the end of the release notes, both in the state (for later steps like
+/// [`crate::step::Step::Release`]) and in the package's changelog. Nothing is added if the command
+/// prints nothing.
+pub(crate) fn api_diff(run_type: RunType, api_diff: &ApiDiff) -> Result<RunType, StepError> {
+    let (mut state, mut dry_run_stdout) = run_type.decompose();
+    let title = api_diff.section.as_deref().unwrap_or(DEFAULT_SECTION);
+    let mut prepared = false;
+    for index in 0..state.releases.len() {
+        let release = match &state.releases[index] {
+            Release::Prepared(release) => release,
+            Release::Bumped { .. } => continue,
+        };
+        prepared = true;
+        let package = state
+            .packages
+            .iter()
+            .find(|package| package.name == release.package_name)
+            .ok_or(StepError::ReleaseNotPrepared)?;
+        let command = replace_variables_for_package(
+            &api_diff.command,
+            api_diff.variables.clone().unwrap_or_default(),
+            &state,
+            Some(package),
+        )?;
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(
+                stdout,
+                "Would add the output of `{command}` to the release notes of {name} as \"{title}\"",
+                name = release.package_name.as_deref().unwrap_or("package"),
+            )?;
+            continue;
+        }
+        let output = command_output(&command, api_diff.shell)?;
+        let changes = output.trim_end();
+        if changes.trim().is_empty() {
+            continue;
+        }
+        let notes = with_section(&release.changelog, title, changes);
+        if let Some(changelog) = &package.changelog {
+            // The changelog was already written by `PrepareRelease`, so the new entry is on disk.
+            let content = read_to_string(&changelog.path)?;
+            if content.contains(&release.changelog) {
+                write(
+                    &changelog.path,
+                    content.replacen(&release.changelog, &notes, 1),
+                )?;
+                add_files(&[&changelog.path])?;
+            }
+        }
+        if let Release::Prepared(release) = &mut state.releases[index] {
+            release.changelog = notes;
+        }
+    }
+    if !prepared {
+        return Err(StepError::ReleaseNotPrepared);
+    }
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { state, stdout })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// Add a section named `title` containing `changes` to the end of the changelog entry `notes`,
+/// in the same format as the other sections.
+fn with_section(notes: &str, title: &str, changes: &str) -> String {
+    format!("{}\n\n### {title}\n\n{changes}\n", notes.trim_end())
+}
+
+#[cfg(test)]
+mod test_with_section {
+    use super::*;
+
+    #[test]
+    fn after_other_sections() {
+        let notes = "## 1.1.0\n\n### Features\n\n- New feature\n";
+        assert_eq!(
+            with_section(notes, "Public API changes", "Added items:\n+pub fn new()"),
+            "## 1.1.0\n\n### Features\n\n- New feature\n\n### Public API changes\n\nAdded items:\n+pub fn new()\n"
+        );
+    }
+
+    #[test]
+    fn only_header() {
+        assert_eq!(
+            with_section("## 1.1.0\n", "API", "- Removed `old`"),
+            "## 1.1.0\n\n### API\n\n- Removed `old`\n"
+        );
+    }
+}
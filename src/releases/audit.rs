@@ -0,0 +1,290 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use git2::{Oid, Repository};
+use itertools::Itertools;
+use semver::Version;
+use serde::Serialize;
+
+use crate::app_config::get_or_prompt_for_github_token;
+use crate::config::GitHub;
+use crate::git::get_commit_messages_in_range;
+use crate::issues::{references, Reference};
+use crate::pull_request::{approvers, merged_pull_requests};
+use crate::releases::git::{released_versions, tag_commit_id, tag_name};
+use crate::releases::yank::selected_package;
+use crate::releases::CommitRange;
+use crate::state::GitHub::{Initialized, New};
+use crate::state::{self, State};
+use crate::step::StepError;
+
+/// The options of `knope audit`.
+pub(crate) struct Audit {
+    pub(crate) version: Version,
+    /// The name of the package which was released, required when there are multiple packages.
+    pub(crate) package: Option<String>,
+    /// Print JSON instead of Markdown.
+    pub(crate) json: bool,
+}
+
+/// The implementation of `knope audit`, for gathering compliance evidence about a release.
+///
+/// Lists every commit that went into the release (everything since the previous release, like
+/// a changelog), who wrote them, and which issues they reference. If GitHub is configured, the
+/// pull requests which the commits were merged in, and who approved them, are listed too.
+/// Nothing is changed.
+pub(crate) fn audit(audit: &Audit, state: &State, stdout: &mut dyn Write) -> Result<(), StepError> {
+    let package = selected_package(&state.packages, audit.package.as_deref())?;
+    let tag_prefix = package.tag_prefix();
+    let tag = tag_name(&audit.version, &tag_prefix);
+    let to = tag_commit_id(&tag).ok_or_else(|| StepError::TagNotFound(tag.clone()))?;
+    let previous_tag = previous_version(&audit.version, released_versions(&tag_prefix)?)
+        .map(|version| tag_name(&version, &tag_prefix));
+    let range = CommitRange {
+        from: previous_tag.as_deref().and_then(tag_commit_id),
+        to,
+    };
+    let commit_messages = get_commit_messages_in_range(&range, package.path_filter())?;
+
+    let repo = Repository::open(".").map_err(|_| StepError::NotAGitRepo)?;
+    let jira_project = state.jira_config.as_ref().map(|jira| jira.project.as_str());
+    let mut commits = Vec::with_capacity(commit_messages.len());
+    let mut issues = Vec::new();
+    // Oldest first, like the history reads.
+    for commit_message in commit_messages.iter().rev() {
+        let commit = repo.find_commit(Oid::from_str(&commit_message.id)?)?;
+        let author = commit.author();
+        commits.push(AuditedCommit {
+            id: commit_message.id.clone(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: author.name().unwrap_or_default().to_string(),
+            email: author.email().unwrap_or_default().to_string(),
+        });
+        issues.extend(
+            references(
+                &commit_message.message,
+                jira_project,
+                state.github_config.is_some(),
+            )
+            .into_iter()
+            .map(|reference| match reference {
+                Reference::Jira(key) => key,
+                Reference::GitHub(number) => format!("#{number}"),
+            }),
+        );
+    }
+    let authors = commits
+        .iter()
+        .map(|commit| format!("{} <{}>", commit.author, commit.email))
+        .unique()
+        .collect();
+    let pull_requests = match &state.github_config {
+        Some(github_config) => Some(pull_requests(github_config, &state.github, &commits)?),
+        None => None,
+    };
+    let report = Report {
+        tag,
+        previous_tag,
+        commits,
+        authors,
+        issues: issues.into_iter().unique().collect(),
+        pull_requests,
+    };
+
+    if audit.json {
+        serde_json::to_writer_pretty(&mut *stdout, &report).map_err(std::io::Error::from)?;
+        writeln!(stdout)?;
+    } else {
+        write!(stdout, "{}", report.markdown())?;
+    }
+    Ok(())
+}
+
+/// The release before `version`: the latest stable version older than it or, if `version` is a
+/// pre-release, the latest version of any kind older than it.
+fn previous_version(version: &Version, released: Vec<Version>) -> Option<Version> {
+    released
+        .into_iter()
+        .filter(|released| {
+            released < version && (released.pre.is_empty() || !version.pre.is_empty())
+        })
+        .max()
+}
+
+/// Every merged pull request that one of `commits` is part of, along with who approved it.
+fn pull_requests(
+    github_config: &GitHub,
+    github_state: &state::GitHub,
+    commits: &[AuditedCommit],
+) -> Result<Vec<AuditedPullRequest>, StepError> {
+    let token = match github_state {
+        Initialized { token } => token.clone(),
+        New => get_or_prompt_for_github_token()?,
+    };
+    let mut pull_requests: Vec<AuditedPullRequest> = Vec::new();
+    for commit in commits {
+        for pull_request in merged_pull_requests(github_config, &token, &commit.id)? {
+            if let Some(audited) = pull_requests
+                .iter_mut()
+                .find(|audited| audited.number == pull_request.number)
+            {
+                audited.commits.push(commit.id.clone());
+                continue;
+            }
+            pull_requests.push(AuditedPullRequest {
+                number: pull_request.number,
+                title: pull_request.title,
+                url: pull_request.html_url,
+                author: pull_request.user.login,
+                approved_by: approvers(github_config, &token, pull_request.number)?,
+                commits: vec![commit.id.clone()],
+            });
+        }
+    }
+    Ok(pull_requests)
+}
+
+/// Everything that went into a release.
+#[derive(Debug, Serialize)]
+struct Report {
+    tag: String,
+    /// The tag of the release before this one, if there was one.
+    previous_tag: Option<String>,
+    commits: Vec<AuditedCommit>,
+    /// Each author of a commit, like `Name <email>`.
+    authors: Vec<String>,
+    /// Referenced Jira issues (like `PROJ-123`) and GitHub issues (like `#42`).
+    issues: Vec<String>,
+    /// `None` if GitHub isn't configured, so pull requests couldn't be audited.
+    pull_requests: Option<Vec<AuditedPullRequest>>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditedCommit {
+    id: String,
+    summary: String,
+    author: String,
+    email: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditedPullRequest {
+    number: u64,
+    title: String,
+    url: String,
+    /// The GitHub user who opened it.
+    author: String,
+    /// The GitHub users who approved it.
+    approved_by: Vec<String>,
+    /// The IDs of the commits in the release which came from it.
+    commits: Vec<String>,
+}
+
+impl Report {
+    fn markdown(&self) -> String {
+        let mut markdown = format!("# Audit of {}\n\n", self.tag);
+        match &self.previous_tag {
+            Some(previous_tag) => {
+                let _ = writeln!(markdown, "Changes since {previous_tag}.");
+            }
+            None => {
+                markdown.push_str("There is no previous release, so every commit is included.\n");
+            }
+        }
+        let commits = self
+            .commits
+            .iter()
+            .map(|commit| {
+                format!(
+                    "`{}` {} ({} <{}>)",
+                    commit.id, commit.summary, commit.author, commit.email
+                )
+            })
+            .collect_vec();
+        markdown.push_str(&section("Commits", &commits));
+        markdown.push_str(&section("Authors", &self.authors));
+        markdown.push_str(&section("Issues", &self.issues));
+        match &self.pull_requests {
+            Some(pull_requests) => {
+                let pull_requests = pull_requests
+                    .iter()
+                    .map(|pull_request| {
+                        let approved_by = if pull_request.approved_by.is_empty() {
+                            String::from("not approved")
+                        } else {
+                            format!(
+                                "approved by {}",
+                                pull_request
+                                    .approved_by
+                                    .iter()
+                                    .map(|login| format!("@{login}"))
+                                    .join(", ")
+                            )
+                        };
+                        format!(
+                            "[#{} {}]({}) by @{}, {approved_by}",
+                            pull_request.number,
+                            pull_request.title,
+                            pull_request.url,
+                            pull_request.author
+                        )
+                    })
+                    .collect_vec();
+                markdown.push_str(&section("Pull Requests", &pull_requests));
+            }
+            None => {
+                markdown.push_str("\n## Pull Requests\n\nNot audited, GitHub isn't configured.\n");
+            }
+        }
+        markdown
+    }
+}
+
+/// A Markdown section titled `title` with each of `items` in a list.
+fn section(title: &str, items: &[String]) -> String {
+    if items.is_empty() {
+        return format!("\n## {title}\n\nNone.\n");
+    }
+    let list = items.iter().map(|item| format!("- {item}\n")).join("");
+    format!("\n## {title}\n\n{list}")
+}
+
+#[cfg(test)]
+mod test_previous_version {
+    use super::*;
+
+    fn versions() -> Vec<Version> {
+        [
+            "1.0.0",
+            "1.1.0-rc.0",
+            "1.1.0",
+            "1.2.0-rc.0",
+            "1.2.0-rc.1",
+            "2.0.0",
+        ]
+        .into_iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn stable() {
+        assert_eq!(
+            previous_version(&Version::new(1, 2, 0), versions()),
+            Some(Version::new(1, 1, 0))
+        );
+    }
+
+    #[test]
+    fn prerelease() {
+        assert_eq!(
+            previous_version(&Version::parse("1.2.0-rc.1").unwrap(), versions()),
+            Some(Version::parse("1.2.0-rc.0").unwrap())
+        );
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(previous_version(&Version::new(1, 0, 0), versions()), None);
+    }
+}
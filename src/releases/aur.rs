@@ -0,0 +1,147 @@
+use std::fs::{read_to_string, write};
+use std::path::Path;
+use std::process::Command;
+
+use itertools::Itertools;
+use semver::Version;
+
+use crate::git::{check_branch_before_push, repository_branch};
+use crate::releases::pkgbuild;
+use crate::state::Release::{Bumped, Prepared};
+use crate::step::{PublishAur, StepError};
+use crate::{RunType, State};
+
+/// The implementation of [`crate::step::Step::PublishAur`].
+///
+/// Bumps the `pkgver` of the `PKGBUILD` in a clone of an AUR repository to the version released
+/// in this workflow, updates its checksums (with `updpkgsums`), regenerates `.SRCINFO` (with
+/// `makepkg --printsrcinfo`), then commits and pushes the clone.
+pub(crate) fn publish_aur(
+    run_type: RunType,
+    publish_aur: &PublishAur,
+) -> Result<RunType, StepError> {
+    let (state, mut dry_run_stdout) = run_type.decompose();
+    let version = release_version(&state, publish_aur.package.as_deref())?.version;
+    let repository = &publish_aur.repository;
+    let pkgbuild_path = repository.join("PKGBUILD");
+    let content = read_to_string(&pkgbuild_path)
+        .map_err(|_| StepError::FileNotFound(pkgbuild_path.clone()))?;
+    let content = pkgbuild::set_version(content, &version)
+        .ok_or_else(|| StepError::InvalidPkgbuild(pkgbuild_path.clone()))?;
+    let pkgver = pkgbuild::pkgver(&version);
+    check_branch_before_push(
+        &state.protected_branches,
+        repository_branch(repository).as_deref(),
+        dry_run_stdout.as_mut(),
+    )?;
+
+    if let Some(stdout) = dry_run_stdout.as_mut() {
+        writeln!(
+            stdout,
+            "Would set pkgver in {} to {pkgver}",
+            pkgbuild_path.display()
+        )?;
+        writeln!(
+            stdout,
+            "Would update the checksums and .SRCINFO in {}",
+            repository.display()
+        )?;
+        writeln!(
+            stdout,
+            "Would commit and push {} to the AUR",
+            repository.display()
+        )?;
+    } else {
+        write(&pkgbuild_path, content)?;
+        run(repository, "updpkgsums", &[])?;
+        let srcinfo = run(repository, "makepkg", &["--printsrcinfo"])?;
+        write(repository.join(".SRCINFO"), srcinfo)?;
+        run(repository, "git", &["add", "PKGBUILD", ".SRCINFO"])?;
+        run(
+            repository,
+            "git",
+            &["commit", "-m", &format!("Update to {pkgver}")],
+        )?;
+        run(repository, "git", &["push"])?;
+        println!("Published {pkgver} to the AUR");
+    }
+
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { state, stdout })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// A version released earlier in the workflow.
+pub(super) struct ReleasedVersion {
+    pub(super) version: Version,
+    pub(super) previous_version: Version,
+    pub(super) package_name: Option<String>,
+}
+
+/// The version released earlier in the workflow, either for `package` or the only one there is.
+pub(super) fn release_version(
+    state: &State,
+    package: Option<&str>,
+) -> Result<ReleasedVersion, StepError> {
+    let mut versions = state
+        .releases
+        .iter()
+        .filter_map(|release| {
+            let (version, previous_version, package_name) = match release {
+                Prepared(release) => (
+                    &release.version,
+                    &release.previous_version,
+                    &release.package_name,
+                ),
+                Bumped {
+                    version,
+                    previous_version,
+                    package_name,
+                    ..
+                } => (version, previous_version, package_name),
+            };
+            (package.is_none() || package_name.as_deref() == package).then(|| ReleasedVersion {
+                version: version.clone(),
+                previous_version: previous_version.clone(),
+                package_name: package_name.clone(),
+            })
+        })
+        .unique_by(|released| released.version.clone())
+        .collect_vec();
+    if versions.len() > 1 {
+        return Err(StepError::TooManyPackages);
+    }
+    match (versions.pop(), package) {
+        (Some(released), _) => Ok(released),
+        (None, Some(package))
+            if !state
+                .packages
+                .iter()
+                .any(|known| known.name.as_deref() == Some(package)) =>
+        {
+            Err(StepError::UnknownPackage(package.to_string()))
+        }
+        (None, _) => Err(StepError::ReleaseNotPrepared),
+    }
+}
+
+/// Run `program` with `args` in `repository`, returning what it printed.
+fn run(repository: &Path, program: &str, args: &[&str]) -> Result<Vec<u8>, StepError> {
+    let failed = |reason: String| StepError::AurCommandFailed {
+        command: [program].iter().chain(args).join(" "),
+        reason,
+    };
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(repository)
+        .output()
+        .map_err(|err| failed(err.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(output.stdout)
+}
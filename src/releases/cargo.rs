@@ -1,8 +1,15 @@
-use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use itertools::Itertools;
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
 use toml::Spanned;
 
 pub(crate) fn get_version(content: &str) -> Result<String, toml::de::Error> {
-    toml::from_str::<Cargo>(content).map(|cargo| cargo.package.version.into_inner())
+    version_span(&toml::from_str(content)?)
+        .map(|(version, _)| version.to_string())
+        .ok_or_else(|| serde::de::Error::missing_field("version"))
 }
 
 pub(crate) fn set_version(
@@ -10,24 +17,247 @@ pub(crate) fn set_version(
     new_version: &str,
 ) -> Result<String, toml::de::Error> {
     let doc: Cargo = toml::from_str(&cargo_toml)?;
+    let (_, (start, end)) =
+        version_span(&doc).ok_or_else(|| serde::de::Error::missing_field("version"))?;
 
     // Account for quotes with +- 1
-    let start = doc.package.version.start() + 1;
-    let end = doc.package.version.end() - 1;
+    let start = start + 1;
+    let end = end - 1;
 
     cargo_toml.replace_range(start..end, new_version);
 
     Ok(cargo_toml)
 }
 
+/// Whether this `Cargo.toml` declares its own version (as opposed to inheriting one from its
+/// workspace).
+pub(crate) fn has_version(content: &str) -> bool {
+    toml::from_str::<Cargo>(content)
+        .ok()
+        .as_ref()
+        .and_then(version_span)
+        .is_some()
+}
+
+/// Whether this `Cargo.toml` inherits its version from its workspace, with
+/// `version.workspace = true`.
+pub(crate) fn inherits_version(content: &str) -> bool {
+    toml::from_str::<Cargo>(content)
+        .ok()
+        .and_then(|cargo| cargo.package)
+        .and_then(|package| package.version)
+        .and_then(|version| version.into_inner().get("workspace").cloned())
+        == Some(toml::Value::Boolean(true))
+}
+
+/// The name of the package defined in this `Cargo.toml`, if any.
+pub(crate) fn package_name(content: &str) -> Option<String> {
+    toml::from_str::<Cargo>(content)
+        .ok()
+        .and_then(|cargo| cargo.package)
+        .and_then(|package| package.name)
+}
+
+/// The `workspace` table of a workspace root, or `None` if this `Cargo.toml` is not a workspace
+/// root.
+pub(crate) fn workspace(content: &str) -> Result<Option<Workspace>, toml::de::Error> {
+    Ok(toml::from_str::<Cargo>(content)?.workspace)
+}
+
+/// Set the version requirement of every dependency on one of the `packages` to `new_version`,
+/// keeping any operator (like `^` or `=`) that was there before.
+///
+/// Only dependencies which already specify a version are updated, so path-only or
+/// `workspace = true` dependencies are left alone.
+pub(crate) fn set_dependency_versions(
+    mut cargo_toml: String,
+    packages: &[String],
+    new_version: &str,
+) -> Result<String, toml::de::Error> {
+    let doc: DependencyTables = toml::from_str(&cargo_toml)?;
+    let mut spans = doc
+        .dependencies
+        .iter()
+        .chain(&doc.dev_dependencies)
+        .chain(&doc.build_dependencies)
+        .chain(
+            doc.workspace
+                .iter()
+                .flat_map(|workspace| &workspace.dependencies),
+        )
+        .filter(|(name, dependency)| {
+            let package = dependency.package.as_ref().unwrap_or(name);
+            packages.contains(package)
+        })
+        .filter_map(|(_, dependency)| dependency.version.as_ref())
+        .filter(|requirement| single_comparator(requirement.get_ref()))
+        .map(|requirement| (requirement.start() + 1, requirement.end() - 1))
+        .collect::<Vec<_>>();
+    // Replace from the end so earlier spans stay valid.
+    spans.sort_unstable();
+    for (start, end) in spans.into_iter().rev() {
+        let operator_length = cargo_toml[start..end]
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or_default();
+        cargo_toml.replace_range(start + operator_length..end, new_version);
+    }
+    Ok(cargo_toml)
+}
+
+/// Set the `version` of every entry for one of the local `packages` in a `Cargo.lock` to
+/// `new_version`. Entries from a registry or Git (which have a `source`) are left alone, even if
+/// they have the same name.
+pub(crate) fn set_lock_versions(
+    mut cargo_lock: String,
+    packages: &[String],
+    new_version: &str,
+) -> Result<String, toml::de::Error> {
+    let lock: CargoLock = toml::from_str(&cargo_lock)?;
+    let mut spans = lock
+        .package
+        .iter()
+        .filter(|locked| locked.source.is_none() && packages.contains(&locked.name))
+        .map(|locked| (locked.version.start() + 1, locked.version.end() - 1))
+        .collect_vec();
+    // Replace from the end so earlier spans stay valid.
+    spans.sort_unstable();
+    for (start, end) in spans.into_iter().rev() {
+        cargo_lock.replace_range(start..end, new_version);
+    }
+    Ok(cargo_lock)
+}
+
+/// Whether a version requirement is simple enough (like `1.2.3` or `^1.2`) to be replaced.
+fn single_comparator(requirement: &str) -> bool {
+    semver::VersionReq::parse(requirement)
+        .map_or(false, |requirement| requirement.comparators.len() == 1)
+}
+
+/// The version of the package itself if it has one, otherwise the version shared by the workspace.
+/// Returned along with the span of the version (including quotes).
+fn version_span(cargo: &Cargo) -> Option<(&str, (usize, usize))> {
+    let package_version = cargo
+        .package
+        .as_ref()
+        .and_then(|package| package.version.as_ref());
+    match package_version.map(|version| (version.get_ref(), version.span())) {
+        // A version which isn't a string is inherited, e.g., `version.workspace = true`.
+        Some((toml::Value::String(version), span)) => Some((version, span)),
+        Some(_) | None => cargo
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.package.as_ref())
+            .and_then(|package| package.version.as_ref())
+            .map(|version| (version.get_ref().as_str(), version.span())),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct Cargo {
-    package: Package,
+    package: Option<Package>,
+    workspace: Option<Workspace>,
 }
 
 #[derive(Debug, Deserialize)]
 struct Package {
+    name: Option<String>,
+    version: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+pub(crate) struct Workspace {
+    /// Paths to members of the workspace, where the last component may contain a `*`.
+    #[serde(default)]
+    pub(crate) members: Vec<String>,
+    /// Paths matched by `members` which are not actually members.
+    #[serde(default)]
+    pub(crate) exclude: Vec<String>,
+    package: Option<WorkspacePackage>,
+}
+
+#[derive(Debug, Deserialize, Eq, PartialEq)]
+struct WorkspacePackage {
+    version: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockedPackage {
+    name: String,
     version: Spanned<String>,
+    source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DependencyTables {
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<String, Dependency>,
+    #[serde(default, rename = "build-dependencies")]
+    build_dependencies: BTreeMap<String, Dependency>,
+    workspace: Option<WorkspaceDependencyTables>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkspaceDependencyTables {
+    #[serde(default)]
+    dependencies: BTreeMap<String, Dependency>,
+}
+
+/// A single entry in a dependencies table, only keeping what's needed to update its version.
+#[derive(Debug, Default)]
+struct Dependency {
+    version: Option<Spanned<String>>,
+    /// The real name of the package, if the dependency is renamed.
+    package: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for Dependency {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // This can't be an untagged enum, since `Spanned` doesn't survive being buffered.
+        struct DependencyVisitor;
+
+        impl<'de> Visitor<'de> for DependencyVisitor {
+            type Value = Dependency;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a version requirement or a table")
+            }
+
+            fn visit_str<E>(self, _requirement: &str) -> Result<Self::Value, E> {
+                // A plain requirement always refers to a registry, never another member.
+                Ok(Dependency::default())
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut dependency = Dependency::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "version" => dependency.version = Some(map.next_value()?),
+                        "package" => dependency.package = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(dependency)
+            }
+        }
+
+        deserializer.deserialize_any(DependencyVisitor)
+    }
 }
 
 #[cfg(test)]
@@ -58,4 +288,131 @@ mod tests {
         let expected = content.replace("0.1.0-rc.0", "1.2.3-rc.4");
         assert_eq!(new, expected);
     }
+
+    #[test]
+    fn test_workspace_version() {
+        let content = r###"
+        [workspace]
+        members = ["first"]
+
+        [workspace.package]
+        version = "0.1.0"
+        "###;
+
+        assert_eq!(get_version(content).unwrap(), "0.1.0".to_string());
+        let new = set_version(String::from(content), "0.2.0").unwrap();
+        assert_eq!(new, content.replace("0.1.0", "0.2.0"));
+    }
+
+    #[test]
+    fn test_inherited_version() {
+        let content = r###"
+        [package]
+        name = "tester"
+        version.workspace = true
+        "###;
+
+        assert!(!has_version(content));
+        assert!(inherits_version(content));
+        assert!(get_version(content).is_err());
+        assert_eq!(package_name(content), Some(String::from("tester")));
+    }
+
+    #[test]
+    fn test_workspace() {
+        let content = r###"
+        [workspace]
+        members = ["crates/*"]
+        exclude = ["crates/old"]
+        "###;
+
+        let workspace = workspace(content).unwrap().unwrap();
+        assert_eq!(workspace.members, vec![String::from("crates/*")]);
+        assert_eq!(workspace.exclude, vec![String::from("crates/old")]);
+        assert_eq!(
+            super::workspace("[package]\nversion = \"1.0.0\"").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_set_dependency_versions() {
+        let content = r###"
+        [package]
+        name = "tester"
+        version = "0.1.0"
+
+        [dependencies]
+        first = { path = "../first", version = "0.1.0" }
+        renamed = { path = "../second", version = "=0.1.0", package = "second" }
+        path_only = { path = "../third" }
+        fourth = { path = "../fourth", version = ">=0.1, <0.3" }
+        other = "0.1.0"
+
+        [dev-dependencies.first]
+        path = "../first"
+        version = "^0.1"
+        "###;
+
+        let new = set_dependency_versions(
+            String::from(content),
+            &[
+                String::from("first"),
+                String::from("second"),
+                String::from("third"),
+                String::from("fourth"),
+                String::from("other"),
+            ],
+            "0.2.0",
+        )
+        .unwrap();
+
+        let expected = content
+            .replace(
+                r#"first = { path = "../first", version = "0.1.0" }"#,
+                r#"first = { path = "../first", version = "0.2.0" }"#,
+            )
+            .replace(r#""=0.1.0""#, r#""=0.2.0""#)
+            .replace(r#""^0.1""#, r#""^0.2.0""#);
+        assert_eq!(new, expected);
+    }
+
+    #[test]
+    fn test_set_lock_versions() {
+        let content = r###"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "first"
+version = "0.1.0"
+dependencies = [
+ "second",
+]
+
+[[package]]
+name = "second"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "0000000000000000000000000000000000000000000000000000000000000000"
+
+[[package]]
+name = "third"
+version = "0.1.0"
+"###;
+
+        let new = set_lock_versions(
+            String::from(content),
+            &[String::from("first"), String::from("second")],
+            "0.2.0",
+        )
+        .unwrap();
+
+        let expected = content.replacen(
+            "name = \"first\"\nversion = \"0.1.0\"",
+            "name = \"first\"\nversion = \"0.2.0\"",
+            1,
+        );
+        assert_eq!(new, expected);
+    }
 }
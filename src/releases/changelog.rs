@@ -2,6 +2,9 @@ use std::iter::Map;
 use std::slice::Iter;
 
 use itertools::Itertools;
+use semver::Version;
+
+use crate::config::ChangelogFormat;
 
 /// Take in some existing markdown in the expected changelog format, find the top entry, and
 /// put the new version above it.
@@ -27,15 +30,30 @@ pub(super) fn add_version_to_changelog(existing: &str, new_changes: &[String]) -
     changelog
 }
 
+/// The notes for a changelog section other than breaking changes, features, and fixes.
+#[derive(Debug, Eq, PartialEq)]
+pub(super) struct Section {
+    pub(super) name: String,
+    pub(super) notes: Vec<String>,
+}
+
 pub(super) fn new_changelog_lines(
     title: &str,
     fixes: &[String],
     features: &[String],
     breaking_changes: &[String],
+    other_sections: &[Section],
 ) -> Vec<String> {
     const HEADERS_AND_PADDING: usize = 10;
     let mut blocks = Vec::with_capacity(
-        fixes.len() + features.len() + breaking_changes.len() + HEADERS_AND_PADDING,
+        fixes.len()
+            + features.len()
+            + breaking_changes.len()
+            + other_sections
+                .iter()
+                .map(|section| section.notes.len() + 2)
+                .sum::<usize>()
+            + HEADERS_AND_PADDING,
     );
 
     blocks.push(format!("## {}\n", title));
@@ -54,9 +72,123 @@ pub(super) fn new_changelog_lines(
         blocks.extend(unordered_list(fixes));
         blocks.push(String::new());
     }
+    for section in other_sections {
+        if !section.notes.is_empty() {
+            blocks.push(format!("### {}\n", section.name));
+            blocks.extend(unordered_list(&section.notes));
+            blocks.push(String::new());
+        }
+    }
     blocks
 }
 
+/// Produce the placeholder entry for a translated changelog from the canonical `new_changes`.
+///
+/// The version header is kept as-is so entries stay tracked per version, and a marker comment
+/// tells translators which entry still needs their attention.
+pub(super) fn localized_changelog_lines(locale: &str, new_changes: &[String]) -> Vec<String> {
+    let mut lines = new_changes.iter();
+    let mut blocks = Vec::with_capacity(new_changes.len() + 1);
+    if let Some(header) = lines.next() {
+        blocks.push(header.clone());
+    }
+    blocks.push(format!("<!-- knope: translation needed ({locale}) -->\n"));
+    blocks.extend(lines.cloned());
+    blocks
+}
+
+/// The notes (list items) of the most recent version in an existing changelog.
+///
+/// If the most recent version is a pre-release, there are no notes, as they should be repeated in
+/// the next version.
+pub(super) fn latest_entry_notes(existing: &str) -> Vec<String> {
+    let mut lines = existing.lines().skip_while(|line| !line.starts_with("## "));
+    let is_prerelease = lines
+        .next()
+        .and_then(|header| header.trim_start_matches("## ").split_whitespace().next())
+        .and_then(|version| Version::parse(version).ok())
+        .map_or(false, |version| !version.pre.is_empty());
+    if is_prerelease {
+        return Vec::new();
+    }
+    lines.take_while(|line| !line.starts_with("## ")).fold(
+        Vec::new(),
+        |mut notes: Vec<String>, line| {
+            if let Some(note) = line.strip_prefix("- ") {
+                notes.push(String::from(note));
+            } else if let (Some(continued), Some(note)) =
+                (line.strip_prefix("  "), notes.last_mut())
+            {
+                // A note which was wrapped onto multiple lines.
+                note.push(' ');
+                note.push_str(continued.trim());
+            }
+            notes
+        },
+    )
+}
+
+/// Apply `format` to a single note. If it's truncated, `full_commit_url` is linked after it.
+pub(super) fn format_note(
+    note: &str,
+    format: &ChangelogFormat,
+    full_commit_url: Option<&str>,
+) -> String {
+    let mut note = match format.max_length {
+        Some(max_length) if note.chars().count() > max_length => {
+            // Include one extra character to tell if the cut lands right at the end of a word.
+            let mut truncated = note.chars().take(max_length + 1).collect::<String>();
+            match truncated.rfind(char::is_whitespace) {
+                Some(last_space) => truncated.truncate(last_space),
+                None => truncated = note.chars().take(max_length).collect(),
+            }
+            let truncated = truncated.trim_end();
+            match full_commit_url {
+                Some(url) => format!("{truncated}… ([full commit]({url}))"),
+                None => format!("{truncated}…"),
+            }
+        }
+        _ => note.to_string(),
+    };
+    if let Some(wrap) = format.wrap {
+        // Leave room for the leading "- " of the list item.
+        note = wrap_words(&note, wrap.saturating_sub(2)).join("\n  ");
+    }
+    note
+}
+
+/// Remove the icon that `format` would have put before `note`, if any.
+pub(super) fn strip_icon<'a>(note: &'a str, format: &ChangelogFormat) -> &'a str {
+    format
+        .icons
+        .values()
+        .find_map(|icon| note.strip_prefix(icon.as_str()))
+        .map_or(note, str::trim_start)
+}
+
+/// Split `text` into lines no longer than `width`, except for single words which are longer.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(String::from(word)),
+        }
+    }
+    lines
+}
+
+/// Normalize a changelog note for comparison, ignoring case, whitespace, and trailing periods.
+pub(super) fn normalize_note(note: &str) -> String {
+    note.split_whitespace()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
 fn unordered_list(items: &[String]) -> Map<Iter<String>, fn(&String) -> String> {
     items.iter().map(|note| format!("- {}", note))
 }
@@ -111,11 +243,157 @@ Sometimes a second paragraph
             &["Fixed something".to_string()],
             &[String::from("New Feature")],
             &[String::from("Breaking change")],
+            &[],
         );
         let changelog = add_version_to_changelog(MARKDOWN, &new_changes);
         assert_eq!(changelog, EXPECTED);
     }
 
+    #[test]
+    fn other_sections() {
+        let new_changes = new_changelog_lines(
+            "0.2.0",
+            &[String::from("Fixed something")],
+            &[],
+            &[],
+            &[
+                Section {
+                    name: String::from("Deprecated"),
+                    notes: vec![String::from("Old API")],
+                },
+                Section {
+                    name: String::from("Removed"),
+                    notes: Vec::new(),
+                },
+                Section {
+                    name: String::from("Security"),
+                    notes: vec![String::from("Patched a vulnerability")],
+                },
+            ],
+        );
+        let expected = r##"## 0.2.0
+
+### Fixes
+
+- Fixed something
+
+### Deprecated
+
+- Old API
+
+### Security
+
+- Patched a vulnerability
+"##;
+
+        assert_eq!(new_changes.join("\n"), expected);
+    }
+
+    #[test]
+    fn latest_notes() {
+        const MARKDOWN: &str = r##"# Changelog
+
+## 1.1.0
+
+### Features
+
+- New Feature
+
+### Fixes
+
+- Fixed something
+
+## 1.0.0
+
+- Initial version
+"##;
+        assert_eq!(
+            latest_entry_notes(MARKDOWN),
+            vec![String::from("New Feature"), String::from("Fixed something")]
+        );
+    }
+
+    #[test]
+    fn latest_notes_wrapped() {
+        const MARKDOWN: &str =
+            "## 1.1.0\n\n### Features\n\n- A feature which\n  was wrapped\n- Another\n";
+        assert_eq!(
+            latest_entry_notes(MARKDOWN),
+            vec![
+                String::from("A feature which was wrapped"),
+                String::from("Another")
+            ]
+        );
+    }
+
+    #[test]
+    fn format_note_wrap() {
+        let format = ChangelogFormat {
+            wrap: Some(20),
+            ..ChangelogFormat::default()
+        };
+        assert_eq!(
+            format_note("Wrap this rather long note onto lines", &format, None),
+            "Wrap this rather\n  long note onto\n  lines"
+        );
+    }
+
+    #[test]
+    fn format_note_truncate() {
+        let format = ChangelogFormat {
+            max_length: Some(20),
+            ..ChangelogFormat::default()
+        };
+        assert_eq!(
+            format_note("Truncate this rather long note", &format, None),
+            "Truncate this rather…"
+        );
+        assert_eq!(
+            format_note(
+                "Truncate this rather long note",
+                &format,
+                Some("https://github.com/knope-dev/knope/commit/1234")
+            ),
+            "Truncate this rather… ([full commit](https://github.com/knope-dev/knope/commit/1234))"
+        );
+        assert_eq!(format_note("Short note", &format, None), "Short note");
+    }
+
+    #[test]
+    fn strip_icons() {
+        let format = ChangelogFormat {
+            icons: [(String::from("feat"), String::from("✨"))]
+                .into_iter()
+                .collect(),
+            ..ChangelogFormat::default()
+        };
+        assert_eq!(strip_icon("✨ New feature", &format), "New feature");
+        assert_eq!(strip_icon("Plain note", &format), "Plain note");
+    }
+
+    #[test]
+    fn latest_notes_prerelease() {
+        const MARKDOWN: &str = "## 1.1.0-rc.0\n\n### Features\n\n- New Feature\n";
+        assert!(latest_entry_notes(MARKDOWN).is_empty());
+    }
+
+    #[test]
+    fn localized_placeholder() {
+        let new_changes =
+            new_changelog_lines("0.2.0", &[], &[String::from("New Feature")], &[], &[]);
+        let expected = r##"## 0.2.0
+
+<!-- knope: translation needed (de) -->
+
+### Features
+
+- New Feature
+"##;
+
+        let lines = localized_changelog_lines("de", &new_changes);
+        assert_eq!(lines.join("\n"), expected);
+    }
+
     #[test]
     fn changelog_no_existing_version() {
         const MARKDOWN: &str = r##"# Changelog
@@ -151,6 +429,7 @@ Sometimes a second paragraph
             &["Fixed something".to_string()],
             &[String::from("New Feature")],
             &[String::from("Breaking change")],
+            &[],
         );
         let changelog = add_version_to_changelog(MARKDOWN, &new_changes);
         assert_eq!(changelog, EXPECTED);
@@ -0,0 +1,177 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the argument after `VERSION` in the `project()` command is, like `1.2.3` in
+/// `project(my_app VERSION 1.2.3 LANGUAGES CXX)`. Quotes around the version are left out.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let mut arguments = arguments(content, project_arguments(content)?).into_iter();
+    arguments.find(|argument| &content[argument.clone()] == "VERSION")?;
+    let span = arguments.next()?;
+    let argument = &content[span.clone()];
+    if !argument.starts_with('"') {
+        Some(span)
+    } else if argument.len() > 1 && argument.ends_with('"') {
+        Some(span.start + 1..span.end - 1)
+    } else {
+        None
+    }
+}
+
+/// Where the arguments of the first `project()` command are, between its parentheses. Command
+/// names are case-insensitive, so `PROJECT()` works too.
+fn project_arguments(content: &str) -> Option<Range<usize>> {
+    let mut position = 0;
+    while let Some(c) = content[position..].chars().next() {
+        let rest = &content[position..];
+        if c == '#' {
+            position += rest.find('\n').unwrap_or(rest.len());
+        } else if c == '"' {
+            position += quoted_len(rest);
+        } else if c.is_alphabetic() || c == '_' {
+            let name_len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let after_name = &rest[name_len..];
+            let open = position + rest.len() - after_name.trim_start_matches([' ', '\t']).len();
+            if !content[open..].starts_with('(') {
+                position += name_len;
+                continue;
+            }
+            let close = closing_paren(content, open)?;
+            if rest[..name_len].eq_ignore_ascii_case("project") {
+                return Some(open + 1..close);
+            }
+            position = close + 1;
+        } else {
+            position += c.len_utf8();
+        }
+    }
+    None
+}
+
+/// The position of the `)` which closes the `(` at `open`, skipping over nested parentheses,
+/// quoted arguments, and comments.
+fn closing_paren(content: &str, open: usize) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut position = open;
+    while let Some(c) = content[position..].chars().next() {
+        let rest = &content[position..];
+        match c {
+            '#' => {
+                position += rest.find('\n').unwrap_or(rest.len());
+                continue;
+            }
+            '"' => {
+                position += quoted_len(rest);
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(position);
+                }
+            }
+            _ => {}
+        }
+        position += c.len_utf8();
+    }
+    None
+}
+
+/// The spans of each argument within `range`, including the quotes of quoted arguments.
+fn arguments(content: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut arguments = Vec::new();
+    let mut position = range.start;
+    while position < range.end {
+        let rest = &content[position..range.end];
+        let c = match rest.chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        if c == '#' {
+            position += rest.find('\n').unwrap_or(rest.len());
+        } else if c == '"' {
+            let len = quoted_len(rest);
+            arguments.push(position..position + len);
+            position += len;
+        } else if c.is_whitespace() || c == '(' || c == ')' {
+            position += c.len_utf8();
+        } else {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || ['(', ')', '#', '"'].contains(&c))
+                .unwrap_or(rest.len());
+            arguments.push(position..position + len);
+            position += len;
+        }
+    }
+    arguments
+}
+
+/// The length of the quoted argument at the start of `text`, including both quotes.
+fn quoted_len(text: &str) -> usize {
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return index + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod test_cmake_lists {
+    const CONTENT: &str = r#"cmake_minimum_required(VERSION 3.14)
+
+# project(old VERSION 0.0.1)
+project(
+  my_app
+  VERSION 1.2.3 # The version of the app
+  DESCRIPTION "An app (with parentheses)"
+  LANGUAGES CXX
+)
+
+find_package(Boost 1.80 REQUIRED)
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("VERSION 1.2.3", "VERSION 2.0.0"))
+        );
+    }
+
+    #[test]
+    fn quoted_version() {
+        let content = "PROJECT(my_lib VERSION \"1.2.3\" LANGUAGES C)\n";
+        assert_eq!(super::get_version(content), Some(String::from("1.2.3")));
+        assert_eq!(
+            super::set_version(String::from(content), "2.0.0"),
+            Some(content.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn no_version() {
+        let content = "cmake_minimum_required(VERSION 3.14)\nproject(my_app LANGUAGES CXX)\n";
+        assert_eq!(super::get_version(content), None);
+    }
+}
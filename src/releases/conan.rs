@@ -0,0 +1,75 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string assigned to the `version` attribute of the recipe class (like
+/// `version = "1.2.3"`) is. Versions set in methods (like `self.version = ...` in
+/// `set_version()`) aren't found.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let rest = match line.trim_start().strip_prefix("version") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        // Allow a type annotation, like `version: str = "1.2.3"`
+        let rest = match rest.split_once('=') {
+            Some((annotation, rest))
+                if annotation.trim().is_empty() || annotation.trim_start().starts_with(':') =>
+            {
+                rest.trim_start()
+            }
+            _ => continue,
+        };
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let value_start = start + line.len() - rest.len() + 1;
+        let value_len = content[value_start..line_start].find(quote)?;
+        return Some(value_start..value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_conanfile {
+    const CONTENT: &str = r#"from conan import ConanFile
+
+
+class MyLibConan(ConanFile):
+    name = "my_lib"
+    version = "1.2.3"
+    settings = "os", "compiler", "build_type", "arch"
+    requires = "fmt/9.1.0"
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("\"1.2.3\"", "\"2.0.0\""))
+        );
+    }
+
+    #[test]
+    fn set_in_method() {
+        let content = "class MyLibConan(ConanFile):\n    def set_version(self):\n        self.version = load(self, \"VERSION\")\n";
+        assert_eq!(super::get_version(content), None);
+    }
+}
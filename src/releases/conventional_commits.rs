@@ -1,17 +1,38 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
 use std::io::Write;
+use std::path::PathBuf;
 
 use git_conventional::{Commit, Type};
+use itertools::Itertools;
 use log::debug;
+use semver::Version;
 
-use crate::git::{add_files, get_commit_messages_after_last_stable_version};
+use crate::config::{ChangelogFormat, ChangelogSection, DebianChangelog, GitHub, Versioning};
+use crate::git::{
+    add_files, get_commit_messages_after_last_stable_version, get_commit_messages_in_range,
+    CommitMessage,
+};
 use crate::releases::semver::PackageVersion;
 use crate::releases::Package;
-use crate::step::StepError;
+use crate::step::{PrereleaseLabel, StepError};
+use crate::template::{format_date, now};
 use crate::{state, step, RunType};
 
-use super::changelog::{add_version_to_changelog, new_changelog_lines};
-use super::semver::{bump_version, ConventionalRule, Rule};
-use super::Release;
+use super::changelog::{
+    add_version_to_changelog, format_note, latest_entry_notes, localized_changelog_lines,
+    new_changelog_lines, normalize_note, strip_icon, Section,
+};
+use super::git::{released_versions, tag_commit_id, tag_name};
+use super::package::{set_internal_dependency_versions, PackageFormat};
+use super::record::write_record;
+use super::revert::without_reverted;
+use super::semver::{
+    bump_version, ensure_consistent_versions, ensure_same_versions, get_version, ConventionalRule,
+    Rule,
+};
+use super::{debian_changelog, metainfo, rpm_spec};
+use super::{CommitRange, Release};
 
 #[derive(Debug)]
 struct ConventionalCommits {
@@ -19,37 +40,155 @@ struct ConventionalCommits {
     features: Vec<String>,
     fixes: Vec<String>,
     breaking_changes: Vec<String>,
+    other_sections: Vec<Section>,
+    /// The ID of the commit which each note came from, if known.
+    note_commits: HashMap<String, String>,
+    /// The conventional commit type which each note came from, if known.
+    note_types: HashMap<String, String>,
 }
 
 impl ConventionalCommits {
     fn from_commit_messages(
-        commit_messages: &[String],
+        commit_messages: &[CommitMessage],
         consider_scopes: bool,
         package: &Package,
     ) -> Self {
+        let commit_messages = without_reverted(commit_messages, |commit| {
+            commit.type_() == Type::FEAT
+                || commit.type_() == Type::FIX
+                || package.changelog_sections.iter().any(|section| {
+                    section.types.iter().any(|commit_type| {
+                        commit.type_().as_str().eq_ignore_ascii_case(commit_type)
+                    })
+                })
+        });
         let commits = commit_messages
             .iter()
-            .filter_map(|message| Commit::parse(message.trim()).ok())
-            .filter(|commit| {
+            .filter_map(|commit_message| {
+                Commit::parse(commit_message.message.trim())
+                    .ok()
+                    .map(|commit| (commit_message.id.as_str(), commit))
+            })
+            .filter(|(_, commit)| {
+                if package.ignore_unscoped && commit.scope().is_none() {
+                    return false;
+                }
                 if !consider_scopes {
                     return true;
                 }
                 match (commit.scope(), &package.scopes) {
                     (None, _) => true,
                     (Some(_), None) => false,
-                    (Some(scope), Some(scopes)) => scopes.contains(&scope.to_string()),
+                    // A commit can name several packages, like `feat(cli,lib): ...`
+                    (Some(scope), Some(scopes)) => scope.split(',').any(|scope| {
+                        scopes
+                            .iter()
+                            .any(|package_scope| package_scope == scope.trim())
+                    }),
                 }
             })
-            .collect();
+            // Cherry-picked commits would otherwise show up more than once.
+            .unique_by(|(_, commit)| dedup_key(commit))
+            .collect::<Vec<_>>();
         debug!("Selected commits: {:?}", commits);
-        Self::from_commits(commits)
+        let note_commits = commits
+            .iter()
+            .flat_map(|(id, commit)| {
+                [Some(commit.description()), commit.breaking_description()]
+                    .into_iter()
+                    .flatten()
+                    .map(|note| (note.to_string(), (*id).to_string()))
+            })
+            .collect();
+        let note_types = commits
+            .iter()
+            .map(|(_, commit)| {
+                (
+                    commit.description().to_string(),
+                    commit.type_().as_str().to_lowercase(),
+                )
+            })
+            .collect();
+        let commits = commits.into_iter().map(|(_, commit)| commit).collect();
+        Self {
+            note_commits,
+            note_types,
+            ..Self::from_commits(commits, &package.changelog_sections)
+        }
+    }
+
+    /// Apply `format` to every note, linking truncated notes to their commit on GitHub if
+    /// `github` is configured.
+    fn formatted(mut self, format: &ChangelogFormat, github: Option<&GitHub>) -> Self {
+        if format.wrap.is_none() && format.max_length.is_none() && format.icons.is_empty() {
+            return self;
+        }
+        let note_commits = &self.note_commits;
+        let note_types = &self.note_types;
+        let format_all = |notes: &mut Vec<String>, breaking: bool| {
+            for note in notes.iter_mut() {
+                let url = github.zip(note_commits.get(note)).map(|(github, id)| {
+                    format!(
+                        "https://github.com/{owner}/{repo}/commit/{id}",
+                        owner = github.owner,
+                        repo = github.repo
+                    )
+                });
+                let icon = if breaking {
+                    format.icons.get("breaking")
+                } else {
+                    note_types
+                        .get(note)
+                        .and_then(|commit_type| format.icons.get(commit_type))
+                };
+                let decorated = match icon {
+                    Some(icon) => format!("{icon} {note}"),
+                    None => note.clone(),
+                };
+                *note = format_note(&decorated, format, url.as_deref());
+            }
+        };
+        format_all(&mut self.features, false);
+        format_all(&mut self.fixes, false);
+        format_all(&mut self.breaking_changes, true);
+        for section in &mut self.other_sections {
+            format_all(&mut section.notes, false);
+        }
+        self
+    }
+
+    /// Remove any notes which were already in the `previous` changelog entry (e.g., a fix that
+    /// was cherry-picked onto a maintenance branch and released there first).
+    fn without_notes(mut self, previous: &[String]) -> Self {
+        if previous.is_empty() {
+            return self;
+        }
+        let previous = previous
+            .iter()
+            .map(|note| normalize_note(note))
+            .collect::<Vec<_>>();
+        let is_new = |note: &String| !previous.contains(&normalize_note(note));
+        self.features.retain(is_new);
+        self.fixes.retain(is_new);
+        self.breaking_changes.retain(is_new);
+        for section in &mut self.other_sections {
+            section.notes.retain(is_new);
+        }
+        self
     }
 
-    fn from_commits(commits: Vec<Commit>) -> Self {
+    fn from_commits(commits: Vec<Commit>, changelog_sections: &[ChangelogSection]) -> Self {
         let mut rule = None;
         let mut features = Vec::new();
         let mut fixes = Vec::new();
         let mut breaking_changes = Vec::new();
+        let mut other_sections = changelog_sections
+            .iter()
+            .map(|section| Section {
+                name: section.name.clone(),
+                notes: Vec::new(),
+            })
+            .collect::<Vec<_>>();
 
         for commit in commits {
             if let Some(breaking_message) = commit.breaking_description() {
@@ -86,6 +225,22 @@ impl ConventionalCommits {
                     rule = Some(ConventionalRule::Patch);
                 }
                 fixes.push(commit.description().to_string());
+            } else if let Some(section_index) = changelog_sections.iter().position(|section| {
+                section
+                    .types
+                    .iter()
+                    .any(|commit_type| commit.type_().as_str().eq_ignore_ascii_case(commit_type))
+            }) {
+                if rule.is_none() {
+                    debug!(
+                        "commit \"{}\" results in Patch rule selection",
+                        commit.description()
+                    );
+                    rule = Some(ConventionalRule::Patch);
+                }
+                other_sections[section_index]
+                    .notes
+                    .push(commit.description().to_string());
             }
         }
 
@@ -94,21 +249,43 @@ impl ConventionalCommits {
             features,
             fixes,
             breaking_changes,
+            other_sections,
+            note_commits: HashMap::new(),
+            note_types: HashMap::new(),
         }
     }
 }
 
+/// Identify duplicate commits by their `Change-Id` trailer if they have one, or by the description.
+fn dedup_key(commit: &Commit) -> String {
+    commit
+        .footers()
+        .iter()
+        .find(|footer| footer.token() == "Change-Id")
+        .map_or_else(
+            || normalize_note(commit.description()),
+            |footer| footer.value().trim().to_string(),
+        )
+}
+
 #[cfg(test)]
 mod test_conventional_commits {
     use super::*;
 
+    fn commit_message(message: &str) -> CommitMessage {
+        CommitMessage {
+            id: String::from("1234"),
+            message: String::from(message),
+        }
+    }
+
     #[test]
     fn non_breaking_features() {
         let commits = vec![
             Commit::parse("feat: add a feature").unwrap(),
             Commit::parse("feat: another feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Minor));
         assert_eq!(
             conventional_commits.features,
@@ -127,7 +304,7 @@ mod test_conventional_commits {
             Commit::parse("fix: a bug").unwrap(),
             Commit::parse("fix: another bug").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Patch));
         assert_eq!(
             conventional_commits.fixes,
@@ -143,7 +320,7 @@ mod test_conventional_commits {
             Commit::parse("fix: a bug").unwrap(),
             Commit::parse("feat: add a feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Minor));
         assert_eq!(conventional_commits.fixes, vec![String::from("a bug")]);
         assert_eq!(
@@ -160,7 +337,7 @@ mod test_conventional_commits {
             Commit::parse("feat!: add a feature").unwrap(),
             Commit::parse("feat: add another feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
         assert_eq!(conventional_commits.fixes, vec![String::from("a bug")]);
         assert_eq!(
@@ -180,7 +357,7 @@ mod test_conventional_commits {
             Commit::parse("fix: another bug").unwrap(),
             Commit::parse("feat: add a feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
         assert_eq!(
             conventional_commits.fixes,
@@ -203,7 +380,7 @@ mod test_conventional_commits {
             Commit::parse("fix: another bug").unwrap(),
             Commit::parse("feat: add a feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
         assert_eq!(
             conventional_commits.fixes,
@@ -226,7 +403,7 @@ mod test_conventional_commits {
             Commit::parse("fix: a bug").unwrap(),
             Commit::parse("feat: add another feature").unwrap(),
         ];
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
         assert_eq!(conventional_commits.fixes, vec![String::from("a bug")]);
         assert_eq!(
@@ -245,28 +422,107 @@ mod test_conventional_commits {
     #[test]
     fn no_commits() {
         let commits = Vec::<Commit>::new();
-        let conventional_commits = ConventionalCommits::from_commits(commits);
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[]);
         assert_eq!(conventional_commits.rule, None);
         assert_eq!(conventional_commits.fixes, Vec::<String>::new());
         assert_eq!(conventional_commits.features, Vec::<String>::new());
         assert_eq!(conventional_commits.breaking_changes, Vec::<String>::new());
     }
 
+    #[test]
+    fn other_sections() {
+        let commits = vec![
+            Commit::parse("deprecate: the old API").unwrap(),
+            Commit::parse("Security: patch a vulnerability").unwrap(),
+            Commit::parse("remove!: the older API").unwrap(),
+            Commit::parse("chore: not in the changelog").unwrap(),
+        ];
+        let sections = [
+            ChangelogSection {
+                name: String::from("Deprecated"),
+                types: vec![String::from("deprecate")],
+            },
+            ChangelogSection {
+                name: String::from("Removed"),
+                types: vec![String::from("remove")],
+            },
+            ChangelogSection {
+                name: String::from("Security"),
+                types: vec![String::from("security")],
+            },
+        ];
+        let conventional_commits = ConventionalCommits::from_commits(commits, &sections);
+        assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
+        assert_eq!(
+            conventional_commits.other_sections,
+            vec![
+                Section {
+                    name: String::from("Deprecated"),
+                    notes: vec![String::from("the old API")],
+                },
+                Section {
+                    name: String::from("Removed"),
+                    notes: Vec::new(),
+                },
+                Section {
+                    name: String::from("Security"),
+                    notes: vec![String::from("patch a vulnerability")],
+                },
+            ]
+        );
+        assert_eq!(
+            conventional_commits.breaking_changes,
+            vec![String::from("the older API")]
+        );
+    }
+
+    #[test]
+    fn deduplicate_cherry_picks() {
+        let commits = [
+            "fix: A bug\n\n(cherry picked from commit 1234)",
+            "fix:  a bug ",
+            "feat: A feature\n\nChange-Id: I1234",
+            "feat: A feature, reworded\n\nChange-Id: I1234",
+            "feat: Another feature\n\nChange-Id: I5678",
+        ]
+        .map(commit_message);
+        let conventional_commits =
+            ConventionalCommits::from_commit_messages(&commits, false, &Package::default());
+        assert_eq!(conventional_commits.fixes, vec![String::from("A bug")]);
+        assert_eq!(
+            conventional_commits.features,
+            vec![String::from("A feature"), String::from("Another feature")]
+        );
+    }
+
+    #[test]
+    fn without_previous_notes() {
+        let commits = vec![
+            Commit::parse("fix: A bug").unwrap(),
+            Commit::parse("fix: Another bug").unwrap(),
+        ];
+        let conventional_commits = ConventionalCommits::from_commits(commits, &[])
+            .without_notes(&[String::from("a bug.")]);
+        assert_eq!(conventional_commits.rule, Some(ConventionalRule::Patch));
+        assert_eq!(
+            conventional_commits.fixes,
+            vec![String::from("Another bug")]
+        );
+    }
+
     #[test]
     fn dont_consider_scopes() {
         let commits = [
             "feat(wrong_scope)!: Wrong scope breaking change!",
             "fix: No scope",
         ]
-        .map(String::from);
+        .map(commit_message);
         let conventional_commits = ConventionalCommits::from_commit_messages(
             &commits,
             false,
             &Package {
-                versioned_files: vec![],
-                changelog: None,
-                name: None,
                 scopes: Some(vec![String::from("scope")]),
+                ..Package::default()
             },
         );
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
@@ -278,17 +534,9 @@ mod test_conventional_commits {
             "feat(scope)!: Wrong scope breaking change!",
             "fix: No scope",
         ]
-        .map(String::from);
-        let conventional_commits = ConventionalCommits::from_commit_messages(
-            &commits,
-            true,
-            &Package {
-                versioned_files: vec![],
-                changelog: None,
-                name: None,
-                scopes: None,
-            },
-        );
+        .map(commit_message);
+        let conventional_commits =
+            ConventionalCommits::from_commit_messages(&commits, true, &Package::default());
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Patch));
     }
 
@@ -299,26 +547,58 @@ mod test_conventional_commits {
             "feat(scope): Right scope feature",
             "fix: No scope",
         ]
-        .map(String::from);
+        .map(commit_message);
         let conventional_commits = ConventionalCommits::from_commit_messages(
             &commits,
             true,
             &Package {
-                versioned_files: vec![],
-                changelog: None,
-                name: None,
                 scopes: Some(vec![String::from("scope")]),
+                ..Package::default()
             },
         );
         assert_eq!(conventional_commits.rule, Some(ConventionalRule::Minor));
     }
+
+    #[test]
+    fn multiple_scopes() {
+        let commits = ["feat(other, scope)!: Breaking change for two packages"].map(commit_message);
+        let conventional_commits = ConventionalCommits::from_commit_messages(
+            &commits,
+            true,
+            &Package {
+                scopes: Some(vec![String::from("scope")]),
+                ..Package::default()
+            },
+        );
+        assert_eq!(conventional_commits.rule, Some(ConventionalRule::Major));
+    }
+
+    #[test]
+    fn ignore_unscoped() {
+        let commits = ["feat: No scope", "fix(scope): Right scope fix"].map(commit_message);
+        let conventional_commits = ConventionalCommits::from_commit_messages(
+            &commits,
+            true,
+            &Package {
+                scopes: Some(vec![String::from("scope")]),
+                ignore_unscoped: true,
+                ..Package::default()
+            },
+        );
+        assert_eq!(conventional_commits.rule, Some(ConventionalRule::Patch));
+    }
 }
 
 fn get_conventional_commits_after_last_stable_version(
     package: &Package,
     consider_scopes: bool,
+    first_parent: bool,
 ) -> Result<ConventionalCommits, StepError> {
-    let commit_messages = get_commit_messages_after_last_stable_version(&package.name)?;
+    let commit_messages = get_commit_messages_after_last_stable_version(
+        &package.tag_prefix(),
+        package.path_filter(),
+        first_parent,
+    )?;
     Ok(ConventionalCommits::from_commit_messages(
         &commit_messages,
         consider_scopes,
@@ -341,17 +621,59 @@ pub(crate) fn update_project_from_conventional_commits(
         .packages
         .iter()
         .any(|package| package.scopes.is_some());
-    for package in &mut state.packages {
+    if let Some(prerelease_label) = &prepare_release.prerelease_label {
+        check_prerelease_label(prerelease_label, &state.packages, state.versioning)?;
+    }
+    ensure_consistent_versions(&state.packages)?;
+    let first_parent = prepare_release.first_parent.unwrap_or_default();
+    let package_commits = state
+        .packages
+        .iter()
+        .map(|package| {
+            package_conventional_commits(
+                package,
+                consider_scopes,
+                first_parent,
+                state.github_config.as_ref(),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let rules = package_commits
+        .iter()
+        .map(|commits| commits.rule)
+        .collect_vec();
+    let shared_rules = shared_rules(&state.packages, &rules, state.versioning)?;
+    let previous_releases = state.releases.len();
+    for ((package, commits), shared_rule) in
+        state.packages.iter().zip(package_commits).zip(shared_rules)
+    {
         let release = prepare_release_for_package(
             package.clone(),
-            consider_scopes,
-            prepare_release.prerelease_label.as_ref(),
+            commits,
+            shared_rule,
+            prepare_release,
             dry_run_stdout.as_mut(),
         )?;
         if let Some(release) = release {
             state.releases.push(state::Release::Prepared(release));
         }
     }
+    set_internal_dependency_versions(
+        &state.packages,
+        &state.releases[previous_releases..],
+        dry_run_stdout.as_mut(),
+    )?;
+    if let Some(record) = &prepare_release.record {
+        let prepared = state
+            .releases
+            .iter()
+            .filter_map(|release| match release {
+                state::Release::Prepared(release) => Some(release),
+                state::Release::Bumped { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        write_record(record, &prepared, dry_run_stdout.as_mut())?;
+    }
     if let Some(dry_run_stdout) = dry_run_stdout {
         Ok(RunType::DryRun {
             state,
@@ -364,10 +686,254 @@ pub(crate) fn update_project_from_conventional_commits(
     }
 }
 
+/// Make sure that every package named by `prerelease_label` exists and that packages with fixed
+/// versioning all get the same label.
+fn check_prerelease_label(
+    prerelease_label: &PrereleaseLabel,
+    packages: &[Package],
+    versioning: Versioning,
+) -> Result<(), StepError> {
+    if let PrereleaseLabel::PerPackage(labels) = prerelease_label {
+        if let Some(unknown) = labels.keys().find(|name| {
+            !packages
+                .iter()
+                .any(|package| package.name.as_deref() == Some(name.as_str()))
+        }) {
+            return Err(StepError::UnknownPackage(unknown.clone()));
+        }
+    }
+    let same_labels = packages
+        .iter()
+        .map(|package| prerelease_label.for_package(package.name.as_deref()))
+        .all_equal();
+    if versioning == Versioning::Fixed && !same_labels {
+        return Err(StepError::InconsistentPrereleaseLabels);
+    }
+    Ok(())
+}
+
+/// The implementation of `knope next-version`.
+///
+/// Writes the version that `PrepareRelease` would bump each package to, along with the rule from
+/// conventional commits that produced it, to `stdout`. Nothing is changed.
+pub(crate) fn next_version(
+    state: &state::State,
+    prerelease_label: Option<&str>,
+    stdout: &mut dyn Write,
+) -> Result<(), StepError> {
+    if state.packages.is_empty() {
+        return Err(StepError::no_defined_packages_with_help());
+    }
+    let consider_scopes = state
+        .packages
+        .iter()
+        .any(|package| package.scopes.is_some());
+    let package_rules = state
+        .packages
+        .iter()
+        .map(|package| {
+            get_conventional_commits_after_last_stable_version(package, consider_scopes, false)
+                .map(|commits| commits.rule)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let shared_rules = shared_rules(&state.packages, &package_rules, state.versioning)?;
+    for ((package, rule), shared_rule) in state.packages.iter().zip(package_rules).zip(shared_rules)
+    {
+        let name = package.name.as_deref().unwrap_or("package");
+        let conventional_rule = if let Some(rule) = shared_rule.or(rule) {
+            rule
+        } else {
+            let current = get_version(package.clone())?.version.into_latest();
+            writeln!(stdout, "{name} {current} (no changes)")?;
+            continue;
+        };
+        let rule = if let Some(label) = prerelease_label {
+            Rule::Pre {
+                label: label.to_string(),
+                stable_rule: conventional_rule,
+                start: 0,
+            }
+        } else {
+            Rule::from(conventional_rule)
+        };
+        let (PackageVersion { version, .. }, previous_version, _) =
+            bump_version(&rule, true, package.clone())?;
+        writeln!(
+            stdout,
+            "{name} {previous_version} -> {next} ({conventional_rule})",
+            next = version.latest()
+        )?;
+    }
+    Ok(())
+}
+
+/// The implementation of `knope regenerate-changelog`.
+///
+/// Rebuilds the changelog of every package which has one from scratch: each stable version tagged
+/// in the history of HEAD gets an entry made from the conventional commits since the version before
+/// it. Anything before the first entry of the existing changelog (like its title) is kept.
+/// Pre-releases don't get entries, since their changes are part of the next stable version.
+pub(crate) fn regenerate_changelog(
+    state: &state::State,
+    mut dry_run_stdout: Option<Box<dyn Write>>,
+) -> Result<(), StepError> {
+    if state.packages.is_empty() {
+        return Err(StepError::no_defined_packages_with_help());
+    }
+    if state
+        .packages
+        .iter()
+        .all(|package| package.changelog.is_none())
+    {
+        return Err(StepError::NoChangelog);
+    }
+    let consider_scopes = state
+        .packages
+        .iter()
+        .any(|package| package.scopes.is_some());
+    for package in &state.packages {
+        let changelog = match &package.changelog {
+            Some(changelog) => changelog,
+            None => continue,
+        };
+        let tag_prefix = package.tag_prefix();
+        let versions = released_versions(&tag_prefix)?
+            .into_iter()
+            .filter(|version| version.pre.is_empty())
+            .sorted()
+            .collect_vec();
+        let mut content = changelog
+            .content
+            .lines()
+            .take_while(|line| !line.starts_with("##"))
+            .fold(String::new(), |mut content, line| {
+                let _ = writeln!(content, "{line}");
+                content
+            });
+        let mut previous_commit = None;
+        for version in &versions {
+            let commit = match tag_commit_id(&tag_name(version, &tag_prefix)) {
+                Some(commit) => commit,
+                None => continue,
+            };
+            let range = CommitRange {
+                from: previous_commit.replace(commit.clone()),
+                to: commit,
+            };
+            let commit_messages = get_commit_messages_in_range(&range, package.path_filter())?;
+            let ConventionalCommits {
+                features,
+                fixes,
+                breaking_changes,
+                other_sections,
+                ..
+            } = ConventionalCommits::from_commit_messages(
+                &commit_messages,
+                consider_scopes,
+                package,
+            )
+            .formatted(&package.changelog_format, state.github_config.as_ref());
+            let new_changes = new_changelog_lines(
+                &version.to_string(),
+                &fixes,
+                &features,
+                &breaking_changes,
+                &other_sections,
+            );
+            content = add_version_to_changelog(&content, &new_changes);
+        }
+
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(
+                stdout,
+                "Would replace the contents of {} with: \n{}",
+                changelog.path.display(),
+                content
+            )?;
+        } else {
+            std::fs::write(&changelog.path, &content)?;
+            println!(
+                "Regenerated {} with {} versions",
+                changelog.path.display(),
+                versions.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The conventional commits which apply to `package`, leaving out any notes which are already in
+/// its changelog.
+fn package_conventional_commits(
+    package: &Package,
+    consider_scopes: bool,
+    first_parent: bool,
+    github_config: Option<&GitHub>,
+) -> Result<ConventionalCommits, StepError> {
+    Ok(
+        get_conventional_commits_after_last_stable_version(package, consider_scopes, first_parent)?
+            .without_notes(
+                &package
+                    .changelog
+                    .as_ref()
+                    .map(|changelog| {
+                        latest_entry_notes(&changelog.content)
+                            .iter()
+                            .map(|note| strip_icon(note, &package.changelog_format).to_string())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default(),
+            )
+            .formatted(&package.changelog_format, github_config),
+    )
+}
+
+/// The rule that each package must be bumped by because it shares its version with other
+/// packages, which is the biggest of all their `rules`. With fixed versioning, every package shares
+/// one version. Otherwise, only those with a versioned file in common do (like members of a Cargo
+/// workspace which inherit its version), and the rest are `None`.
+fn shared_rules(
+    packages: &[Package],
+    rules: &[Option<ConventionalRule>],
+    versioning: Versioning,
+) -> Result<Vec<Option<ConventionalRule>>, StepError> {
+    if versioning == Versioning::Fixed {
+        ensure_same_versions(packages)?;
+        // Every package is bumped by the biggest change to any of them.
+        let fixed_rule = rules.iter().max().copied().flatten();
+        return Ok(vec![fixed_rule; packages.len()]);
+    }
+    Ok(packages
+        .iter()
+        .enumerate()
+        .map(|(index, package)| {
+            let sharing = packages
+                .iter()
+                .enumerate()
+                .filter(|(other_index, other)| {
+                    *other_index != index && package.shares_version_with(other)
+                })
+                .map(|(other_index, _)| rules[other_index])
+                .collect_vec();
+            if sharing.is_empty() {
+                None
+            } else {
+                sharing.into_iter().chain([rules[index]]).max().flatten()
+            }
+        })
+        .collect())
+}
+
+/// Bump the version of `package` and add `commits` to its changelog.
+///
+/// If `fixed_rule` is set (because the package shares its version with others, see
+/// [`shared_rules`]), it's used instead of the rule from `commits`, so the package is released
+/// even if none of the commits apply to it.
 fn prepare_release_for_package(
     package: Package,
-    consider_scopes: bool,
-    prerelease_label: Option<&String>,
+    commits: ConventionalCommits,
+    fixed_rule: Option<ConventionalRule>,
+    prepare_release: &step::PrepareRelease,
     dry_run_stdout: Option<&mut Box<dyn Write>>,
 ) -> Result<Option<Release>, StepError> {
     let ConventionalCommits {
@@ -375,56 +941,203 @@ fn prepare_release_for_package(
         features,
         fixes,
         breaking_changes,
-    } = get_conventional_commits_after_last_stable_version(&package, consider_scopes)?;
-    let rule = if let Some(rule) = rule {
+        other_sections,
+        ..
+    } = commits;
+    let rule = if let Some(rule) = fixed_rule.or(rule) {
         rule
     } else {
         return Ok(None);
     };
 
-    let rule = if let Some(label) = prerelease_label {
+    let label = prepare_release
+        .prerelease_label
+        .as_ref()
+        .and_then(|prerelease_label| prerelease_label.for_package(package.name.as_deref()));
+    let rule = if let Some(label) = label {
         Rule::Pre {
-            label: label.clone(),
+            label: label.to_string(),
             stable_rule: rule,
+            start: prepare_release.prerelease_start.unwrap_or_default(),
         }
     } else {
         Rule::from(rule)
     };
-    let PackageVersion { package, version } =
+    let (PackageVersion { package, version }, previous_version, bump_level) =
         bump_version(&rule, dry_run_stdout.is_some(), package)?;
     let new_version_string = version.latest().to_string();
-    let new_changes =
-        new_changelog_lines(&new_version_string, &fixes, &features, &breaking_changes);
+    let new_changes = new_changelog_lines(
+        &new_version_string,
+        &fixes,
+        &features,
+        &breaking_changes,
+        &other_sections,
+    );
 
     let release = Release {
         version: version.into_latest(),
+        previous_version,
+        bump_level,
         changelog: new_changes.join("\n"),
-        package_name: package.name,
+        tag_prefix: package.tag_prefix(),
+        commits: CommitRange::since_last_stable(&package.tag_prefix())?,
+        package_name: package.name.clone(),
     };
-    let changelog = package.changelog.as_ref();
+    let debian_entry = package
+        .debian_changelog
+        .as_ref()
+        .map(|config| -> Result<_, StepError> {
+            let existing = debian_changelog::read(&config.path)?;
+            let entry = debian_changelog::entry(
+                config,
+                &existing,
+                release.package_name.as_deref(),
+                &release.version,
+                now(),
+                &new_changes,
+            )?;
+            Ok((config, existing, entry))
+        })
+        .transpose()?;
+    let rpm_date = format_date(now(), "%a %b %d %Y");
+    let rpm_entries = package
+        .versioned_files
+        .iter()
+        .filter(|versioned_file| matches!(versioned_file.format, PackageFormat::RpmSpec))
+        .map(|versioned_file| -> Result<_, StepError> {
+            let entry = rpm_spec::changelog_entry(
+                &std::fs::read_to_string(&versioned_file.path)?,
+                &release.version,
+                &rpm_date,
+                &new_changes,
+            )
+            .ok_or_else(|| StepError::MissingRpmPackager(versioned_file.path.clone()))?;
+            Ok((&versioned_file.path, entry))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     if let Some(stdout) = dry_run_stdout {
-        writeln!(
+        report_changelogs(
             stdout,
-            "Would bump {} version to {}",
-            release.package_name.as_deref().unwrap_or("package"),
-            new_version_string
+            &package,
+            &new_version_string,
+            &new_changes,
+            debian_entry.as_ref(),
+            &rpm_entries,
         )?;
-        if let Some(changelog) = changelog {
-            writeln!(
-                stdout,
-                "Would add the following to {}: \n{}",
-                changelog.path.display(),
-                new_changes.join("\n")
-            )?;
-        }
-        Ok(Some(release))
     } else {
-        if let Some(changelog) = changelog {
-            let contents = add_version_to_changelog(&changelog.content, &new_changes);
-            std::fs::write(&changelog.path, contents)?;
-            add_files(&[&changelog.path])?;
+        write_changelogs(
+            &package,
+            &release.version,
+            &new_changes,
+            debian_entry.as_ref(),
+            &rpm_entries,
+        )?;
+    }
+    Ok(Some(release))
+}
+
+/// Report the new version of `package` and what [`write_changelogs`] would add to each of its
+/// changelogs.
+fn report_changelogs(
+    stdout: &mut Box<dyn Write>,
+    package: &Package,
+    new_version_string: &str,
+    new_changes: &[String],
+    debian_entry: Option<&(&DebianChangelog, String, String)>,
+    rpm_entries: &[(&PathBuf, String)],
+) -> Result<(), StepError> {
+    writeln!(
+        stdout,
+        "Would bump {} version to {}",
+        package.name.as_deref().unwrap_or("package"),
+        new_version_string
+    )?;
+    if let Some(changelog) = &package.changelog {
+        writeln!(
+            stdout,
+            "Would add the following to {}: \n{}",
+            changelog.path.display(),
+            new_changes.join("\n")
+        )?;
+    }
+    for localized in &package.localized_changelogs {
+        writeln!(
+            stdout,
+            "Would add the following to {}: \n{}",
+            localized.changelog.path.display(),
+            localized_changelog_lines(&localized.locale, new_changes).join("\n")
+        )?;
+    }
+    if let Some(metainfo) = &package.metainfo {
+        writeln!(
+            stdout,
+            "Would add release {} to {}",
+            new_version_string,
+            metainfo.display()
+        )?;
+    }
+    if let Some((config, _, entry)) = debian_entry {
+        writeln!(
+            stdout,
+            "Would add the following to {}: \n{}",
+            config.path.display(),
+            entry
+        )?;
+    }
+    for (path, entry) in rpm_entries {
+        writeln!(
+            stdout,
+            "Would add the following to {}: \n{}",
+            path.display(),
+            entry
+        )?;
+    }
+    Ok(())
+}
+
+/// Add `new_changes` for `version` to every changelog of `package`.
+fn write_changelogs(
+    package: &Package,
+    version: &Version,
+    new_changes: &[String],
+    debian_entry: Option<&(&DebianChangelog, String, String)>,
+    rpm_entries: &[(&PathBuf, String)],
+) -> Result<(), StepError> {
+    if let Some(changelog) = &package.changelog {
+        let contents = add_version_to_changelog(&changelog.content, new_changes);
+        std::fs::write(&changelog.path, contents)?;
+        add_files(&[&changelog.path])?;
+    }
+    for localized in &package.localized_changelogs {
+        let new_lines = localized_changelog_lines(&localized.locale, new_changes);
+        let contents = add_version_to_changelog(&localized.changelog.content, &new_lines);
+        std::fs::write(&localized.changelog.path, contents)?;
+        add_files(&[&localized.changelog.path])?;
+    }
+    if let Some(metainfo) = &package.metainfo {
+        let date = format_date(now(), "%Y-%m-%d");
+        let contents = metainfo::add_release(
+            &std::fs::read_to_string(metainfo)?,
+            version,
+            &date,
+            new_changes,
+        )
+        .ok_or_else(|| StepError::InvalidMetainfo(metainfo.clone()))?;
+        std::fs::write(metainfo, contents)?;
+        add_files(&[metainfo])?;
+    }
+    if let Some((config, existing, entry)) = debian_entry {
+        if let Some(parent) = config.path.parent() {
+            std::fs::create_dir_all(parent)?;
         }
-        Ok(Some(release))
+        std::fs::write(&config.path, debian_changelog::add_entry(existing, entry))?;
+        add_files(&[&config.path])?;
+    }
+    for (path, entry) in rpm_entries {
+        let contents = rpm_spec::add_changelog_entry(&std::fs::read_to_string(path)?, entry);
+        std::fs::write(path, contents)?;
+        add_files(&[path])?;
     }
+    Ok(())
 }
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use semver::Version;
+
+use crate::config::DebianChangelog;
+use crate::step::StepError;
+use crate::template::format_date;
+
+/// Create the entry (stanza) of a Debian `changelog` for `version`, with a `*` item for every note
+/// in `new_changes` (the lines of the new Keep a Changelog entry).
+///
+/// The source package name and maintainer come from `config` if set, otherwise from the latest
+/// entry of `existing` (the current contents of the changelog). For a new changelog, the name of
+/// the knope package and `DEBFULLNAME <DEBEMAIL>` (like `dch` uses) are the last resort.
+pub(crate) fn entry(
+    config: &DebianChangelog,
+    existing: &str,
+    package_name: Option<&str>,
+    version: &Version,
+    seconds: u64,
+    new_changes: &[String],
+) -> Result<String, StepError> {
+    let invalid = || StepError::InvalidDebianChangelog(config.path.clone());
+    let source = config
+        .package
+        .clone()
+        .or_else(|| latest_source(existing))
+        .or_else(|| package_name.map(String::from))
+        .ok_or_else(invalid)?;
+    let maintainer = config
+        .maintainer
+        .clone()
+        .or_else(|| latest_maintainer(existing))
+        .or_else(maintainer_from_env)
+        .ok_or_else(invalid)?;
+    let distribution = config.distribution.as_deref().unwrap_or("unstable");
+    let urgency = config.urgency.as_deref().unwrap_or("medium");
+
+    let mut items = items(new_changes);
+    if items.is_empty() {
+        items.push(String::from("  * New release."));
+    }
+    Ok(format!(
+        "{source} ({version}) {distribution}; urgency={urgency}\n\n{items}\n\n -- {maintainer}  {date}\n",
+        version = debian_version(version),
+        items = items.join("\n"),
+        date = format_date(seconds, "%a, %d %b %Y %H:%M:%S +0000"),
+    ))
+}
+
+/// Add `entry` to the top of a Debian changelog, where the newest entry goes.
+pub(crate) fn add_entry(existing: &str, entry: &str) -> String {
+    if existing.trim().is_empty() {
+        entry.to_string()
+    } else {
+        format!("{entry}\n{existing}")
+    }
+}
+
+/// The contents of the changelog at `path`, which may not have been created yet.
+pub(crate) fn read(path: &Path) -> Result<String, StepError> {
+    if path.exists() {
+        Ok(std::fs::read_to_string(path)?)
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// A Debian version sorts `~` _before_ anything else, so `1.2.0-rc.0` becomes `1.2.0~rc.0` to stay
+/// lower than `1.2.0`.
+fn debian_version(version: &Version) -> String {
+    if version.pre.is_empty() {
+        version.to_string()
+    } else {
+        version.to_string().replacen('-', "~", 1)
+    }
+}
+
+/// The notes of a Keep a Changelog entry as Debian changelog items, leaving out the headers.
+fn items(new_changes: &[String]) -> Vec<String> {
+    new_changes
+        .iter()
+        .skip(1)
+        .flat_map(|block| block.lines())
+        .filter_map(|line| {
+            if let Some(note) = line.strip_prefix("- ") {
+                Some(format!("  * {}", note.trim()))
+            } else {
+                // A note which was wrapped onto multiple lines.
+                line.strip_prefix("  ")
+                    .map(|continued| format!("    {}", continued.trim()))
+            }
+        })
+        .collect()
+}
+
+fn maintainer_from_env() -> Option<String> {
+    let name = std::env::var("DEBFULLNAME").ok()?;
+    let email = std::env::var("DEBEMAIL").ok()?;
+    Some(format!("{name} <{email}>"))
+}
+
+/// The source package name from the first line of the latest entry, like `knope (0.1.0) ...`.
+fn latest_source(existing: &str) -> Option<String> {
+    let first_line = existing.lines().find(|line| !line.trim().is_empty())?;
+    first_line
+        .split_whitespace()
+        .next()
+        .filter(|source| !source.starts_with('('))
+        .map(String::from)
+}
+
+/// The maintainer from the trailer line of the latest entry, like ` -- Name <email>  <date>`.
+fn latest_maintainer(existing: &str) -> Option<String> {
+    let trailer = existing
+        .lines()
+        .find_map(|line| line.strip_prefix(" -- "))?;
+    let maintainer = trailer.split("  ").next()?.trim();
+    if maintainer.is_empty() {
+        None
+    } else {
+        Some(maintainer.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_entry {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn config() -> DebianChangelog {
+        DebianChangelog {
+            path: PathBuf::from("debian/changelog"),
+            package: None,
+            distribution: None,
+            urgency: None,
+            maintainer: None,
+        }
+    }
+
+    fn new_changes() -> Vec<String> {
+        vec![
+            String::from("## 1.1.0\n"),
+            String::from("### Features\n"),
+            String::from("- A new feature"),
+            String::from(""),
+            String::from("### Fixes\n"),
+            String::from("- A fix which was wrapped onto\n  two lines"),
+            String::from(""),
+        ]
+    }
+
+    const EXISTING: &str = "\
+knope-cli (1.0.0) jammy; urgency=low
+
+  * Initial release.
+
+ -- Jane Doe <jane@example.com>  Sun, 01 Jan 2023 00:00:00 +0000
+";
+
+    #[test]
+    fn from_latest_entry() {
+        let entry = entry(
+            &config(),
+            EXISTING,
+            Some("knope"),
+            &Version::new(1, 1, 0),
+            1_685_577_600,
+            &new_changes(),
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            "\
+knope-cli (1.1.0) unstable; urgency=medium
+
+  * A new feature
+  * A fix which was wrapped onto
+    two lines
+
+ -- Jane Doe <jane@example.com>  Thu, 01 Jun 2023 00:00:00 +0000
+"
+        );
+        assert_eq!(add_entry(EXISTING, &entry), format!("{entry}\n{EXISTING}"));
+    }
+
+    #[test]
+    fn from_config() {
+        let config = DebianChangelog {
+            package: Some(String::from("knope")),
+            distribution: Some(String::from("bookworm")),
+            urgency: Some(String::from("high")),
+            maintainer: Some(String::from("Release Bot <bot@example.com>")),
+            ..config()
+        };
+        let version = Version::parse("2.0.0-rc.1").unwrap();
+        let entry = entry(
+            &config,
+            "",
+            None,
+            &version,
+            0,
+            &[String::from("## 2.0.0-rc.1\n")],
+        );
+        assert_eq!(
+            entry.unwrap(),
+            "\
+knope (2.0.0~rc.1) bookworm; urgency=high
+
+  * New release.
+
+ -- Release Bot <bot@example.com>  Thu, 01 Jan 1970 00:00:00 +0000
+"
+        );
+    }
+
+    #[test]
+    fn no_maintainer() {
+        if maintainer_from_env().is_some() {
+            return;
+        }
+        let result = entry(
+            &config(),
+            "",
+            Some("knope"),
+            &Version::new(1, 0, 0),
+            0,
+            &new_changes(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn build_metadata() {
+        assert_eq!(
+            debian_version(&Version::parse("1.0.0+build-7").unwrap()),
+            "1.0.0+build-7"
+        );
+    }
+}
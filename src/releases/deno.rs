@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string value of the top-level `"version"` key is, without its quotes.
+///
+/// `deno.jsonc` can have comments (and trailing commas), which a JSON parser would reject, so
+/// the version is found and replaced in place—which also keeps the formatting of the file.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let mut depth = 0_usize;
+    let mut position = 0;
+    while let Some(c) = content[position..].chars().next() {
+        let rest = &content[position..];
+        if rest.starts_with("//") || rest.starts_with("/*") {
+            position = skip_trivia(content, position);
+            continue;
+        }
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth = depth.checked_sub(1)?,
+            '"' => {
+                let key = position..position + string_len(rest)?;
+                position = skip_trivia(content, key.end);
+                let is_version_key = depth == 1
+                    && &content[key.start + 1..key.end - 1] == "version"
+                    && content[position..].starts_with(':');
+                if is_version_key {
+                    let value_start = skip_trivia(content, position + 1);
+                    let value_len = string_len(&content[value_start..])?;
+                    return Some(value_start + 1..value_start + value_len - 1);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        position += c.len_utf8();
+    }
+    None
+}
+
+/// The position of the first thing after `position` which isn't whitespace or a comment.
+pub(super) fn skip_trivia(content: &str, mut position: usize) -> usize {
+    loop {
+        let rest = &content[position..];
+        let trimmed = rest.trim_start();
+        position += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            position += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else if trimmed.starts_with("/*") {
+            position += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+        } else {
+            return position;
+        }
+    }
+}
+
+/// The length of the string at the start of `text`, including both quotes, if there is one.
+pub(super) fn string_len(text: &str) -> Option<usize> {
+    if !text.starts_with('"') {
+        return None;
+    }
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_deno_json {
+    const CONTENT: &str = r#"{
+  // The name and version are used when publishing to JSR
+  "name": "@scope/my_package",
+  /* "version": "0.0.1", */
+  "version": "1.2.3",
+  "exports": "./mod.ts",
+  "imports": {
+    "version": "jsr:@std/version@0.1.0",
+  },
+}
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace(r#""version": "1.2.3""#, r#""version": "2.0.0""#))
+        );
+    }
+
+    #[test]
+    fn nested_version() {
+        let content = r#"{"name": "@scope/my_package", "tasks": {"version": "echo 1.2.3"}}"#;
+        assert_eq!(super::get_version(content), None);
+    }
+}
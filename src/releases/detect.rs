@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+use itertools::Itertools;
+use miette::{IntoDiagnostic, Result};
+
+use crate::releases::git::default_tag_prefix;
+use crate::releases::package::{PackageFormat, PACKAGE_FORMAT_FILE_NAMES};
+
+/// Directories which are never worth scanning, even outside of a Git repo.
+const SKIPPED_DIRECTORIES: [&str; 2] = ["node_modules", "target"];
+
+/// The implementation of `knope detect`.
+///
+/// Recursively scans the current directory for every supported versioned file, prints the version
+/// found in each, and warns if the files of a package don't all agree. Every versioned file in the
+/// same directory is treated as part of the same package.
+pub(crate) fn detect() -> Result<()> {
+    let repo = Repository::open(".").ok();
+    let mut paths = Vec::new();
+    find_versioned_files(Path::new("."), repo.as_ref(), &mut paths).into_diagnostic()?;
+    paths.sort();
+
+    if paths.is_empty() {
+        println!(
+            "No supported versioned files found. The supported formats are {formats}.",
+            formats = PACKAGE_FORMAT_FILE_NAMES.join(", ")
+        );
+        return Ok(());
+    }
+
+    println!("Found the following versioned files:");
+    let mut packages: BTreeMap<&Path, Vec<String>> = BTreeMap::new();
+    for path in &paths {
+        match detect_version(path) {
+            Ok((version, PackageFormat::Go)) => {
+                // go.mod doesn't record a full version, so it can't disagree with anything.
+                println!("  {}: {version} (from Git tags)", path.display());
+            }
+            Ok((version, _)) => {
+                println!("  {}: {version}", path.display());
+                let dir = path.parent().unwrap_or_else(|| Path::new(""));
+                packages.entry(dir).or_default().push(version);
+            }
+            Err(reason) => println!("  {}: could not be parsed ({reason})", path.display()),
+        }
+    }
+
+    for (dir, versions) in packages {
+        let unique_versions = versions.into_iter().unique().collect_vec();
+        if unique_versions.len() > 1 {
+            let dir = if dir.as_os_str().is_empty() {
+                String::from("the current directory")
+            } else {
+                dir.display().to_string()
+            };
+            println!(
+                "WARNING: Found mismatched versions {} in {dir}. A single package must have the same version in every versioned file.",
+                unique_versions.join(", "),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn find_versioned_files(
+    dir: &Path,
+    repo: Option<&Repository>,
+    paths: &mut Vec<PathBuf>,
+) -> std::io::Result<()> {
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let path = path
+            .strip_prefix(".")
+            .map(Path::to_path_buf)
+            .unwrap_or(path);
+        if let Some(repo) = repo {
+            if matches!(repo.is_path_ignored(&path), Ok(true)) {
+                continue;
+            }
+        }
+        if entry.file_type()?.is_dir() {
+            if !SKIPPED_DIRECTORIES.contains(&file_name.as_ref()) {
+                find_versioned_files(&path, repo, paths)?;
+            }
+        } else if PACKAGE_FORMAT_FILE_NAMES.contains(&file_name.as_ref()) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Get the version string and format of a single versioned file, or a description of why it
+/// couldn't be parsed.
+fn detect_version(path: &Path) -> Result<(String, PackageFormat), String> {
+    let format = PackageFormat::try_from(&path.to_path_buf()).map_err(|err| err.to_string())?;
+    let content = read_to_string(path).map_err(|err| err.to_string())?;
+    format
+        .get_version(&content, &default_tag_prefix(None), path)
+        .map(|version| (version, format))
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,171 @@
+use std::ops::Range;
+
+use semver::Version;
+
+/// The build argument which holds the version, like `ARG VERSION=1.2.3`.
+const ARG: &str = "VERSION";
+/// The label which holds the version, like `LABEL org.opencontainers.image.version=1.2.3`.
+const LABEL: &str = "org.opencontainers.image.version";
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_spans(content)
+        .first()
+        .map(|span| String::from(&content[span.clone()]))
+}
+
+/// Replace the version in every `ARG VERSION` and `org.opencontainers.image.version` label.
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let spans = version_spans(&content);
+    if spans.is_empty() {
+        return None;
+    }
+    // Replace from the end, so that the earlier spans are still in the right place.
+    for span in spans.into_iter().rev() {
+        content.replace_range(span, new_version);
+    }
+    Some(content)
+}
+
+/// Where the default value of every `ARG VERSION` and the value of every
+/// `org.opencontainers.image.version` label are, as long as they're semantic versions.
+fn version_spans(content: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut line_start = 0;
+    // If the previous line ended with `\`, whether the instruction it continues is a `LABEL`.
+    let mut continued_label = None;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let trimmed = line.trim_start();
+        // Comments can be in the middle of an instruction, without ending it.
+        if trimmed.starts_with('#') {
+            continue;
+        }
+        let offset = start + line.len() - trimmed.len();
+        let continues = trimmed.trim_end().ends_with('\\');
+        let (instruction, arguments) = trimmed
+            .split_once(char::is_whitespace)
+            .unwrap_or((trimmed, ""));
+        let arguments_offset = offset + trimmed.len() - arguments.len();
+        let label_pairs = match continued_label {
+            Some(true) => Some((trimmed, offset)),
+            Some(false) => None,
+            None if instruction.eq_ignore_ascii_case("LABEL") => {
+                Some((arguments, arguments_offset))
+            }
+            None => {
+                if instruction.eq_ignore_ascii_case("ARG") {
+                    spans
+                        .extend(arg_version_span(arguments).map(|span| {
+                            arguments_offset + span.start..arguments_offset + span.end
+                        }));
+                }
+                None
+            }
+        };
+        if let Some((pairs, pairs_offset)) = label_pairs {
+            spans.extend(
+                label_version_spans(pairs)
+                    .into_iter()
+                    .map(|span| pairs_offset + span.start..pairs_offset + span.end),
+            );
+        }
+        continued_label = continues.then_some(label_pairs.is_some());
+    }
+    spans
+}
+
+/// Where the default value is in the arguments of an `ARG` instruction, like `VERSION=1.2.3`.
+fn arg_version_span(arguments: &str) -> Option<Range<usize>> {
+    let trimmed = arguments.trim_start();
+    let value_start = arguments.len() - trimmed.len() + ARG.len() + 1;
+    trimmed.strip_prefix(ARG)?.strip_prefix('=')?;
+    value_span(&arguments[value_start..])
+        .map(|span| value_start + span.start..value_start + span.end)
+}
+
+/// Where the value of every version label is in `key=value` pairs, where the key could be quoted.
+fn label_version_spans(pairs: &str) -> Vec<Range<usize>> {
+    pairs
+        .match_indices(LABEL)
+        .filter_map(|(key_start, _)| {
+            let before = &pairs[..key_start];
+            let quoted = before.ends_with(['"', '\'']);
+            let key_boundary = before
+                .trim_end_matches(['"', '\''])
+                .chars()
+                .last()
+                .map_or(true, char::is_whitespace);
+            if !key_boundary {
+                return None;
+            }
+            let mut value_start = key_start + LABEL.len();
+            if quoted {
+                value_start += 1;
+            }
+            pairs[value_start..].strip_prefix('=')?;
+            value_start += 1;
+            value_span(&pairs[value_start..])
+                .map(|span| value_start + span.start..value_start + span.end)
+        })
+        .collect()
+}
+
+/// Where a (possibly quoted) value at the start of `text` is, without its quotes, if it's a
+/// semantic version.
+fn value_span(text: &str) -> Option<Range<usize>> {
+    let span = match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => 1..1 + text[1..].find(quote)?,
+        _ => {
+            0..text
+                .find(|c: char| c.is_whitespace() || c == '\\')
+                .unwrap_or(text.len())
+        }
+    };
+    Version::parse(&text[span.clone()]).is_ok().then_some(span)
+}
+
+#[cfg(test)]
+mod test_dockerfile {
+    use super::*;
+
+    const CONTENT: &str = r#"# syntax=docker/dockerfile:1
+FROM rust:1.64.0 AS build
+ARG VERSION=1.2.3
+RUN cargo install my-tool --version "$VERSION"
+
+FROM debian:bullseye-slim
+LABEL org.opencontainers.image.title="My Tool" \
+      # The version of my-tool, not of Debian
+      org.opencontainers.image.version="1.2.3"
+COPY --from=build /usr/local/cargo/bin/my-tool /usr/local/bin/my-tool
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn label_only() {
+        let content =
+            "FROM scratch\nlabel version=\"0.1.0\" org.opencontainers.image.version=1.2.3\n";
+        assert_eq!(get_version(content), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn not_a_version() {
+        assert_eq!(get_version("ARG VERSION=latest\n"), None);
+        assert_eq!(get_version("ARG VERSION\n"), None);
+        assert_eq!(get_version("ARG OTHER_VERSION=1.2.3\n"), None);
+        assert_eq!(get_version("RUN echo \\\n  ARG VERSION=1.2.3\n"), None);
+    }
+}
@@ -1,19 +1,50 @@
+use std::collections::HashSet;
 use std::env::current_dir;
 use std::io::Write;
+use std::path::Path;
+use std::process::Command;
 
 use git_repository::object::Kind;
-use git_repository::open;
 use git_repository::refs::transaction::PreviousValue;
+use git_repository::{open, Id, ObjectId};
+use itertools::Itertools;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 
-use crate::releases::{CurrentVersions, Release};
+use crate::config::Versioning;
+use crate::releases::{CurrentVersions, Package, Release};
 use crate::step::StepError;
 
-pub(crate) fn tag_name(version: &Version, package_name: &Option<String>) -> String {
-    let prefix = package_name
-        .as_ref()
-        .map_or_else(|| "v".to_string(), |name| format!("{name}/v"));
-    format!("{prefix}{version}")
+/// The commits that a release was prepared from: everything reachable from `to` but not `from`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) struct CommitRange {
+    /// The commit of the last stable release, if there was one.
+    pub(crate) from: Option<String>,
+    /// The commit that HEAD pointed at.
+    pub(crate) to: String,
+}
+
+impl CommitRange {
+    /// The commits since the last stable release with `tag_prefix`, up to the current HEAD.
+    pub(crate) fn since_last_stable(tag_prefix: &str) -> Result<Self, StepError> {
+        let from = get_current_versions_from_tag(tag_prefix)?.and_then(|current_versions| {
+            tag_commit_id(&tag_name(&current_versions.stable, tag_prefix))
+        });
+        Ok(Self {
+            from,
+            to: head_commit_id()?,
+        })
+    }
+}
+
+pub(crate) fn tag_name(version: &Version, tag_prefix: &str) -> String {
+    format!("{tag_prefix}{version}")
+}
+
+/// The prefix of Git tags for a package which doesn't configure one: `v` for a single package, or
+/// `<name>/v` for a package named `name`.
+pub(crate) fn default_tag_prefix(package_name: Option<&str>) -> String {
+    package_name.map_or_else(|| String::from("v"), |name| format!("{name}/v"))
 }
 
 pub(crate) fn release(
@@ -22,10 +53,10 @@ pub(crate) fn release(
 ) -> Result<(), StepError> {
     let Release {
         version,
-        changelog: _changelog,
-        package_name,
+        tag_prefix,
+        ..
     } = release;
-    let tag = tag_name(version, package_name);
+    let tag = tag_name(version, tag_prefix);
 
     if let Some(stdout) = dry_run_stdout {
         writeln!(stdout, "Would create Git tag {}", tag)?;
@@ -46,41 +77,310 @@ pub(crate) fn release(
     Ok(())
 }
 
+/// The full ID of the current HEAD commit.
+pub(crate) fn head_commit_id() -> Result<String, StepError> {
+    let repo = open(current_dir()?).map_err(|_e| StepError::NotAGitRepo)?;
+    let id = repo.head_commit()?.id.to_string();
+    Ok(id)
+}
+
+/// The full ID of the commit that `tag` points at, if it exists.
+pub(crate) fn tag_commit_id(tag: &str) -> Option<String> {
+    let repo = open(current_dir().ok()?).ok()?;
+    let reference = repo.find_reference(&format!("refs/tags/{tag}")).ok()?;
+    Some(reference.into_fully_peeled_id().ok()?.detach().to_string())
+}
+
+/// Check the signature of `tag` with `git verify-tag`, only trusting SSH keys listed in the
+/// `allowed_signers` file.
+pub(crate) fn verify_tag(tag: &str, allowed_signers: &Path) -> Result<(), StepError> {
+    let output = Command::new("git")
+        .arg("-c")
+        .arg(format!(
+            "gpg.ssh.allowedSignersFile={}",
+            allowed_signers.display()
+        ))
+        .arg("verify-tag")
+        .arg(tag)
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(StepError::UnverifiedTag {
+            tag: tag.to_string(),
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// Which of `tags` exist on `remote` right now, asking the remote itself (with `git ls-remote`)
+/// rather than relying on the last fetch.
+pub(crate) fn remote_tags(remote: &str, tags: &[String]) -> Result<Vec<String>, StepError> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--tags", remote])
+        .args(tags.iter().map(|tag| format!("refs/tags/{tag}")))
+        .output()?;
+    if !output.status.success() {
+        return Err(StepError::CommandError(output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(tags
+        .iter()
+        .filter(|tag| {
+            let reference = format!("refs/tags/{tag}");
+            stdout.lines().any(|line| {
+                line.split_whitespace()
+                    .nth(1)
+                    .map(|name| name.trim_end_matches("^{}"))
+                    == Some(reference.as_str())
+            })
+        })
+        .cloned()
+        .collect())
+}
+
+/// Delete `tag` from the local repo and from `remote`.
+pub(crate) fn delete_tag(tag: &str, remote: &str) -> Result<(), StepError> {
+    run_git(&["push", remote, "--delete", &format!("refs/tags/{tag}")])?;
+    run_git(&["tag", "--delete", tag])
+}
+
+/// Create a new commit which undoes the changes of `commit`.
+pub(crate) fn revert_commit(commit: &str) -> Result<(), StepError> {
+    run_git(&["revert", "--no-edit", commit])
+}
+
+fn run_git(args: &[&str]) -> Result<(), StepError> {
+    let status = Command::new("git").args(args).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(StepError::CommandError(status))
+    }
+}
+
+/// Find the latest stable and pre-release versions of a package from its Git tags.
+///
+/// Only tags which point at HEAD or one of its ancestors are considered, so that a maintenance
+/// branch (e.g., 1.x) isn't affected by tags created on other branches (e.g., 2.x).
 pub(crate) fn get_current_versions_from_tag(
-    prefix: Option<&str>,
+    tag_prefix: &str,
 ) -> Result<Option<CurrentVersions>, StepError> {
+    Ok(current_versions(released_versions(tag_prefix)?))
+}
+
+/// Every version of a package which has a Git tag pointing at HEAD or one of its ancestors, in no
+/// particular order.
+pub(crate) fn released_versions(tag_prefix: &str) -> Result<Vec<Version>, StepError> {
     let repo = open(current_dir()?).map_err(|_e| StepError::NotAGitRepo)?;
+    let ancestors = match repo.head_commit() {
+        Ok(head) => head
+            .ancestors()
+            .all()?
+            .map(|id| id.map(Id::detach))
+            .collect::<Result<HashSet<ObjectId>, _>>()?,
+        // No commits means nothing could be tagged.
+        Err(_) => return Ok(Vec::new()),
+    };
     let references = repo.references().map_err(|_e| StepError::NotAGitRepo)?;
-    let tags = references
+    Ok(references
         .tags()
         .map_err(|_e| StepError::NotAGitRepo)?
-        .flat_map(|tag| {
-            tag.map(|reference| {
-                reference
-                    .name()
-                    .as_bstr()
-                    .to_string()
-                    .replace("refs/tags/", "")
-            })
-        });
-    let mut stable = None;
-    let mut prerelease = None;
-    let pattern = prefix
-        .as_ref()
-        .map_or_else(|| String::from("v"), |prefix| format!("{}/v", prefix));
-    for tag in tags {
-        if !tag.starts_with(&pattern) {
-            continue;
-        }
-        let version_string = tag.replace(&pattern, "");
-        if let Ok(version) = Version::parse(version_string.as_str()) {
-            if version.pre.is_empty() {
-                stable = Some(version);
-                prerelease = None; // Don't consider prereleases older than the stable version.
-            } else {
-                prerelease.get_or_insert(version);
+        .flatten()
+        .filter_map(|reference| {
+            let tag = reference
+                .name()
+                .as_bstr()
+                .to_string()
+                .replace("refs/tags/", "");
+            let version = Version::parse(tag.strip_prefix(tag_prefix)?).ok()?;
+            let target = reference.into_fully_peeled_id().ok()?.detach();
+            ancestors.contains(&target).then_some(version)
+        })
+        .collect())
+}
+
+/// Pick the latest stable version and any pre-release version newer than it.
+fn current_versions(versions: Vec<Version>) -> Option<CurrentVersions> {
+    let (prereleases, stables): (Vec<_>, Vec<_>) = versions
+        .into_iter()
+        .partition(|version| !version.pre.is_empty());
+    let stable = stables.into_iter().max()?;
+    // Don't consider prereleases older than the stable version.
+    let prerelease = prereleases
+        .into_iter()
+        .filter(|prerelease| prerelease > &stable)
+        .max();
+    Some(CurrentVersions { stable, prerelease })
+}
+
+/// Every tag in the current repo, or none if it isn't a Git repo.
+fn all_tags() -> Vec<String> {
+    let repo = match current_dir().ok().and_then(|dir| open(dir).ok()) {
+        Some(repo) => repo,
+        None => return Vec::new(),
+    };
+    let references = match repo.references() {
+        Ok(references) => references,
+        Err(_) => return Vec::new(),
+    };
+    references
+        .tags()
+        .map(|tags| {
+            tags.flatten()
+                .map(|reference| {
+                    reference
+                        .name()
+                        .as_bstr()
+                        .to_string()
+                        .replace("refs/tags/", "")
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Warnings for packages whose tags can't be told apart: packages which share a tag prefix (when
+/// they're versioned independently), and existing tags which look like a version of more than one
+/// package.
+pub(crate) fn tag_prefix_warnings(packages: &[Package], versioning: Versioning) -> Vec<String> {
+    if packages.len() < 2 {
+        return Vec::new();
+    }
+    let prefixes = packages
+        .iter()
+        .map(|package| {
+            (
+                package.name.clone().unwrap_or_default(),
+                package.tag_prefix(),
+            )
+        })
+        .collect_vec();
+    collisions(&prefixes, versioning, &all_tags())
+}
+
+/// The implementation of [`tag_prefix_warnings`] for `prefixes` of `(package_name, tag_prefix)`.
+fn collisions(
+    prefixes: &[(String, String)],
+    versioning: Versioning,
+    tags: &[String],
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if versioning == Versioning::Independent {
+        let groups = prefixes
+            .iter()
+            .into_group_map_by(|(_, prefix)| prefix)
+            .into_iter()
+            .sorted();
+        for (prefix, group) in groups {
+            if group.len() > 1 {
+                warnings.push(format!(
+                    "Packages {names} all use the tag prefix {prefix}, so they will create the same tags. Set a different tag_prefix for each of them.",
+                    names = group.iter().map(|(name, _)| name).join(", ")
+                ));
             }
         }
     }
-    Ok(stable.map(|stable| CurrentVersions { stable, prerelease }))
+    for tag in tags {
+        let matching = prefixes
+            .iter()
+            .filter(|(_, prefix)| {
+                tag.strip_prefix(prefix.as_str())
+                    .map_or(false, |version| Version::parse(version).is_ok())
+            })
+            .collect_vec();
+        // Packages which share a prefix were already warned about above.
+        if matching.iter().map(|(_, prefix)| prefix).unique().count() > 1 {
+            warnings.push(format!(
+                "The tag {tag} could be a version of any of the packages {names}. Change the tag_prefix of all but one of them.",
+                names = matching.iter().map(|(name, _)| name).join(", ")
+            ));
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod test_current_versions {
+    use super::*;
+
+    fn versions(versions: &[&str]) -> Vec<Version> {
+        versions
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn latest_by_version_not_by_name() {
+        let current = current_versions(versions(&["1.9.0", "1.10.0", "1.2.0"])).unwrap();
+        assert_eq!(current.stable, Version::new(1, 10, 0));
+        assert_eq!(current.prerelease, None);
+    }
+
+    #[test]
+    fn ignore_old_prerelease() {
+        let current = current_versions(versions(&[
+            "1.0.0-rc.0",
+            "1.0.0",
+            "1.1.0-rc.0",
+            "1.1.0-rc.1",
+        ]))
+        .unwrap();
+        assert_eq!(current.stable, Version::new(1, 0, 0));
+        assert_eq!(
+            current.prerelease,
+            Some(Version::parse("1.1.0-rc.1").unwrap())
+        );
+
+        let current = current_versions(versions(&["1.1.0-rc.0", "1.1.0"])).unwrap();
+        assert_eq!(current.prerelease, None);
+    }
+
+    #[test]
+    fn prerelease_only() {
+        assert_eq!(current_versions(versions(&["1.0.0-rc.0"])), None);
+    }
+}
+
+#[cfg(test)]
+mod test_collisions {
+    use super::*;
+
+    fn prefixes(prefixes: &[(&str, &str)]) -> Vec<(String, String)> {
+        prefixes
+            .iter()
+            .map(|(name, prefix)| (String::from(*name), String::from(*prefix)))
+            .collect()
+    }
+
+    #[test]
+    fn shared_prefix() {
+        let prefixes = prefixes(&[("first", "v"), ("second", "v"), ("third", "third/v")]);
+
+        assert_eq!(
+            collisions(&prefixes, Versioning::Independent, &[]),
+            vec![String::from("Packages first, second all use the tag prefix v, so they will create the same tags. Set a different tag_prefix for each of them.")]
+        );
+        assert!(collisions(&prefixes, Versioning::Fixed, &[]).is_empty());
+    }
+
+    #[test]
+    fn ambiguous_tag() {
+        let prefixes = prefixes(&[("first", ""), ("second", "1")]);
+        let tags = [String::from("12.0.0"), String::from("3.0.0")];
+
+        assert_eq!(
+            collisions(&prefixes, Versioning::Independent, &tags),
+            vec![String::from("The tag 12.0.0 could be a version of any of the packages first, second. Change the tag_prefix of all but one of them.")]
+        );
+    }
+
+    #[test]
+    fn distinct_prefixes() {
+        let prefixes = prefixes(&[("first", "first/v"), ("second", "second/v")]);
+        let tags = [String::from("first/v1.0.0"), String::from("second/v2.0.0")];
+
+        assert!(collisions(&prefixes, Versioning::Independent, &tags).is_empty());
+    }
 }
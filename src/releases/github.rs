@@ -1,6 +1,9 @@
+use std::ffi::OsStr;
+use std::fs::read;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::app_config::get_or_prompt_for_github_token;
 use crate::config::GitHub;
@@ -10,43 +13,65 @@ use crate::state;
 use crate::state::GitHub::{Initialized, New};
 use crate::step::StepError;
 
+/// The most characters GitHub allows in the body of a release.
+const MAX_BODY_LENGTH: usize = 125_000;
+
+/// The name of the asset which holds the full release notes, when they're truncated.
+const FULL_NOTES_ASSET_NAME: &str = "release-notes.md";
+
+/// Create a GitHub release, attaching `asset` to it (if any).
+///
+/// Release notes which are too long for GitHub are truncated, with a link to `changelog` (if any)
+/// for the rest. If `full_notes_asset` is set, the full notes are also attached to the release.
 pub(crate) fn release(
     release: &Release,
     github_state: state::GitHub,
     github_config: &GitHub,
+    asset: Option<&Path>,
+    full_notes_asset: bool,
+    changelog: Option<&Path>,
     dry_run_stdout: Option<&mut Box<dyn Write>>,
 ) -> Result<state::GitHub, StepError> {
     let Release {
         version,
-        changelog,
+        changelog: release_notes,
         package_name,
+        ..
     } = release;
     let version_string = release.version.to_string();
 
-    let tag_name = tag_name(version, package_name);
+    let tag_name = tag_name(version, &release.tag_prefix);
     let name = if let Some(package_name) = package_name {
         format!("{} {}", package_name, version_string)
     } else {
         version_string
     };
 
+    let changelog_url = changelog.map(|path| {
+        format!(
+            "https://github.com/{owner}/{repo}/blob/{tag_name}/{path}",
+            owner = github_config.owner,
+            repo = github_config.repo,
+            path = path.display(),
+        )
+    });
+    let truncated_body = truncate_body(release_notes, changelog_url.as_deref(), full_notes_asset);
+    let full_notes = truncated_body.is_some() && full_notes_asset;
+
     let github_release = GitHubRelease {
         tag_name: &tag_name,
         name: &name,
-        body: changelog,
+        body: truncated_body.as_deref().unwrap_or(release_notes),
         prerelease: !release.version.pre.is_empty(),
     };
 
     if let Some(stdout) = dry_run_stdout {
-        let release_type = if github_release.prerelease {
-            "prerelease"
-        } else {
-            "release"
-        };
-        writeln!(
+        report_release(
             stdout,
-            "Would create a {} on GitHub with name and tag {} and body:\n{}",
-            release_type, github_release.tag_name, github_release.body
+            &github_release,
+            truncated_body.is_some(),
+            asset,
+            full_notes,
         )?;
         return Ok(github_state);
     }
@@ -70,9 +95,173 @@ pub(crate) fn release(
     if response.status() != 201 {
         return Err(StepError::ApiResponseError(None));
     }
+    if asset.is_some() || full_notes {
+        let created: CreatedRelease = response
+            .into_json()
+            .map_err(|_| StepError::ApiResponseError(None))?;
+        if let Some(asset) = asset {
+            let name = asset
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .ok_or_else(|| StepError::FileNotFound(asset.to_path_buf()))?;
+            let content = read(asset)?;
+            upload_asset(
+                &created.upload_url,
+                &token_header,
+                &name,
+                "application/json",
+                &content,
+            )?;
+        }
+        if full_notes {
+            upload_asset(
+                &created.upload_url,
+                &token_header,
+                FULL_NOTES_ASSET_NAME,
+                "text/markdown",
+                release_notes.as_bytes(),
+            )?;
+        }
+    }
     Ok(Initialized { token })
 }
 
+/// Report what [`release`] would create on GitHub.
+fn report_release(
+    stdout: &mut Box<dyn Write>,
+    github_release: &GitHubRelease,
+    truncated: bool,
+    asset: Option<&Path>,
+    full_notes: bool,
+) -> Result<(), StepError> {
+    let release_type = if github_release.prerelease {
+        "prerelease"
+    } else {
+        "release"
+    };
+    writeln!(
+        stdout,
+        "Would create a {} on GitHub with name and tag {} and body:\n{}",
+        release_type, github_release.tag_name, github_release.body
+    )?;
+    if truncated {
+        writeln!(
+            stdout,
+            "Would truncate the release notes to fit GitHub's limit of {MAX_BODY_LENGTH} characters"
+        )?;
+    }
+    if let Some(asset) = asset {
+        writeln!(stdout, "Would attach {} to the release", asset.display())?;
+    }
+    if full_notes {
+        writeln!(
+            stdout,
+            "Would attach {FULL_NOTES_ASSET_NAME} to the release"
+        )?;
+    }
+    Ok(())
+}
+
+/// Attach every one of `files` to the existing GitHub release for `tag_name`.
+pub(crate) fn upload_assets(
+    tag_name: &str,
+    files: &[PathBuf],
+    github_state: state::GitHub,
+    github_config: &GitHub,
+) -> Result<state::GitHub, StepError> {
+    let token = match github_state {
+        Initialized { token } => token,
+        New => get_or_prompt_for_github_token()?,
+    };
+    let token_header = format!("token {}", &token);
+    let existing: CreatedRelease = ureq::get(&format!(
+        "https://api.github.com/repos/{owner}/{repo}/releases/tags/{tag_name}",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    ))
+    .set("Authorization", &token_header)
+    .call()?
+    .into_json()
+    .map_err(|_| StepError::ApiResponseError(None))?;
+    for file in files {
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| StepError::FileNotFound(file.clone()))?;
+        let content_type = match file.extension().and_then(OsStr::to_str) {
+            Some("json") => "application/json",
+            Some("txt") => "text/plain",
+            _ => "application/octet-stream",
+        };
+        upload_asset(
+            &existing.upload_url,
+            &token_header,
+            &name,
+            content_type,
+            &read(file)?,
+        )?;
+    }
+    Ok(Initialized { token })
+}
+
+/// Attach `content` to a release as a file named `name`, using the `upload_url` that GitHub
+/// returned for it.
+fn upload_asset(
+    upload_url: &str,
+    token_header: &str,
+    name: &str,
+    content_type: &str,
+    content: &[u8],
+) -> Result<(), StepError> {
+    // The URL is a template like `.../assets{?name,label}`
+    let url = upload_url
+        .split_once('{')
+        .map_or(upload_url, |(url, _)| url);
+    let response = ureq::post(url)
+        .query("name", name)
+        .set("Authorization", token_header)
+        .set("Content-Type", content_type)
+        .send_bytes(content)?;
+    if response.status() != 201 {
+        return Err(StepError::ApiResponseError(None));
+    }
+    Ok(())
+}
+
+/// If `body` is too long for a GitHub release, cut it off at the end of a line so that it fits
+/// along with a note about where to find the rest: `changelog_url` and/or the full notes asset.
+fn truncate_body(
+    body: &str,
+    changelog_url: Option<&str>,
+    full_notes_asset: bool,
+) -> Option<String> {
+    if body.chars().count() <= MAX_BODY_LENGTH {
+        return None;
+    }
+    let rest = match (changelog_url, full_notes_asset) {
+        (Some(url), true) => {
+            format!(" See [the changelog]({url}) or the attached `{FULL_NOTES_ASSET_NAME}` for the full notes.")
+        }
+        (Some(url), false) => format!(" See [the changelog]({url}) for the full notes."),
+        (None, true) => format!(" See the attached `{FULL_NOTES_ASSET_NAME}` for the full notes."),
+        (None, false) => String::new(),
+    };
+    let footer = format!("\n\n---\n\n_These release notes were too long for GitHub, so they were truncated._{rest}\n");
+    let length = MAX_BODY_LENGTH - footer.chars().count();
+    let end = body
+        .char_indices()
+        .nth(length)
+        .map_or(body.len(), |(index, _)| index);
+    let kept = &body[..end];
+    let kept = kept.rfind('\n').map_or(kept, |line_end| &kept[..line_end]);
+    Some(format!("{}{footer}", kept.trim_end()))
+}
+
+#[derive(Deserialize)]
+struct CreatedRelease {
+    upload_url: String,
+}
+
 #[derive(Serialize)]
 struct GitHubRelease<'a> {
     tag_name: &'a str,
@@ -80,3 +269,100 @@ struct GitHubRelease<'a> {
     body: &'a str,
     prerelease: bool,
 }
+
+/// What to do with the GitHub release of a version which is being yanked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum YankRelease {
+    Delete,
+    Draft,
+    Prerelease,
+}
+
+/// Delete the GitHub release for `tag_name`, or hide it as a draft or pre-release.
+pub(crate) fn yank(
+    tag_name: &str,
+    action: YankRelease,
+    github_state: state::GitHub,
+    github_config: &GitHub,
+) -> Result<state::GitHub, StepError> {
+    let token = match github_state {
+        Initialized { token } => token,
+        New => get_or_prompt_for_github_token()?,
+    };
+    let releases_url = format!(
+        "https://api.github.com/repos/{owner}/{repo}/releases",
+        owner = github_config.owner,
+        repo = github_config.repo,
+    );
+    let token_header = format!("token {}", &token);
+
+    let existing: ExistingRelease = ureq::get(&format!("{releases_url}/tags/{tag_name}"))
+        .set("Authorization", &token_header)
+        .call()?
+        .into_json()
+        .map_err(|_| StepError::ApiResponseError(None))?;
+    let url = format!("{releases_url}/{id}", id = existing.id);
+    let (response, expected_status) = match action {
+        YankRelease::Delete => (
+            ureq::delete(&url)
+                .set("Authorization", &token_header)
+                .call()?,
+            204,
+        ),
+        YankRelease::Draft => (
+            ureq::patch(&url)
+                .set("Authorization", &token_header)
+                .send_json(serde_json::json!({ "draft": true }))?,
+            200,
+        ),
+        YankRelease::Prerelease => (
+            ureq::patch(&url)
+                .set("Authorization", &token_header)
+                .send_json(serde_json::json!({ "prerelease": true }))?,
+            200,
+        ),
+    };
+    if response.status() != expected_status {
+        return Err(StepError::ApiResponseError(None));
+    }
+    Ok(Initialized { token })
+}
+
+#[derive(Deserialize)]
+struct ExistingRelease {
+    id: u64,
+}
+
+#[cfg(test)]
+mod test_truncate_body {
+    use super::*;
+
+    #[test]
+    fn short_body() {
+        assert_eq!(truncate_body("## 1.2.3\n\n- A note\n", None, false), None);
+    }
+
+    #[test]
+    fn long_body() {
+        let note = "- A note which is repeated many times\n";
+        let body = format!(
+            "## 1.2.3\n\n{}",
+            note.repeat(MAX_BODY_LENGTH / note.len() + 1)
+        );
+        let truncated = truncate_body(
+            &body,
+            Some("https://github.com/knope-dev/knope/blob/v1.2.3/CHANGELOG.md"),
+            true,
+        )
+        .unwrap();
+
+        assert!(truncated.chars().count() <= MAX_BODY_LENGTH);
+        let (notes, footer) = truncated.split_once("\n\n---\n\n").unwrap();
+        assert!(body.starts_with(notes));
+        assert!(notes.ends_with(note.trim_end()));
+        assert_eq!(
+            footer,
+            "_These release notes were too long for GitHub, so they were truncated._ See [the changelog](https://github.com/knope-dev/knope/blob/v1.2.3/CHANGELOG.md) or the attached `release-notes.md` for the full notes.\n"
+        );
+    }
+}
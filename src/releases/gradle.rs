@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+pub(crate) fn get_gradle_properties_version(content: &str) -> Option<String> {
+    gradle_properties_version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_gradle_properties_version(
+    mut content: String,
+    new_version: &str,
+) -> Option<String> {
+    let span = gradle_properties_version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+pub(crate) fn get_build_gradle_version(content: &str) -> Option<String> {
+    build_gradle_version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_build_gradle_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = build_gradle_version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the value of the `version` property (like `version=1.2.3`) is.
+fn gradle_properties_version_span(content: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        if line.trim_start().starts_with(['#', '!']) {
+            continue;
+        }
+        let delimiter = match line.find(['=', ':']) {
+            Some(delimiter) => delimiter,
+            None => continue,
+        };
+        if line[..delimiter].trim() != "version" {
+            continue;
+        }
+        let value = &line[delimiter + 1..];
+        let value_start = start + delimiter + 1 + (value.len() - value.trim_start().len());
+        let value = value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        return Some(value_start..value_start + value.len());
+    }
+    None
+}
+
+/// Where the string assigned to `version` at the start of a line is, like `version = "1.2.3"` in
+/// `build.gradle.kts` or `version '1.2.3'` in `build.gradle`. Plugin versions (like
+/// `id 'org.example' version '1.0'`) don't start a line, so they're left alone.
+fn build_gradle_version_span(content: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let rest = match line.trim_start().strip_prefix("version") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let value = match rest.trim_start().strip_prefix('=') {
+            Some(value) => value.trim_start(),
+            None if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            None => continue,
+        };
+        let quote = match value.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let value_start = start + line.len() - value.len() + 1;
+        let value_len = content[value_start..line_start].find(quote)?;
+        return Some(value_start..value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_gradle_properties {
+    use super::*;
+
+    const CONTENT: &str = "# The version of the project
+group=org.example
+version = 1.2.3
+org.gradle.jvmargs=-Xmx2g
+";
+
+    #[test]
+    fn get_version() {
+        assert_eq!(
+            get_gradle_properties_version(CONTENT),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            set_gradle_properties_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn commented_version() {
+        let content = "#version=0.0.1\nversion:1.2.3\n";
+        assert_eq!(
+            get_gradle_properties_version(content),
+            Some(String::from("1.2.3"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_build_gradle {
+    use super::*;
+
+    #[test]
+    fn kotlin() {
+        let content = r#"plugins {
+    id("org.jetbrains.kotlin.jvm") version "1.7.20"
+}
+
+group = "org.example"
+version = "1.2.3"
+"#;
+        assert_eq!(
+            get_build_gradle_version(content),
+            Some(String::from("1.2.3"))
+        );
+        assert_eq!(
+            set_build_gradle_version(String::from(content), "2.0.0"),
+            Some(content.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn groovy() {
+        let content = "plugins {\n    id 'java'\n}\n\nversion '1.2.3'\n";
+        assert_eq!(
+            get_build_gradle_version(content),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn no_version() {
+        let content = "versionName = \"1.2.3\"\n";
+        assert_eq!(get_build_gradle_version(content), None);
+    }
+}
@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    value_span(content, "version").map(|span| String::from(&content[span]))
+}
+
+/// Set `version` in `Chart.yaml` to `new_version`. If `appVersion` was the same as `version`,
+/// it's kept in sync, so a chart versioned along with its app stays that way.
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let version_span = value_span(&content, "version")?;
+    let app_version_span = value_span(&content, "appVersion")
+        .filter(|span| content[span.clone()] == content[version_span.clone()]);
+    // Replace the later one first so the other span stays valid.
+    let mut spans = vec![version_span];
+    spans.extend(app_version_span);
+    spans.sort_by_key(|span| std::cmp::Reverse(span.start));
+    for span in spans {
+        content.replace_range(span, new_version);
+    }
+    Some(content)
+}
+
+/// Where the value of the top-level `key` is, without any quotes or trailing comment.
+pub(super) fn value_span(content: &str, key: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let rest = match line
+            .strip_prefix(key)
+            .and_then(|rest| rest.trim_end().strip_prefix(':'))
+        {
+            Some(rest) => rest,
+            None => continue,
+        };
+        return scalar_span(rest, start + key.len() + 1);
+    }
+    None
+}
+
+/// Where the scalar value in `rest` (the text after a key's `:`, starting at `rest_start`) is,
+/// without any quotes or trailing comment.
+pub(super) fn scalar_span(rest: &str, rest_start: usize) -> Option<Range<usize>> {
+    let value = rest.split(" #").next().unwrap_or_default();
+    let value_start = rest_start + (value.len() - value.trim_start().len());
+    let value = value.trim();
+    let unquoted = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        });
+    match unquoted {
+        Some(unquoted) => Some(value_start + 1..value_start + 1 + unquoted.len()),
+        None if value.is_empty() => None,
+        None => Some(value_start..value_start + value.len()),
+    }
+}
+
+#[cfg(test)]
+mod test_chart_yaml {
+    use super::*;
+
+    const CONTENT: &str = r#"apiVersion: v2
+name: mychart
+description: A Helm chart
+version: 1.2.3 # The chart version
+appVersion: "1.2.3"
+dependencies:
+  - name: postgresql
+    version: 11.6.12
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_with_app_version() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn different_app_version() {
+        let content = CONTENT.replace("appVersion: \"1.2.3\"", "appVersion: '4.5.6'");
+        assert_eq!(
+            set_version(content.clone(), "2.0.0"),
+            Some(content.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn no_version() {
+        assert_eq!(get_version("apiVersion: v2\nname: mychart\n"), None);
+    }
+}
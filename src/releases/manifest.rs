@@ -0,0 +1,361 @@
+use std::ffi::OsStr;
+use std::fs::{read_to_string, write};
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::process::Command;
+
+use crate::app_config::get_or_prompt_for_github_token;
+use crate::config::GitHub;
+use crate::git::{check_branch_before_push, repository_branch};
+use crate::pull_request::{open_pull_request, GitHubPullRequest};
+use crate::releases::aur::release_version;
+use crate::releases::git::{default_tag_prefix, head_commit_id, tag_commit_id, tag_name};
+use crate::releases::helm::{scalar_span, value_span};
+use crate::releases::Package;
+use crate::state::GitHub::{Initialized, New};
+use crate::step::{StepError, UpdateManifest};
+use crate::{RunType, State};
+
+/// The implementation of [`crate::step::Step::UpdateManifest`].
+///
+/// Updates a Flatpak manifest or `snapcraft.yaml` in a clone of a packaging repository to the
+/// version released in this workflow, then either pushes the clone or opens a pull request for it.
+pub(crate) fn update_manifest(
+    run_type: RunType,
+    update_manifest: &UpdateManifest,
+) -> Result<RunType, StepError> {
+    let (mut state, mut dry_run_stdout) = run_type.decompose();
+    let released = release_version(&state, update_manifest.package.as_deref())?;
+    let tag_prefix = tag_prefix(&state, released.package_name.as_deref());
+    let old_tag = tag_name(&released.previous_version, &tag_prefix);
+    let new_tag = tag_name(&released.version, &tag_prefix);
+    let commits = match tag_commit_id(&old_tag) {
+        // The new tag may not exist until the `Release` step runs, which tags the current commit.
+        Some(old_commit) => Some((
+            old_commit,
+            tag_commit_id(&new_tag).map_or_else(head_commit_id, Ok)?,
+        )),
+        None => None,
+    };
+
+    let repository = &update_manifest.repository;
+    let manifest_path = repository.join(&update_manifest.manifest);
+    let content = read_to_string(&manifest_path)
+        .map_err(|_| StepError::FileNotFound(manifest_path.clone()))?;
+    let kind = Kind::from_path(&manifest_path)
+        .ok_or_else(|| StepError::InvalidManifest(manifest_path.clone()))?;
+    let content = kind
+        .update(
+            content,
+            &released.version.to_string(),
+            (&old_tag, &new_tag),
+            commits
+                .as_ref()
+                .map(|(old_commit, new_commit)| (old_commit.as_str(), new_commit.as_str())),
+        )
+        .ok_or_else(|| StepError::InvalidManifest(manifest_path.clone()))?;
+    let message = format!("Update to {new_tag}");
+    let branch = format!("update-to-{new_tag}");
+    check_target_branch(&state, update_manifest, &branch, dry_run_stdout.as_mut())?;
+
+    if let Some(stdout) = dry_run_stdout.as_mut() {
+        writeln!(
+            stdout,
+            "Would update {} to {new_tag}",
+            manifest_path.display()
+        )?;
+        if let Some(pull_request) = &update_manifest.pull_request {
+            writeln!(
+                stdout,
+                "Would push {} to branch {branch} and open a pull request into {} of {}/{}",
+                repository.display(),
+                pull_request.base,
+                pull_request.owner,
+                pull_request.repo
+            )?;
+        } else {
+            writeln!(stdout, "Would commit and push {}", repository.display())?;
+        }
+    } else {
+        write(&manifest_path, content)?;
+        let manifest = update_manifest.manifest.to_string_lossy();
+        if let Some(pull_request) = &update_manifest.pull_request {
+            git(repository, &["checkout", "-b", &branch])?;
+            git(repository, &["add", &manifest])?;
+            git(repository, &["commit", "-m", &message])?;
+            git(repository, &["push", "--set-upstream", "origin", &branch])?;
+            let token = match state.github {
+                Initialized { token } => token,
+                New => get_or_prompt_for_github_token()?,
+            };
+            let github_config = GitHub {
+                owner: pull_request.owner.clone(),
+                repo: pull_request.repo.clone(),
+            };
+            let number = open_pull_request(
+                &github_config,
+                &token,
+                &GitHubPullRequest {
+                    title: &message,
+                    head: &branch,
+                    base: &pull_request.base,
+                    body: "",
+                },
+            )?
+            .number;
+            println!(
+                "Created pull request #{number} in {}/{}",
+                pull_request.owner, pull_request.repo
+            );
+            state.github = Initialized { token };
+        } else {
+            git(repository, &["add", &manifest])?;
+            git(repository, &["commit", "-m", &message])?;
+            git(repository, &["push"])?;
+            println!("Pushed {message} to {}", repository.display());
+        }
+    }
+
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { state, stdout })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// Make sure that the branch which gets the commit may be pushed to. That's `branch` for a pull
+/// request, otherwise the current branch of the clone.
+fn check_target_branch(
+    state: &State,
+    update_manifest: &UpdateManifest,
+    branch: &str,
+    dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<(), StepError> {
+    let target_branch = match update_manifest.pull_request {
+        Some(_) => Some(branch.to_string()),
+        None => repository_branch(&update_manifest.repository),
+    };
+    check_branch_before_push(
+        &state.protected_branches,
+        target_branch.as_deref(),
+        dry_run_stdout,
+    )
+}
+
+/// The prefix of the Git tags of the package named `package_name`.
+fn tag_prefix(state: &State, package_name: Option<&str>) -> String {
+    state
+        .packages
+        .iter()
+        .find(|package| package.name.as_deref() == package_name)
+        .map_or_else(|| default_tag_prefix(package_name), Package::tag_prefix)
+}
+
+/// Run `git` with `args` in `repository`.
+fn git(repository: &Path, args: &[&str]) -> Result<(), StepError> {
+    let failed = |reason: String| StepError::ManifestCommandFailed {
+        command: format!("git {}", args.join(" ")),
+        reason,
+    };
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repository)
+        .output()
+        .map_err(|err| failed(err.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The kinds of manifests which can be updated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    /// A Flatpak manifest, in JSON or YAML.
+    Flatpak,
+    /// A `snapcraft.yaml`.
+    Snap,
+}
+
+impl Kind {
+    fn from_path(path: &Path) -> Option<Self> {
+        let file_name = path.file_name().and_then(OsStr::to_str)?;
+        if file_name == "snapcraft.yaml" || file_name == ".snapcraft.yaml" {
+            return Some(Self::Snap);
+        }
+        match path.extension().and_then(OsStr::to_str) {
+            Some("json" | "yaml" | "yml") => Some(Self::Flatpak),
+            _ => None,
+        }
+    }
+
+    /// Replace every source tag which is `tags.0` with `tags.1` (and every source commit which
+    /// is `commits.0` with `commits.1`). A snap's `version` is also set to `version`.
+    ///
+    /// Returns `None` if there is nothing to update: a snap with no `version`, or a Flatpak
+    /// manifest with no source tagged with `tags.0`.
+    fn update(
+        self,
+        mut content: String,
+        version: &str,
+        tags: (&str, &str),
+        commits: Option<(&str, &str)>,
+    ) -> Option<String> {
+        let (tag_key, commit_key) = match self {
+            Self::Flatpak => ("tag", "commit"),
+            Self::Snap => ("source-tag", "source-commit"),
+        };
+        let mut replacements = value_spans(&content, tag_key)
+            .into_iter()
+            .filter(|span| content[span.clone()] == *tags.0)
+            .map(|span| (span, tags.1))
+            .collect::<Vec<_>>();
+        if let Some((old_commit, new_commit)) = commits {
+            replacements.extend(
+                value_spans(&content, commit_key)
+                    .into_iter()
+                    .filter(|span| content[span.clone()] == *old_commit)
+                    .map(|span| (span, new_commit)),
+            );
+        }
+        match self {
+            Self::Flatpak if replacements.is_empty() => return None,
+            Self::Flatpak => {}
+            Self::Snap => replacements.push((value_span(&content, "version")?, version)),
+        }
+        // Replace from the end so the other spans stay valid.
+        replacements.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+        for (span, value) in replacements {
+            content.replace_range(span, value);
+        }
+        Some(content)
+    }
+}
+
+/// Where every value of `key` is (at any depth, including in lists) in a YAML or JSON manifest,
+/// without any quotes.
+fn value_spans(content: &str, key: &str) -> Vec<Range<usize>> {
+    let yaml_key = format!("{key}:");
+    let json_key = format!("\"{key}\":");
+    let mut spans = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let trimmed = line.trim_start().trim_start_matches("- ").trim_start();
+        let key_start = start + line.len() - trimmed.len();
+        let rest_start = if trimmed.starts_with(&yaml_key) {
+            key_start + yaml_key.len()
+        } else if trimmed.starts_with(&json_key) {
+            key_start + json_key.len()
+        } else {
+            continue;
+        };
+        // JSON values can be followed by a `,` or the end of their object or array.
+        let rest = content[rest_start..line_start]
+            .trim_end_matches(|c: char| c == ',' || c == '}' || c == ']' || c.is_whitespace());
+        spans.extend(scalar_span(rest, rest_start));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod test_manifest {
+    use super::*;
+
+    const TAGS: (&str, &str) = ("v1.2.3", "v1.3.0");
+    const COMMITS: Option<(&str, &str)> = Some(("1111111", "2222222"));
+
+    #[test]
+    fn flatpak_yaml() {
+        let content = r#"app-id: com.example.App
+modules:
+  - name: shared-modules
+    sources:
+      - type: git
+        url: https://github.com/flathub/shared-modules.git
+        commit: 3333333
+  - name: app
+    sources:
+      - type: git
+        url: https://github.com/example/app.git
+        tag: v1.2.3
+        commit: 1111111
+"#;
+        let expected = content
+            .replace("tag: v1.2.3", "tag: v1.3.0")
+            .replace("1111111", "2222222");
+        assert_eq!(
+            Kind::Flatpak.update(String::from(content), "1.3.0", TAGS, COMMITS),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn flatpak_json() {
+        let content = r#"{
+  "app-id": "com.example.App",
+  "modules": [
+    {
+      "name": "app",
+      "sources": [
+        { "type": "git", "url": "https://github.com/example/app.git",
+          "tag": "v1.2.3",
+          "commit": "1111111" }
+      ]
+    }
+  ]
+}
+"#;
+        let expected = content
+            .replace("\"v1.2.3\"", "\"v1.3.0\"")
+            .replace("1111111", "2222222");
+        assert_eq!(
+            Kind::Flatpak.update(String::from(content), "1.3.0", TAGS, COMMITS),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn flatpak_without_tag() {
+        let content = "modules:\n  - name: app\n    sources:\n      - type: archive\n";
+        assert_eq!(
+            Kind::Flatpak.update(String::from(content), "1.3.0", TAGS, COMMITS),
+            None
+        );
+    }
+
+    #[test]
+    fn snap() {
+        let content = r#"name: app
+version: '1.2.3'
+parts:
+  app:
+    source: https://github.com/example/app.git
+    source-tag: v1.2.3
+"#;
+        let expected = content
+            .replace("'1.2.3'", "'1.3.0'")
+            .replace("v1.2.3", "v1.3.0");
+        assert_eq!(
+            Kind::Snap.update(String::from(content), "1.3.0", TAGS, None),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(
+            Kind::from_path(Path::new("snap/snapcraft.yaml")),
+            Some(Kind::Snap)
+        );
+        assert_eq!(
+            Kind::from_path(Path::new("com.example.App.yml")),
+            Some(Kind::Flatpak)
+        );
+        assert_eq!(Kind::from_path(Path::new("PKGBUILD")), None);
+    }
+}
@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use semver::Version;
+
+/// The comment which starts a region named `name`, like `<!-- version-start -->`.
+pub(crate) fn start_marker(name: &str) -> String {
+    format!("<!-- {name}-start -->")
+}
+
+/// The comment which ends a region named `name`, like `<!-- version-end -->`.
+pub(crate) fn end_marker(name: &str) -> String {
+    format!("<!-- {name}-end -->")
+}
+
+/// The first version between any pair of markers.
+pub(crate) fn get_version(content: &str, name: &str) -> Option<String> {
+    version_spans(content, name)
+        .first()
+        .map(|span| String::from(&content[span.clone()]))
+}
+
+/// Replace every version between every pair of markers with `new_version`.
+pub(crate) fn set_version(mut content: String, name: &str, new_version: &str) -> Option<String> {
+    let spans = version_spans(&content, name);
+    if spans.is_empty() {
+        return None;
+    }
+    // Replace from the end, so that the earlier spans are still in the right place.
+    for span in spans.into_iter().rev() {
+        content.replace_range(span, new_version);
+    }
+    Some(content)
+}
+
+/// Where every semantic version is between the markers, which could be surrounded by other text
+/// (like an install command).
+fn version_spans(content: &str, name: &str) -> Vec<Range<usize>> {
+    let (start_marker, end_marker) = (start_marker(name), end_marker(name));
+    let mut spans = Vec::new();
+    let mut position = 0;
+    while let Some(start) = content[position..].find(&start_marker) {
+        let region_start = position + start + start_marker.len();
+        let region_end = match content[region_start..].find(&end_marker) {
+            Some(end) => region_start + end,
+            None => break,
+        };
+        spans.extend(
+            versions_in(&content[region_start..region_end])
+                .into_iter()
+                .map(|span| region_start + span.start..region_start + span.end),
+        );
+        position = region_end + end_marker.len();
+    }
+    spans
+}
+
+/// Every version in `text`, where a version is a whole word (other than a `v` prefix), or starts a
+/// word that isn't a version, like the `1.2.3` in `my-tool-1.2.3-linux.tar.gz`. In the second case,
+/// only `MAJOR.MINOR.PATCH` is part of the version.
+fn versions_in(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    for (start, word) in words(text) {
+        // Versions can also come after the name of something, like in `my-tool-1.2.3.tar.gz`.
+        for (offset, _) in word.match_indices(|c: char| c.is_ascii_digit()) {
+            let before = &word[..offset];
+            if !(before.is_empty() || before.ends_with(['-', '_', 'v', '@'])) {
+                continue;
+            }
+            let rest = &word[offset..];
+            let len = if Version::parse(rest).is_ok() {
+                rest.len()
+            } else {
+                core_len(rest)
+            };
+            if len > 0 {
+                spans.push(start + offset..start + offset + len);
+                break;
+            }
+        }
+    }
+    spans
+}
+
+/// Every word in `text` along with where it starts, where a word is anything that could be (or
+/// contain) a version, like `my-tool@1.2.3`.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let is_word_char =
+        |c: char| c.is_ascii_alphanumeric() || ['.', '-', '+', '_', '@'].contains(&c);
+    let mut words = Vec::new();
+    let mut word_start = None;
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if is_word_char(c) {
+            word_start.get_or_insert(index);
+        } else if let Some(start) = word_start.take() {
+            words.push((start, &text[start..index]));
+        }
+    }
+    words
+}
+
+/// The length of the `MAJOR.MINOR.PATCH` at the start of `text`, or 0 if there isn't one.
+fn core_len(text: &str) -> usize {
+    let mut len = 0;
+    for part in 0..3 {
+        if part > 0 {
+            if !text[len..].starts_with('.') {
+                return 0;
+            }
+            len += 1;
+        }
+        let digits = text[len..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len() - len);
+        if digits == 0 {
+            return 0;
+        }
+        len += digits;
+    }
+    // Something like `1.2.3.4` isn't a version at all.
+    let rest = &text[len..];
+    if rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        return 0;
+    }
+    len
+}
+
+#[cfg(test)]
+mod test_markers {
+    use super::*;
+
+    const CONTENT: &str = r#"# My Tool
+
+Version <!-- version-start -->1.2.3<!-- version-end --> requires version 4.5.6 of Rust.
+
+<!-- version-start -->
+```sh
+cargo install my-tool --version 1.2.3
+curl -O https://example.com/my-tool-v1.2.3-x86_64-linux.tar.gz
+npm install my-tool@1.2.3
+```
+<!-- version-end -->
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT, "version"), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "version", "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn prerelease() {
+        let content = "<!-- version-start -->v1.2.3-rc.1 is here<!-- version-end -->";
+        assert_eq!(
+            get_version(content, "version"),
+            Some(String::from("1.2.3-rc.1"))
+        );
+    }
+
+    #[test]
+    fn not_versions() {
+        let content = "<!-- version-start -->Python 3.10, 1.2.3.4, and py1.2.3<!-- version-end -->";
+        assert_eq!(get_version(content, "version"), None);
+    }
+
+    #[test]
+    fn other_names() {
+        assert_eq!(get_version(CONTENT, "other"), None);
+        assert_eq!(set_version(String::from(CONTENT), "other", "2.0.0"), None);
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(
+            get_version("<!-- version-start -->1.2.3\n", "version"),
+            None
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Whether the project in `content` has its own `version`, rather than inheriting it from its
+/// `parent`.
+pub(crate) fn has_version(content: &str) -> bool {
+    version_span(content).is_some()
+}
+
+/// The `artifactId` of the project in `content`.
+pub(crate) fn artifact_id(content: &str) -> Option<String> {
+    let project = project_span(content)?;
+    let span = child_span(content, project, "artifactId")?;
+    Some(String::from(content[span].trim()))
+}
+
+/// The `artifactId` of the `parent` of the project in `content`, if it has one.
+pub(crate) fn parent_artifact_id(content: &str) -> Option<String> {
+    let parent = child_span(content, project_span(content)?, "parent")?;
+    let span = child_span(content, parent, "artifactId")?;
+    Some(String::from(content[span].trim()))
+}
+
+/// Set the `version` of the `parent` of the project in `content`.
+pub(crate) fn set_parent_version(mut content: String, new_version: &str) -> Option<String> {
+    let parent = child_span(&content, project_span(&content)?, "parent")?;
+    let span = trimmed(&content, child_span(&content, parent, "version")?);
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Every `module` listed in the `modules` of the project in `content`.
+pub(crate) fn modules(content: &str) -> Vec<String> {
+    project_span(content)
+        .and_then(|project| child_span(content, project, "modules"))
+        .map(|modules| {
+            children(content, modules)
+                .into_iter()
+                .filter(|(name, _)| *name == "module")
+                .map(|(_, span)| String::from(content[span].trim()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Where the `version` of the project itself is. The `version` of its `parent`, dependencies, and
+/// plugins are all nested deeper, so they're never matched.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let span = child_span(content, project_span(content)?, "version")?;
+    Some(trimmed(content, span))
+}
+
+/// The contents of the root `project` element.
+fn project_span(content: &str) -> Option<Range<usize>> {
+    child_span(content, 0..content.len(), "project")
+}
+
+/// The contents of the first element named `name` directly inside `range`.
+fn child_span(content: &str, range: Range<usize>, name: &str) -> Option<Range<usize>> {
+    children(content, range)
+        .into_iter()
+        .find(|(child, _)| *child == name)
+        .map(|(_, span)| span)
+}
+
+/// The name and contents of every element directly inside `range`. Comments, processing
+/// instructions (like `<?xml ... ?>`), and declarations (like `<!DOCTYPE ...>`) are skipped.
+fn children(content: &str, range: Range<usize>) -> Vec<(&str, Range<usize>)> {
+    let mut children = Vec::new();
+    let mut depth = 0_usize;
+    let mut current: Option<(&str, usize)> = None;
+    let mut position = range.start;
+    while let Some(offset) = content[position..range.end].find('<') {
+        let tag_start = position + offset;
+        let tag = &content[tag_start..range.end];
+        if tag.starts_with("<!--") {
+            position = tag_start + tag.find("-->").map_or(tag.len(), |end| end + 3);
+            continue;
+        }
+        let tag_end = match tag.find('>') {
+            Some(end) => tag_start + end + 1,
+            None => break,
+        };
+        position = tag_end;
+        if tag.starts_with("<?") || tag.starts_with("<!") {
+            continue;
+        }
+        if tag.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            if depth == 0 {
+                if let Some((name, inner_start)) = current.take() {
+                    children.push((name, inner_start..tag_start));
+                }
+            }
+            continue;
+        }
+        let name_end = tag[1..]
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .map_or(tag.len(), |end| end + 1);
+        let name = &tag[1..name_end];
+        if content[..tag_end - 1].ends_with('/') {
+            // A self-closing element, like `<version/>`.
+            if depth == 0 {
+                children.push((name, tag_end..tag_end));
+            }
+        } else {
+            if depth == 0 {
+                current = Some((name, tag_end));
+            }
+            depth += 1;
+        }
+    }
+    children
+}
+
+/// Narrow `span` to leave out any whitespace around the text in it.
+fn trimmed(content: &str, span: Range<usize>) -> Range<usize> {
+    let text = &content[span.clone()];
+    let start = span.start + (text.len() - text.trim_start().len());
+    start..start + text.trim().len()
+}
+
+#[cfg(test)]
+mod test_pom_xml {
+    use super::*;
+
+    const CONTENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+    <modelVersion>4.0.0</modelVersion>
+    <parent>
+        <groupId>org.springframework.boot</groupId>
+        <artifactId>spring-boot-starter-parent</artifactId>
+        <version>3.0.0</version>
+    </parent>
+    <!-- <version>0.0.1</version> -->
+    <groupId>org.example</groupId>
+    <artifactId>app</artifactId>
+    <version>1.2.3</version>
+    <modules>
+        <module>core</module>
+        <module>cli</module>
+    </modules>
+    <dependencies>
+        <dependency>
+            <groupId>org.example</groupId>
+            <artifactId>other</artifactId>
+            <version>4.5.6</version>
+        </dependency>
+    </dependencies>
+</project>
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("<version>1.2.3</version>", "<version>2.0.0</version>"))
+        );
+    }
+
+    #[test]
+    fn modules() {
+        assert_eq!(
+            super::modules(CONTENT),
+            vec![String::from("core"), String::from("cli")]
+        );
+    }
+
+    #[test]
+    fn parent() {
+        assert_eq!(artifact_id(CONTENT), Some(String::from("app")));
+        assert_eq!(
+            parent_artifact_id(CONTENT),
+            Some(String::from("spring-boot-starter-parent"))
+        );
+        assert_eq!(
+            set_parent_version(String::from(CONTENT), "3.1.0"),
+            Some(CONTENT.replace("<version>3.0.0</version>", "<version>3.1.0</version>"))
+        );
+    }
+
+    #[test]
+    fn inherited_version() {
+        let content = "<project>\n  <parent>\n    <artifactId>app</artifactId>\n    <version>1.2.3</version>\n  </parent>\n  <artifactId>core</artifactId>\n</project>\n";
+        assert!(!has_version(content));
+        assert_eq!(super::modules(content), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,336 @@
+use std::cmp::Reverse;
+use std::fs::{read_to_string, write};
+use std::path::Path;
+
+use itertools::Itertools;
+use miette::{IntoDiagnostic, Result, WrapErr};
+use semver::Version;
+
+/// The implementation of `knope merge-changelog`, which Git calls as a merge driver.
+///
+/// Merges the changes from `base` to `theirs` into `ours`, writing the result to `ours` (where Git
+/// expects it).
+pub(crate) fn merge_changelog(base: &Path, ours: &Path, theirs: &Path) -> Result<()> {
+    let read = |path: &Path| {
+        read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Could not read {}", path.display()))
+    };
+    let merged = merge(&read(base)?, &read(ours)?, &read(theirs)?);
+    write(ours, merged)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("Could not write {}", ours.display()))
+}
+
+/// Merge two changelogs which were both changed from `base`. The result is the same no matter
+/// which side is `ours` and which is `theirs`, other than the order of notes added to the same
+/// version by both sides.
+///
+/// 1. Versions which are new on either side go at the top, with the highest version first (any
+///    which aren't a version, like `Unreleased`, go above those).
+/// 2. Versions which were already in `base` stay in the order they're in, unless either side
+///    removed them.
+/// 3. If both sides changed the same version, the notes from `theirs` which aren't in `ours` are
+///    added to the matching `###` section.
+fn merge(base: &str, ours: &str, theirs: &str) -> String {
+    let base = Changelog::parse(base);
+    let ours_changelog = Changelog::parse(ours);
+    let theirs = Changelog::parse(theirs);
+
+    let header = if ours_changelog.header == base.header {
+        &theirs.header
+    } else {
+        &ours_changelog.header
+    };
+
+    let new_sections = ours_changelog
+        .sections
+        .iter()
+        .chain(&theirs.sections)
+        .filter(|section| base.section(section.title).is_none())
+        .map(|section| section.title)
+        .unique()
+        .sorted_by_key(|title| {
+            let version = title_version(title);
+            (version.is_some(), Reverse(version))
+        })
+        .map(|title| {
+            let empty = Section {
+                title,
+                lines: Vec::new(),
+            };
+            merge_section(&empty, ours_changelog.section(title), theirs.section(title))
+        });
+    let existing_sections = ours_changelog.sections.iter().filter_map(|section| {
+        let base_section = base.section(section.title)?;
+        let theirs_section = theirs.section(section.title)?;
+        Some(merge_section(
+            base_section,
+            Some(section),
+            Some(theirs_section),
+        ))
+    });
+
+    let mut blocks = Vec::new();
+    if !header.is_empty() {
+        blocks.push(header.join("\n"));
+    }
+    blocks.extend(new_sections.chain(existing_sections).map(|section| {
+        if section.lines.is_empty() {
+            String::from(section.title)
+        } else {
+            format!("{}\n\n{}", section.title, section.lines.join("\n"))
+        }
+    }));
+    let mut merged = blocks.join("\n\n");
+    if ours.ends_with('\n') {
+        merged.push('\n');
+    }
+    merged
+}
+
+/// The version in a title like `## 1.2.3`, `## [1.2.3] - 2022-09-01`, or `## v1.2.3`, if any.
+fn title_version(title: &str) -> Option<Version> {
+    let first_word = title.trim_start_matches('#').split_whitespace().next()?;
+    let version = first_word.trim_matches(|c| c == '[' || c == ']');
+    Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()
+}
+
+/// Three-way merge of one section, where either side may not have it.
+fn merge_section<'a>(
+    base: &Section<'a>,
+    ours: Option<&Section<'a>>,
+    theirs: Option<&Section<'a>>,
+) -> Section<'a> {
+    let (ours, theirs) = match (ours, theirs) {
+        (Some(ours), Some(theirs)) => (ours, theirs),
+        (Some(only), None) | (None, Some(only)) => return only.clone(),
+        (None, None) => return base.clone(),
+    };
+    if ours == theirs || theirs == base {
+        return ours.clone();
+    }
+    if ours == base {
+        return theirs.clone();
+    }
+    let mut subsections = ours.subsections();
+    for (title, notes) in theirs.subsections() {
+        match subsections
+            .iter_mut()
+            .find(|(ours_title, _)| *ours_title == title)
+        {
+            Some((_, ours_notes)) => {
+                for note in notes {
+                    if !ours_notes.contains(&note) {
+                        ours_notes.push(note);
+                    }
+                }
+            }
+            None => subsections.push((title, notes)),
+        }
+    }
+    let mut lines = Vec::new();
+    for (title, notes) in subsections {
+        if !lines.is_empty() {
+            lines.push("");
+        }
+        if let Some(title) = title {
+            lines.extend([title, ""]);
+        }
+        lines.extend(notes);
+    }
+    Section {
+        title: ours.title,
+        lines,
+    }
+}
+
+/// A changelog split into its `##` (version) sections.
+#[derive(Debug)]
+struct Changelog<'a> {
+    /// Everything before the first section, like a `# Changelog` title.
+    header: Vec<&'a str>,
+    sections: Vec<Section<'a>>,
+}
+
+impl<'a> Changelog<'a> {
+    fn parse(content: &'a str) -> Self {
+        let mut lines = content.lines();
+        let header = trim_blank_lines(
+            lines
+                .take_while_ref(|line| !line.starts_with("## "))
+                .collect(),
+        );
+        let mut sections = Vec::new();
+        while let Some(title) = lines.next() {
+            let section_lines = lines
+                .take_while_ref(|line| !line.starts_with("## "))
+                .collect();
+            sections.push(Section {
+                title,
+                lines: trim_blank_lines(section_lines),
+            });
+        }
+        Self { header, sections }
+    }
+
+    fn section(&self, title: &str) -> Option<&Section<'a>> {
+        self.sections.iter().find(|section| section.title == title)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Section<'a> {
+    /// The whole `##` line.
+    title: &'a str,
+    /// Every line after the title, with blank lines at the start and end removed.
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Section<'a> {
+    /// The non-blank lines of this section grouped by their `###` title, if any.
+    fn subsections(&self) -> Vec<(Option<&'a str>, Vec<&'a str>)> {
+        let mut subsections: Vec<(Option<&str>, Vec<&str>)> = Vec::new();
+        for line in &self.lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with("### ") {
+                subsections.push((Some(line), Vec::new()));
+            } else if let Some((_, notes)) = subsections.last_mut() {
+                notes.push(line);
+            } else {
+                subsections.push((None, vec![line]));
+            }
+        }
+        subsections
+    }
+}
+
+fn trim_blank_lines(mut lines: Vec<&str>) -> Vec<&str> {
+    while lines.last().map_or(false, |line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let leading = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty())
+        .count();
+    lines.drain(..leading);
+    lines
+}
+
+#[cfg(test)]
+mod test_merge {
+    use super::*;
+
+    const BASE: &str = "# Changelog
+
+## 1.0.0
+
+### Features
+
+- Existing feature
+";
+
+    #[test]
+    fn new_versions_on_both_sides() {
+        let ours = BASE.replace(
+            "## 1.0.0",
+            "## 1.1.0\n\n### Features\n\n- Our feature\n\n## 1.0.0",
+        );
+        let theirs = BASE.replace(
+            "## 1.0.0",
+            "## 1.0.1\n\n### Fixes\n\n- Their fix\n\n## 1.0.0",
+        );
+
+        let expected = "# Changelog
+
+## 1.1.0
+
+### Features
+
+- Our feature
+
+## 1.0.1
+
+### Fixes
+
+- Their fix
+
+## 1.0.0
+
+### Features
+
+- Existing feature
+";
+        assert_eq!(merge(BASE, &ours, &theirs), expected);
+        assert_eq!(merge(BASE, &theirs, &ours), expected);
+    }
+
+    #[test]
+    fn same_version_on_both_sides() {
+        let ours = BASE.replace(
+            "## 1.0.0",
+            "## 1.1.0\n\n### Features\n\n- Our feature\n\n## 1.0.0",
+        );
+        let theirs = BASE.replace(
+            "## 1.0.0",
+            "## 1.1.0\n\n### Features\n\n- Their feature\n\n### Fixes\n\n- Their fix\n\n## 1.0.0",
+        );
+
+        let expected = "# Changelog
+
+## 1.1.0
+
+### Features
+
+- Our feature
+- Their feature
+
+### Fixes
+
+- Their fix
+
+## 1.0.0
+
+### Features
+
+- Existing feature
+";
+        assert_eq!(merge(BASE, &ours, &theirs), expected);
+    }
+
+    #[test]
+    fn unreleased_goes_first() {
+        let ours = BASE.replace("## 1.0.0", "## 1.1.0\n\n- Our note\n\n## 1.0.0");
+        let theirs = BASE.replace("## 1.0.0", "## Unreleased\n\n- Their note\n\n## 1.0.0");
+
+        let merged = merge(BASE, &ours, &theirs);
+
+        assert!(merged.starts_with("# Changelog\n\n## Unreleased\n\n- Their note\n\n## 1.1.0\n"));
+    }
+
+    #[test]
+    fn one_side_edits_existing_version() {
+        let ours = BASE.replace("- Existing feature", "- Existing feature, reworded");
+        let theirs = BASE.replace("## 1.0.0", "## 1.1.0\n\n- Their note\n\n## 1.0.0");
+
+        let merged = merge(BASE, &ours, &theirs);
+
+        assert_eq!(
+            merged,
+            "# Changelog\n\n## 1.1.0\n\n- Their note\n\n## 1.0.0\n\n### Features\n\n- Existing feature, reworded\n"
+        );
+    }
+
+    #[test]
+    fn title_versions() {
+        assert_eq!(title_version("## 1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(
+            title_version("## [1.2.3] - 2022-09-01"),
+            Some(Version::new(1, 2, 3))
+        );
+        assert_eq!(title_version("## v1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(title_version("## Unreleased"), None);
+    }
+}
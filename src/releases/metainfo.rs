@@ -0,0 +1,233 @@
+use semver::Version;
+
+/// Add a `<release>` for `version` to the `<releases>` of an `AppStream` `metainfo.xml`, with a
+/// description made from `new_changes` (the lines of the new changelog entry).
+///
+/// Releases are listed newest first, so the new one goes at the top. If there are no `<releases>`
+/// yet, they're added at the end of the `<component>`. `None` means there's no `<component>` to
+/// add them to.
+pub(crate) fn add_release(
+    content: &str,
+    version: &Version,
+    date: &str,
+    new_changes: &[String],
+) -> Option<String> {
+    if content.contains(&format!("<release version=\"{version}\"")) {
+        // Already released, like when a failed release is retried.
+        return Some(content.to_string());
+    }
+    let mut content = content.to_string();
+    if let Some(start) = content.find("<releases>") {
+        let position = start + "<releases>".len();
+        let indent = match content[position..].find("<release ") {
+            Some(existing) => line_indent(&content, position + existing),
+            None => format!("{}  ", line_indent(&content, start)),
+        };
+        let release = release_element(version, date, new_changes, &indent);
+        content.insert_str(position, &format!("\n{release}"));
+        return Some(content);
+    }
+    if let Some(start) = content
+        .find("<releases/>")
+        .or_else(|| content.find("<releases />"))
+    {
+        let end = start + content[start..].find("/>")? + 2;
+        let indent = line_indent(&content, start);
+        let release = release_element(version, date, new_changes, &format!("{indent}  "));
+        content.replace_range(
+            start..end,
+            &format!("<releases>\n{release}\n{indent}</releases>"),
+        );
+        return Some(content);
+    }
+    let end = content.rfind("</component>")?;
+    let line_start = content[..end].rfind('\n').map_or(0, |newline| newline + 1);
+    let indent = format!("{}  ", line_indent(&content, end));
+    let release = release_element(version, date, new_changes, &format!("{indent}  "));
+    if line_start + line_indent(&content, end).len() == end {
+        // `</component>` is on its own line, so the releases go on their own lines before it.
+        content.insert_str(
+            line_start,
+            &format!("{indent}<releases>\n{release}\n{indent}</releases>\n"),
+        );
+    } else {
+        content.insert_str(
+            end,
+            &format!("<releases>\n{release}\n{indent}</releases>\n"),
+        );
+    }
+    Some(content)
+}
+
+/// The whitespace at the start of the line which `position` is in.
+fn line_indent(content: &str, position: usize) -> String {
+    let line_start = content[..position]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    content[line_start..position]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect()
+}
+
+/// A `<release>` element, with every line starting with `indent`. Pre-releases are marked as
+/// development releases, so software centers don't offer them as updates.
+fn release_element(version: &Version, date: &str, new_changes: &[String], indent: &str) -> String {
+    let release_type = if version.pre.is_empty() {
+        ""
+    } else {
+        " type=\"development\""
+    };
+    let start = format!("{indent}<release version=\"{version}\" date=\"{date}\"{release_type}");
+    let description = description_lines(new_changes);
+    if description.is_empty() {
+        return format!("{start}/>");
+    }
+    let mut lines = vec![format!("{start}>"), format!("{indent}  <description>")];
+    lines.extend(
+        description
+            .into_iter()
+            .map(|line| format!("{indent}    {line}")),
+    );
+    lines.push(format!("{indent}  </description>"));
+    lines.push(format!("{indent}</release>"));
+    lines.join("\n")
+}
+
+/// Convert the Markdown of a changelog entry (after its version header) into the limited markup
+/// that `AppStream` allows in a description: a `<p>` for each section, followed by a `<ul>` of its
+/// notes.
+fn description_lines(new_changes: &[String]) -> Vec<String> {
+    let mut sections: Vec<(String, Vec<String>)> = Vec::new();
+    for line in new_changes.iter().skip(1).flat_map(|block| block.lines()) {
+        if let Some(title) = line.strip_prefix("### ") {
+            sections.push((title.trim().to_string(), Vec::new()));
+        } else if let (Some(note), Some((_, notes))) =
+            (line.strip_prefix("- "), sections.last_mut())
+        {
+            notes.push(note.trim().to_string());
+        } else if let (Some(continued), Some(note)) = (
+            line.strip_prefix("  "),
+            sections.last_mut().and_then(|(_, notes)| notes.last_mut()),
+        ) {
+            // A note which was wrapped onto multiple lines.
+            note.push(' ');
+            note.push_str(continued.trim());
+        }
+    }
+    let mut lines = Vec::new();
+    for (title, notes) in sections {
+        lines.push(format!("<p>{}</p>", escape(&title)));
+        lines.push(String::from("<ul>"));
+        lines.extend(
+            notes
+                .iter()
+                .map(|note| format!("  <li>{}</li>", escape(note))),
+        );
+        lines.push(String::from("</ul>"));
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test_add_release {
+    use super::*;
+
+    fn new_changes() -> Vec<String> {
+        vec![
+            String::from("## 1.1.0\n"),
+            String::from("### Features\n"),
+            String::from("- Support <b>bold</b> & italic"),
+            String::from(""),
+            String::from("### Fixes\n"),
+            String::from("- A fix which was wrapped onto\n  two lines"),
+            String::from(""),
+        ]
+    }
+
+    #[test]
+    fn existing_releases() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>com.example.App</id>
+  <releases>
+    <release version="1.0.0" date="2023-01-01"/>
+  </releases>
+</component>
+"#;
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>com.example.App</id>
+  <releases>
+    <release version="1.1.0" date="2023-06-01">
+      <description>
+        <p>Features</p>
+        <ul>
+          <li>Support &lt;b&gt;bold&lt;/b&gt; &amp; italic</li>
+        </ul>
+        <p>Fixes</p>
+        <ul>
+          <li>A fix which was wrapped onto two lines</li>
+        </ul>
+      </description>
+    </release>
+    <release version="1.0.0" date="2023-01-01"/>
+  </releases>
+</component>
+"#;
+        assert_eq!(
+            add_release(
+                content,
+                &Version::new(1, 1, 0),
+                "2023-06-01",
+                &new_changes()
+            ),
+            Some(String::from(expected))
+        );
+    }
+
+    #[test]
+    fn no_releases() {
+        let content = "<component>\n  <id>com.example.App</id>\n</component>\n";
+        let expected = "<component>\n  <id>com.example.App</id>\n  <releases>\n    <release version=\"2.0.0-rc.0\" date=\"2023-06-01\" type=\"development\"/>\n  </releases>\n</component>\n";
+        let version = Version::parse("2.0.0-rc.0").unwrap();
+        let new_changes = [String::from("## 2.0.0-rc.0\n")];
+        assert_eq!(
+            add_release(content, &version, "2023-06-01", &new_changes),
+            Some(String::from(expected))
+        );
+    }
+
+    #[test]
+    fn empty_releases() {
+        let content = "<component>\n  <releases />\n</component>\n";
+        let expected = "<component>\n  <releases>\n    <release version=\"1.0.0\" date=\"2023-06-01\"/>\n  </releases>\n</component>\n";
+        assert_eq!(
+            add_release(content, &Version::new(1, 0, 0), "2023-06-01", &[]),
+            Some(String::from(expected))
+        );
+    }
+
+    #[test]
+    fn already_released() {
+        let content = "<component>\n  <releases>\n    <release version=\"1.0.0\"/>\n  </releases>\n</component>\n";
+        assert_eq!(
+            add_release(content, &Version::new(1, 0, 0), "2023-06-01", &[]),
+            Some(String::from(content))
+        );
+    }
+
+    #[test]
+    fn not_a_component() {
+        assert_eq!(
+            add_release("<foo/>", &Version::new(1, 0, 0), "2023-06-01", &[]),
+            None
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string for `version:` in the `project/0` function of `mix.exs` is. If it's a module
+/// attribute (like `version: @version`), the span of the string assigned to that attribute is
+/// returned instead.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let project = project_span(content)?;
+    let body = &content[project.clone()];
+    let index = body
+        .match_indices("version:")
+        .map(|(index, _)| index)
+        .find(|&index| {
+            !body[..index]
+                .chars()
+                .next_back()
+                .map_or(false, |c| c.is_alphanumeric() || c == '_')
+        })?;
+    let value_start = project.start + index + "version:".len();
+    let value = content[value_start..].trim_start();
+    let value_start = content.len() - value.len();
+    if let Some(attribute) = value.strip_prefix('@') {
+        let name_len = attribute
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(attribute.len());
+        return attribute_span(content, &attribute[..name_len]);
+    }
+    string_span(content, value_start)
+}
+
+/// The body of `def project do ... end`, ending at the first `end` indented like the `def`.
+fn project_span(content: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    let mut project: Option<(usize, usize)> = None;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        match project {
+            None if trimmed
+                .strip_prefix("def project")
+                .map_or(false, |rest| rest.starts_with([' ', '('])) =>
+            {
+                project = Some((indent, line_start));
+            }
+            Some((def_indent, body_start)) if indent == def_indent && trimmed == "end" => {
+                return Some(body_start..start);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Where the string assigned to the module attribute `@name` (like `@version "1.2.3"`) is.
+fn attribute_span(content: &str, name: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let rest = match line.trim_start().strip_prefix('@') {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let value = match rest.strip_prefix(name) {
+            Some(value) if value.starts_with(char::is_whitespace) => value.trim_start(),
+            _ => continue,
+        };
+        return string_span(content, start + line.len() - value.len());
+    }
+    None
+}
+
+/// The contents of the double-quoted string starting at `start` in `content`.
+fn string_span(content: &str, start: usize) -> Option<Range<usize>> {
+    let value = content[start..].strip_prefix('"')?;
+    let len = value.find('"')?;
+    Some(start + 1..start + 1 + len)
+}
+
+#[cfg(test)]
+mod test_mix_exs {
+    use super::*;
+
+    const CONTENT: &str = r#"defmodule MyApp.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: :my_app,
+      version: "1.2.3",
+      elixir: "~> 1.14",
+      deps: deps()
+    ]
+  end
+
+  defp deps do
+    [
+      {:jason, version: "~> 1.4"}
+    ]
+  end
+end
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("version: \"1.2.3\"", "version: \"2.0.0\""))
+        );
+    }
+
+    #[test]
+    fn module_attribute() {
+        let content = CONTENT
+            .replace(
+                "  use Mix.Project\n",
+                "  use Mix.Project\n\n  @version \"1.2.3\"\n",
+            )
+            .replace("version: \"1.2.3\"", "version: @version");
+        assert_eq!(get_version(&content), Some(String::from("1.2.3")));
+        assert_eq!(
+            set_version(content.clone(), "2.0.0"),
+            Some(content.replace("@version \"1.2.3\"", "@version \"2.0.0\""))
+        );
+    }
+
+    #[test]
+    fn only_in_project() {
+        let content = CONTENT.replace("      version: \"1.2.3\",\n", "");
+        assert_eq!(get_version(&content), None);
+    }
+}
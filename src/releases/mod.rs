@@ -1,31 +1,92 @@
 use ::semver::Version;
 pub(crate) use conventional_commits::update_project_from_conventional_commits as prepare_release;
+pub(crate) use conventional_commits::{next_version, regenerate_changelog};
 
+use crate::issues::resolved_issues_section;
 use crate::state::Release::{Bumped, Prepared};
-use crate::step::StepError;
+use crate::step::{ReleaseOptions, StepError, VerifyTag};
 use crate::RunType;
 
-pub(crate) use self::git::{get_current_versions_from_tag, tag_name};
-pub(crate) use self::package::{find_packages, suggested_package_toml, Package};
+pub(crate) use self::api_diff::api_diff;
+pub(crate) use self::audit::{audit, Audit};
+pub(crate) use self::aur::publish_aur;
+pub(crate) use self::detect::detect;
+pub(crate) use self::git::{
+    default_tag_prefix, get_current_versions_from_tag, head_commit_id, tag_name,
+    tag_prefix_warnings, CommitRange,
+};
+pub(crate) use self::github::YankRelease;
+pub(crate) use self::manifest::update_manifest;
+pub(crate) use self::merge_changelog::merge_changelog;
+pub(crate) use self::package::{
+    find_packages, set_override_versions, suggested_package_toml, tag_only_package, Package,
+};
+pub(crate) use self::project_graph::{projects, Project};
+use self::record::read_record;
 pub(crate) use self::semver::bump_version_and_update_state as bump_version;
-pub(crate) use self::semver::{get_version, Rule};
+use self::semver::ensure_consistent_versions;
+pub(crate) use self::semver::{get_version, BumpLevel, Rule};
+pub(crate) use self::sigstore::sign_release;
+pub(crate) use self::yank::{yank, Yank};
 
+mod api_diff;
+mod audit;
+mod aur;
 mod cargo;
 mod changelog;
+mod cmake;
+mod conan;
 mod conventional_commits;
+mod debian_changelog;
+mod deno;
+mod detect;
+mod dockerfile;
 mod git;
 mod github;
 mod go;
+mod gradle;
+mod helm;
+mod manifest;
+mod markers;
+mod maven;
+mod merge_changelog;
+mod metainfo;
+mod mix;
+mod nix;
 mod package;
 mod package_json;
+mod pattern;
+mod pkgbuild;
+mod plain;
+mod project_graph;
+mod provenance;
+mod pubspec;
 mod pyproject;
+mod python;
+mod record;
+mod revert;
+mod rpm_spec;
+mod ruby;
 mod semver;
+mod setuptools;
+mod sigstore;
+mod structured;
+mod vcpkg;
+mod version_scheme;
+mod yank;
 
 #[derive(Clone, Debug)]
 pub(crate) struct Release {
     pub(crate) version: Version,
+    /// The version this release was bumped from.
+    pub(crate) previous_version: Version,
+    pub(crate) bump_level: BumpLevel,
     pub(crate) changelog: String,
     pub(crate) package_name: Option<String>,
+    /// The prefix of the Git tag for this release, see [`Package::tag_prefix`].
+    pub(crate) tag_prefix: String,
+    /// The commits this release was prepared from.
+    pub(crate) commits: CommitRange,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -56,25 +117,113 @@ impl Default for CurrentVersions {
 /// Create a release for the package.
 ///
 /// If GitHub config is present, this creates a GitHub release. Otherwise, it tags the Git repo.
-pub(crate) fn release(run_type: RunType) -> Result<RunType, StepError> {
+///
+/// If `provenance` is set, a provenance statement is written for each release and attached to
+/// any GitHub release.
+///
+/// If `jira_issues` is set, the Jira issues referenced by each release are added to its GitHub
+/// release.
+///
+/// If no release was prepared earlier in this workflow, the releases are read from `record`
+/// (written by a previous `PrepareRelease`), if any.
+///
+/// If `full_notes_asset` is set, the full notes of any release which is too long for GitHub are
+/// attached to it.
+///
+/// If `remote` is set, nothing is released if any of the new tags already exist on that remote.
+pub(crate) fn release(run_type: RunType, options: &ReleaseOptions) -> Result<RunType, StepError> {
     let (mut state, mut dry_run_stdout) = run_type.decompose();
+    if let (Some(record), true) = (&options.record, state.releases.is_empty()) {
+        state.releases = read_record(record)?.into_iter().map(Prepared).collect();
+    }
+    let prepared_releases = state
+        .releases
+        .iter()
+        .map(|release| match release {
+            Prepared(release) => Ok(release),
+            Bumped { .. } => Err(StepError::ReleaseNotPrepared),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let releases = combine_shared_tags(prepared_releases);
+    let jira_config = if options.jira_issues {
+        if state.github_config.is_none() {
+            return Err(StepError::GitHubNotConfigured);
+        }
+        Some(
+            state
+                .jira_config
+                .clone()
+                .ok_or(StepError::JiraNotConfigured)?,
+        )
+    } else {
+        None
+    };
 
-    for release in &state.releases {
-        let prepared = match release {
-            Prepared(release) => release,
-            Bumped { .. } => return Err(StepError::ReleaseNotPrepared),
+    if let Some(remote) = &options.remote {
+        let tags = releases
+            .iter()
+            .map(|release| tag_name(&release.version, &release.tag_prefix))
+            .collect::<Vec<_>>();
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            for tag in &tags {
+                writeln!(
+                    stdout,
+                    "Would check that tag {tag} doesn't exist on {remote}"
+                )?;
+            }
+        } else if let Some(tag) = git::remote_tags(remote, &tags)?.into_iter().next() {
+            return Err(StepError::TagExistsOnRemote {
+                tag,
+                remote: remote.clone(),
+            });
+        }
+    }
+
+    for mut prepared in releases {
+        let provenance_path = if options.provenance {
+            Some(provenance::write_provenance(
+                &prepared,
+                dry_run_stdout.as_mut(),
+            )?)
+        } else {
+            None
         };
 
         let github_config = state.github_config.clone();
         if let Some(github_config) = github_config {
+            let changelog = state
+                .packages
+                .iter()
+                .find(|package| package.tag_prefix() == prepared.tag_prefix)
+                .and_then(|package| package.changelog.as_ref())
+                .map(|changelog| changelog.path.as_path());
+            if let Some(jira_config) = &jira_config {
+                let path_filter = state
+                    .packages
+                    .iter()
+                    .find(|package| package.name == prepared.package_name)
+                    .map(Package::path_filter)
+                    .unwrap_or_default();
+                if let Some(section) = resolved_issues_section(
+                    jira_config,
+                    &prepared,
+                    path_filter,
+                    dry_run_stdout.as_mut(),
+                )? {
+                    prepared.changelog = format!("{}\n\n{section}", prepared.changelog.trim_end());
+                }
+            }
             state.github = github::release(
-                prepared,
+                &prepared,
                 state.github,
                 &github_config,
+                provenance_path.as_deref(),
+                options.full_notes_asset,
+                changelog,
                 dry_run_stdout.as_mut(),
             )?;
         } else {
-            git::release(dry_run_stdout.as_mut(), prepared)?;
+            git::release(dry_run_stdout.as_mut(), &prepared)?;
         }
     }
 
@@ -84,3 +233,142 @@ pub(crate) fn release(run_type: RunType) -> Result<RunType, StepError> {
         Ok(RunType::Real(state))
     }
 }
+
+/// Combine releases which would create the same tag (like every package when using fixed
+/// versioning) into a single release, with a section in the changelog for each package.
+fn combine_shared_tags(releases: Vec<&Release>) -> Vec<Release> {
+    let mut combined: Vec<(Release, Vec<&Release>)> = Vec::with_capacity(releases.len());
+    for release in releases {
+        let tag = tag_name(&release.version, &release.tag_prefix);
+        let existing = combined
+            .iter_mut()
+            .find(|(first, _)| tag_name(&first.version, &first.tag_prefix) == tag);
+        if let Some((_, others)) = existing {
+            others.push(release);
+        } else {
+            combined.push((release.clone(), vec![release]));
+        }
+    }
+    combined
+        .into_iter()
+        .map(|(mut first, group)| {
+            if group.len() > 1 {
+                first.changelog = group
+                    .iter()
+                    .map(|release| {
+                        // Replace the version header with the name of the package
+                        let notes = release
+                            .changelog
+                            .split_once('\n')
+                            .map_or("", |(_, notes)| notes);
+                        let name = release.package_name.as_deref().unwrap_or("package");
+                        format!("## {name}\n{notes}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                first.package_name = None;
+            }
+            first
+        })
+        .collect()
+}
+
+/// The implementation of [`crate::step::Step::VerifyVersions`].
+///
+/// Makes sure the versioned files of every package agree on its version. Nothing is changed, so
+/// this is the same for dry runs.
+pub(crate) fn verify_versions(run_type: RunType) -> Result<RunType, StepError> {
+    let (state, dry_run_stdout) = run_type.decompose();
+    if state.packages.is_empty() {
+        return Err(StepError::no_defined_packages_with_help());
+    }
+    ensure_consistent_versions(&state.packages)?;
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { stdout, state })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// The implementation of [`crate::step::Step::VerifyTag`].
+///
+/// Verifies the signature of the latest release tag of every package. Packages which have never
+/// been released have nothing to verify.
+pub(crate) fn verify_tag(run_type: RunType, verify_tag: &VerifyTag) -> Result<RunType, StepError> {
+    let (state, mut dry_run_stdout) = run_type.decompose();
+    if state.packages.is_empty() {
+        return Err(StepError::no_defined_packages_with_help());
+    }
+    let allowed_signers = &verify_tag.allowed_signers;
+    if !allowed_signers.exists() {
+        return Err(StepError::FileNotFound(allowed_signers.clone()));
+    }
+
+    for package in &state.packages {
+        let tag_prefix = package.tag_prefix();
+        let current_versions = get_current_versions_from_tag(&tag_prefix)?;
+        let tag = match current_versions {
+            Some(current_versions) => tag_name(current_versions.latest(), &tag_prefix),
+            None => continue,
+        };
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(
+                stdout,
+                "Would verify the signature of tag {tag} using {}",
+                allowed_signers.display()
+            )?;
+        } else {
+            git::verify_tag(&tag, allowed_signers)?;
+        }
+    }
+
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { stdout, state })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+#[cfg(test)]
+mod test_combine_shared_tags {
+    use super::*;
+
+    fn release(name: &str, tag_prefix: &str, changelog: &str) -> Release {
+        Release {
+            version: Version::new(1, 3, 0),
+            previous_version: Version::new(1, 2, 3),
+            bump_level: BumpLevel::Minor,
+            changelog: String::from(changelog),
+            package_name: Some(String::from(name)),
+            tag_prefix: String::from(tag_prefix),
+            commits: CommitRange::default(),
+        }
+    }
+
+    #[test]
+    fn shared_tag() {
+        let first = release("first", "v", "## 1.3.0\n\n### Features\n\n- New feature\n");
+        let second = release("second", "v", "## 1.3.0\n");
+
+        let combined = combine_shared_tags(vec![&first, &second]);
+
+        assert_eq!(combined.len(), 1);
+        assert_eq!(combined[0].package_name, None);
+        assert_eq!(
+            combined[0].changelog,
+            "## first\n\n### Features\n\n- New feature\n\n## second\n"
+        );
+    }
+
+    #[test]
+    fn separate_tags() {
+        let first = release("first", "first/v", "## 1.3.0\n");
+        let second = release("second", "second/v", "## 1.3.0\n");
+
+        let combined = combine_shared_tags(vec![&first, &second]);
+
+        assert_eq!(combined.len(), 2);
+        assert_eq!(combined[0].changelog, first.changelog);
+        assert_eq!(combined[1].package_name, second.package_name);
+    }
+}
@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use semver::Version;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_spans(content)
+        .first()
+        .map(|span| String::from(&content[span.clone()]))
+}
+
+/// Replace the version in every `version = "...";` binding which has the same version as the
+/// first one, so a flake with several packages (or a package and its `meta`) stays consistent.
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let spans = version_spans(&content);
+    let current = String::from(&content[spans.first()?.clone()]);
+    // Replace from the end, so that the earlier spans are still in the right place.
+    for span in spans.into_iter().rev() {
+        if content[span.clone()] == current {
+            content.replace_range(span, new_version);
+        }
+    }
+    Some(content)
+}
+
+/// Where the string of every `version = "1.2.3";` binding is, as long as it's a semantic version
+/// (not an interpolation like `"${base}-1"`). Comments are skipped.
+fn version_spans(content: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let code = line.split_once('#').map_or(line, |(code, _)| code);
+        for (index, _) in code.match_indices("version") {
+            let boundary = code[..index]
+                .chars()
+                .last()
+                .map_or(true, |c| c.is_whitespace() || matches!(c, '{' | ';' | '.'));
+            if !boundary {
+                continue;
+            }
+            let after = &code[index + "version".len()..];
+            let value = match after.trim_start().strip_prefix('=') {
+                Some(value) => value.trim_start(),
+                None => continue,
+            };
+            let value_offset = code.len() - value.len();
+            let string = match value.strip_prefix('"') {
+                Some(string) => string,
+                None => continue,
+            };
+            let end = match string.find('"') {
+                Some(end) => end,
+                None => continue,
+            };
+            if !string[end + 1..].trim_start().starts_with(';')
+                || Version::parse(&string[..end]).is_err()
+            {
+                continue;
+            }
+            let value_start = start + value_offset + 1;
+            spans.push(value_start..value_start + end);
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod test_nix {
+    use super::*;
+
+    const FLAKE: &str = r#"{
+  description = "My tool";
+
+  outputs = { self, nixpkgs }: {
+    packages.x86_64-linux.default = nixpkgs.legacyPackages.x86_64-linux.rustPlatform.buildRustPackage {
+      pname = "my-tool";
+      version = "1.2.3";
+      src = ./.;
+      cargoLock.lockFile = ./Cargo.lock;
+    };
+    # version = "0.0.1"; was the first release
+    lib.version = "1.2.3";
+  };
+}
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(FLAKE), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(FLAKE), "1.3.0"),
+            Some(FLAKE.replace("\"1.2.3\"", "\"1.3.0\""))
+        );
+    }
+
+    #[test]
+    fn single_line() {
+        let content = "{ pname = \"my-tool\"; version = \"0.1.0\"; }\n";
+        assert_eq!(
+            set_version(String::from(content), "0.2.0-rc.0"),
+            Some(content.replace("0.1.0", "0.2.0-rc.0"))
+        );
+    }
+
+    #[test]
+    fn not_a_version() {
+        assert_eq!(get_version("{ version = \"${base}-1\"; }\n"), None);
+        assert_eq!(get_version("{ nodeVersion = \"18.0.0\"; }\n"), None);
+        assert_eq!(get_version("{ version = lib.version; }\n"), None);
+    }
+}
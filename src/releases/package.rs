@@ -1,22 +1,71 @@
 use std::ffi::OsStr;
-use std::fs::{read_to_string, write};
-use std::path::{Path, PathBuf};
+use std::fs::{read_dir, read_to_string, write};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
 
 use itertools::Itertools;
 use log::trace;
 use semver::Version;
 
-use crate::config::Package as PackageConfig;
-use crate::releases::{cargo, get_current_versions_from_tag, go, package_json, pyproject};
+use crate::config::{
+    BuildNumber, ChangelogFormat, ChangelogSection, DebianChangelog, MajorVersionZero,
+    Package as PackageConfig, VersionedFile as VersionedFileConfig,
+};
+use crate::git::{add_files, PathFilter};
+use crate::releases::git::default_tag_prefix;
+use crate::releases::version_scheme::VersionScheme;
+use crate::releases::{
+    cargo, cmake, conan, deno, dockerfile, get_current_versions_from_tag, go, gradle, helm,
+    markers, maven, mix, nix, package_json, pattern, pkgbuild, plain, pubspec, pyproject, python,
+    rpm_spec, ruby, setuptools, structured, vcpkg,
+};
+use crate::state;
 use crate::step::StepError;
 use crate::step::StepError::InvalidCargoToml;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub(crate) struct Package {
     pub(crate) versioned_files: Vec<VersionedFile>,
     pub(crate) changelog: Option<Changelog>,
     pub(crate) name: Option<String>,
     pub(crate) scopes: Option<Vec<String>>,
+    /// Copies of `changelog` in other languages which receive placeholder entries for translators.
+    pub(crate) localized_changelogs: Vec<LocalizedChangelog>,
+    /// Changelog sections other than breaking changes, features, and fixes, in the order they
+    /// should appear.
+    pub(crate) changelog_sections: Vec<ChangelogSection>,
+    /// How each note added to `changelog` should be formatted.
+    pub(crate) changelog_format: ChangelogFormat,
+    /// The Cargo workspace whose root is in `versioned_files`, if any.
+    pub(crate) cargo_workspace: Option<CargoWorkspace>,
+    /// The multi-module Maven project whose root is in `versioned_files`, if any.
+    pub(crate) maven_project: Option<MavenProject>,
+    /// Overrides the default prefix of this package's Git tags.
+    pub(crate) tag_prefix: Option<String>,
+    /// If not empty, only commits which change files matching one of these paths apply to this
+    /// package.
+    pub(crate) paths: Vec<String>,
+    /// Changes to files matching one of these paths don't apply to this package.
+    pub(crate) exclude_paths: Vec<String>,
+    /// Whether commits without a conventional commit scope should be left out of this package.
+    pub(crate) ignore_unscoped: bool,
+    /// A `Cargo.lock` which records the version of every `Cargo.toml` in `versioned_files`.
+    pub(crate) cargo_lock: Option<PathBuf>,
+    /// An `AppStream` `metainfo.xml` which gets a `<release>` for every new version.
+    pub(crate) metainfo: Option<PathBuf>,
+    /// A Debian `changelog` which gets an entry for every new version.
+    pub(crate) debian_changelog: Option<DebianChangelog>,
+    /// The names of the other packages this one depends on, if they're known from a project graph.
+    pub(crate) dependencies: Option<Vec<String>>,
+    /// How the version is written in `versioned_files`, if it isn't a semantic version.
+    pub(crate) version_scheme: Option<VersionScheme>,
+    /// The version to bump to instead of the one computed from a rule, set by the
+    /// `--override-version` option.
+    pub(crate) override_version: Option<Version>,
+    /// The pre-release labels in the order they're promoted through, if there is such an order.
+    pub(crate) prerelease_channels: Vec<String>,
+    /// How the version is bumped while its major component is 0.
+    pub(crate) major_version_zero: MajorVersionZero,
 }
 
 impl Package {
@@ -24,16 +73,649 @@ impl Package {
         let versioned_files = config
             .versioned_files
             .into_iter()
-            .map(VersionedFile::try_from)
+            .map(|versioned_file| {
+                let mut versioned_file = match versioned_file {
+                    VersionedFileConfig::Path(path) => VersionedFile::try_from(path)?,
+                    VersionedFileConfig::Pattern { path, pattern } => {
+                        VersionedFile::with_pattern(path, pattern)?
+                    }
+                    VersionedFileConfig::Key { path, key } => VersionedFile::with_key(path, key)?,
+                    VersionedFileConfig::Format { path, format } => {
+                        VersionedFile::with_format(path, &format)?
+                    }
+                    VersionedFileConfig::Markers { path, markers } => {
+                        VersionedFile::with_custom_version(path, CustomVersion::Markers(markers))?
+                    }
+                };
+                if let PackageFormat::Pubspec(build_number) = &mut versioned_file.format {
+                    *build_number = config.build_number.unwrap_or_default();
+                }
+                Ok::<_, StepError>(versioned_file)
+            })
             .collect::<Result<Vec<_>, _>>()?;
+        let (versioned_files, cargo_workspace) = expand_cargo_workspace(versioned_files)?;
+        let (versioned_files, maven_project) = expand_maven_modules(versioned_files)?;
+        let localized_changelogs = match (&config.changelog, config.changelog_locales) {
+            (Some(path), Some(locales)) => locales
+                .into_iter()
+                .map(|locale| LocalizedChangelog::new(path, locale))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
         let changelog = config.changelog.map(Changelog::try_from).transpose()?;
+        if let Some(cargo_lock) = &config.cargo_lock {
+            if !cargo_lock.exists() {
+                return Err(StepError::FileNotFound(cargo_lock.clone()));
+            }
+        }
+        if let Some(metainfo) = &config.metainfo {
+            if !metainfo.exists() {
+                return Err(StepError::FileNotFound(metainfo.clone()));
+            }
+        }
+        let version_scheme = config
+            .version_scheme
+            .as_ref()
+            .map(VersionScheme::new)
+            .transpose()?;
+        if version_scheme.is_some() {
+            if let Some(versioned_file) = versioned_files
+                .iter()
+                .find(|versioned_file| versioned_file.format.requires_semver())
+            {
+                return Err(StepError::VersionSchemeUnsupported(
+                    versioned_file.path.clone(),
+                ));
+            }
+        }
         Ok(Package {
             versioned_files,
             changelog,
             name,
             scopes: config.scopes,
+            localized_changelogs,
+            changelog_sections: changelog_sections(config.changelog_sections),
+            changelog_format: config.changelog_format.unwrap_or_default(),
+            cargo_workspace,
+            maven_project,
+            tag_prefix: config.tag_prefix,
+            paths: config.paths.unwrap_or_default(),
+            exclude_paths: config.exclude_paths.unwrap_or_default(),
+            ignore_unscoped: config.ignore_unscoped.unwrap_or_default(),
+            cargo_lock: config.cargo_lock,
+            metainfo: config.metainfo,
+            debian_changelog: config.debian_changelog,
+            dependencies: None,
+            version_scheme,
+            override_version: None,
+            prerelease_channels: config.prerelease_channels.unwrap_or_default(),
+            major_version_zero: config.major_version_zero.unwrap_or_default(),
         })
     }
+
+    /// Whether `self` and `other` have a versioned file in common (like the root of a Cargo
+    /// workspace whose version they both inherit), so they always have the same version.
+    pub(crate) fn shares_version_with(&self, other: &Package) -> bool {
+        self.versioned_files.iter().any(|versioned_file| {
+            other
+                .versioned_files
+                .iter()
+                .any(|other_file| other_file.path == versioned_file.path)
+        })
+    }
+
+    /// Whether `self` could depend on `other`, which is always the case unless the dependencies
+    /// of `self` are known from a project graph.
+    pub(crate) fn may_depend_on(&self, other: &Package) -> bool {
+        match (&self.dependencies, &other.name) {
+            (Some(dependencies), Some(name)) => dependencies.contains(name),
+            _ => true,
+        }
+    }
+
+    /// Update the entry in `cargo_lock` (if any) of every `Cargo.toml` in `versioned_files` (and
+    /// every member of `cargo_workspace`) to `version`, returning the path to `cargo_lock` if it changed.
+    pub(crate) fn set_cargo_lock_versions(
+        &self,
+        version: &Version,
+    ) -> Result<Option<&PathBuf>, StepError> {
+        let path = if let Some(path) = &self.cargo_lock {
+            path
+        } else {
+            return Ok(None);
+        };
+        let names = self
+            .versioned_files
+            .iter()
+            .filter(|versioned_file| versioned_file.format == PackageFormat::Cargo)
+            .filter_map(|versioned_file| cargo::package_name(&versioned_file.content))
+            .chain(
+                self.cargo_workspace
+                    .iter()
+                    .flat_map(|cargo_workspace| cargo_workspace.members.iter().cloned()),
+            )
+            .unique()
+            .collect_vec();
+        let content = read_to_string(path)?;
+        let new_content = cargo::set_lock_versions(content.clone(), &names, &version.to_string())
+            .map_err(|_| StepError::InvalidCargoLock(path.clone()))?;
+        if new_content == content {
+            return Ok(None);
+        }
+        trace!("Updating package versions in {}", path.display());
+        write(path, new_content)?;
+        Ok(Some(path))
+    }
+
+    /// Set the root version recorded in any npm lockfile next to a `package.json` in
+    /// `versioned_files`, returning the paths of any lockfiles which changed.
+    pub(crate) fn set_npm_lock_versions(
+        &self,
+        version: &Version,
+    ) -> Result<Vec<PathBuf>, StepError> {
+        let mut changed = Vec::new();
+        let lockfiles = self
+            .versioned_files
+            .iter()
+            .filter(|versioned_file| versioned_file.format == PackageFormat::JavaScript)
+            .flat_map(|versioned_file| {
+                let dir = versioned_file
+                    .path
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""));
+                package_json::LOCKFILE_NAMES.map(|name| dir.join(name))
+            })
+            .filter(|path| path.exists());
+        for path in lockfiles {
+            let content = read_to_string(&path)?;
+            let new_content = package_json::set_lock_version(&content, &version.to_string())
+                .map_err(|_| StepError::InvalidPackageLock(path.clone()))?;
+            if new_content != content {
+                trace!("Updating the version in {}", path.display());
+                write(&path, new_content)?;
+                changed.push(path);
+            }
+        }
+        Ok(changed)
+    }
+
+    /// The prefix of every Git tag for this package, which is followed by the version.
+    ///
+    /// Go requires the tags of a module which isn't at the root of the repo to start with its
+    /// directory, so that's the default for a nested `go.mod`.
+    pub(crate) fn tag_prefix(&self) -> String {
+        self.tag_prefix
+            .clone()
+            .or_else(|| self.go_module_dir().map(|dir| format!("{dir}/v")))
+            .unwrap_or_else(|| default_tag_prefix(self.name.as_deref()))
+    }
+
+    /// Which files a commit must change for it to apply to this package.
+    pub(crate) fn path_filter(&self) -> PathFilter<'_> {
+        PathFilter {
+            paths: &self.paths,
+            exclude_paths: &self.exclude_paths,
+        }
+    }
+
+    /// The directory (relative to the repo root) of a `go.mod` in `versioned_files`, if it isn't
+    /// at the root.
+    fn go_module_dir(&self) -> Option<String> {
+        self.versioned_files
+            .iter()
+            .filter(|versioned_file| versioned_file.format == PackageFormat::Go)
+            .find_map(|versioned_file| {
+                let dir = versioned_file
+                    .path
+                    .parent()?
+                    .components()
+                    .filter_map(|component| match component {
+                        Component::Normal(part) => Some(part.to_string_lossy()),
+                        _ => None,
+                    })
+                    .join("/");
+                if dir.is_empty() {
+                    None
+                } else {
+                    Some(dir)
+                }
+            })
+    }
+}
+
+/// A Cargo workspace, discovered from the `workspace.members` of its root `Cargo.toml`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct CargoWorkspace {
+    /// The names of every package in the workspace.
+    members: Vec<String>,
+    /// The `Cargo.toml` of the root and of every member, any of which may depend on the others.
+    manifests: Vec<PathBuf>,
+}
+
+impl CargoWorkspace {
+    /// Make every dependency between members of the workspace require `version`, returning the
+    /// paths of any files which changed.
+    pub(crate) fn set_dependency_versions(
+        &self,
+        version: &Version,
+    ) -> Result<Vec<PathBuf>, StepError> {
+        let mut changed = Vec::new();
+        for path in &self.manifests {
+            let content = read_to_string(path)?;
+            let new_content = cargo::set_dependency_versions(
+                content.clone(),
+                &self.members,
+                &version.to_string(),
+            )
+            .map_err(|_| InvalidCargoToml(path.clone()))?;
+            if new_content != content {
+                trace!("Updating dependency versions in {}", path.display());
+                write(path, new_content)?;
+                changed.push(path.clone());
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// A multi-module Maven project, discovered from the `modules` of its root `pom.xml`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct MavenProject {
+    /// The `artifactId` of the root and of every module, any of which may be the `parent` of
+    /// another module.
+    artifact_ids: Vec<String>,
+    /// The `pom.xml` of every module (not including the root).
+    modules: Vec<PathBuf>,
+}
+
+impl MavenProject {
+    /// Make the `parent` of every module which inherits from another part of the project require
+    /// `version`, returning the paths of any files which changed.
+    pub(crate) fn set_parent_versions(&self, version: &Version) -> Result<Vec<PathBuf>, StepError> {
+        let mut changed = Vec::new();
+        for path in &self.modules {
+            let content = read_to_string(path)?;
+            let inherits_from_project = maven::parent_artifact_id(&content)
+                .map_or(false, |parent| self.artifact_ids.contains(&parent));
+            if !inherits_from_project {
+                continue;
+            }
+            let new_content = maven::set_parent_version(content.clone(), &version.to_string())
+                .ok_or_else(|| StepError::InvalidPomXml(path.clone()))?;
+            if new_content != content {
+                trace!("Updating the parent version in {}", path.display());
+                write(path, new_content)?;
+                changed.push(path.clone());
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Make every dependency of one package on another package which was just released require the
+/// new version, so that the manifests in a monorepo stay consistent with each other.
+///
+/// Dependencies between members of the same Cargo workspace are handled by [`CargoWorkspace`].
+pub(crate) fn set_internal_dependency_versions(
+    packages: &[Package],
+    releases: &[state::Release],
+    mut dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<(), StepError> {
+    for release in releases {
+        let (package_name, version) = match release {
+            state::Release::Bumped {
+                package_name,
+                version,
+                ..
+            } => (package_name, version),
+            state::Release::Prepared(release) => (&release.package_name, &release.version),
+        };
+        let dependency = if let Some(dependency) = packages
+            .iter()
+            .find(|package| package.name.is_some() && package.name == *package_name)
+        {
+            dependency
+        } else {
+            continue;
+        };
+        let dependency_names = dependency
+            .versioned_files
+            .iter()
+            .filter_map(|versioned_file| {
+                versioned_file.format.package_name(&versioned_file.content)
+            })
+            .unique()
+            .collect_vec();
+        if dependency_names.is_empty() {
+            continue;
+        }
+        let dependents = packages
+            .iter()
+            .filter(|package| package.name != dependency.name && package.may_depend_on(dependency))
+            .flat_map(|package| &package.versioned_files)
+            .unique_by(|versioned_file| &versioned_file.path)
+            .map(|versioned_file| (versioned_file.format, &versioned_file.path));
+        for (format, path) in dependents {
+            let content = read_to_string(path)?;
+            let new_content = format.set_dependency_versions(
+                content.clone(),
+                &dependency_names,
+                version,
+                path,
+            )?;
+            if new_content == content {
+                continue;
+            }
+            if let Some(stdout) = dry_run_stdout.as_mut() {
+                writeln!(
+                    stdout,
+                    "Would update the dependencies on {name} in {path} to {version}",
+                    name = package_name.as_deref().unwrap_or_default(),
+                    path = path.display()
+                )?;
+            } else {
+                trace!("Updating dependency versions in {}", path.display());
+                write(path, new_content)?;
+                add_files(&[path])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// If any of `versioned_files` is the root of a Cargo workspace, add the `Cargo.toml` of every
+/// member which has its own version. The root itself is only kept if it has a version (either
+/// `package.version` or `workspace.package.version`).
+fn expand_cargo_workspace(
+    versioned_files: Vec<VersionedFile>,
+) -> Result<(Vec<VersionedFile>, Option<CargoWorkspace>), StepError> {
+    let mut cargo_workspace = None;
+    // The same file can be configured more than once (e.g., with different patterns), but a
+    // manifest found by expanding a workspace is only added if it isn't there already.
+    let configured = versioned_files
+        .iter()
+        .map(|versioned_file| versioned_file.path.clone())
+        .collect::<Vec<_>>();
+    let is_new = |expanded: &[VersionedFile], path: &Path| {
+        !configured.iter().any(|configured| configured == path)
+            && !expanded
+                .iter()
+                .any(|versioned_file| versioned_file.path == path)
+    };
+    let mut expanded = Vec::with_capacity(versioned_files.len());
+    for versioned_file in versioned_files {
+        let workspace = if versioned_file.format == PackageFormat::Cargo {
+            cargo::workspace(&versioned_file.content)
+                .map_err(|_| InvalidCargoToml(versioned_file.path.clone()))?
+        } else {
+            None
+        };
+        let workspace = if let Some(workspace) = workspace {
+            workspace
+        } else if versioned_file.format == PackageFormat::Cargo
+            && cargo::inherits_version(&versioned_file.content)
+        {
+            // The member itself is left alone, its version is in `workspace.package.version`.
+            let root = inherited_version_root(&versioned_file.path)?;
+            if is_new(&expanded, &root.path) {
+                expanded.push(root);
+            }
+            continue;
+        } else {
+            expanded.push(versioned_file);
+            continue;
+        };
+        let root = versioned_file
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""));
+        let mut manifests = vec![versioned_file.path.clone()];
+        manifests.extend(
+            workspace_member_dirs(root, &workspace.members, &workspace.exclude)?
+                .into_iter()
+                .map(|dir| dir.join("Cargo.toml")),
+        );
+        let mut names = Vec::with_capacity(manifests.len());
+        for path in &manifests {
+            let is_root = *path == versioned_file.path;
+            let manifest = if is_root {
+                versioned_file.clone()
+            } else {
+                VersionedFile::try_from(path.clone())?
+            };
+            names.extend(cargo::package_name(&manifest.content));
+            if cargo::has_version(&manifest.content) && (is_root || is_new(&expanded, path)) {
+                expanded.push(manifest);
+            }
+        }
+        cargo_workspace = Some(CargoWorkspace {
+            members: names,
+            manifests,
+        });
+    }
+    Ok((expanded, cargo_workspace))
+}
+
+/// If any of `versioned_files` is the root of a multi-module Maven project, add the `pom.xml` of
+/// every module (including nested ones) which has its own version.
+fn expand_maven_modules(
+    versioned_files: Vec<VersionedFile>,
+) -> Result<(Vec<VersionedFile>, Option<MavenProject>), StepError> {
+    let mut maven_project: Option<MavenProject> = None;
+    // Like in `expand_cargo_workspace`, only modules which aren't there already are added.
+    let configured = versioned_files
+        .iter()
+        .map(|versioned_file| versioned_file.path.clone())
+        .collect::<Vec<_>>();
+    let mut expanded: Vec<VersionedFile> = Vec::with_capacity(versioned_files.len());
+    for versioned_file in versioned_files {
+        if versioned_file.format != PackageFormat::Maven
+            || maven::modules(&versioned_file.content).is_empty()
+        {
+            expanded.push(versioned_file);
+            continue;
+        }
+        let project = maven_project.get_or_insert(MavenProject {
+            artifact_ids: Vec::new(),
+            modules: Vec::new(),
+        });
+        let mut poms = vec![versioned_file];
+        while let Some(pom) = poms.pop() {
+            project
+                .artifact_ids
+                .extend(maven::artifact_id(&pom.content));
+            let dir = pom.path.parent().unwrap_or_else(|| Path::new(""));
+            for module in maven::modules(&pom.content) {
+                let path = dir.join(&module);
+                let path = if path.extension() == Some(OsStr::new("xml")) {
+                    path
+                } else {
+                    path.join("pom.xml")
+                };
+                project.modules.push(path.clone());
+                let is_new = !configured.contains(&path)
+                    && !expanded
+                        .iter()
+                        .any(|versioned_file| versioned_file.path == path);
+                if is_new {
+                    poms.push(VersionedFile::try_from(path)?);
+                }
+            }
+            if maven::has_version(&pom.content) {
+                expanded.push(pom);
+            }
+        }
+    }
+    Ok((expanded, maven_project))
+}
+
+/// Find the root `Cargo.toml` of the workspace which the member at `path` inherits its version
+/// from—the closest one in a parent directory which defines a `workspace`.
+fn inherited_version_root(path: &Path) -> Result<VersionedFile, StepError> {
+    let member_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    for dir in member_dir.ancestors().skip(1) {
+        let root_path = dir.join("Cargo.toml");
+        if !root_path.exists() {
+            continue;
+        }
+        let root = VersionedFile::try_from(root_path)?;
+        let is_workspace = cargo::workspace(&root.content)
+            .map_err(|_| InvalidCargoToml(root.path.clone()))?
+            .is_some();
+        if is_workspace {
+            return Ok(root);
+        }
+    }
+    Err(StepError::CargoWorkspaceNotFound(path.into()))
+}
+
+/// Resolve the `members` of a workspace (relative to the `root` directory), skipping any which are
+/// in `exclude`. A `*` is supported in the last component of a member, like `crates/*`.
+fn workspace_member_dirs(
+    root: &Path,
+    members: &[String],
+    exclude: &[String],
+) -> Result<Vec<PathBuf>, StepError> {
+    let mut dirs = Vec::new();
+    for member in members {
+        let member = Path::new(member);
+        let pattern = member
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let (prefix, suffix) = if let Some(parts) = pattern.split_once('*') {
+            parts
+        } else {
+            dirs.push(root.join(member));
+            continue;
+        };
+        let parent = root.join(member.parent().unwrap_or_else(|| Path::new("")));
+        let search_dir = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent.as_path()
+        };
+        let mut matches = Vec::new();
+        for entry in read_dir(search_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let dir = parent.join(&name);
+            if name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+                && dir.join("Cargo.toml").exists()
+            {
+                matches.push(dir);
+            }
+        }
+        matches.sort();
+        dirs.extend(matches);
+    }
+    dirs.retain(|dir| !exclude.iter().any(|excluded| *dir == root.join(excluded)));
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod test_cargo_workspace {
+    use std::fs::{create_dir_all, write};
+
+    use super::*;
+
+    #[test]
+    fn member_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        for member in ["crates/first", "crates/second", "crates/old", "tools"] {
+            create_dir_all(root.path().join(member)).unwrap();
+            write(root.path().join(member).join("Cargo.toml"), "").unwrap();
+        }
+        create_dir_all(root.path().join("crates/not_a_crate")).unwrap();
+
+        let dirs = workspace_member_dirs(
+            root.path(),
+            &[String::from("crates/*"), String::from("tools")],
+            &[String::from("crates/old")],
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs,
+            vec![
+                root.path().join("crates/first"),
+                root.path().join("crates/second"),
+                root.path().join("tools")
+            ]
+        );
+    }
+}
+
+/// The remaining [Keep a Changelog](https://keepachangelog.com/en/1.0.0/) sections, which apply
+/// to every package unless overridden by name.
+const DEFAULT_CHANGELOG_SECTIONS: [(&str, &str); 4] = [
+    ("Deprecated", "deprecate"),
+    ("Removed", "remove"),
+    ("Security", "security"),
+    ("Reverted", "revert"),
+];
+
+/// Combine the default changelog sections with any configured ones. A configured section with the
+/// same name as a default replaces its commit types, any others are added after the defaults.
+fn changelog_sections(configured: Option<Vec<ChangelogSection>>) -> Vec<ChangelogSection> {
+    let mut sections = DEFAULT_CHANGELOG_SECTIONS
+        .iter()
+        .map(|(name, commit_type)| ChangelogSection {
+            name: String::from(*name),
+            types: vec![String::from(*commit_type)],
+        })
+        .collect_vec();
+    for section in configured.into_iter().flatten() {
+        if let Some(existing) = sections
+            .iter_mut()
+            .find(|existing| existing.name == section.name)
+        {
+            existing.types = section.types;
+        } else {
+            sections.push(section);
+        }
+    }
+    sections
+}
+
+#[cfg(test)]
+mod test_changelog_sections {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let sections = changelog_sections(None);
+        assert_eq!(
+            sections
+                .iter()
+                .map(|section| section.name.as_str())
+                .collect_vec(),
+            vec!["Deprecated", "Removed", "Security", "Reverted"]
+        );
+    }
+
+    #[test]
+    fn override_and_extend() {
+        let sections = changelog_sections(Some(vec![
+            ChangelogSection {
+                name: String::from("Security"),
+                types: vec![String::from("sec"), String::from("security")],
+            },
+            ChangelogSection {
+                name: String::from("Documentation"),
+                types: vec![String::from("docs")],
+            },
+        ]));
+        assert_eq!(sections.len(), 5);
+        assert_eq!(
+            sections[2].types,
+            vec![String::from("sec"), String::from("security")]
+        );
+        assert_eq!(sections[3].name, "Reverted");
+        assert_eq!(sections[4].name, "Documentation");
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -44,6 +726,8 @@ pub(crate) struct VersionedFile {
     pub(crate) path: PathBuf,
     /// The raw content of the package manager file so it doesn't have to be read again.
     content: String,
+    /// Where the version is in a file which isn't a supported format.
+    custom: Option<CustomVersion>,
 }
 
 impl TryFrom<PathBuf> for VersionedFile {
@@ -51,6 +735,12 @@ impl TryFrom<PathBuf> for VersionedFile {
 
     fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
         let format = PackageFormat::try_from(&path)?;
+        Self::new(path, format)
+    }
+}
+
+impl VersionedFile {
+    fn new(path: PathBuf, format: PackageFormat) -> Result<Self, StepError> {
         if !path.exists() {
             return Err(StepError::FileNotFound(path));
         }
@@ -59,26 +749,123 @@ impl TryFrom<PathBuf> for VersionedFile {
             format,
             path,
             content,
+            custom: None,
         })
     }
-}
 
-impl VersionedFile {
-    pub(crate) fn get_version(&self, package_name: Option<&str>) -> Result<String, StepError> {
+    /// A file with the same format as a file named `format`, like `VERSION`.
+    fn with_format(path: PathBuf, format: &str) -> Result<Self, StepError> {
+        let format = PackageFormat::try_from(&PathBuf::from(format))
+            .map_err(|_| StepError::UnknownVersionedFileFormat(format.to_string()))?;
+        Self::new(path, format)
+    }
+
+    /// A file of any format, whose version is wherever `pattern` matches.
+    fn with_pattern(path: PathBuf, pattern: String) -> Result<Self, StepError> {
+        if !pattern::is_valid(&pattern) {
+            return Err(StepError::InvalidVersionPattern(pattern));
+        }
+        Self::with_custom_version(path, CustomVersion::Pattern(pattern))
+    }
+
+    /// A TOML, JSON, or YAML file, whose version is at `key`.
+    fn with_key(path: PathBuf, key: String) -> Result<Self, StepError> {
+        let format = structured::Format::from_path(&path)
+            .ok_or_else(|| StepError::VersionKeyFormat(path.clone()))?;
+        Self::with_custom_version(path, CustomVersion::Key(format, key))
+    }
+
+    fn with_custom_version(path: PathBuf, custom: CustomVersion) -> Result<Self, StepError> {
+        Ok(Self {
+            custom: Some(custom),
+            ..Self::new(path, PackageFormat::Custom)?
+        })
+    }
+
+    pub(crate) fn get_version(&self, tag_prefix: &str) -> Result<String, StepError> {
+        if let Some(custom) = &self.custom {
+            return custom
+                .get_version(&self.content)
+                .ok_or_else(|| custom.not_found(&self.path));
+        }
         self.format
-            .get_version(&self.content, package_name, &self.path)
+            .get_version(&self.content, tag_prefix, &self.path)
     }
 
-    pub(crate) fn set_version(&mut self, version_str: &Version) -> Result<(), StepError> {
-        self.content = self
-            .format
-            .set_version(self.content.clone(), version_str, &self.path)?;
+    /// Write `version` to the file, formatted by `version_scheme` if there is one.
+    pub(crate) fn set_version(
+        &mut self,
+        version: &Version,
+        version_scheme: Option<&VersionScheme>,
+    ) -> Result<(), StepError> {
+        let formatted = version_scheme.map_or_else(
+            || version.to_string(),
+            |version_scheme| version_scheme.format(version),
+        );
+        self.content = if let Some(custom) = &self.custom {
+            // The same file can be listed more than once (e.g., with different patterns), so
+            // start from whatever the others have already written.
+            let content = read_to_string(&self.path)?;
+            custom
+                .set_version(content, &formatted)
+                .ok_or_else(|| custom.not_found(&self.path))?
+        } else {
+            self.format
+                .set_version(self.content.clone(), version, &formatted, &self.path)?
+        };
         trace!("Writing {} to {}", self.content, self.path.display());
         write(&self.path, &self.content)?;
         Ok(())
     }
 }
 
+/// Where the version is in a file which isn't one of the supported formats.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum CustomVersion {
+    /// Wherever a pattern like `version: {version}` matches.
+    Pattern(String),
+    /// The string at a key like `tool.mytool.version`.
+    Key(structured::Format, String),
+    /// Every version between markers like `<!-- version-start -->` and `<!-- version-end -->`,
+    /// named `version`.
+    Markers(String),
+}
+
+impl CustomVersion {
+    fn get_version(&self, content: &str) -> Option<String> {
+        match self {
+            CustomVersion::Pattern(pattern) => pattern::get_version(content, pattern),
+            CustomVersion::Key(format, key) => structured::get_version(content, *format, key),
+            CustomVersion::Markers(name) => markers::get_version(content, name),
+        }
+    }
+
+    fn set_version(&self, content: String, new_version: &str) -> Option<String> {
+        match self {
+            CustomVersion::Pattern(pattern) => pattern::set_version(content, pattern, new_version),
+            CustomVersion::Key(format, key) => {
+                structured::set_version(content, *format, key, new_version)
+            }
+            CustomVersion::Markers(name) => markers::set_version(content, name, new_version),
+        }
+    }
+
+    /// The error for when the version isn't in the file at `path`.
+    fn not_found(&self, path: &Path) -> StepError {
+        match self {
+            CustomVersion::Pattern(pattern) => {
+                StepError::VersionPatternNotFound(path.into(), pattern.clone())
+            }
+            CustomVersion::Key(_, key) => StepError::VersionKeyNotFound(path.into(), key.clone()),
+            CustomVersion::Markers(name) => StepError::VersionMarkersNotFound(
+                path.into(),
+                markers::start_marker(name),
+                markers::end_marker(name),
+            ),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) struct Changelog {
     pub(crate) path: PathBuf,
@@ -98,12 +885,104 @@ impl TryFrom<PathBuf> for Changelog {
     }
 }
 
+/// A translated copy of a package's [`Changelog`], e.g. `CHANGELOG.de.md` for the `de` locale.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct LocalizedChangelog {
+    pub(crate) locale: String,
+    pub(crate) changelog: Changelog,
+}
+
+impl LocalizedChangelog {
+    fn new(canonical_path: &Path, locale: String) -> Result<Self, StepError> {
+        let path = localized_path(canonical_path, &locale);
+        Ok(Self {
+            locale,
+            changelog: Changelog::try_from(path)?,
+        })
+    }
+}
+
+/// Insert `locale` between the stem and extension of `path`, so `CHANGELOG.md` becomes `CHANGELOG.de.md`.
+fn localized_path(path: &Path, locale: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}.{locale}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{locale}"),
+    };
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod test_localized_path {
+    use super::*;
+
+    #[test]
+    fn with_extension() {
+        assert_eq!(
+            localized_path(Path::new("docs/CHANGELOG.md"), "de"),
+            PathBuf::from("docs/CHANGELOG.de.md")
+        );
+    }
+
+    #[test]
+    fn without_extension() {
+        assert_eq!(
+            localized_path(Path::new("CHANGES"), "pt-BR"),
+            PathBuf::from("CHANGES.pt-BR")
+        );
+    }
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum PackageFormat {
     Cargo,
+    /// A `CMake` `CMakeLists.txt` with a `VERSION` in its `project()` command.
+    CMake,
+    /// A Conan recipe's `conanfile.py`.
+    Conan,
     Go,
+    /// A Gradle `build.gradle` or `build.gradle.kts`.
+    Gradle,
+    /// A Gradle `gradle.properties`.
+    GradleProperties,
+    /// A Helm chart's `Chart.yaml`.
+    Helm,
     JavaScript,
+    /// A Deno or JSR `deno.json`, `deno.jsonc`, or `jsr.json`.
+    Deno,
+    /// A Maven `pom.xml`.
+    Maven,
+    /// An Elixir project's `mix.exs`.
+    Mix,
     Poetry,
+    /// A Dart or Flutter `pubspec.yaml`, along with what to do with its build number.
+    Pubspec(BuildNumber),
+    SetupCfg,
+    SetupPy,
+    /// Any other Python source file, like `mypkg/__init__.py`, which has a `__version__`.
+    PythonModule,
+    /// A vcpkg manifest's `vcpkg.json`.
+    Vcpkg,
+    /// A Ruby `version.rb`, like `lib/my_gem/version.rb`, which has a `VERSION` constant.
+    RubyVersion,
+    /// A Ruby gem's `.gemspec`.
+    Gemspec,
+    /// A `VERSION` file, which contains only the version.
+    Plain,
+    /// A `Dockerfile`, which has an `ARG VERSION` or an `org.opencontainers.image.version` label.
+    Dockerfile,
+    /// An Arch Linux `PKGBUILD`, which has a `pkgver` (and a `pkgrel` which is reset).
+    Pkgbuild,
+    /// An RPM `.spec` file, which has a `Version` (and a `Release` which is reset).
+    RpmSpec,
+    /// A Nix file, like `flake.nix` or `default.nix`, which has a `version = "1.2.3";` binding.
+    Nix,
+    /// Any file at all, whose version is found by the custom location (like a pattern) of its
+    /// [`VersionedFile`].
+    Custom,
 }
 
 impl TryFrom<&PathBuf> for PackageFormat {
@@ -118,83 +997,318 @@ impl TryFrom<&PathBuf> for PackageFormat {
             .iter()
             .find_position(|&name| *name == file_name)
             .map(|(pos, _)| ALL_PACKAGE_FORMATS[pos])
+            .or_else(|| match path.extension().and_then(OsStr::to_str) {
+                Some("py") => Some(PackageFormat::PythonModule),
+                Some("gemspec") => Some(PackageFormat::Gemspec),
+                Some("dockerfile") => Some(PackageFormat::Dockerfile),
+                Some("spec") => Some(PackageFormat::RpmSpec),
+                Some("nix") => Some(PackageFormat::Nix),
+                _ => None,
+            })
             .ok_or_else(|| StepError::VersionedFileFormat(path.clone()))
     }
 }
 
 impl PackageFormat {
-    /// Get the version from `content` for package named `name` (if any name).
+    /// Get the version from `content` for the package whose Git tags start with `tag_prefix`.
     /// `path` is used for error reporting.
     pub(crate) fn get_version(
         self,
         content: &str,
-        name: Option<&str>,
+        tag_prefix: &str,
         path: &Path,
     ) -> Result<String, StepError> {
         match self {
             PackageFormat::Cargo => {
                 cargo::get_version(content).map_err(|_| InvalidCargoToml(path.into()))
             }
+            PackageFormat::CMake => {
+                cmake::get_version(content).ok_or_else(|| StepError::InvalidCMakeLists(path.into()))
+            }
+            PackageFormat::Conan => {
+                conan::get_version(content).ok_or_else(|| StepError::InvalidConanfile(path.into()))
+            }
             PackageFormat::Poetry => pyproject::get_version(content)
                 .map_err(|_| StepError::InvalidPyProject(path.into())),
             PackageFormat::JavaScript => package_json::get_version(content)
                 .map_err(|_| StepError::InvalidPackageJson(path.into())),
-            PackageFormat::Go => get_current_versions_from_tag(name).map(|current_versions| {
-                current_versions
-                    .unwrap_or_default()
-                    .into_latest()
-                    .to_string()
-            }),
+            PackageFormat::Deno => {
+                deno::get_version(content).ok_or_else(|| StepError::InvalidDenoJson(path.into()))
+            }
+            PackageFormat::Gradle => gradle::get_build_gradle_version(content)
+                .ok_or_else(|| StepError::InvalidBuildGradle(path.into())),
+            PackageFormat::GradleProperties => gradle::get_gradle_properties_version(content)
+                .ok_or_else(|| StepError::InvalidGradleProperties(path.into())),
+            PackageFormat::Helm => {
+                helm::get_version(content).ok_or_else(|| StepError::InvalidChartYaml(path.into()))
+            }
+            PackageFormat::Maven => {
+                maven::get_version(content).ok_or_else(|| StepError::InvalidPomXml(path.into()))
+            }
+            PackageFormat::Mix => {
+                mix::get_version(content).ok_or_else(|| StepError::InvalidMixExs(path.into()))
+            }
+            PackageFormat::Pubspec(_) => {
+                pubspec::get_version(content).ok_or_else(|| StepError::InvalidPubspec(path.into()))
+            }
+            PackageFormat::SetupCfg => setuptools::get_setup_cfg_version(content)
+                .ok_or_else(|| StepError::InvalidSetupCfg(path.into())),
+            PackageFormat::SetupPy => setuptools::get_setup_py_version(content)
+                .ok_or_else(|| StepError::InvalidSetupPy(path.into())),
+            PackageFormat::PythonModule => python::get_version(content)
+                .ok_or_else(|| StepError::InvalidPythonVersion(path.into())),
+            PackageFormat::Vcpkg => {
+                vcpkg::get_version(content).ok_or_else(|| StepError::InvalidVcpkgJson(path.into()))
+            }
+            PackageFormat::RubyVersion => ruby::get_version_rb_version(content)
+                .ok_or_else(|| StepError::InvalidVersionRb(path.into())),
+            PackageFormat::Gemspec => ruby::get_gemspec_version(content)
+                .ok_or_else(|| StepError::InvalidGemspec(path.into())),
+            PackageFormat::Plain => plain::get_version(content)
+                .ok_or_else(|| StepError::InvalidVersionFile(path.into())),
+            PackageFormat::Dockerfile => dockerfile::get_version(content)
+                .ok_or_else(|| StepError::InvalidDockerfile(path.into())),
+            PackageFormat::Pkgbuild => pkgbuild::get_version(content)
+                .ok_or_else(|| StepError::InvalidPkgbuild(path.into())),
+            PackageFormat::RpmSpec => {
+                rpm_spec::get_version(content).ok_or_else(|| StepError::InvalidRpmSpec(path.into()))
+            }
+            PackageFormat::Nix => {
+                nix::get_version(content).ok_or_else(|| StepError::InvalidNix(path.into()))
+            }
+            // Without the custom location, there's no way to find the version.
+            PackageFormat::Custom => Err(StepError::VersionedFileFormat(path.into())),
+            PackageFormat::Go => {
+                get_current_versions_from_tag(tag_prefix).map(|current_versions| {
+                    current_versions
+                        .unwrap_or_default()
+                        .into_latest()
+                        .to_string()
+                })
+            }
         }
     }
 
-    /// Consume the `content` and return a version of it which contains `new_version`.
+    /// Whether this format only allows semantic versions (like `1.2.3`), so it can't be written
+    /// with a [`VersionScheme`].
+    fn requires_semver(self) -> bool {
+        matches!(
+            self,
+            PackageFormat::Cargo
+                | PackageFormat::JavaScript
+                | PackageFormat::Deno
+                | PackageFormat::Helm
+                | PackageFormat::Mix
+                | PackageFormat::Pubspec(_)
+        )
+    }
+
+    /// The name that other packages use to depend on the package defined in `content`, if this
+    /// format supports dependencies between packages.
+    pub(crate) fn package_name(self, content: &str) -> Option<String> {
+        match self {
+            PackageFormat::Cargo => cargo::package_name(content),
+            PackageFormat::JavaScript => package_json::package_name(content),
+            PackageFormat::Poetry
+            | PackageFormat::SetupCfg
+            | PackageFormat::SetupPy
+            | PackageFormat::PythonModule
+            | PackageFormat::Vcpkg
+            | PackageFormat::RubyVersion
+            | PackageFormat::Gemspec
+            | PackageFormat::Plain
+            | PackageFormat::Dockerfile
+            | PackageFormat::Pkgbuild
+            | PackageFormat::RpmSpec
+            | PackageFormat::Nix
+            | PackageFormat::Custom
+            | PackageFormat::CMake
+            | PackageFormat::Conan
+            | PackageFormat::Deno
+            | PackageFormat::Go
+            | PackageFormat::Gradle
+            | PackageFormat::GradleProperties
+            | PackageFormat::Helm
+            | PackageFormat::Maven
+            | PackageFormat::Mix
+            | PackageFormat::Pubspec(_) => None,
+        }
+    }
+
+    /// Consume the `content` and return a version of it where every dependency on one of
+    /// `packages` requires `new_version`.
     ///
     /// `path` is only used for error reporting.
-    pub(crate) fn set_version(
+    pub(crate) fn set_dependency_versions(
         self,
         content: String,
+        packages: &[String],
         new_version: &Version,
         path: &Path,
     ) -> Result<String, StepError> {
         match self {
-            PackageFormat::Cargo => cargo::set_version(content, &new_version.to_string())
-                .map_err(|_| InvalidCargoToml(path.into())),
-            PackageFormat::Poetry => pyproject::set_version(content, &new_version.to_string())
-                .map_err(|_| StepError::InvalidPyProject(path.into())),
+            PackageFormat::Cargo => {
+                cargo::set_dependency_versions(content, packages, &new_version.to_string())
+                    .map_err(|_| InvalidCargoToml(path.into()))
+            }
             PackageFormat::JavaScript => {
-                package_json::set_version(&content, &new_version.to_string())
+                package_json::set_dependency_versions(&content, packages, &new_version.to_string())
                     .map_err(|_| StepError::InvalidPackageJson(path.into()))
             }
+            PackageFormat::Poetry
+            | PackageFormat::SetupCfg
+            | PackageFormat::SetupPy
+            | PackageFormat::PythonModule
+            | PackageFormat::Vcpkg
+            | PackageFormat::RubyVersion
+            | PackageFormat::Gemspec
+            | PackageFormat::Plain
+            | PackageFormat::Dockerfile
+            | PackageFormat::Pkgbuild
+            | PackageFormat::RpmSpec
+            | PackageFormat::Nix
+            | PackageFormat::Custom
+            | PackageFormat::CMake
+            | PackageFormat::Conan
+            | PackageFormat::Deno
+            | PackageFormat::Go
+            | PackageFormat::Gradle
+            | PackageFormat::GradleProperties
+            | PackageFormat::Helm
+            | PackageFormat::Maven
+            | PackageFormat::Mix
+            | PackageFormat::Pubspec(_) => Ok(content),
+        }
+    }
+
+    /// Set the version in `content` to `new_version`, which is written as `formatted` by every
+    /// format that doesn't need a semantic version (like `PKGBUILD`, which has its own rules).
+    /// `path` is used for error reporting.
+    pub(crate) fn set_version(
+        self,
+        content: String,
+        new_version: &Version,
+        formatted: &str,
+        path: &Path,
+    ) -> Result<String, StepError> {
+        match self {
+            PackageFormat::Cargo => {
+                cargo::set_version(content, formatted).map_err(|_| InvalidCargoToml(path.into()))
+            }
+            PackageFormat::CMake => cmake::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidCMakeLists(path.into())),
+            PackageFormat::Conan => conan::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidConanfile(path.into())),
+            PackageFormat::Poetry => pyproject::set_version(content, formatted)
+                .map_err(|_| StepError::InvalidPyProject(path.into())),
+            PackageFormat::JavaScript => package_json::set_version(&content, formatted)
+                .map_err(|_| StepError::InvalidPackageJson(path.into())),
+            PackageFormat::Deno => deno::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidDenoJson(path.into())),
+            PackageFormat::Gradle => gradle::set_build_gradle_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidBuildGradle(path.into())),
+            PackageFormat::GradleProperties => {
+                gradle::set_gradle_properties_version(content, formatted)
+                    .ok_or_else(|| StepError::InvalidGradleProperties(path.into()))
+            }
+            PackageFormat::Helm => helm::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidChartYaml(path.into())),
+            PackageFormat::Maven => maven::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidPomXml(path.into())),
+            PackageFormat::Mix => mix::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidMixExs(path.into())),
+            PackageFormat::Pubspec(build_number) => {
+                pubspec::set_version(content, formatted, build_number)
+                    .ok_or_else(|| StepError::InvalidPubspec(path.into()))
+            }
+            PackageFormat::SetupCfg => setuptools::set_setup_cfg_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidSetupCfg(path.into())),
+            PackageFormat::SetupPy => setuptools::set_setup_py_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidSetupPy(path.into())),
+            PackageFormat::PythonModule => python::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidPythonVersion(path.into())),
+            PackageFormat::Vcpkg => vcpkg::set_version(&content, formatted)
+                .ok_or_else(|| StepError::InvalidVcpkgJson(path.into())),
+            PackageFormat::RubyVersion => ruby::set_version_rb_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidVersionRb(path.into())),
+            PackageFormat::Gemspec => ruby::set_gemspec_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidGemspec(path.into())),
+            PackageFormat::Plain => plain::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidVersionFile(path.into())),
+            PackageFormat::Dockerfile => dockerfile::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidDockerfile(path.into())),
+            PackageFormat::Pkgbuild => pkgbuild::set_version(content, new_version)
+                .ok_or_else(|| StepError::InvalidPkgbuild(path.into())),
+            PackageFormat::RpmSpec => rpm_spec::set_version(content, new_version)
+                .ok_or_else(|| StepError::InvalidRpmSpec(path.into())),
+            PackageFormat::Nix => nix::set_version(content, formatted)
+                .ok_or_else(|| StepError::InvalidNix(path.into())),
+            PackageFormat::Custom => Err(StepError::VersionedFileFormat(path.into())),
             PackageFormat::Go => go::set_version(content, new_version),
         }
     }
 }
 
-const ALL_PACKAGE_FORMATS: [PackageFormat; 4] = [
+const ALL_PACKAGE_FORMATS: [PackageFormat; 23] = [
     PackageFormat::Cargo,
+    PackageFormat::Helm,
+    PackageFormat::CMake,
+    PackageFormat::Vcpkg,
+    PackageFormat::Conan,
     PackageFormat::Go,
+    PackageFormat::Gradle,
+    PackageFormat::Gradle,
+    PackageFormat::GradleProperties,
+    PackageFormat::Mix,
     PackageFormat::JavaScript,
+    PackageFormat::Deno,
+    PackageFormat::Deno,
+    PackageFormat::Deno,
     PackageFormat::Poetry,
+    PackageFormat::Pubspec(BuildNumber::Preserve),
+    PackageFormat::Maven,
+    PackageFormat::SetupCfg,
+    PackageFormat::SetupPy,
+    PackageFormat::RubyVersion,
+    PackageFormat::Plain,
+    PackageFormat::Dockerfile,
+    PackageFormat::Pkgbuild,
+];
+pub(super) const PACKAGE_FORMAT_FILE_NAMES: [&str; ALL_PACKAGE_FORMATS.len()] = [
+    "Cargo.toml",
+    "Chart.yaml",
+    "CMakeLists.txt",
+    "vcpkg.json",
+    "conanfile.py",
+    "go.mod",
+    "build.gradle",
+    "build.gradle.kts",
+    "gradle.properties",
+    "mix.exs",
+    "package.json",
+    "deno.json",
+    "deno.jsonc",
+    "jsr.json",
+    "pyproject.toml",
+    "pubspec.yaml",
+    "pom.xml",
+    "setup.cfg",
+    "setup.py",
+    "version.rb",
+    "VERSION",
+    "Dockerfile",
+    "PKGBUILD",
 ];
-const PACKAGE_FORMAT_FILE_NAMES: [&str; ALL_PACKAGE_FORMATS.len()] =
-    ["Cargo.toml", "go.mod", "package.json", "pyproject.toml"];
 
 /// Find all supported package formats in the current directory.
 pub(crate) fn find_packages() -> Option<PackageConfig> {
-    let default = PathBuf::from("CHANGELOG.md");
-    let changelog = if Path::exists(&default) {
-        Some(default)
-    } else {
-        None
-    };
-
+    let changelog = default_changelog();
     let versioned_files = PACKAGE_FORMAT_FILE_NAMES
         .iter()
         .filter_map(|name| {
             let path = PathBuf::from(name);
             if path.exists() {
-                Some(path)
+                Some(VersionedFileConfig::Path(path))
             } else {
                 None
             }
@@ -207,9 +1321,72 @@ pub(crate) fn find_packages() -> Option<PackageConfig> {
         versioned_files,
         changelog,
         scopes: None,
+        changelog_locales: None,
+        changelog_sections: None,
+        changelog_format: None,
+        tag_prefix: None,
+        paths: None,
+        exclude_paths: None,
+        ignore_unscoped: None,
+        cargo_lock: None,
+        build_number: None,
+        metainfo: None,
+        debian_changelog: None,
+        version_scheme: None,
+        prerelease_channels: None,
+        major_version_zero: None,
     })
 }
 
+/// Apply each `--override-version` option in `overrides` to `packages`. An option is either a
+/// version for every package (like `1.2.3`) or a version for the package with a name (like
+/// `knope=1.2.3`), which takes precedence.
+pub(crate) fn set_override_versions(
+    packages: &mut [Package],
+    overrides: &[String],
+) -> Result<(), StepError> {
+    for value in overrides {
+        let (name, version) = match value.split_once('=') {
+            Some((name, version)) => (Some(name), version),
+            None => (None, value.as_str()),
+        };
+        let version = Version::parse(version)
+            .map_err(|_| StepError::InvalidSemanticVersion(version.to_string()))?;
+        if let Some(name) = name {
+            let package = packages
+                .iter_mut()
+                .find(|package| package.name.as_deref() == Some(name))
+                .ok_or_else(|| StepError::UnknownPackage(name.to_string()))?;
+            package.override_version = Some(version);
+        } else {
+            for package in packages.iter_mut() {
+                package
+                    .override_version
+                    .get_or_insert_with(|| version.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A package for a project without any supported package formats (like shell scripts), whose
+/// version comes from Git tags alone. Releases only update the changelog (if any) and add tags.
+pub(crate) fn tag_only_package() -> PackageConfig {
+    PackageConfig {
+        changelog: default_changelog(),
+        ..PackageConfig::default()
+    }
+}
+
+fn default_changelog() -> Option<PathBuf> {
+    let default = PathBuf::from("CHANGELOG.md");
+    if Path::exists(&default) {
+        Some(default)
+    } else {
+        None
+    }
+}
+
 /// Includes some helper text for the user to understand how to use the config to define packages.
 pub(crate) fn suggested_package_toml() -> String {
     let package = find_packages();
@@ -217,7 +1394,7 @@ pub(crate) fn suggested_package_toml() -> String {
         format!(
             "Found the package metadata files {files} in the current directory. You may need to add this \
             to your knope.toml:\n\n```\n[package]\n{toml}```",
-            files = package.versioned_files.iter().map(|path| path.to_str().unwrap())
+            files = package.versioned_files.iter().map(|versioned_file| versioned_file.path().to_str().unwrap())
                 .collect::<Vec<_>>()
                 .join(", "),
             toml = toml::to_string(&package).unwrap()
@@ -226,7 +1403,8 @@ pub(crate) fn suggested_package_toml() -> String {
         format!(
             "No supported package managers found in current directory. \
             The supported formats are {formats}. Here's how you might define a package for `Cargo.toml`:\
-            \n\n```\n[package]\nversioned_files = [\"Cargo.toml\"]\nchangelog = \"CHANGELOG.md\"\n```",
+            \n\n```\n[package]\nversioned_files = [\"Cargo.toml\"]\nchangelog = \"CHANGELOG.md\"\n```\n\n\
+            Or, to version with Git tags alone, set `versioned_files = []`.",
             formats = PACKAGE_FORMAT_FILE_NAMES.join(", ")
         )
     }
@@ -17,6 +17,124 @@ pub(crate) fn set_version(
     serde_json::to_string_pretty(&json)
 }
 
+/// Lockfiles which npm keeps next to `package.json`, which record the version of the package.
+pub(crate) const LOCKFILE_NAMES: [&str; 2] = ["package-lock.json", "npm-shrinkwrap.json"];
+
+/// Set the `version` of the root package in a `package-lock.json` (or `npm-shrinkwrap.json`),
+/// which is recorded at the top level and, in newer lockfiles, in `packages[""]`.
+pub(crate) fn set_lock_version(
+    package_lock: &str,
+    new_version: &str,
+) -> Result<String, serde_json::Error> {
+    let mut json = serde_json::from_str::<Map<String, Value>>(package_lock)?;
+    json.insert(
+        "version".to_string(),
+        Value::String(new_version.to_string()),
+    );
+    if let Some(Value::Object(root)) = json
+        .get_mut("packages")
+        .and_then(|packages| packages.get_mut(""))
+    {
+        root.insert(
+            "version".to_string(),
+            Value::String(new_version.to_string()),
+        );
+    }
+    let mut new_lock = serde_json::to_string_pretty(&json)?;
+    // npm always ends the file with a newline, so keep it to avoid noise in diffs.
+    if package_lock.ends_with('\n') {
+        new_lock.push('\n');
+    }
+    Ok(new_lock)
+}
+
+/// The `dependencies`-like objects which can refer to other packages.
+const DEPENDENCY_TABLES: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// The `name` of this package, if it has one.
+pub(crate) fn package_name(content: &str) -> Option<String> {
+    serde_json::from_str::<Map<String, Value>>(content)
+        .ok()?
+        .get("name")?
+        .as_str()
+        .map(String::from)
+}
+
+/// The name of every package in any of the `dependencies`-like objects, each only once.
+pub(crate) fn dependency_names(content: &str) -> Vec<String> {
+    let json = match serde_json::from_str::<Map<String, Value>>(content) {
+        Ok(json) => json,
+        Err(_) => return Vec::new(),
+    };
+    let mut names = Vec::new();
+    for table in DEPENDENCY_TABLES {
+        if let Some(Value::Object(dependencies)) = json.get(table) {
+            for name in dependencies.keys() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Set the version requirement of every dependency on one of the `packages` to `new_version`,
+/// keeping any `workspace:` protocol and operator (like `^` or `~`) that was there before.
+///
+/// Requirements which aren't a single version (like `*`, `file:../other`, or `1 || 2`) are left
+/// alone. If nothing changes, `package_json` is returned as-is.
+pub(crate) fn set_dependency_versions(
+    package_json: &str,
+    packages: &[String],
+    new_version: &str,
+) -> Result<String, serde_json::Error> {
+    let mut json = serde_json::from_str::<Map<String, Value>>(package_json)?;
+    let mut changed = false;
+    for table in DEPENDENCY_TABLES {
+        if let Some(Value::Object(dependencies)) = json.get_mut(table) {
+            for (name, requirement) in dependencies.iter_mut() {
+                if !packages.contains(name) {
+                    continue;
+                }
+                let new_requirement = requirement
+                    .as_str()
+                    .and_then(|requirement| new_requirement(requirement, new_version));
+                if let Some(new_requirement) = new_requirement {
+                    *requirement = Value::String(new_requirement);
+                    changed = true;
+                }
+            }
+        }
+    }
+    if changed {
+        serde_json::to_string_pretty(&json)
+    } else {
+        Ok(package_json.to_string())
+    }
+}
+
+fn new_requirement(requirement: &str, new_version: &str) -> Option<String> {
+    let (protocol, range) = requirement
+        .strip_prefix("workspace:")
+        .map_or(("", requirement), |range| ("workspace:", range));
+    let single_comparator = semver::VersionReq::parse(range)
+        .map_or(false, |requirement| requirement.comparators.len() == 1);
+    if !single_comparator {
+        return None;
+    }
+    let operator_length = range.find(|c: char| c.is_ascii_digit())?;
+    Some(format!(
+        "{protocol}{}{new_version}",
+        &range[..operator_length]
+    ))
+}
+
 #[derive(Debug, Deserialize)]
 struct Package {
     version: String,
@@ -71,4 +189,83 @@ mod tests {
             .to_string();
         assert_eq!(new, expected);
     }
+
+    #[test]
+    fn test_set_dependency_versions() {
+        let content = r###"{
+        "name": "tester",
+        "version": "1.0.0",
+        "dependencies": {
+            "first": "^1.0.0",
+            "second": "workspace:~1.0",
+            "third": "workspace:*",
+            "fourth": "file:../fourth",
+            "other": "1.0.0"
+        },
+        "devDependencies": {
+            "first": "1.0.0"
+        }
+        }"###;
+
+        let new = set_dependency_versions(
+            content,
+            &[
+                String::from("first"),
+                String::from("second"),
+                String::from("third"),
+                String::from("fourth"),
+            ],
+            "1.1.0",
+        )
+        .unwrap();
+
+        let expected = r###"{
+  "name": "tester",
+  "version": "1.0.0",
+  "dependencies": {
+    "first": "^1.1.0",
+    "second": "workspace:~1.1.0",
+    "third": "workspace:*",
+    "fourth": "file:../fourth",
+    "other": "1.0.0"
+  },
+  "devDependencies": {
+    "first": "1.1.0"
+  }
+}"###;
+        assert_eq!(new, expected);
+        assert_eq!(package_name(content), Some(String::from("tester")));
+    }
+
+    #[test]
+    fn unchanged_dependencies() {
+        let content = r###"{"name": "tester", "dependencies": {"other": "1.0.0"}}"###;
+        let new = set_dependency_versions(content, &[String::from("first")], "1.1.0").unwrap();
+        assert_eq!(new, content);
+    }
+
+    #[test]
+    fn test_set_lock_version() {
+        let content = r###"{
+  "name": "tester",
+  "version": "1.0.0",
+  "lockfileVersion": 3,
+  "requires": true,
+  "packages": {
+    "": {
+      "name": "tester",
+      "version": "1.0.0"
+    },
+    "node_modules/left-pad": {
+      "version": "1.0.0"
+    }
+  }
+}
+"###;
+
+        let new = set_lock_version(content, "1.1.0").unwrap();
+
+        let expected = content.replacen("\"1.0.0\"", "\"1.1.0\"", 2);
+        assert_eq!(new, expected);
+    }
 }
@@ -0,0 +1,95 @@
+use std::ops::Range;
+
+use semver::Version;
+
+/// The placeholder for the version in a pattern, like `version: {version}`.
+pub(crate) const PLACEHOLDER: &str = "{version}";
+
+/// Whether `pattern` has exactly one [`PLACEHOLDER`].
+pub(crate) fn is_valid(pattern: &str) -> bool {
+    pattern.matches(PLACEHOLDER).count() == 1
+}
+
+pub(crate) fn get_version(content: &str, pattern: &str) -> Option<String> {
+    version_span(content, pattern).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, pattern: &str, new_version: &str) -> Option<String> {
+    let span = version_span(&content, pattern)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the version is in the first match of `pattern`, which is text surrounding a
+/// [`PLACEHOLDER`] that matches any semantic version.
+fn version_span(content: &str, pattern: &str) -> Option<Range<usize>> {
+    let (prefix, suffix) = pattern.split_once(PLACEHOLDER)?;
+    content.match_indices(prefix).find_map(|(start, _)| {
+        let version_start = start + prefix.len();
+        let rest = &content[version_start..];
+        let max_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || ['.', '-', '+'].contains(&c)))
+            .unwrap_or(rest.len());
+        // The version could be followed by characters which are also allowed in versions (like the
+        // `.` in `{version}.tar.gz`), so try the longest candidate first.
+        (1..=max_len)
+            .rev()
+            .find(|len| rest[*len..].starts_with(suffix) && Version::parse(&rest[..*len]).is_ok())
+            .map(|len| version_start..version_start + len)
+    })
+}
+
+#[cfg(test)]
+mod test_pattern {
+    use super::*;
+
+    const CONTENT: &str = r#"# My Library
+
+Requires `other-lib` version 4.5.6.
+
+```toml
+my-lib = "1.2.3"
+```
+
+Download [my-lib-1.2.3.tar.gz](https://example.com/my-lib-1.2.3.tar.gz).
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(
+            get_version(CONTENT, "my-lib = \"{version}\""),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "my-lib = \"{version}\"", "2.0.0"),
+            Some(CONTENT.replace("my-lib = \"1.2.3\"", "my-lib = \"2.0.0\""))
+        );
+    }
+
+    #[test]
+    fn suffix_like_a_version() {
+        assert_eq!(
+            get_version(CONTENT, "my-lib-{version}.tar.gz"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn not_a_version() {
+        assert_eq!(
+            get_version("version = \"latest\"\n", "version = \"{version}\""),
+            None
+        );
+    }
+
+    #[test]
+    fn validity() {
+        assert!(is_valid("version {version}"));
+        assert!(!is_valid("version"));
+        assert!(!is_valid("{version} {version}"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::ops::Range;
+
+use semver::Version;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    let version = &content[value_span(content, "pkgver")?];
+    // Hyphens aren't allowed in `pkgver`, so a pre-release is written with an underscore.
+    Some(version.replacen('_', "-", 1))
+}
+
+/// Replace `pkgver` with `new_version` and, since it's the first release of that version, reset
+/// `pkgrel` to 1.
+pub(crate) fn set_version(mut content: String, new_version: &Version) -> Option<String> {
+    let version_span = value_span(&content, "pkgver")?;
+    let release_span = value_span(&content, "pkgrel");
+    let new_version = pkgver(new_version);
+    // Replace the later one first, so that the other is still in the right place.
+    match release_span {
+        Some(release_span) if release_span.start > version_span.start => {
+            content.replace_range(release_span, "1");
+            content.replace_range(version_span, &new_version);
+        }
+        Some(release_span) => {
+            content.replace_range(version_span, &new_version);
+            content.replace_range(release_span, "1");
+        }
+        None => content.replace_range(version_span, &new_version),
+    }
+    Some(content)
+}
+
+/// `version` as an Arch Linux `pkgver`, which can't contain a hyphen (or a `+`). Build metadata
+/// doesn't change which version is newer, so it's left out.
+pub(crate) fn pkgver(version: &Version) -> String {
+    let mut pkgver = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        pkgver.push('_');
+        pkgver.push_str(version.pre.as_str());
+    }
+    pkgver
+}
+
+/// Where the value of a top-level assignment like `pkgver=1.2.3` (or `pkgver='1.2.3'`) is, without
+/// any quotes.
+fn value_span(content: &str, variable: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let value = match line
+            .strip_prefix(variable)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            Some(value) => value,
+            None => continue,
+        };
+        let value_start = start + variable.len() + 1;
+        let (quoted, value_start) = match value.chars().next() {
+            Some(quote @ ('\'' | '"')) => (Some(quote), value_start + 1),
+            _ => (None, value_start),
+        };
+        let value = &content[value_start..line_start];
+        let end = match quoted {
+            Some(quote) => value.find(quote)?,
+            None => value
+                .find(|c: char| c.is_whitespace() || c == '#' || c == ';')
+                .unwrap_or(value.len()),
+        };
+        if end == 0 {
+            return None;
+        }
+        return Some(value_start..value_start + end);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_pkgbuild {
+    use super::*;
+
+    const PKGBUILD: &str = "\
+# Maintainer: Jane Doe <jane@example.com>
+pkgname=my-tool
+pkgver=1.2.3
+pkgrel=4
+pkgdesc=\"A tool\"
+source=(\"https://example.com/my-tool-$pkgver.tar.gz\")
+";
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(PKGBUILD), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(PKGBUILD), &Version::new(1, 3, 0)),
+            Some(
+                PKGBUILD
+                    .replace("pkgver=1.2.3", "pkgver=1.3.0")
+                    .replace("pkgrel=4", "pkgrel=1")
+            )
+        );
+    }
+
+    #[test]
+    fn quoted_pre_release() {
+        let content = String::from("pkgrel='2'\npkgver='1.2.3'\n");
+        let version = Version::parse("2.0.0-rc.0").unwrap();
+        let content = super::set_version(content, &version).unwrap();
+        assert_eq!(content, "pkgrel='1'\npkgver='2.0.0_rc.0'\n");
+        assert_eq!(
+            super::get_version(&content),
+            Some(String::from("2.0.0-rc.0"))
+        );
+    }
+
+    #[test]
+    fn pkgver_function() {
+        let content = "pkgver() {\n  git describe --tags\n}\n";
+        assert_eq!(super::get_version(content), None);
+    }
+}
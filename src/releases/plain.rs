@@ -0,0 +1,44 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the version is in a file which contains nothing else, other than whitespace around it
+/// (like a trailing newline, which is kept).
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let trimmed = content.trim();
+    if trimmed.is_empty() || trimmed.contains(char::is_whitespace) {
+        return None;
+    }
+    let start = content.len() - content.trim_start().len();
+    Some(start..start + trimmed.len())
+}
+
+#[cfg(test)]
+mod test_plain {
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version("1.2.3\n"), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from("1.2.3\n"), "2.0.0"),
+            Some(String::from("2.0.0\n"))
+        );
+    }
+
+    #[test]
+    fn not_only_a_version() {
+        assert_eq!(super::get_version("version 1.2.3\n"), None);
+        assert_eq!(super::get_version("\n"), None);
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::config::{ProjectGraph, ProjectGraphTool};
+use crate::releases::package_json;
+use crate::step::StepError;
+
+/// A project (package) in the project graph of a JS monorepo tool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Project {
+    pub(crate) name: String,
+    /// The directory of the project, relative to the root of the repo.
+    pub(crate) root: PathBuf,
+    /// The names of other projects in the graph which this project depends on.
+    pub(crate) dependencies: Vec<String>,
+}
+
+/// Read every project from the graph of `project_graph.tool`, either from an exported `file` or by
+/// running the tool.
+///
+/// # Errors
+///
+/// [`StepError::ProjectGraph`] if the graph can't be read or parsed.
+pub(crate) fn projects(project_graph: &ProjectGraph) -> Result<Vec<Project>, StepError> {
+    let content = match &project_graph.file {
+        Some(file) => read_to_string(file).map_err(|err| {
+            StepError::ProjectGraph(format!("could not read {}: {err}", file.display()))
+        })?,
+        None => export(project_graph.tool)?,
+    };
+    match project_graph.tool {
+        ProjectGraphTool::Nx => nx_projects(&content),
+        ProjectGraphTool::Turborepo => turborepo_projects(&content, |root| {
+            read_to_string(root.join("package.json")).ok()
+        }),
+    }
+    .map_err(|err| StepError::ProjectGraph(format!("could not parse the graph: {err}")))
+}
+
+/// Ask `tool` for its project graph as JSON.
+fn export(tool: ProjectGraphTool) -> Result<String, StepError> {
+    let args: &[&str] = match tool {
+        ProjectGraphTool::Nx => &["nx", "graph", "--file=stdout"],
+        ProjectGraphTool::Turborepo => &["turbo", "ls", "--output=json"],
+    };
+    let command = format!("npx {}", args.join(" "));
+    let output = Command::new("npx")
+        .args(args)
+        .output()
+        .map_err(|err| StepError::ProjectGraph(format!("could not run `{command}`: {err}")))?;
+    if !output.status.success() {
+        return Err(StepError::ProjectGraph(format!(
+            "`{command}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| StepError::ProjectGraph(format!("`{command}` did not output valid UTF-8")))
+}
+
+/// The output of `nx graph --file=stdout`.
+#[derive(Deserialize)]
+struct NxGraphFile {
+    graph: NxGraph,
+}
+
+#[derive(Deserialize)]
+struct NxGraph {
+    nodes: BTreeMap<String, NxNode>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, Vec<NxDependency>>,
+}
+
+#[derive(Deserialize)]
+struct NxNode {
+    data: NxNodeData,
+}
+
+#[derive(Deserialize)]
+struct NxNodeData {
+    root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct NxDependency {
+    target: String,
+}
+
+fn nx_projects(content: &str) -> Result<Vec<Project>, serde_json::Error> {
+    let NxGraph {
+        nodes,
+        mut dependencies,
+    } = serde_json::from_str::<NxGraphFile>(content)?.graph;
+    Ok(nodes
+        .iter()
+        .map(|(name, node)| Project {
+            name: name.clone(),
+            root: node.data.root.clone(),
+            // External dependencies (like `npm:react`) aren't nodes, so they're left out.
+            dependencies: dependencies
+                .remove(name)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|dependency| dependency.target)
+                .filter(|target| target != name && nodes.contains_key(target))
+                .collect(),
+        })
+        .collect())
+}
+
+/// The output of `turbo ls --output=json`.
+#[derive(Deserialize)]
+struct TurboPackages {
+    packages: TurboPackageList,
+}
+
+#[derive(Deserialize)]
+struct TurboPackageList {
+    items: Vec<TurboPackage>,
+}
+
+#[derive(Deserialize)]
+struct TurboPackage {
+    name: String,
+    path: PathBuf,
+}
+
+/// Turborepo builds its graph from the `package.json` of each package (which
+/// `read_package_json` reads given its directory), so dependencies come from there too.
+fn turborepo_projects(
+    content: &str,
+    read_package_json: impl Fn(&Path) -> Option<String>,
+) -> Result<Vec<Project>, serde_json::Error> {
+    let items = serde_json::from_str::<TurboPackages>(content)?
+        .packages
+        .items
+        .into_iter()
+        // The root of the repo is listed as `//`, but isn't a package of its own.
+        .filter(|item| item.name != "//")
+        .collect::<Vec<_>>();
+    Ok(items
+        .iter()
+        .map(|item| Project {
+            name: item.name.clone(),
+            root: item.path.clone(),
+            dependencies: read_package_json(&item.path)
+                .map(|content| package_json::dependency_names(&content))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|dependency| {
+                    *dependency != item.name && items.iter().any(|other| other.name == *dependency)
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test_project_graph {
+    use super::*;
+
+    #[test]
+    fn nx() {
+        let content = r#"{
+  "graph": {
+    "nodes": {
+      "app": {"name": "app", "type": "app", "data": {"root": "apps/app", "tags": []}},
+      "ui": {"name": "ui", "type": "lib", "data": {"root": "libs/ui"}}
+    },
+    "dependencies": {
+      "app": [
+        {"source": "app", "target": "ui", "type": "static"},
+        {"source": "app", "target": "npm:react", "type": "static"}
+      ],
+      "ui": []
+    }
+  }
+}"#;
+        assert_eq!(
+            nx_projects(content).unwrap(),
+            vec![
+                Project {
+                    name: String::from("app"),
+                    root: PathBuf::from("apps/app"),
+                    dependencies: vec![String::from("ui")],
+                },
+                Project {
+                    name: String::from("ui"),
+                    root: PathBuf::from("libs/ui"),
+                    dependencies: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn turborepo() {
+        let content = r#"{
+  "packageManager": "pnpm",
+  "packages": {
+    "count": 3,
+    "items": [
+      {"name": "//", "path": "."},
+      {"name": "web", "path": "apps/web"},
+      {"name": "@repo/ui", "path": "packages/ui"}
+    ]
+  }
+}"#;
+        let read_package_json = |root: &Path| {
+            (root == Path::new("apps/web")).then(|| {
+                String::from(
+                    r#"{"name": "web", "dependencies": {"@repo/ui": "workspace:*", "react": "^18.0.0"}}"#,
+                )
+            })
+        };
+        assert_eq!(
+            turborepo_projects(content, read_package_json).unwrap(),
+            vec![
+                Project {
+                    name: String::from("web"),
+                    root: PathBuf::from("apps/web"),
+                    dependencies: vec![String::from("@repo/ui")],
+                },
+                Project {
+                    name: String::from("@repo/ui"),
+                    root: PathBuf::from("packages/ui"),
+                    dependencies: Vec::new(),
+                },
+            ]
+        );
+    }
+}
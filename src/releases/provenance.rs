@@ -0,0 +1,161 @@
+use std::env;
+use std::fs::write;
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+
+use crate::ci::Ci;
+use crate::git::get_first_remote;
+use crate::releases::git::{head_commit_id, tag_commit_id, tag_name};
+use crate::releases::{get_current_versions_from_tag, Release};
+use crate::step::StepError;
+
+/// Used as the builder when no supported CI environment is detected.
+const LOCAL_BUILDER_ID: &str = "https://knope-dev.github.io/knope/provenance/local";
+
+/// Write a [SLSA provenance](https://slsa.dev/provenance/v0.2) statement for `release` to the
+/// current directory, returning the path of the file.
+///
+/// The subject is the release tag, the builder is the detected CI environment, and the materials
+/// are the commit range since the last stable release.
+pub(super) fn write_provenance(
+    release: &Release,
+    dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<PathBuf, StepError> {
+    let tag = tag_name(&release.version, &release.tag_prefix);
+    let path = PathBuf::from(format!("{}.intoto.json", tag.replace('/', "-")));
+    if let Some(stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would write provenance statement for {tag} to {}",
+            path.display()
+        )?;
+        return Ok(path);
+    }
+
+    let repo_uri = format!(
+        "git+{}",
+        get_first_remote().unwrap_or_else(|| String::from("."))
+    );
+    let head = head_commit_id()?;
+    let previous_release = get_current_versions_from_tag(&release.tag_prefix)?
+        .map(|current_versions| tag_name(&current_versions.stable, &release.tag_prefix))
+        .filter(|previous_tag| *previous_tag != tag)
+        .and_then(|previous_tag| {
+            // Prefer the commit recorded by `PrepareRelease`, so the materials match the notes.
+            release
+                .commits
+                .from
+                .clone()
+                .or_else(|| tag_commit_id(&previous_tag))
+                .map(|commit| (format!("refs/tags/{previous_tag}"), commit))
+        });
+    let materials = previous_release
+        .into_iter()
+        .chain([(String::from("HEAD"), head.clone())])
+        .map(|(reference, commit)| Material {
+            uri: format!("{repo_uri}@{reference}"),
+            commit,
+        })
+        .collect::<Vec<_>>();
+
+    let statement = statement(
+        &tag,
+        &head,
+        &materials,
+        &builder_id(|key| env::var(key).ok()),
+    );
+    write(&path, format!("{statement:#}\n"))?;
+    Ok(path)
+}
+
+/// Something used to produce a release, identified by a Git URI and commit.
+struct Material {
+    uri: String,
+    commit: String,
+}
+
+/// Build an [in-toto statement](https://in-toto.io/Statement/v0.1) containing the provenance.
+fn statement(tag: &str, commit: &str, materials: &[Material], builder_id: &str) -> Value {
+    json!({
+        "_type": "https://in-toto.io/Statement/v0.1",
+        "subject": [{
+            "name": tag,
+            "digest": { "sha1": commit },
+        }],
+        "predicateType": "https://slsa.dev/provenance/v0.2",
+        "predicate": {
+            "builder": { "id": builder_id },
+            "buildType": "https://knope-dev.github.io/knope/provenance/release",
+            "materials": materials
+                .iter()
+                .map(|material| json!({
+                    "uri": material.uri,
+                    "digest": { "sha1": material.commit },
+                }))
+                .collect::<Vec<_>>(),
+        },
+    })
+}
+
+/// Identify the CI environment running knope from its environment variables.
+fn builder_id(var: impl Fn(&str) -> Option<String>) -> String {
+    Ci::detect(&var)
+        .and_then(|ci| ci.build_url(&var))
+        .unwrap_or_else(|| String::from(LOCAL_BUILDER_ID))
+}
+
+#[cfg(test)]
+mod test_provenance {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn github_actions_builder() {
+        let vars = HashMap::from([
+            ("GITHUB_SERVER_URL", "https://github.com"),
+            ("GITHUB_REPOSITORY", "knope-dev/knope"),
+            ("GITHUB_RUN_ID", "42"),
+            ("CI_JOB_URL", "https://gitlab.com/job"),
+        ]);
+        let builder = builder_id(|key| vars.get(key).map(ToString::to_string));
+        assert_eq!(
+            builder,
+            "https://github.com/knope-dev/knope/actions/runs/42"
+        );
+    }
+
+    #[test]
+    fn local_builder() {
+        assert_eq!(builder_id(|_| None), LOCAL_BUILDER_ID);
+    }
+
+    #[test]
+    fn statement_contents() {
+        let materials = [
+            Material {
+                uri: String::from("git+https://github.com/knope-dev/knope@refs/tags/v1.0.0"),
+                commit: String::from("abc"),
+            },
+            Material {
+                uri: String::from("git+https://github.com/knope-dev/knope@HEAD"),
+                commit: String::from("def"),
+            },
+        ];
+        let statement = statement("v1.1.0", "def", &materials, LOCAL_BUILDER_ID);
+
+        assert_eq!(statement["subject"][0]["name"], "v1.1.0");
+        assert_eq!(statement["subject"][0]["digest"]["sha1"], "def");
+        assert_eq!(statement["predicate"]["builder"]["id"], LOCAL_BUILDER_ID);
+        assert_eq!(
+            statement["predicate"]["materials"][0]["uri"],
+            "git+https://github.com/knope-dev/knope@refs/tags/v1.0.0"
+        );
+        assert_eq!(
+            statement["predicate"]["materials"][1]["digest"]["sha1"],
+            "def"
+        );
+    }
+}
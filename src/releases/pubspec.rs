@@ -0,0 +1,85 @@
+use crate::config::BuildNumber;
+use crate::releases::helm::value_span;
+
+/// Get the version from a `pubspec.yaml`, leaving off any `+buildnumber` which Flutter uses for
+/// the build number of apps.
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    let span = value_span(content, "version")?;
+    let version = &content[span];
+    Some(String::from(
+        version
+            .split_once('+')
+            .map_or(version, |(version, _)| version),
+    ))
+}
+
+/// Set the `version` in `pubspec.yaml` to `new_version`, handling any existing build number
+/// according to `build_number`.
+pub(crate) fn set_version(
+    mut content: String,
+    new_version: &str,
+    build_number: BuildNumber,
+) -> Option<String> {
+    let span = value_span(&content, "version")?;
+    let existing_build = content[span.clone()]
+        .split_once('+')
+        .map(|(_, build)| build.to_string());
+    let build = match (build_number, existing_build) {
+        (BuildNumber::Preserve, build) => build,
+        (BuildNumber::Increment, Some(build)) => Some(
+            build
+                .parse::<u64>()
+                .ok()
+                .map_or(build, |number| (number + 1).to_string()),
+        ),
+        (BuildNumber::Increment, None) => Some(String::from("1")),
+    };
+    let new_value = match build {
+        Some(build) => format!("{new_version}+{build}"),
+        None => new_version.to_string(),
+    };
+    content.replace_range(span, &new_value);
+    Some(content)
+}
+
+#[cfg(test)]
+mod test_pubspec {
+    use super::*;
+
+    const CONTENT: &str = r#"name: my_app
+description: A Flutter app
+version: 1.2.3+7 # Build number for the stores
+environment:
+  sdk: ">=2.17.0 <3.0.0"
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn preserve_build_number() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "1.3.0", BuildNumber::Preserve),
+            Some(CONTENT.replace("1.2.3+7", "1.3.0+7"))
+        );
+    }
+
+    #[test]
+    fn increment_build_number() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "1.3.0", BuildNumber::Increment),
+            Some(CONTENT.replace("1.2.3+7", "1.3.0+8"))
+        );
+    }
+
+    #[test]
+    fn no_build_number() {
+        let content = CONTENT.replace("1.2.3+7", "1.2.3");
+        assert_eq!(
+            set_version(content.clone(), "1.3.0", BuildNumber::Preserve),
+            Some(content.replace("1.2.3", "1.3.0"))
+        );
+    }
+}
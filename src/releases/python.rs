@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string assigned to `__version__` (like `__version__ = "1.2.3"`) at the top level of
+/// a Python module is.
+fn version_span(content: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let rest = match line.strip_prefix("__version__") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        // Allow a type annotation, like `__version__: str = "1.2.3"`
+        let rest = match rest.split_once('=') {
+            Some((annotation, rest))
+                if annotation.trim().is_empty() || annotation.trim_start().starts_with(':') =>
+            {
+                rest.trim_start()
+            }
+            _ => continue,
+        };
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let value_start = start + line.len() - rest.len() + 1;
+        let value_len = content[value_start..].find(quote)?;
+        return Some(value_start..value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_version_span {
+    use super::*;
+
+    const CONTENT: &str = r#""""My package."""
+
+from mypkg.core import run
+
+__version__ = "1.2.3"
+__version_info__ = (1, 2, 3)
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("\"1.2.3\"", "\"2.0.0\""))
+        );
+    }
+
+    #[test]
+    fn annotated() {
+        assert_eq!(
+            get_version("__version__: str = '1.2.3'\n"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn not_a_string() {
+        assert_eq!(get_version("__version__ = get_version()\n"), None);
+    }
+}
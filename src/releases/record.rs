@@ -0,0 +1,131 @@
+use std::fs::{read_to_string, write};
+use std::io::Write;
+use std::path::Path;
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::releases::{BumpLevel, CommitRange, Release};
+use crate::step::StepError;
+
+/// The contents of a file written by [`crate::step::Step::PrepareRelease`] with `record` set.
+#[derive(Debug, Deserialize, Serialize)]
+struct Record {
+    releases: Vec<RecordedRelease>,
+}
+
+/// A [`Release`] as it's recorded, so that a later [`crate::step::Step::Release`] (possibly on
+/// another machine) uses the same details and commits, even if HEAD has moved.
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordedRelease {
+    package_name: Option<String>,
+    version: String,
+    previous_version: String,
+    bump_level: BumpLevel,
+    tag_prefix: String,
+    changelog: String,
+    commits: CommitRange,
+}
+
+impl From<&Release> for RecordedRelease {
+    fn from(release: &Release) -> Self {
+        Self {
+            package_name: release.package_name.clone(),
+            version: release.version.to_string(),
+            previous_version: release.previous_version.to_string(),
+            bump_level: release.bump_level,
+            tag_prefix: release.tag_prefix.clone(),
+            changelog: release.changelog.clone(),
+            commits: release.commits.clone(),
+        }
+    }
+}
+
+impl TryFrom<RecordedRelease> for Release {
+    type Error = StepError;
+
+    fn try_from(recorded: RecordedRelease) -> Result<Self, Self::Error> {
+        let parse = |version: &str| {
+            Version::parse(version)
+                .map_err(|_| StepError::InvalidSemanticVersion(version.to_string()))
+        };
+        Ok(Self {
+            version: parse(&recorded.version)?,
+            previous_version: parse(&recorded.previous_version)?,
+            bump_level: recorded.bump_level,
+            changelog: recorded.changelog,
+            package_name: recorded.package_name,
+            tag_prefix: recorded.tag_prefix,
+            commits: recorded.commits,
+        })
+    }
+}
+
+/// Write `releases` to the file at `path`, to be read later by [`read_record`].
+pub(crate) fn write_record(
+    path: &Path,
+    releases: &[&Release],
+    dry_run_stdout: Option<&mut Box<dyn Write>>,
+) -> Result<(), StepError> {
+    if let Some(stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would record {count} prepared releases in {path}",
+            count = releases.len(),
+            path = path.display()
+        )?;
+        return Ok(());
+    }
+    let record = Record {
+        releases: releases.iter().map(|release| (*release).into()).collect(),
+    };
+    let contents = serde_json::to_string_pretty(&record)
+        .map_err(|_| StepError::InvalidRecord(path.to_path_buf()))?;
+    write(path, format!("{contents}\n"))?;
+    Ok(())
+}
+
+/// Read the releases that [`write_record`] wrote to `path`.
+pub(crate) fn read_record(path: &Path) -> Result<Vec<Release>, StepError> {
+    if !path.exists() {
+        return Err(StepError::FileNotFound(path.to_path_buf()));
+    }
+    let record: Record = serde_json::from_str(&read_to_string(path)?)
+        .map_err(|_| StepError::InvalidRecord(path.to_path_buf()))?;
+    record.releases.into_iter().map(Release::try_from).collect()
+}
+
+#[cfg(test)]
+mod test_record {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let release = Release {
+            version: Version::new(1, 3, 0),
+            previous_version: Version::new(1, 2, 3),
+            bump_level: BumpLevel::Minor,
+            changelog: String::from("## 1.3.0\n\n### Features\n\n- New feature\n"),
+            package_name: Some(String::from("knope")),
+            tag_prefix: String::from("knope/v"),
+            commits: CommitRange {
+                from: Some(String::from("abc")),
+                to: String::from("def"),
+            },
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("release.json");
+
+        write_record(&path, &[&release], None).unwrap();
+        let read = read_record(&path).unwrap();
+
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].version, release.version);
+        assert_eq!(read[0].previous_version, release.previous_version);
+        assert_eq!(read[0].bump_level, release.bump_level);
+        assert_eq!(read[0].changelog, release.changelog);
+        assert_eq!(read[0].package_name, release.package_name);
+        assert_eq!(read[0].tag_prefix, release.tag_prefix);
+        assert_eq!(read[0].commits, release.commits);
+    }
+}
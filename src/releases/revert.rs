@@ -0,0 +1,229 @@
+use git_conventional::Commit;
+
+use crate::git::CommitMessage;
+
+/// Leave out every commit which was reverted before being released, along with the commit which
+/// reverted it, so the changelog doesn't list changes which aren't there anymore.
+///
+/// Reverts of commits which were already released are kept, as `revert: <description>` commits,
+/// so they get their own changelog entry. `is_listed` tells whether a commit type would be in the
+/// changelog—Git-style reverts (`Revert "..."`) of anything else (like the commit of a release)
+/// are left out entirely.
+///
+/// `commit_messages` must be newest first (like `git log`), so that reverting a revert brings back
+/// the original commit.
+pub(super) fn without_reverted(
+    commit_messages: &[CommitMessage],
+    is_listed: impl Fn(&Commit) -> bool,
+) -> Vec<CommitMessage> {
+    let mut kept = commit_messages.iter().map(Some).collect::<Vec<_>>();
+    let mut rewritten = Vec::new();
+    for index in 0..kept.len() {
+        let commit_message = match kept[index] {
+            Some(commit_message) => commit_message,
+            None => continue,
+        };
+        let revert = match Revert::parse(&commit_message.message) {
+            Some(revert) => revert,
+            None => continue,
+        };
+        let target = kept.iter().enumerate().position(|(other_index, other)| {
+            other_index != index && other.map_or(false, |other| revert.targets(other))
+        });
+        if let Some(target) = target {
+            // Reverted before it was released, so neither is a change.
+            kept[index] = None;
+            kept[target] = None;
+            continue;
+        }
+        kept[index] = None;
+        if let Some(message) = revert.changelog_message(&is_listed) {
+            rewritten.push((
+                index,
+                CommitMessage {
+                    id: commit_message.id.clone(),
+                    message,
+                },
+            ));
+        }
+    }
+    let mut commit_messages = kept
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, commit_message)| Some((index, commit_message?.clone())))
+        .chain(rewritten)
+        .collect::<Vec<_>>();
+    commit_messages.sort_by_key(|(index, _)| *index);
+    commit_messages
+        .into_iter()
+        .map(|(_, commit_message)| commit_message)
+        .collect()
+}
+
+/// A commit which reverts another one.
+#[derive(Debug, Eq, PartialEq)]
+struct Revert<'a> {
+    /// The full message of the revert itself.
+    message: &'a str,
+    /// The ID (or a prefix of it) of the reverted commit, if known.
+    id: Option<&'a str>,
+    /// The summary (first line) of the reverted commit, if known.
+    summary: Option<&'a str>,
+}
+
+impl<'a> Revert<'a> {
+    /// Parse a revert created by `git revert` (`Revert "<summary>"`, followed by
+    /// `This reverts commit <id>.`), or a conventional commit with the `revert` type, which may
+    /// refer to the reverted commit in a `Refs` footer.
+    fn parse(message: &'a str) -> Option<Self> {
+        let message = message.trim();
+        let first_line = message.lines().next().unwrap_or_default();
+        let summary = if let Some(quoted) = first_line.strip_prefix("Revert \"") {
+            Some(quoted.strip_suffix('"').unwrap_or(quoted))
+        } else if Commit::parse(message).map_or(false, |commit| {
+            commit.type_().as_str().eq_ignore_ascii_case("revert")
+        }) {
+            None
+        } else {
+            return None;
+        };
+        let id = message.lines().find_map(|line| {
+            let line = line.trim();
+            let rest = line
+                .strip_prefix("This reverts commit ")
+                .or_else(|| line.strip_prefix("Refs:"))?;
+            // The first (abbreviated) commit ID, which is at least 7 characters long.
+            rest.split(|c: char| !c.is_ascii_hexdigit())
+                .find(|id| !id.is_empty())
+                .filter(|id| id.len() >= 7)
+        });
+        Some(Self {
+            message,
+            id,
+            summary,
+        })
+    }
+
+    /// Whether `commit_message` is the commit which was reverted.
+    fn targets(&self, commit_message: &CommitMessage) -> bool {
+        if let Some(id) = self.id {
+            return commit_message.id.starts_with(id);
+        }
+        self.summary.map_or(false, |summary| {
+            commit_message.message.trim().lines().next() == Some(summary)
+        })
+    }
+
+    /// The message to use for this revert in the changelog, if it should be listed.
+    fn changelog_message(&self, is_listed: impl Fn(&Commit) -> bool) -> Option<String> {
+        let summary = match self.summary {
+            // Already a conventional commit.
+            None => return Some(self.message.to_string()),
+            Some(summary) => summary,
+        };
+        let reverted = Commit::parse(summary).ok()?;
+        if !(reverted.breaking() || is_listed(&reverted)) {
+            return None;
+        }
+        let scope = reverted
+            .scope()
+            .map_or_else(String::new, |scope| format!("({scope})"));
+        Some(format!("revert{scope}: {}", reverted.description()))
+    }
+}
+
+#[cfg(test)]
+mod test_without_reverted {
+    use git_conventional::Type;
+
+    use super::*;
+
+    fn commit_message(id: &str, message: &str) -> CommitMessage {
+        CommitMessage {
+            id: String::from(id),
+            message: String::from(message),
+        }
+    }
+
+    fn messages(commit_messages: &[CommitMessage]) -> Vec<&str> {
+        commit_messages
+            .iter()
+            .map(|commit_message| commit_message.message.as_str())
+            .collect()
+    }
+
+    fn is_listed(commit: &Commit) -> bool {
+        commit.type_() == Type::FEAT || commit.type_() == Type::FIX
+    }
+
+    #[test]
+    fn reverted_before_release() {
+        let commit_messages = [
+            commit_message(
+                "3333333333",
+                "Revert \"feat: Add a thing\"\n\nThis reverts commit 1111111111.",
+            ),
+            commit_message("2222222222", "fix: A fix"),
+            commit_message("1111111111", "feat: Add a thing"),
+        ];
+        assert_eq!(
+            messages(&without_reverted(&commit_messages, is_listed)),
+            vec!["fix: A fix"]
+        );
+    }
+
+    #[test]
+    fn conventional_revert_by_reference() {
+        let commit_messages = [
+            commit_message(
+                "3333333333",
+                "revert: Never mind the thing\n\nRefs: 1111111",
+            ),
+            commit_message("1111111111", "feat: Add a thing"),
+        ];
+        assert!(without_reverted(&commit_messages, is_listed).is_empty());
+    }
+
+    #[test]
+    fn reverted_revert() {
+        let commit_messages = [
+            commit_message(
+                "3333333333",
+                "Revert \"Revert \"feat: Add a thing\"\"\n\nThis reverts commit 2222222222.",
+            ),
+            commit_message(
+                "2222222222",
+                "Revert \"feat: Add a thing\"\n\nThis reverts commit 1111111111.",
+            ),
+            commit_message("1111111111", "feat: Add a thing"),
+        ];
+        assert_eq!(
+            messages(&without_reverted(&commit_messages, is_listed)),
+            vec!["feat: Add a thing"]
+        );
+    }
+
+    #[test]
+    fn reverted_after_release() {
+        let commit_messages = [
+            commit_message(
+                "3333333333",
+                "Revert \"feat(cli): Add a thing\"\n\nThis reverts commit 1111111111.",
+            ),
+            commit_message("2222222222", "fix: A fix"),
+        ];
+        assert_eq!(
+            messages(&without_reverted(&commit_messages, is_listed)),
+            vec!["revert(cli): Add a thing", "fix: A fix"]
+        );
+    }
+
+    #[test]
+    fn unlisted_revert() {
+        let commit_messages = [commit_message(
+            "3333333333",
+            "Revert \"chore: Release\"\n\nThis reverts commit 1111111111.",
+        )];
+        assert!(without_reverted(&commit_messages, is_listed).is_empty());
+    }
+}
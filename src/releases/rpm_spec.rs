@@ -0,0 +1,248 @@
+use std::ops::Range;
+
+use semver::Version;
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    let version = &content[tag_span(content, "Version")?];
+    if version.contains('%') {
+        // Set by a macro, which can't be bumped.
+        return None;
+    }
+    // A hyphen isn't allowed in `Version`, so a pre-release is written with a tilde.
+    Some(version.replacen('~', "-", 1))
+}
+
+/// Replace the `Version` with `new_version` and, since it's the first build of that version,
+/// reset the number at the start of `Release` (like `3%{?dist}`) to 1.
+pub(crate) fn set_version(mut content: String, new_version: &Version) -> Option<String> {
+    let version_span = tag_span(&content, "Version")?;
+    let release_span = tag_span(&content, "Release").and_then(|span| {
+        let digits = content[span.clone()]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .count();
+        (digits > 0).then(|| span.start..span.start + digits)
+    });
+    let new_version = rpm_version(new_version);
+    // Replace the later one first, so that the other is still in the right place.
+    match release_span {
+        Some(release_span) if release_span.start > version_span.start => {
+            content.replace_range(release_span, "1");
+            content.replace_range(version_span, &new_version);
+        }
+        Some(release_span) => {
+            content.replace_range(version_span, &new_version);
+            content.replace_range(release_span, "1");
+        }
+        None => content.replace_range(version_span, &new_version),
+    }
+    Some(content)
+}
+
+/// Create a `%changelog` entry for `version` (released on `date`, like `Thu Jun 01 2023`) with an
+/// item for every note in `new_changes` (the lines of the new Keep a Changelog entry).
+///
+/// The packager is the one of the latest entry, or else the `Packager` tag. `None` means there's
+/// neither.
+pub(crate) fn changelog_entry(
+    content: &str,
+    version: &Version,
+    date: &str,
+    new_changes: &[String],
+) -> Option<String> {
+    let packager = latest_packager(content)
+        .or_else(|| tag_span(content, "Packager").map(|span| content[span].trim().to_string()))?;
+    let mut items = new_changes
+        .iter()
+        .skip(1)
+        .flat_map(|block| block.lines())
+        .filter(|line| line.starts_with("- ") || line.starts_with("  "))
+        .map(str::trim_end)
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        items.push("- New release");
+    }
+    Some(format!(
+        "* {date} {packager} - {version}-1\n{items}\n",
+        version = rpm_version(version),
+        items = items.join("\n"),
+    ))
+}
+
+/// Add `entry` to the top of the `%changelog` section, which is created at the end if needed.
+pub(crate) fn add_changelog_entry(content: &str, entry: &str) -> String {
+    let section = content
+        .match_indices("%changelog")
+        .find(|(start, _)| *start == 0 || content[..*start].ends_with('\n'));
+    if let Some((start, _)) = section {
+        match content[start..].find('\n') {
+            Some(newline) => {
+                let (before, existing) = content.split_at(start + newline + 1);
+                let separator = if existing.trim().is_empty() { "" } else { "\n" };
+                format!("{before}{entry}{separator}{existing}")
+            }
+            None => format!("{content}\n{entry}"),
+        }
+    } else {
+        let newline = if content.ends_with('\n') { "" } else { "\n" };
+        format!("{content}{newline}\n%changelog\n{entry}")
+    }
+}
+
+/// `version` as an RPM `Version`, which can't contain a hyphen. A tilde sorts before anything
+/// else, so `1.2.0~rc.0` is still lower than `1.2.0`. Build metadata doesn't change which version
+/// is newer, so it's left out.
+fn rpm_version(version: &Version) -> String {
+    let mut rpm_version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+    if !version.pre.is_empty() {
+        rpm_version.push('~');
+        rpm_version.push_str(version.pre.as_str());
+    }
+    rpm_version
+}
+
+/// The packager from the header of the latest `%changelog` entry, like
+/// `* Thu Jun 01 2023 Jane Doe <jane@example.com> - 1.2.3-1`.
+fn latest_packager(content: &str) -> Option<String> {
+    let header = content
+        .lines()
+        .skip_while(|line| !line.starts_with("%changelog"))
+        .find(|line| line.starts_with("* "))?;
+    // Skip the `*` and the four parts of the date.
+    let rest = header.splitn(6, ' ').nth(5)?;
+    let packager = rest
+        .rsplit_once(" - ")
+        .map_or(rest, |(packager, _)| packager);
+    let packager = packager.trim();
+    (!packager.is_empty()).then(|| packager.to_string())
+}
+
+/// Where the value of a preamble tag like `Version: 1.2.3` is. Tag names aren't case-sensitive.
+fn tag_span(content: &str, tag: &str) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let (name, value) = match line.split_once(':') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        if !name.eq_ignore_ascii_case(tag) {
+            continue;
+        }
+        let value_start = start + name.len() + 1 + (value.len() - value.trim_start().len());
+        let value_end = start + line.trim_end().len();
+        if value_end <= value_start {
+            return None;
+        }
+        return Some(value_start..value_end);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_rpm_spec {
+    use super::*;
+
+    const SPEC: &str = "\
+Name:           my-tool
+Version:        1.2.3
+Release:        4%{?dist}
+Summary:        A tool
+
+%description
+A tool.
+
+%changelog
+* Sun Jan 01 2023 Jane Doe <jane@example.com> - 1.2.3-4
+- Initial release
+";
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(SPEC), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(String::from(SPEC), &Version::new(1, 3, 0)),
+            Some(
+                SPEC.replace("Version:        1.2.3", "Version:        1.3.0")
+                    .replace("Release:        4%{?dist}", "Release:        1%{?dist}")
+            )
+        );
+    }
+
+    #[test]
+    fn pre_release() {
+        let version = Version::parse("2.0.0-rc.0").unwrap();
+        let content = super::set_version(String::from(SPEC), &version).unwrap();
+        assert!(content.contains("Version:        2.0.0~rc.0\n"));
+        assert_eq!(
+            super::get_version(&content),
+            Some(String::from("2.0.0-rc.0"))
+        );
+    }
+
+    #[test]
+    fn version_macro() {
+        assert_eq!(super::get_version("Version: %{upstream}\n"), None);
+    }
+
+    #[test]
+    fn add_entry() {
+        let new_changes = [
+            String::from("## 1.3.0\n"),
+            String::from("### Features\n"),
+            String::from("- A new feature"),
+            String::from(""),
+            String::from("### Fixes\n"),
+            String::from("- A fix which was wrapped onto\n  two lines"),
+            String::from(""),
+        ];
+        let entry = changelog_entry(
+            SPEC,
+            &Version::new(1, 3, 0),
+            "Thu Jun 01 2023",
+            &new_changes,
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            "* Thu Jun 01 2023 Jane Doe <jane@example.com> - 1.3.0-1\n- A new feature\n- A fix which was wrapped onto\n  two lines\n"
+        );
+        assert_eq!(
+            add_changelog_entry(SPEC, &entry),
+            SPEC.replace("%changelog\n", &format!("%changelog\n{entry}\n"))
+        );
+    }
+
+    #[test]
+    fn packager_tag() {
+        let content = "Version: 1.0.0\nPackager: Release Bot <bot@example.com>\n";
+        let entry = changelog_entry(
+            content,
+            &Version::new(1, 0, 1),
+            "Thu Jun 01 2023",
+            &[String::from("## 1.0.1\n")],
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            "* Thu Jun 01 2023 Release Bot <bot@example.com> - 1.0.1-1\n- New release\n"
+        );
+        assert_eq!(
+            add_changelog_entry(content, &entry),
+            format!("{content}\n%changelog\n{entry}")
+        );
+    }
+
+    #[test]
+    fn no_packager() {
+        assert_eq!(
+            changelog_entry("Version: 1.0.0\n", &Version::new(1, 0, 1), "", &[]),
+            None
+        );
+    }
+}
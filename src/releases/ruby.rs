@@ -0,0 +1,123 @@
+use std::ops::Range;
+
+pub(crate) fn get_version_rb_version(content: &str) -> Option<String> {
+    version_rb_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version_rb_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = version_rb_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+pub(crate) fn get_gemspec_version(content: &str) -> Option<String> {
+    gemspec_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_gemspec_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = gemspec_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string assigned to the `VERSION` constant (like `VERSION = "1.2.3"`) is.
+fn version_rb_span(content: &str) -> Option<Range<usize>> {
+    assigned_string_span(content, |target| target == "VERSION")
+}
+
+/// Where the string assigned to the `version` of the specification (like
+/// `spec.version = "1.2.3"`) is. Versions read from somewhere else (like
+/// `spec.version = MyGem::VERSION`) aren't strings, so they're not found.
+fn gemspec_span(content: &str) -> Option<Range<usize>> {
+    assigned_string_span(content, |target| {
+        target
+            .split_once('.')
+            .map_or(false, |(receiver, attribute)| {
+                attribute == "version"
+                    && !receiver.is_empty()
+                    && receiver.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+    })
+}
+
+/// Where the first string literal assigned to a target matching `is_target` is.
+fn assigned_string_span(content: &str, is_target: impl Fn(&str) -> bool) -> Option<Range<usize>> {
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let (target, value) = match line.split_once('=') {
+            Some(parts) => parts,
+            None => continue,
+        };
+        // Comparisons (`==`) and pattern matches (`=~`) aren't assignments.
+        if !is_target(target.trim()) || value.starts_with(['=', '~']) {
+            continue;
+        }
+        let value = value.trim_start();
+        let quote = match value.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let value_start = start + line.len() - value.len() + 1;
+        let value_len = content[value_start..line_start].find(quote)?;
+        return Some(value_start..value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_version_rb {
+    use super::*;
+
+    const CONTENT: &str = r#"# frozen_string_literal: true
+
+module MyGem
+  VERSION = "1.2.3".freeze
+end
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(get_version_rb_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            set_version_rb_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_gemspec {
+    use super::*;
+
+    const CONTENT: &str = r#"Gem::Specification.new do |spec|
+  spec.name = "my_gem"
+  spec.version = '1.2.3'
+  spec.required_ruby_version = ">= 2.7"
+end
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(get_gemspec_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            set_gemspec_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn version_from_constant() {
+        let content = "Gem::Specification.new do |s|\n  s.version = MyGem::VERSION\nend\n";
+        assert_eq!(get_gemspec_version(content), None);
+    }
+}
@@ -1,11 +1,18 @@
-use semver::{Prerelease, Version};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+use itertools::Itertools;
+use semver::{BuildMetadata, Prerelease, Version};
 use serde::{Deserialize, Serialize};
 
+use crate::config::{MajorVersionZero, Versioning};
 use crate::git::add_files;
 use crate::releases::git::get_current_versions_from_tag;
-use crate::releases::package::Package;
+use crate::releases::package::{set_internal_dependency_versions, Package};
 use crate::releases::CurrentVersions;
 use crate::step::StepError;
+use crate::template::render_expressions;
 use crate::{state, RunType};
 
 /// The various rules that can be used when bumping the current version of a project via
@@ -20,8 +27,25 @@ pub(crate) enum Rule {
         label: String,
         #[serde(skip)]
         stable_rule: ConventionalRule,
+        /// The counter used for the first pre-release of a new version, e.g., `rc.0`.
+        #[serde(default, skip_serializing_if = "is_zero")]
+        start: u64,
     },
     Release,
+    /// Move the current pre-release to the next of the package's `prerelease_channels` (e.g.,
+    /// from `beta` to `rc`), or to a stable release from the last one.
+    Promote,
+    /// Set the build metadata (e.g., `+42`) of the version, or increment it if it's a number.
+    Build {
+        /// The new build metadata, which can contain template expressions like `{{ commit() }}`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        metadata: Option<String>,
+    },
+    /// Set the version to exactly `version`, even if it's lower than the current one.
+    Set {
+        /// The new version, like `1.2.3` or `2.0.0-rc.1`.
+        version: String,
+    },
 }
 
 impl From<ConventionalRule> for Rule {
@@ -34,17 +58,72 @@ impl From<ConventionalRule> for Rule {
     }
 }
 
-/// The rules that can be derived from Conventional Commits.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(clippy::trivially_copy_pass_by_ref)] // Required by serde
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// The rules that can be derived from Conventional Commits, ordered from smallest to largest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) enum ConventionalRule {
+    #[default]
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Display for ConventionalRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rule = match self {
+            ConventionalRule::Major => "major",
+            ConventionalRule::Minor => "minor",
+            ConventionalRule::Patch => "patch",
+        };
+        f.write_str(rule)
+    }
+}
+
+/// How much a version changed when it was bumped, compared to the last stable version.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub(crate) enum BumpLevel {
     Major,
     Minor,
     Patch,
+    /// The new version is a pre-release.
+    Pre,
+    /// Only the build metadata of the version changed.
+    Build,
 }
 
-impl Default for ConventionalRule {
-    fn default() -> Self {
-        ConventionalRule::Patch
+impl BumpLevel {
+    /// Determine the level of the bump from `previous` to `new`.
+    fn between(previous: &CurrentVersions, new: &Version) -> Self {
+        let stable = &previous.stable;
+        let latest = previous.latest();
+        if base(new) == base(latest) && new.pre == latest.pre && new.build != latest.build {
+            BumpLevel::Build
+        } else if !new.pre.is_empty() {
+            BumpLevel::Pre
+        } else if new.major != stable.major {
+            BumpLevel::Major
+        } else if new.minor != stable.minor {
+            BumpLevel::Minor
+        } else {
+            BumpLevel::Patch
+        }
+    }
+}
+
+impl Display for BumpLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let level = match self {
+            BumpLevel::Major => "major",
+            BumpLevel::Minor => "minor",
+            BumpLevel::Patch => "patch",
+            BumpLevel::Pre => "pre",
+            BumpLevel::Build => "build",
+        };
+        f.write_str(level)
     }
 }
 
@@ -62,15 +141,40 @@ impl PackageVersion {
     }
 }
 
-/// Bump the version of a single `package` using `rule`.
+/// Bump the version of a single `package` using `rule`, or to its `override_version` if it has
+/// one.
+///
+/// Returns the new version along with the version it was bumped from and how big the bump was.
 pub(super) fn bump_version(
     rule: &Rule,
     dry_run: bool,
     package: Package,
-) -> Result<PackageVersion, StepError> {
+) -> Result<(PackageVersion, Version, BumpLevel), StepError> {
     let mut package_version = get_version(package)?;
-    package_version.version = bump(package_version.version, rule)?;
-    set_version(package_version, dry_run)
+    let previous = package_version.version.clone();
+    package_version.version = match package_version.package.override_version.clone() {
+        Some(version) if version.pre.is_empty() => CurrentVersions {
+            stable: version,
+            prerelease: None,
+        },
+        Some(version) => CurrentVersions {
+            stable: previous.stable.clone(),
+            prerelease: Some(version),
+        },
+        None => {
+            let major_version_zero = package_version.package.major_version_zero;
+            let rule = in_channels(
+                rule,
+                &package_version.package.prerelease_channels,
+                &package_version.version,
+                major_version_zero,
+            )?;
+            bump(package_version.version, &rule, major_version_zero)?
+        }
+    };
+    let bump_level = BumpLevel::between(&previous, package_version.latest_version());
+    let package_version = set_version(package_version, dry_run)?;
+    Ok((package_version, previous.into_latest(), bump_level))
 }
 
 /// The implementation of [`crate::step::Step::BumpVersion`].
@@ -85,8 +189,13 @@ pub(crate) fn bump_version_and_update_state(
         RunType::Real(state) => (None, state),
     };
 
+    ensure_consistent_versions(&state.packages)?;
+    if state.versioning == Versioning::Fixed {
+        ensure_same_versions(&state.packages)?;
+    }
+    let previous_releases = state.releases.len();
     for package in state.packages.iter().cloned() {
-        let PackageVersion { package, version } =
+        let (PackageVersion { package, version }, previous_version, bump_level) =
             bump_version(rule, dry_run_stdout.is_some(), package)?;
         if let Some(stdout) = dry_run_stdout.as_mut() {
             writeln!(
@@ -98,9 +207,16 @@ pub(crate) fn bump_version_and_update_state(
         }
         state.releases.push(state::Release::Bumped {
             version: version.into_latest(),
+            previous_version,
+            bump_level,
             package_name: package.name.clone(),
         });
     }
+    set_internal_dependency_versions(
+        &state.packages,
+        &state.releases[previous_releases..],
+        dry_run_stdout.as_mut(),
+    )?;
     if let Some(stdout) = dry_run_stdout {
         Ok(RunType::DryRun { state, stdout })
     } else {
@@ -108,41 +224,107 @@ pub(crate) fn bump_version_and_update_state(
     }
 }
 
-/// Get the current version of a package.
-pub(crate) fn get_version(package: Package) -> Result<PackageVersion, StepError> {
-    let stable_version = package
+/// Make sure every package in `packages` has the same current version, as required by fixed
+/// versioning.
+pub(crate) fn ensure_same_versions(packages: &[Package]) -> Result<(), StepError> {
+    let mut expected: Option<(Option<String>, Version)> = None;
+    for package in packages {
+        let version = get_version(package.clone())?.version.into_latest();
+        match &expected {
+            None => expected = Some((package.name.clone(), version)),
+            Some((expected_name, expected_version)) if *expected_version != version => {
+                return Err(StepError::InconsistentPackageVersions {
+                    first: expected_name.clone().unwrap_or_default(),
+                    first_version: expected_version.to_string(),
+                    second: package.name.clone().unwrap_or_default(),
+                    second_version: version.to_string(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Make sure that the versioned files of every package agree on its version before any of them are
+/// bumped, so that a mismatch in one package doesn't leave others half-released.
+///
+/// # Errors
+///
+/// [`StepError::InconsistentVersions`] lists the version in every file of every package whose
+/// files don't agree.
+pub(crate) fn ensure_consistent_versions(packages: &[Package]) -> Result<(), StepError> {
+    let mut mismatches = Vec::new();
+    for package in packages {
+        if let Some(mismatch) = version_mismatch(&file_versions(package)?) {
+            mismatches.push(match &package.name {
+                Some(name) => format!("{name} ({mismatch})"),
+                None => mismatch,
+            });
+        }
+    }
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(StepError::InconsistentVersions(mismatches.join("; ")))
+    }
+}
+
+/// The version in every versioned file of `package`.
+fn file_versions(package: &Package) -> Result<Vec<(&Path, Version)>, StepError> {
+    let tag_prefix = package.tag_prefix();
+    package
         .versioned_files
         .iter()
-        .map(|versioned_file| versioned_file.get_version(package.name.as_deref()))
-        .map(|result| {
-            result.and_then(|version_string| {
-                Version::parse(&version_string)
-                    .map_err(|_| StepError::InvalidSemanticVersion(version_string))
-            })
-        })
-        .reduce(|accumulator, version| match (version, accumulator) {
-            (Ok(version), Ok(accumulator)) => {
-                if version == accumulator {
-                    Ok(accumulator)
-                } else {
-                    Err(StepError::InconsistentVersions(
-                        version.to_string(),
-                        accumulator.to_string(),
-                    ))
-                }
-            }
-            (_, Err(err)) | (Err(err), _) => Err(err),
+        .map(|versioned_file| {
+            let version_string = versioned_file.get_version(&tag_prefix)?;
+            let version = match &package.version_scheme {
+                Some(version_scheme) => version_scheme.parse(&version_string).ok_or_else(|| {
+                    StepError::VersionSchemeMismatch {
+                        version: version_string,
+                        template: version_scheme.template().to_string(),
+                    }
+                })?,
+                None => Version::parse(&version_string)
+                    .map_err(|_| StepError::InvalidSemanticVersion(version_string))?,
+            };
+            Ok((versioned_file.path.as_path(), version))
         })
-        .transpose()?;
+        .collect()
+}
+
+/// If `file_versions` don't all agree, a description of each, like
+/// `1.2.3 in Cargo.toml, 1.2.4 in package.json`.
+fn version_mismatch(file_versions: &[(&Path, Version)]) -> Option<String> {
+    if file_versions.iter().map(|(_, version)| version).all_equal() {
+        return None;
+    }
+    Some(
+        file_versions
+            .iter()
+            .map(|(path, version)| format!("{version} in {}", path.display()))
+            .join(", "),
+    )
+}
+
+/// Get the current version of a package.
+pub(crate) fn get_version(package: Package) -> Result<PackageVersion, StepError> {
+    let stable_version = {
+        let file_versions = file_versions(&package)?;
+        if let Some(mismatch) = version_mismatch(&file_versions) {
+            return Err(StepError::InconsistentVersions(mismatch));
+        }
+        file_versions.into_iter().next().map(|(_, version)| version)
+    };
 
     let version = match stable_version {
-        None => get_current_versions_from_tag(package.name.as_deref())?.unwrap_or_default(),
+        None => get_current_versions_from_tag(&package.tag_prefix())?.unwrap_or_default(),
         Some(stable) if stable.pre.is_empty() => CurrentVersions {
             stable,
             prerelease: None,
         },
         Some(pre) => {
-            let stable = get_current_versions_from_tag(package.name.as_deref())?.map_or_else(
+            let stable = get_current_versions_from_tag(&package.tag_prefix())?.map_or_else(
                 || Version::new(0, 0, 0),
                 |current_versions| current_versions.stable,
             );
@@ -170,11 +352,33 @@ fn set_version(
         version,
     } = package_version;
     let latest = version.latest();
-    let mut paths = Vec::with_capacity(package.versioned_files.len());
     for versioned_file in &mut package.versioned_files {
-        versioned_file.set_version(latest)?;
-        paths.push(&versioned_file.path);
+        versioned_file.set_version(latest, package.version_scheme.as_ref())?;
     }
+    let dependency_paths = package
+        .cargo_workspace
+        .as_ref()
+        .map(|cargo_workspace| cargo_workspace.set_dependency_versions(latest))
+        .transpose()?
+        .unwrap_or_default();
+    let parent_paths = package
+        .maven_project
+        .as_ref()
+        .map(|maven_project| maven_project.set_parent_versions(latest))
+        .transpose()?
+        .unwrap_or_default();
+    let cargo_lock = package.set_cargo_lock_versions(latest)?;
+    let npm_locks = package.set_npm_lock_versions(latest)?;
+    let paths = package
+        .versioned_files
+        .iter()
+        .map(|versioned_file| &versioned_file.path)
+        .chain(&dependency_paths)
+        .chain(&parent_paths)
+        .chain(cargo_lock)
+        .chain(&npm_locks)
+        .unique()
+        .collect_vec();
     add_files(&paths)?;
     Ok(PackageVersion { version, package })
 }
@@ -184,46 +388,113 @@ fn set_version(
 /// ### Versions 0.x
 ///
 /// Versions with major component 0 have special meaning in Semantic Versioning and therefore have
-/// different behavior:
-/// 1. [`Rule::Major`] will bump the minor component.
-/// 2. [`Rule::Minor`] will bump the patch component.
-fn bump(mut version: CurrentVersions, rule: &Rule) -> Result<CurrentVersions, StepError> {
+/// different behavior, depending on `major_version_zero`:
+/// 1. [`Rule::Major`] will bump the minor component, unless it's [`MajorVersionZero::Standard`].
+/// 2. [`Rule::Minor`] will bump the patch component, if it's [`MajorVersionZero::Shift`].
+///
+/// Build metadata describes a single build, so every rule other than [`Rule::Build`] (and
+/// [`Rule::Set`], which sets the whole version) removes it.
+fn bump(
+    mut version: CurrentVersions,
+    rule: &Rule,
+    major_version_zero: MajorVersionZero,
+) -> Result<CurrentVersions, StepError> {
     let stable = &mut version.stable;
     let is_0 = stable.major == 0;
+    let shift_major = is_0 && major_version_zero != MajorVersionZero::Standard;
+    let shift_minor = is_0 && major_version_zero == MajorVersionZero::Shift;
     let prerelease = version.prerelease.take();
-    match (rule, is_0) {
-        (Rule::Major, false) => {
+    match (rule, shift_major, shift_minor) {
+        (Rule::Major, false, _) => {
             stable.major += 1;
             stable.minor = 0;
             stable.patch = 0;
             stable.pre = Prerelease::EMPTY;
+            stable.build = BuildMetadata::EMPTY;
             Ok(version)
         }
-        (Rule::Minor, false) | (Rule::Major, true) => {
+        (Rule::Minor, _, false) | (Rule::Major, true, _) => {
             stable.minor += 1;
             stable.patch = 0;
             stable.pre = Prerelease::EMPTY;
+            stable.build = BuildMetadata::EMPTY;
             Ok(version)
         }
-        (Rule::Patch, _) | (Rule::Minor, true) => {
+        (Rule::Patch, _, _) | (Rule::Minor, _, true) => {
             stable.patch += 1;
             stable.pre = Prerelease::EMPTY;
+            stable.build = BuildMetadata::EMPTY;
             Ok(version)
         }
-        (Rule::Release, _) => {
+        // Promotion is resolved to one of the other rules by `in_channels`, without channels
+        // it's the same as a release.
+        (Rule::Release | Rule::Promote, _, _) => {
             let mut prerelease = prerelease.ok_or_else(|| {
                 StepError::InvalidPreReleaseVersion(
                     "No prerelease version found, but a Release rule was requested".to_string(),
                 )
             })?;
             prerelease.pre = Prerelease::EMPTY;
+            prerelease.build = BuildMetadata::EMPTY;
             *stable = prerelease;
             Ok(version)
         }
-        (Rule::Pre { label, stable_rule }, _) => bump_pre(version, prerelease, label, *stable_rule),
+        (
+            Rule::Pre {
+                label,
+                stable_rule,
+                start,
+            },
+            _,
+            _,
+        ) => bump_pre(
+            version,
+            prerelease,
+            label,
+            *stable_rule,
+            *start,
+            major_version_zero,
+        ),
+        (Rule::Build { metadata }, _, _) => {
+            version.prerelease = prerelease;
+            bump_build(version, metadata.as_deref())
+        }
+        (Rule::Set { version: exact }, _, _) => {
+            let exact = Version::parse(exact)
+                .map_err(|_| StepError::InvalidSemanticVersion(exact.clone()))?;
+            // Like tags, a pre-release doesn't replace the last stable version.
+            if exact.pre.is_empty() {
+                *stable = exact;
+            } else {
+                version.prerelease = Some(exact);
+            }
+            Ok(version)
+        }
     }
 }
 
+/// Set the build metadata of the latest version to `metadata` (rendering any template
+/// expressions in it), or increment it (starting from 1) if there's no `metadata`.
+fn bump_build(
+    mut version: CurrentVersions,
+    metadata: Option<&str>,
+) -> Result<CurrentVersions, StepError> {
+    let latest = version.prerelease.as_mut().unwrap_or(&mut version.stable);
+    let metadata = match metadata {
+        Some(metadata) => render_expressions(metadata, &HashMap::new())?,
+        None if latest.build.is_empty() => String::from("1"),
+        None => latest
+            .build
+            .as_str()
+            .parse::<u64>()
+            .map(|build| (build + 1).to_string())
+            .map_err(|_| StepError::InvalidBuildMetadata(latest.build.to_string()))?,
+    };
+    latest.build =
+        BuildMetadata::new(&metadata).map_err(|_| StepError::InvalidBuildMetadata(metadata))?;
+    Ok(version)
+}
+
 #[cfg(test)]
 mod test_bump {
     use rstest::rstest;
@@ -239,6 +510,7 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Major,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -254,6 +526,7 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Major,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -272,6 +545,7 @@ mod test_bump {
                 prerelease: Some(Version::parse(pre_version).unwrap()),
             },
             &Rule::Major,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -288,6 +562,7 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Minor,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -303,12 +578,60 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Minor,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
         assert_eq!(version.stable, Version::new(0, 1, 3));
     }
 
+    #[rstest]
+    #[case::breaking_minor_major(MajorVersionZero::BreakingMinor, Rule::Major, "0.2.0")]
+    #[case::breaking_minor_minor(MajorVersionZero::BreakingMinor, Rule::Minor, "0.2.0")]
+    #[case::breaking_minor_patch(MajorVersionZero::BreakingMinor, Rule::Patch, "0.1.3")]
+    #[case::standard_major(MajorVersionZero::Standard, Rule::Major, "1.0.0")]
+    #[case::standard_minor(MajorVersionZero::Standard, Rule::Minor, "0.2.0")]
+    #[case::standard_patch(MajorVersionZero::Standard, Rule::Patch, "0.1.3")]
+    fn major_version_zero_options(
+        #[case] major_version_zero: MajorVersionZero,
+        #[case] rule: Rule,
+        #[case] expected: &str,
+    ) {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::new(0, 1, 2),
+                prerelease: None,
+            },
+            &rule,
+            major_version_zero,
+        )
+        .unwrap();
+
+        assert_eq!(version.stable, Version::parse(expected).unwrap());
+    }
+
+    #[test]
+    fn standard_pre() {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::new(0, 1, 2),
+                prerelease: None,
+            },
+            &Rule::Pre {
+                label: String::from("rc"),
+                stable_rule: ConventionalRule::Major,
+                start: 0,
+            },
+            MajorVersionZero::Standard,
+        )
+        .unwrap();
+
+        assert_eq!(
+            version.prerelease,
+            Some(Version::parse("1.0.0-rc.0").unwrap())
+        );
+    }
+
     #[rstest]
     #[case("1.2.4-rc.0")]
     #[case("1.3.0-rc.0")]
@@ -320,6 +643,7 @@ mod test_bump {
                 prerelease: Some(Version::parse(pre_version).unwrap()),
             },
             &Rule::Minor,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -336,6 +660,7 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Patch,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -351,6 +676,7 @@ mod test_bump {
                 prerelease: None,
             },
             &Rule::Patch,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -366,6 +692,7 @@ mod test_bump {
                 prerelease: Some(Version::parse("1.2.4-rc.0").unwrap()),
             },
             &Rule::Patch,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -384,7 +711,9 @@ mod test_bump {
             &Rule::Pre {
                 label: String::from("rc"),
                 stable_rule: ConventionalRule::Minor,
+                start: 0,
             },
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -404,7 +733,9 @@ mod test_bump {
             &Rule::Pre {
                 label: String::from("rc"),
                 stable_rule: ConventionalRule::Minor,
+                start: 0,
             },
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -424,7 +755,9 @@ mod test_bump {
             &Rule::Pre {
                 label: String::from("rc"),
                 stable_rule: ConventionalRule::Minor,
+                start: 0,
             },
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -444,7 +777,9 @@ mod test_bump {
             &Rule::Pre {
                 label: String::from("rc"),
                 stable_rule: ConventionalRule::Minor,
+                start: 0,
             },
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
@@ -452,6 +787,56 @@ mod test_bump {
         assert_eq!(new.stable, stable);
     }
 
+    #[rstest]
+    #[case::first("1.2.3", None, ConventionalRule::Minor, 0, "1.3.0-rc.0")]
+    #[case::increment("1.2.3", Some("1.3.0-rc.0"), ConventionalRule::Minor, 0, "1.3.0-rc.1")]
+    #[case::reset_on_breaking(
+        "1.2.3",
+        Some("1.3.0-rc.2"),
+        ConventionalRule::Major,
+        0,
+        "2.0.0-rc.0"
+    )]
+    #[case::never_backwards("1.2.3", Some("2.0.0-rc.0"), ConventionalRule::Minor, 0, "2.0.0-rc.1")]
+    #[case::custom_start("1.2.3", None, ConventionalRule::Minor, 1, "1.3.0-rc.1")]
+    #[case::custom_start_increment(
+        "1.2.3",
+        Some("1.3.0-rc.1"),
+        ConventionalRule::Minor,
+        1,
+        "1.3.0-rc.2"
+    )]
+    #[case::custom_start_after_lower(
+        "1.2.3",
+        Some("1.3.0-rc.0"),
+        ConventionalRule::Minor,
+        5,
+        "1.3.0-rc.5"
+    )]
+    fn pre_counter(
+        #[case] stable: &str,
+        #[case] prerelease: Option<&str>,
+        #[case] stable_rule: ConventionalRule,
+        #[case] start: u64,
+        #[case] expected: &str,
+    ) {
+        let new = bump(
+            CurrentVersions {
+                stable: Version::parse(stable).unwrap(),
+                prerelease: prerelease.map(|prerelease| Version::parse(prerelease).unwrap()),
+            },
+            &Rule::Pre {
+                label: String::from("rc"),
+                stable_rule,
+                start,
+            },
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+
+        assert_eq!(new.prerelease, Some(Version::parse(expected).unwrap()));
+    }
+
     #[test]
     fn release() {
         let version = bump(
@@ -460,50 +845,359 @@ mod test_bump {
                 prerelease: Some(Version::parse("1.2.3-rc.0").unwrap()),
             },
             &Rule::Release,
+            MajorVersionZero::Shift,
         )
         .unwrap();
 
         assert_eq!(version.stable, Version::new(1, 2, 3));
         assert!(version.prerelease.is_none());
     }
+
+    #[rstest]
+    #[case::first("1.2.3", None, "1.2.3+1")]
+    #[case::increment("1.2.3+41", None, "1.2.3+42")]
+    #[case::set("1.2.3+41", Some("abc123"), "1.2.3+abc123")]
+    fn build(#[case] stable: &str, #[case] metadata: Option<&str>, #[case] expected: &str) {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::parse(stable).unwrap(),
+                prerelease: None,
+            },
+            &Rule::Build {
+                metadata: metadata.map(String::from),
+            },
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+
+        assert_eq!(version.stable, Version::parse(expected).unwrap());
+    }
+
+    #[test]
+    fn build_pre_release() {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::new(1, 2, 3),
+                prerelease: Some(Version::parse("1.3.0-rc.0").unwrap()),
+            },
+            &Rule::Build { metadata: None },
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+
+        assert_eq!(version.stable, Version::new(1, 2, 3));
+        assert_eq!(
+            version.prerelease,
+            Some(Version::parse("1.3.0-rc.0+1").unwrap())
+        );
+    }
+
+    #[test]
+    fn build_not_a_number() {
+        let result = bump(
+            CurrentVersions {
+                stable: Version::parse("1.2.3+abc123").unwrap(),
+                prerelease: None,
+            },
+            &Rule::Build { metadata: None },
+            MajorVersionZero::Shift,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case::major(Rule::Major, "2.0.0")]
+    #[case::patch(Rule::Patch, "1.2.4")]
+    fn strips_build(#[case] rule: Rule, #[case] expected: &str) {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::parse("1.2.3+42").unwrap(),
+                prerelease: None,
+            },
+            &rule,
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+
+        assert_eq!(version.stable, Version::parse(expected).unwrap());
+    }
+
+    #[rstest]
+    #[case::lower("1.2.3", None, "1.1.0", "1.1.0", None)]
+    #[case::build("1.2.3", None, "1.2.3+42", "1.2.3+42", None)]
+    #[case::from_pre("1.2.3", Some("1.3.0-rc.0"), "1.2.4", "1.2.4", None)]
+    #[case::pre("1.2.3", None, "2.0.0-rc.1", "1.2.3", Some("2.0.0-rc.1"))]
+    fn set(
+        #[case] stable: &str,
+        #[case] prerelease: Option<&str>,
+        #[case] exact: &str,
+        #[case] expected_stable: &str,
+        #[case] expected_prerelease: Option<&str>,
+    ) {
+        let version = bump(
+            CurrentVersions {
+                stable: Version::parse(stable).unwrap(),
+                prerelease: prerelease.map(|prerelease| Version::parse(prerelease).unwrap()),
+            },
+            &Rule::Set {
+                version: String::from(exact),
+            },
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+
+        assert_eq!(version.stable, Version::parse(expected_stable).unwrap());
+        assert_eq!(
+            version.prerelease,
+            expected_prerelease.map(|prerelease| Version::parse(prerelease).unwrap())
+        );
+    }
+
+    #[test]
+    fn set_invalid() {
+        let result = bump(
+            CurrentVersions::default(),
+            &Rule::Set {
+                version: String::from("1.2"),
+            },
+            MajorVersionZero::Shift,
+        );
+
+        assert!(result.is_err());
+    }
+}
+
+/// Adjust `rule` for the pre-release `channels` of a package (like `alpha`, `beta`, and `rc`), so
+/// that pre-releases only move forward through them.
+///
+/// A [`Rule::Pre`] stays on the channel of the `current` pre-release if it's later than `label`,
+/// unless a bigger change starts a new version. A [`Rule::Promote`] moves the `current`
+/// pre-release to the next channel, or to a stable release from the last one (or if there are no
+/// channels).
+///
+/// # Errors
+///
+/// If a label isn't one of `channels`, or there is no pre-release to promote.
+fn in_channels(
+    rule: &Rule,
+    channels: &[String],
+    current: &CurrentVersions,
+    major_version_zero: MajorVersionZero,
+) -> Result<Rule, StepError> {
+    let position = |label: &str| {
+        channels
+            .iter()
+            .position(|channel| channel == label)
+            .ok_or_else(|| StepError::UnknownPrereleaseChannel {
+                label: label.to_string(),
+                channels: channels.join(", "),
+            })
+    };
+    match rule {
+        Rule::Promote => {
+            let prerelease = current.prerelease.as_ref().ok_or_else(|| {
+                StepError::InvalidPreReleaseVersion(
+                    "No prerelease version found, but a Promote rule was requested".to_string(),
+                )
+            })?;
+            if channels.is_empty() {
+                return Ok(Rule::Release);
+            }
+            match channels.get(position(prerelease_label(prerelease))? + 1) {
+                // The smallest rule, so that the version of the current pre-release is kept.
+                Some(next) => Ok(Rule::Pre {
+                    label: next.clone(),
+                    stable_rule: ConventionalRule::Patch,
+                    start: 0,
+                }),
+                None => Ok(Rule::Release),
+            }
+        }
+        Rule::Pre {
+            label,
+            stable_rule,
+            start,
+        } if !channels.is_empty() => {
+            let requested = position(label)?;
+            let later_channel = match &current.prerelease {
+                Some(prerelease) => {
+                    let next_stable = bump(
+                        CurrentVersions {
+                            stable: current.stable.clone(),
+                            prerelease: None,
+                        },
+                        &Rule::from(*stable_rule),
+                        major_version_zero,
+                    )?
+                    .stable;
+                    channels
+                        .iter()
+                        .position(|channel| channel == prerelease_label(prerelease))
+                        .filter(|current_position| {
+                            *current_position > requested && base(prerelease) >= base(&next_stable)
+                        })
+                }
+                None => None,
+            };
+            Ok(Rule::Pre {
+                label: channels[later_channel.unwrap_or(requested)].clone(),
+                stable_rule: *stable_rule,
+                start: *start,
+            })
+        }
+        _ => Ok(rule.clone()),
+    }
+}
+
+/// The label of a pre-release like `rc.2`.
+fn prerelease_label(version: &Version) -> &str {
+    version.pre.as_str().split('.').next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test_in_channels {
+    use super::*;
+
+    fn channels() -> Vec<String> {
+        ["alpha", "beta", "rc"].map(String::from).to_vec()
+    }
+
+    fn current(prerelease: &str) -> CurrentVersions {
+        CurrentVersions {
+            stable: Version::new(1, 2, 3),
+            prerelease: Some(Version::parse(prerelease).unwrap()),
+        }
+    }
+
+    fn pre(label: &str, stable_rule: ConventionalRule) -> Rule {
+        Rule::Pre {
+            label: label.to_string(),
+            stable_rule,
+            start: 0,
+        }
+    }
+
+    #[test]
+    fn promote_to_next_channel() {
+        let rule = in_channels(
+            &Rule::Promote,
+            &channels(),
+            &current("1.3.0-beta.2"),
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+        assert_eq!(rule, pre("rc", ConventionalRule::Patch));
+        let version = bump(current("1.3.0-beta.2"), &rule, MajorVersionZero::Shift).unwrap();
+        assert_eq!(
+            version.prerelease,
+            Some(Version::parse("1.3.0-rc.0").unwrap())
+        );
+    }
+
+    #[test]
+    fn promote_last_channel() {
+        let rule = in_channels(
+            &Rule::Promote,
+            &channels(),
+            &current("1.3.0-rc.1"),
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+        assert_eq!(rule, Rule::Release);
+    }
+
+    #[test]
+    fn promote_without_channels() {
+        let rule = in_channels(
+            &Rule::Promote,
+            &[],
+            &current("1.3.0-beta.2"),
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+        assert_eq!(rule, Rule::Release);
+    }
+
+    #[test]
+    fn promote_without_prerelease() {
+        let result = in_channels(
+            &Rule::Promote,
+            &channels(),
+            &CurrentVersions::default(),
+            MajorVersionZero::Shift,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn pre_stays_on_later_channel() {
+        let rule = in_channels(
+            &pre("beta", ConventionalRule::Minor),
+            &channels(),
+            &current("1.3.0-rc.1"),
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+        assert_eq!(rule, pre("rc", ConventionalRule::Minor));
+    }
+
+    #[test]
+    fn pre_new_version_restarts_channels() {
+        let rule = in_channels(
+            &pre("beta", ConventionalRule::Major),
+            &channels(),
+            &current("1.3.0-rc.1"),
+            MajorVersionZero::Shift,
+        )
+        .unwrap();
+        assert_eq!(rule, pre("beta", ConventionalRule::Major));
+    }
+
+    #[test]
+    fn pre_unknown_channel() {
+        let result = in_channels(
+            &pre("preview", ConventionalRule::Minor),
+            &channels(),
+            &current("1.3.0-rc.1"),
+            MajorVersionZero::Shift,
+        );
+        assert!(result.is_err());
+    }
 }
 
 /// Bumps the pre-release component of a [`Version`].
 ///
-/// If the existing [`Version`] has no pre-release,
-/// `semantic_rule` will be used to bump to primary components before the
-/// pre-release component is added.
+/// The base of the new pre-release (the major, minor, and patch components) is the stable version
+/// bumped by `stable_rule`, or the base of the existing pre-release if that is higher, so that
+/// pre-releases never go backwards. The counter after `label` increments from the existing
+/// pre-release only when it has the same base and label, otherwise it resets to `start`.
 ///
 /// # Errors
 ///
-/// Can fail if there is an existing pre-release component that can't be incremented.
+/// Can fail if the resulting pre-release component is invalid (e.g., `label` contains spaces).
 fn bump_pre(
     stable_only: CurrentVersions,
     prerelease: Option<Version>,
     label: &str,
     stable_rule: ConventionalRule,
+    start: u64,
+    major_version_zero: MajorVersionZero,
 ) -> Result<CurrentVersions, StepError> {
     let stable = stable_only.stable.clone();
-    let next_stable = bump(stable_only, &stable_rule.into())?.stable;
-    let prerelease_version = prerelease
-        .and_then(|prerelease| {
-            if prerelease.major != next_stable.major
-                || prerelease.minor != next_stable.minor
-                || prerelease.patch != next_stable.patch
-            {
-                return None;
-            }
-            let pre_string = prerelease.pre.as_str();
-            let parts = pre_string.split('.').collect::<Vec<_>>();
-            if parts.len() != 2 || parts[0] != label {
-                return None;
-            }
-            let pre_version = parts[1].parse::<u16>().ok()?;
-            Some(format!("{}.{}", label, pre_version + 1))
-        })
-        .unwrap_or_else(|| format!("{}.0", label));
+    let mut next_prerelease = bump(stable_only, &stable_rule.into(), major_version_zero)?.stable;
+    let counter = match prerelease {
+        Some(prerelease) if base(&prerelease) >= base(&next_prerelease) => {
+            let counter = prerelease_counter(&prerelease, label)
+                .map_or(start, |counter| (counter + 1).max(start));
+            next_prerelease = prerelease;
+            counter
+        }
+        _ => start,
+    };
 
-    let mut next_prerelease = next_stable;
+    next_prerelease.build = BuildMetadata::EMPTY;
+    let prerelease_version = format!("{label}.{counter}");
     next_prerelease.pre = Prerelease::new(&prerelease_version)
         .map_err(|_| StepError::InvalidPreReleaseVersion(prerelease_version))?;
     Ok(CurrentVersions {
@@ -511,3 +1205,49 @@ fn bump_pre(
         prerelease: Some(next_prerelease),
     })
 }
+
+/// The major, minor, and patch components of a [`Version`].
+fn base(version: &Version) -> (u64, u64, u64) {
+    (version.major, version.minor, version.patch)
+}
+
+/// The counter of a pre-release like `rc.2`, if it has the expected `label`.
+fn prerelease_counter(version: &Version, label: &str) -> Option<u64> {
+    let (version_label, counter) = version.pre.as_str().split_once('.')?;
+    if version_label != label {
+        return None;
+    }
+    counter.parse().ok()
+}
+
+#[cfg(test)]
+mod test_bump_level {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::major("1.2.3", None, "2.0.0", BumpLevel::Major)]
+    #[case::minor("1.2.3", None, "1.3.0", BumpLevel::Minor)]
+    #[case::patch("1.2.3", None, "1.2.4", BumpLevel::Patch)]
+    #[case::pre("1.2.3", None, "1.3.0-rc.0", BumpLevel::Pre)]
+    #[case::release_from_pre("1.2.3", Some("1.3.0-rc.1"), "1.3.0", BumpLevel::Minor)]
+    #[case::build("1.2.3+1", None, "1.2.3+2", BumpLevel::Build)]
+    #[case::pre_build("1.2.3", Some("1.3.0-rc.1"), "1.3.0-rc.1+1", BumpLevel::Build)]
+    fn between(
+        #[case] stable: &str,
+        #[case] prerelease: Option<&str>,
+        #[case] new: &str,
+        #[case] expected: BumpLevel,
+    ) {
+        let previous = CurrentVersions {
+            stable: Version::parse(stable).unwrap(),
+            prerelease: prerelease.map(|prerelease| Version::parse(prerelease).unwrap()),
+        };
+
+        assert_eq!(
+            BumpLevel::between(&previous, &Version::parse(new).unwrap()),
+            expected
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::ops::Range;
+
+pub(crate) fn get_setup_cfg_version(content: &str) -> Option<String> {
+    setup_cfg_version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_setup_cfg_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = setup_cfg_version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+pub(crate) fn get_setup_py_version(content: &str) -> Option<String> {
+    setup_py_version_span(content).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_setup_py_version(mut content: String, new_version: &str) -> Option<String> {
+    let span = setup_py_version_span(&content)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the value of `version` in the `[metadata]` section is. Versions which setuptools reads
+/// from somewhere else (like `version = attr: mypkg.__version__`) aren't supported.
+fn setup_cfg_version_span(content: &str) -> Option<Range<usize>> {
+    let mut in_metadata = false;
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            in_metadata = trimmed == "[metadata]";
+            continue;
+        }
+        if !in_metadata {
+            continue;
+        }
+        let delimiter = match line.find(['=', ':']) {
+            Some(delimiter) => delimiter,
+            None => continue,
+        };
+        if line[..delimiter].trim() != "version" {
+            continue;
+        }
+        let value = &line[delimiter + 1..];
+        let value_start = start + delimiter + 1 + (value.len() - value.trim_start().len());
+        let value = value.trim();
+        if value.is_empty() || value.starts_with("attr:") || value.starts_with("file:") {
+            return None;
+        }
+        return Some(value_start..value_start + value.len());
+    }
+    None
+}
+
+/// Where the string passed as the `version` keyword argument (like `version="1.2.3"`) is.
+fn setup_py_version_span(content: &str) -> Option<Range<usize>> {
+    let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+    for (index, _) in content.match_indices("version") {
+        if content[..index]
+            .chars()
+            .next_back()
+            .map_or(false, is_identifier)
+        {
+            continue;
+        }
+        let rest = content[index + "version".len()..].trim_start();
+        let rest = match rest.strip_prefix('=') {
+            Some(rest) if !rest.starts_with('=') => rest.trim_start(),
+            _ => continue,
+        };
+        let quote = match rest.chars().next() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => continue,
+        };
+        let value_start = content.len() - rest.len() + 1;
+        let value_len = content[value_start..].find(quote)?;
+        return Some(value_start..value_start + value_len);
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_setup_cfg {
+    use super::*;
+
+    const CONTENT: &str = "[metadata]
+name = mypkg
+version = 1.2.3
+
+[options]
+python_requires = >=3.7
+";
+
+    #[test]
+    fn get_version() {
+        assert_eq!(get_setup_cfg_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            set_setup_cfg_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn only_metadata_section() {
+        let content = "[bdist_wheel]\nversion = 0.0.1\n\n[metadata]\nversion:1.2.3\n";
+        assert_eq!(
+            set_setup_cfg_version(String::from(content), "2.0.0"),
+            Some(String::from(
+                "[bdist_wheel]\nversion = 0.0.1\n\n[metadata]\nversion:2.0.0\n"
+            ))
+        );
+    }
+
+    #[test]
+    fn dynamic_version() {
+        let content = "[metadata]\nversion = attr: mypkg.__version__\n";
+        assert_eq!(get_setup_cfg_version(content), None);
+    }
+}
+
+#[cfg(test)]
+mod test_setup_py {
+    use super::*;
+
+    const CONTENT: &str = r#"from setuptools import setup
+
+setup(
+    name="mypkg",
+    python_version=">=3.7",
+    version = '1.2.3',
+)
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(get_setup_py_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            set_setup_py_version(String::from(CONTENT), "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn no_version() {
+        assert_eq!(get_setup_py_version("setup(name=\"mypkg\")\n"), None);
+    }
+}
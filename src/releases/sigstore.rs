@@ -0,0 +1,150 @@
+use std::fs::write;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::releases::git::{tag_commit_id, tag_name};
+use crate::releases::github;
+use crate::state::Release::{Bumped, Prepared};
+use crate::step::{SignRelease, StepError};
+use crate::RunType;
+
+/// The implementation of [`crate::step::Step::SignRelease`].
+///
+/// For every tag created by [`crate::step::Step::Release`], writes a file naming the tag and the
+/// commit it points at, and signs it with Sigstore (using `cosign`). Every one of `assets` is
+/// signed too, along with any provenance statement that `Release` wrote. Each signature is a
+/// Sigstore bundle (holding the signature and the certificate) named after the signed file, like
+/// `v1.2.3.tag.txt.sigstore.json`.
+///
+/// If GitHub is configured, the tag files, the assets, and every bundle are attached to the
+/// GitHub release of each tag (provenance statements are already attached by `Release`).
+pub(crate) fn sign_release(
+    run_type: RunType,
+    sign_release: &SignRelease,
+) -> Result<RunType, StepError> {
+    let (mut state, mut dry_run_stdout) = run_type.decompose();
+    let mut tags: Vec<String> = Vec::with_capacity(state.releases.len());
+    for release in &state.releases {
+        let tag = match release {
+            Prepared(release) => tag_name(&release.version, &release.tag_prefix),
+            Bumped { .. } => return Err(StepError::ReleaseNotPrepared),
+        };
+        // Packages using fixed versioning share a tag.
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.is_empty() {
+        return Err(StepError::ReleaseNotPrepared);
+    }
+
+    // Assets are the same for every release, so they're only signed once.
+    let mut asset_files = Vec::with_capacity(sign_release.assets.len() * 2);
+    for asset in &sign_release.assets {
+        if !asset.exists() && dry_run_stdout.is_none() {
+            return Err(StepError::FileNotFound(asset.clone()));
+        }
+        let bundle = sign(asset, dry_run_stdout.as_mut())?;
+        asset_files.push(asset.clone());
+        asset_files.push(bundle);
+    }
+
+    for tag in &tags {
+        let file_stem = tag.replace('/', "-");
+        let tag_file = PathBuf::from(format!("{file_stem}.tag.txt"));
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(
+                stdout,
+                "Would write the commit of tag {tag} to {}",
+                tag_file.display()
+            )?;
+        } else {
+            let commit = tag_commit_id(tag).ok_or_else(|| StepError::TagNotFound(tag.clone()))?;
+            write(&tag_file, format!("{tag} {commit}\n"))?;
+        }
+        let mut files = vec![tag_file.clone(), sign(&tag_file, dry_run_stdout.as_mut())?];
+        files.extend(asset_files.iter().cloned());
+        let provenance = PathBuf::from(format!("{file_stem}.intoto.json"));
+        if provenance.exists() {
+            files.push(sign(&provenance, dry_run_stdout.as_mut())?);
+        }
+
+        let github_config = match state.github_config.clone() {
+            Some(github_config) => github_config,
+            None => continue,
+        };
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            for file in &files {
+                writeln!(
+                    stdout,
+                    "Would attach {} to the GitHub release for {tag}",
+                    file.display()
+                )?;
+            }
+        } else {
+            state.github = github::upload_assets(tag, &files, state.github, &github_config)?;
+        }
+    }
+
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { state, stdout })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// Sign `path` with Sigstore keyless signing, returning the path of the bundle which holds the
+/// signature and certificate.
+///
+/// In CI, `cosign` gets an identity token from the CI provider (like GitHub Actions, when the job
+/// has the `id-token: write` permission). Anywhere else, it opens a browser to sign in.
+fn sign(path: &Path, dry_run_stdout: Option<&mut Box<dyn Write>>) -> Result<PathBuf, StepError> {
+    let bundle = bundle_path(path);
+    if let Some(stdout) = dry_run_stdout {
+        writeln!(
+            stdout,
+            "Would sign {} with Sigstore, writing the signature to {}",
+            path.display(),
+            bundle.display()
+        )?;
+        return Ok(bundle);
+    }
+    let output = Command::new("cosign")
+        .arg("sign-blob")
+        .arg("--yes")
+        .arg("--bundle")
+        .arg(&bundle)
+        .arg(path)
+        .output()
+        .map_err(|err| StepError::SigningFailed {
+            path: path.to_path_buf(),
+            reason: err.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(StepError::SigningFailed {
+            path: path.to_path_buf(),
+            reason: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(bundle)
+}
+
+fn bundle_path(path: &Path) -> PathBuf {
+    let mut bundle = path.as_os_str().to_os_string();
+    bundle.push(".sigstore.json");
+    PathBuf::from(bundle)
+}
+
+#[cfg(test)]
+mod test_bundle_path {
+    use super::*;
+
+    #[test]
+    fn next_to_the_file() {
+        assert_eq!(
+            bundle_path(Path::new("dist/my-tool.tar.gz")),
+            PathBuf::from("dist/my-tool.tar.gz.sigstore.json")
+        );
+    }
+}
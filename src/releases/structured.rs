@@ -0,0 +1,401 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use toml::Spanned;
+
+use super::deno::{skip_trivia, string_len};
+use super::helm::scalar_span;
+
+/// A format of file where a value can be found by its key.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Format {
+    Toml,
+    /// JSON, with or without comments.
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// The format of the file at `path`, based on its extension.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => Some(Self::Toml),
+            Some("json" | "jsonc") => Some(Self::Json),
+            Some("yaml" | "yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn get_version(content: &str, format: Format, key: &str) -> Option<String> {
+    version_span(content, format, key).map(|span| String::from(&content[span]))
+}
+
+pub(crate) fn set_version(
+    mut content: String,
+    format: Format,
+    key: &str,
+    new_version: &str,
+) -> Option<String> {
+    let span = version_span(&content, format, key)?;
+    content.replace_range(span, new_version);
+    Some(content)
+}
+
+/// Where the string at `key` is, without its quotes. Each `.` in `key` is a step into a nested
+/// table (or object, or mapping), so `tool.mytool.version` is the `version` in the `mytool` in
+/// the top-level `tool`.
+fn version_span(content: &str, format: Format, key: &str) -> Option<Range<usize>> {
+    let path = key.split('.').collect::<Vec<_>>();
+    match format {
+        Format::Toml => toml_span(content, &path),
+        Format::Json => json_span(content, &path),
+        Format::Yaml => yaml_span(content, &path),
+    }
+}
+
+fn toml_span(content: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut deserializer = toml::Deserializer::new(content);
+    let version = KeySeed(path).deserialize(&mut deserializer).ok()??;
+    let (start, end) = version.span();
+    // The span includes the quotes. Strings with escapes (or more than one quote, like `"""`)
+    // can't be replaced in place, so they're left out.
+    let span = start + 1..end - 1;
+    (content.get(span.clone()) == Some(version.get_ref().as_str())).then_some(span)
+}
+
+/// Finds the string at the end of a path of keys in a TOML document.
+struct KeySeed<'a>(&'a [&'a str]);
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_> {
+    type Value = Option<Spanned<String>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if self.0.is_empty() {
+            Spanned::<String>::deserialize(deserializer).map(Some)
+        } else {
+            deserializer.deserialize_map(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_> {
+    type Value = Option<Spanned<String>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a table containing {}", self.0.join("."))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut found = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if found.is_none() && key == self.0[0] {
+                found = map.next_value_seed(KeySeed(&self.0[1..]))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+fn json_span(content: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut position = skip_trivia(content, 0);
+    for key in path {
+        position = json_member(content, position, key)?;
+    }
+    let len = string_len(&content[position..])?;
+    Some(position + 1..position + len - 1)
+}
+
+/// Where the value of `key` is in the object at `position`.
+fn json_member(content: &str, mut position: usize, key: &str) -> Option<usize> {
+    if !content[position..].starts_with('{') {
+        return None;
+    }
+    position += 1;
+    loop {
+        position = skip_trivia(content, position);
+        // Anything other than a key (like the closing `}`) means there are no more members.
+        let key_len = string_len(&content[position..])?;
+        let is_key = &content[position + 1..position + key_len - 1] == key;
+        position = skip_trivia(content, position + key_len);
+        if !content[position..].starts_with(':') {
+            return None;
+        }
+        position = skip_trivia(content, position + 1);
+        if is_key {
+            return Some(position);
+        }
+        position = skip_trivia(content, position + json_value_len(&content[position..])?);
+        if !content[position..].starts_with(',') {
+            return None;
+        }
+        position += 1;
+    }
+}
+
+/// Remove the member `key` (and a comma next to it) from the top-level object in `content`,
+/// keeping the formatting of everything else. `None` if there is no such member.
+pub(super) fn remove_json_member(content: &str, key: &str) -> Option<String> {
+    let mut position = skip_trivia(content, 0);
+    if !content[position..].starts_with('{') {
+        return None;
+    }
+    position += 1;
+    // Where the value of the member before this one ends.
+    let mut previous_end = None;
+    loop {
+        let key_start = skip_trivia(content, position);
+        let key_len = string_len(&content[key_start..])?;
+        let is_key = &content[key_start + 1..key_start + key_len - 1] == key;
+        position = skip_trivia(content, key_start + key_len);
+        if !content[position..].starts_with(':') {
+            return None;
+        }
+        position = skip_trivia(content, position + 1);
+        let value_end = position + json_value_len(&content[position..])?;
+        position = skip_trivia(content, value_end);
+        let has_comma = content[position..].starts_with(',');
+        if is_key {
+            let range = match (has_comma, previous_end) {
+                // Up to the next key, so its indentation takes the place of this one's.
+                (true, _) => key_start..skip_trivia(content, position + 1),
+                (false, Some(previous_end)) => previous_end..value_end,
+                (false, None) => key_start..value_end,
+            };
+            let mut content = content.to_string();
+            content.replace_range(range, "");
+            return Some(content);
+        }
+        if !has_comma {
+            return None;
+        }
+        previous_end = Some(value_end);
+        position += 1;
+    }
+}
+
+/// The length of the JSON value at the start of `text`, including anything nested in it.
+fn json_value_len(text: &str) -> Option<usize> {
+    if text.starts_with('"') {
+        return string_len(text);
+    }
+    if !text.starts_with(['{', '[']) {
+        return text
+            .find(|c: char| c.is_whitespace() || [',', '}', ']', '/'].contains(&c))
+            .or(Some(text.len()));
+    }
+    let mut depth = 0_usize;
+    let mut position = 0;
+    while let Some(c) = text[position..].chars().next() {
+        let rest = &text[position..];
+        if rest.starts_with("//") || rest.starts_with("/*") {
+            position = skip_trivia(text, position);
+            continue;
+        }
+        match c {
+            '"' => {
+                position += string_len(rest)?;
+                continue;
+            }
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(position + 1);
+                }
+            }
+            _ => {}
+        }
+        position += c.len_utf8();
+    }
+    None
+}
+
+/// Find the value of nested block mappings by their indentation. Flow mappings (like
+/// `{version: 1.2.3}`) and quoted keys aren't supported.
+fn yaml_span(content: &str, path: &[&str]) -> Option<Range<usize>> {
+    let mut keys = path.iter();
+    let mut key = keys.next()?;
+    // The indentation of the mapping being searched, which isn't known until its first key.
+    let mut indent = None;
+    // The indentation of the key which holds that mapping, if it's nested.
+    let mut parent_indent = None;
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let trimmed = line.trim_start_matches(' ');
+        if trimmed.trim().is_empty() || trimmed.starts_with('#') || line.starts_with("---") {
+            continue;
+        }
+        let line_indent = line.len() - trimmed.len();
+        if parent_indent.map_or(false, |parent_indent| line_indent <= parent_indent) {
+            // The end of the nested mapping, without finding the key.
+            return None;
+        }
+        if line_indent != *indent.get_or_insert(line_indent) {
+            continue;
+        }
+        let rest = match trimmed
+            .strip_prefix(key)
+            .and_then(|rest| rest.trim_end().strip_prefix(':'))
+        {
+            Some(rest) => rest,
+            None => continue,
+        };
+        match keys.next() {
+            Some(next) => {
+                key = next;
+                parent_indent = Some(line_indent);
+                indent = None;
+            }
+            None => return scalar_span(rest, start + line_indent + key.len() + 1),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_toml {
+    use super::*;
+
+    const CONTENT: &str = r#"version = "0.1.0"
+
+[tool.mytool]
+name = "my-tool"
+version = "1.2.3" # The version of the tool
+
+[tool.other]
+version = "4.5.6"
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(
+            get_version(CONTENT, Format::Toml, "tool.mytool.version"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(
+                String::from(CONTENT),
+                Format::Toml,
+                "tool.mytool.version",
+                "2.0.0"
+            ),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn dotted_keys() {
+        let content = "tool.mytool.version = '1.2.3'\n";
+        assert_eq!(
+            get_version(content, Format::Toml, "tool.mytool.version"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(
+            get_version(CONTENT, Format::Toml, "tool.missing.version"),
+            None
+        );
+        assert_eq!(get_version(CONTENT, Format::Toml, "tool.mytool"), None);
+    }
+}
+
+#[cfg(test)]
+mod test_json {
+    use super::*;
+
+    const CONTENT: &str = r#"{
+  "version": "0.1.0",
+  "plugins": [{"version": "4.5.6"}],
+  // Comments are allowed, like in `.jsonc` files
+  "tool": {
+    "name": "my-tool",
+    "version": "1.2.3",
+  }
+}
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(
+            get_version(CONTENT, Format::Json, "tool.version"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(String::from(CONTENT), Format::Json, "tool.version", "2.0.0"),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(get_version(CONTENT, Format::Json, "tool.missing"), None);
+        assert_eq!(get_version(CONTENT, Format::Json, "plugins.version"), None);
+    }
+}
+
+#[cfg(test)]
+mod test_yaml {
+    use super::*;
+
+    const CONTENT: &str = r#"version: 0.1.0
+tool:
+  # The tool's own metadata
+  name: my-tool
+  plugins:
+    - version: 4.5.6
+  mytool:
+    version: "1.2.3" # The version of the tool
+other:
+  version: 7.8.9
+"#;
+
+    #[test]
+    fn get() {
+        assert_eq!(
+            get_version(CONTENT, Format::Yaml, "tool.mytool.version"),
+            Some(String::from("1.2.3"))
+        );
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(
+            set_version(
+                String::from(CONTENT),
+                Format::Yaml,
+                "tool.mytool.version",
+                "2.0.0"
+            ),
+            Some(CONTENT.replace("1.2.3", "2.0.0"))
+        );
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(get_version(CONTENT, Format::Yaml, "tool.version"), None);
+        assert_eq!(
+            get_version(CONTENT, Format::Yaml, "tool.other.version"),
+            None
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use serde_json::{Map, Value};
+
+use super::structured::{self, remove_json_member, Format};
+
+/// The fields which can hold the version of a vcpkg port. `version-date` isn't a semantic
+/// version, so it isn't supported.
+const VERSION_FIELDS: [&str; 3] = ["version", "version-semver", "version-string"];
+
+pub(crate) fn get_version(content: &str) -> Option<String> {
+    let json = serde_json::from_str::<Map<String, Value>>(content).ok()?;
+    VERSION_FIELDS
+        .iter()
+        .find_map(|field| json.get(*field)?.as_str())
+        .map(String::from)
+}
+
+/// Set whichever version field the manifest in `content` uses to `new_version`. Any
+/// `port-version` is removed, since it counts changes to a port _within_ a version.
+///
+/// Both are changed in place, so the order of keys and the formatting of the file are kept.
+pub(crate) fn set_version(content: &str, new_version: &str) -> Option<String> {
+    let field = VERSION_FIELDS
+        .iter()
+        .find(|field| structured::get_version(content, Format::Json, field).is_some())?;
+    let content =
+        remove_json_member(content, "port-version").unwrap_or_else(|| content.to_string());
+    structured::set_version(content, Format::Json, field, new_version)
+}
+
+#[cfg(test)]
+mod test_vcpkg_json {
+    const CONTENT: &str = r#"{
+  "name": "my-lib",
+  "version-semver": "1.2.3",
+  "port-version": 2,
+  "dependencies": [
+    "fmt"
+  ]
+}
+"#;
+
+    #[test]
+    fn get_version() {
+        assert_eq!(super::get_version(CONTENT), Some(String::from("1.2.3")));
+    }
+
+    #[test]
+    fn set_version() {
+        assert_eq!(
+            super::set_version(CONTENT, "2.0.0"),
+            Some(String::from(
+                r#"{
+  "name": "my-lib",
+  "version-semver": "2.0.0",
+  "dependencies": [
+    "fmt"
+  ]
+}
+"#
+            ))
+        );
+    }
+
+    #[test]
+    fn keep_order() {
+        let content = r#"{
+  "version-string": "1.2.3",
+  "name": "my-lib",
+  "port-version": 2
+}"#;
+        assert_eq!(
+            super::set_version(content, "2.0.0"),
+            Some(String::from(
+                r#"{
+  "version-string": "2.0.0",
+  "name": "my-lib"
+}"#
+            ))
+        );
+    }
+
+    #[test]
+    fn version_date() {
+        let content = r#"{"name": "my-lib", "version-date": "2022-12-01"}"#;
+        assert_eq!(super::get_version(content), None);
+    }
+}
@@ -0,0 +1,214 @@
+use semver::{Prerelease, Version};
+
+use crate::config;
+use crate::step::StepError;
+
+/// How versions are written in the `versioned_files` of a package which doesn't use three-part
+/// semantic versions, like `1.2.3.0` for .NET assemblies.
+///
+/// Releases still use a semantic version, made up of the components which the `Major`, `Minor`,
+/// and `Patch` rules bump. Every other component is written as 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct VersionScheme {
+    template: String,
+    segments: Vec<Segment>,
+    /// Which component (by its index among the components of `segments`) each rule bumps.
+    major: usize,
+    minor: usize,
+    patch: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Segment {
+    Literal(String),
+    Component(String),
+}
+
+impl VersionScheme {
+    pub(crate) fn new(config: &config::VersionScheme) -> Result<Self, StepError> {
+        let invalid = |reason: String| StepError::InvalidVersionScheme {
+            template: config.template.clone(),
+            reason,
+        };
+        let segments = segments(&config.template).map_err(invalid)?;
+        let components = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Component(name) => Some(name.as_str()),
+                Segment::Literal(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let position = |rule: &str, name: &Option<String>| {
+            let name = name.as_deref().unwrap_or(rule);
+            components
+                .iter()
+                .position(|component| *component == name)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "there is no `{{{name}}}` component for the {rule} rule to bump"
+                    ))
+                })
+        };
+        let major = position("major", &config.major)?;
+        let minor = position("minor", &config.minor)?;
+        let patch = position("patch", &config.patch)?;
+        if major == minor || minor == patch || major == patch {
+            return Err(invalid(String::from(
+                "each rule must bump a different component",
+            )));
+        }
+        Ok(Self {
+            template: config.template.clone(),
+            segments,
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    pub(crate) fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The semantic version of `version` (like `1.2.3.0`), or `None` if it doesn't match the
+    /// template. A pre-release (like `-rc.0`) can follow the template.
+    pub(crate) fn parse(&self, version: &str) -> Option<Version> {
+        let mut components = Vec::new();
+        let mut rest = version;
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => rest = rest.strip_prefix(literal.as_str())?,
+                Segment::Component(_) => {
+                    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+                    components.push(rest[..digits].parse::<u64>().ok()?);
+                    rest = &rest[digits..];
+                }
+            }
+        }
+        let pre = match rest.strip_prefix('-') {
+            Some(pre) => Prerelease::new(pre).ok()?,
+            None if rest.is_empty() => Prerelease::EMPTY,
+            None => return None,
+        };
+        let mut parsed = Version::new(
+            components[self.major],
+            components[self.minor],
+            components[self.patch],
+        );
+        parsed.pre = pre;
+        Some(parsed)
+    }
+
+    /// Write `version` using the template, with 0 for every component that no rule bumps.
+    pub(crate) fn format(&self, version: &Version) -> String {
+        let mut formatted = String::new();
+        let mut index = 0;
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(literal) => formatted.push_str(literal),
+                Segment::Component(_) => {
+                    let value = if index == self.major {
+                        version.major
+                    } else if index == self.minor {
+                        version.minor
+                    } else if index == self.patch {
+                        version.patch
+                    } else {
+                        0
+                    };
+                    formatted.push_str(&value.to_string());
+                    index += 1;
+                }
+            }
+        }
+        if !version.pre.is_empty() {
+            formatted.push('-');
+            formatted.push_str(version.pre.as_str());
+        }
+        formatted
+    }
+}
+
+/// Split a template like `{major}.{minor}.{patch}.{build}` into its literals and components.
+fn segments(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            segments.push(Segment::Literal(rest[..start].to_string()));
+        } else if matches!(segments.last(), Some(Segment::Component(_))) {
+            return Err(String::from(
+                "components must be separated, like `{major}.{minor}`",
+            ));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| String::from("a `{` is missing its `}`"))?;
+        let name = rest[start + 1..start + end].trim();
+        if name.is_empty() {
+            return Err(String::from("every component needs a name"));
+        }
+        if segments.contains(&Segment::Component(name.to_string())) {
+            return Err(format!("the component `{{{name}}}` is used twice"));
+        }
+        segments.push(Segment::Component(name.to_string()));
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(rest.to_string()));
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod test_version_scheme {
+    use super::*;
+
+    fn scheme(template: &str, patch: Option<&str>) -> Result<VersionScheme, StepError> {
+        VersionScheme::new(&config::VersionScheme {
+            template: String::from(template),
+            major: None,
+            minor: None,
+            patch: patch.map(String::from),
+        })
+    }
+
+    #[test]
+    fn four_parts() {
+        let scheme = scheme("{major}.{minor}.{patch}.{revision}", None).unwrap();
+        assert_eq!(scheme.parse("1.2.3.4"), Some(Version::new(1, 2, 3)));
+        assert_eq!(scheme.format(&Version::new(1, 3, 0)), "1.3.0.0");
+        assert_eq!(scheme.parse("1.2.3"), None);
+        assert_eq!(scheme.parse("1.2.3.4.5"), None);
+    }
+
+    #[test]
+    fn mapped_rule() {
+        let scheme = scheme("{major}.{minor}.{build}.{patch}", Some("build")).unwrap();
+        assert_eq!(scheme.parse("1.2.3.4"), Some(Version::new(1, 2, 3)));
+        assert_eq!(scheme.format(&Version::new(1, 2, 4)), "1.2.4.0");
+    }
+
+    #[test]
+    fn pre_release() {
+        let scheme = scheme("{major}.{minor}.{patch}.{build}", None).unwrap();
+        let version = Version::parse("2.0.0-rc.1").unwrap();
+        assert_eq!(scheme.format(&version), "2.0.0.0-rc.1");
+        assert_eq!(scheme.parse("2.0.0.0-rc.1"), Some(version));
+    }
+
+    #[test]
+    fn literals() {
+        let scheme = scheme("r{major}_{minor}_{patch}", None).unwrap();
+        assert_eq!(scheme.parse("r1_2_3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(scheme.format(&Version::new(1, 2, 4)), "r1_2_4");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(scheme("{major}.{minor}.{build}", None).is_err());
+        assert!(scheme("{major}{minor}.{patch}", None).is_err());
+        assert!(scheme("{major}.{minor}.{patch", None).is_err());
+        assert!(scheme("{major}.{minor}.{patch}", Some("minor")).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::io::Write;
+
+use semver::Version;
+
+use crate::prompt::confirm;
+use crate::releases::git::{delete_tag, revert_commit, tag_commit_id, tag_name};
+use crate::releases::github::{self, YankRelease};
+use crate::releases::Package;
+use crate::state::State;
+use crate::step::StepError;
+
+/// The options of `knope yank`.
+pub(crate) struct Yank {
+    pub(crate) version: Version,
+    /// The name of the package which was released, required when there are multiple packages.
+    pub(crate) package: Option<String>,
+    /// What to do with the GitHub release, if GitHub is configured.
+    pub(crate) github_release: YankRelease,
+    /// The remote to delete the tag from.
+    pub(crate) remote: String,
+    /// Whether to also revert the commit which was tagged.
+    pub(crate) revert: bool,
+    /// Skip the confirmation before each change.
+    pub(crate) yes: bool,
+}
+
+/// The implementation of `knope yank`, for quickly pulling a bad release.
+///
+/// 1. Deletes the GitHub release (if GitHub is configured), or turns it into a draft or
+///    pre-release.
+/// 2. Deletes the release tag locally and from the remote.
+/// 3. Optionally reverts the commit which was tagged.
+///
+/// Each change is confirmed first, unless `yes` is set. Declining one skips only that change.
+pub(crate) fn yank(
+    yank: &Yank,
+    mut state: State,
+    mut dry_run_stdout: Option<Box<dyn Write>>,
+) -> Result<(), StepError> {
+    let tag_prefix = selected_package(&state.packages, yank.package.as_deref())?.tag_prefix();
+    let tag = tag_name(&yank.version, &tag_prefix);
+    let commit = tag_commit_id(&tag).ok_or_else(|| StepError::TagNotFound(tag.clone()))?;
+
+    if let Some(github_config) = state.github_config.clone() {
+        let change = match yank.github_release {
+            YankRelease::Delete => format!("delete the GitHub release for {tag}"),
+            YankRelease::Draft => format!("convert the GitHub release for {tag} to a draft"),
+            YankRelease::Prerelease => {
+                format!("mark the GitHub release for {tag} as a pre-release")
+            }
+        };
+        if confirmed(&change, yank.yes, &mut dry_run_stdout)? {
+            state.github = github::yank(&tag, yank.github_release, state.github, &github_config)?;
+        }
+    }
+
+    let change = format!("delete the tag {tag} locally and from {}", yank.remote);
+    if confirmed(&change, yank.yes, &mut dry_run_stdout)? {
+        delete_tag(&tag, &yank.remote)?;
+    }
+
+    let change = format!("revert the commit tagged {tag}");
+    if yank.revert && confirmed(&change, yank.yes, &mut dry_run_stdout)? {
+        revert_commit(&commit)?;
+    }
+    Ok(())
+}
+
+/// Whether `change` should be made. In a dry run, it's reported instead of being made.
+fn confirmed(
+    change: &str,
+    yes: bool,
+    dry_run_stdout: &mut Option<Box<dyn Write>>,
+) -> Result<bool, StepError> {
+    if let Some(stdout) = dry_run_stdout {
+        writeln!(stdout, "Would {change}")?;
+        Ok(false)
+    } else {
+        Ok(yes || confirm(&format!("Are you sure you want to {change}?"))?)
+    }
+}
+
+/// The package named `name`, or the only package if there's no name.
+pub(super) fn selected_package<'a>(
+    packages: &'a [Package],
+    name: Option<&str>,
+) -> Result<&'a Package, StepError> {
+    match (name, packages) {
+        (Some(name), _) => packages
+            .iter()
+            .find(|package| package.name.as_deref() == Some(name))
+            .ok_or_else(|| StepError::UnknownPackage(name.to_string())),
+        (None, [package]) => Ok(package),
+        (None, []) => Err(StepError::no_defined_packages_with_help()),
+        (None, _) => Err(StepError::PackageNotSelected),
+    }
+}
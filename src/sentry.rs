@@ -0,0 +1,194 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::app_config::get_or_prompt_for_sentry_token;
+use crate::config::Sentry;
+use crate::git::get_commit_messages_in_range;
+use crate::releases::{Package, Release};
+use crate::state::Release::{Bumped, Prepared};
+use crate::step::{CreateSentryRelease, StepError};
+use crate::template::{format_date, Template};
+use crate::{RunType, State};
+
+/// Used when no `url` is configured for a self-hosted instance.
+const DEFAULT_URL: &str = "https://sentry.io";
+
+/// The implementation of [`crate::step::Step::CreateSentryRelease`].
+///
+/// Creates a release in Sentry for every release prepared in this workflow, associating the
+/// commits it was prepared from, then finalizes it so Sentry knows it was deployed.
+pub(crate) fn create_release(
+    run_type: RunType,
+    create_sentry_release: &CreateSentryRelease,
+) -> Result<RunType, StepError> {
+    let (state, mut dry_run_stdout) = run_type.decompose();
+    let sentry_config = state
+        .sentry_config
+        .clone()
+        .ok_or(StepError::SentryNotConfigured)?;
+    if state.releases.is_empty() {
+        return Err(StepError::ReleaseNotPrepared);
+    }
+    let token = if dry_run_stdout.is_none() {
+        Some(get_or_prompt_for_sentry_token()?)
+    } else {
+        None
+    };
+
+    for release in &state.releases {
+        let release = match release {
+            Prepared(release) => release,
+            Bumped { .. } => return Err(StepError::ReleaseNotPrepared),
+        };
+        let version = release_version(
+            &state,
+            &sentry_config,
+            release,
+            create_sentry_release.version.clone(),
+        )?;
+        let path_filter = state
+            .packages
+            .iter()
+            .find(|package| package.name == release.package_name)
+            .map(Package::path_filter)
+            .unwrap_or_default();
+        let commits = get_commit_messages_in_range(&release.commits, path_filter)?;
+
+        if let Some(stdout) = dry_run_stdout.as_mut() {
+            writeln!(
+                stdout,
+                "Would create release {version} in Sentry project {project} with {count} commits",
+                project = sentry_config.project,
+                count = commits.len()
+            )?;
+            writeln!(stdout, "Would finalize release {version} in Sentry")?;
+            continue;
+        }
+
+        let api = Api::new(&sentry_config, token.as_deref().unwrap_or_default());
+        let response = ureq::post(&api.releases_url)
+            .set("Authorization", &api.authorization)
+            .send_json(NewRelease {
+                version: &version,
+                projects: [&sentry_config.project],
+                commits: commits
+                    .iter()
+                    .map(|commit| Commit {
+                        id: &commit.id,
+                        message: commit.message.trim(),
+                    })
+                    .collect(),
+            })?;
+        // 208 means the release already exists, which is fine when re-running a workflow.
+        if !matches!(response.status(), 201 | 208) {
+            return Err(StepError::ApiResponseError(None));
+        }
+        let response = ureq::put(&format!("{}{version}/", api.releases_url))
+            .set("Authorization", &api.authorization)
+            .send_json(FinalizedRelease {
+                date_released: rfc3339(
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |duration| duration.as_secs()),
+                ),
+            })?;
+        if response.status() != 200 {
+            return Err(StepError::ApiResponseError(None));
+        }
+        println!("Created release {version} in Sentry");
+    }
+
+    if let Some(stdout) = dry_run_stdout {
+        Ok(RunType::DryRun { state, stdout })
+    } else {
+        Ok(RunType::Real(state))
+    }
+}
+
+/// The name of `release` in Sentry, rendered from `template` if there is one.
+fn release_version(
+    state: &State,
+    sentry_config: &Sentry,
+    release: &Release,
+    template: Option<Template>,
+) -> Result<String, StepError> {
+    let template = if let Some(template) = template {
+        template
+    } else {
+        let name = release
+            .package_name
+            .as_ref()
+            .unwrap_or(&sentry_config.project);
+        return Ok(format!("{name}@{}", release.version));
+    };
+    let package = state
+        .packages
+        .iter()
+        .find(|package| package.name == release.package_name);
+    match package {
+        Some(package) => template.render_for_package(state, package),
+        None => template.render(state),
+    }
+}
+
+/// Format `seconds` since the Unix epoch as an RFC 3339 timestamp in UTC.
+fn rfc3339(seconds: u64) -> String {
+    format_date(seconds, "%Y-%m-%dT%H:%M:%SZ")
+}
+
+struct Api {
+    releases_url: String,
+    authorization: String,
+}
+
+impl Api {
+    fn new(sentry_config: &Sentry, token: &str) -> Self {
+        let url = sentry_config
+            .url
+            .as_deref()
+            .unwrap_or(DEFAULT_URL)
+            .trim_end_matches('/');
+        Self {
+            releases_url: format!(
+                "{url}/api/0/organizations/{organization}/releases/",
+                organization = sentry_config.organization
+            ),
+            authorization: format!("Bearer {token}"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NewRelease<'a> {
+    version: &'a str,
+    projects: [&'a str; 1],
+    commits: Vec<Commit<'a>>,
+}
+
+#[derive(Serialize)]
+struct Commit<'a> {
+    id: &'a str,
+    message: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FinalizedRelease {
+    date_released: String,
+}
+
+#[cfg(test)]
+mod test_rfc3339 {
+    use super::*;
+
+    #[test]
+    fn epoch() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn leap_day() {
+        assert_eq!(rfc3339(1_709_210_096), "2024-02-29T12:34:56Z");
+    }
+}
@@ -10,7 +10,14 @@ pub(crate) struct State {
     pub(crate) jira_config: Option<config::Jira>,
     pub(crate) github: GitHub,
     pub(crate) github_config: Option<config::GitHub>,
+    pub(crate) smtp_config: Option<config::Smtp>,
+    pub(crate) sentry_config: Option<config::Sentry>,
+    pub(crate) versioning: config::Versioning,
     pub(crate) issue: Issue,
+    /// Skip the cache when listing issues, set by the `--refresh` option.
+    pub(crate) refresh_issues: bool,
+    /// Branches (which may contain `*` wildcards) that steps must ask before modifying.
+    pub(crate) protected_branches: Vec<String>,
     /// All of the releases that have been prepared in the current workflow.
     pub(crate) releases: Vec<Release>,
     pub(crate) packages: Vec<releases::Package>,
@@ -21,13 +28,21 @@ impl State {
     pub(crate) fn new(
         jira_config: Option<config::Jira>,
         github_config: Option<config::GitHub>,
+        smtp_config: Option<config::Smtp>,
+        sentry_config: Option<config::Sentry>,
+        versioning: config::Versioning,
         packages: Vec<releases::Package>,
     ) -> Self {
         State {
             jira_config,
             github: GitHub::New,
             github_config,
+            smtp_config,
+            sentry_config,
+            versioning,
             issue: Issue::Initial,
+            refresh_issues: false,
+            protected_branches: Vec::new(),
             releases: Vec::with_capacity(packages.len()),
             packages,
         }
@@ -64,6 +79,10 @@ pub(crate) enum Issue {
     /// contains details of the issue you're working against to use for things like transitioning
     /// or creating branches.
     Selected(issues::Issue),
+    /// Triggered by the `multiple` option of [`crate::Step::SelectJiraIssue`] or
+    /// [`crate::Step::SelectGitHubIssue`], contains every issue that was selected, for steps which
+    /// work on a batch of issues.
+    SelectedMany(Vec<issues::Issue>),
 }
 
 /// Tracks what's been done with respect to releases in this workflow.
@@ -72,6 +91,8 @@ pub(crate) enum Release {
     /// Triggered by [`crate::Step::BumpVersion`].
     Bumped {
         version: semver::Version,
+        previous_version: semver::Version,
+        bump_level: releases::BumpLevel,
         package_name: Option<String>,
     },
     /// Triggered by [`crate::Step::PrepareRelease`]. Contains the generated release notes and new
@@ -3,6 +3,7 @@ use git_repository::reference::{head_commit, peel};
 use git_repository::tag;
 use git_traverse::commit::ancestors;
 use std::collections::HashMap;
+use std::io::stdout;
 use std::path::PathBuf;
 
 use miette::Diagnostic;
@@ -11,7 +12,8 @@ use thiserror::Error;
 
 use crate::releases::suggested_package_toml;
 use crate::state::RunType;
-use crate::{command, git, issues, releases};
+use crate::template::Template;
+use crate::{command, email, generate_file, git, issues, pull_request, releases, sentry};
 
 /// Each variant describes an action you can take using knope, they are used when defining your
 /// [`crate::Workflow`] via whatever config format is being utilized.
@@ -19,27 +21,38 @@ use crate::{command, git, issues, releases};
 #[serde(tag = "type")]
 pub(crate) enum Step {
     /// Search for Jira issues by status and display the list of them in the terminal.
-    /// User is allowed to select one issue which will then change the workflow's state to
-    /// [`State::IssueSelected`].
+    /// User is allowed to select one issue (or several, if `multiple` is set) which will then
+    /// change the workflow's state to [`State::IssueSelected`].
     SelectJiraIssue {
         /// Issues with this status in Jira will be listed for the user to select.
         status: String,
+        /// Let the user select any number of issues, for steps which work on a batch of them.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        multiple: bool,
     },
-    /// Transition a Jira issue to a new status.
-    TransitionJiraIssue {
-        /// The status to transition the current issue to.
-        status: String,
-    },
+    /// Transition a Jira issue to a new status, optionally submitting fields that the
+    /// transition's screen requires.
+    TransitionJiraIssue(TransitionJiraIssue),
+    /// Transition every Jira issue referenced by the commits of each release from
+    /// [`Step::PrepareRelease`] to a new status, and close every referenced GitHub issue.
+    TransitionReleasedIssues(TransitionReleasedIssues),
     /// Search for GitHub issues by status and display the list of them in the terminal.
-    /// User is allowed to select one issue which will then change the workflow's state to
-    /// [`State::IssueSelected`].
+    /// User is allowed to select one issue (or several, if `multiple` is set) which will then
+    /// change the workflow's state to [`State::IssueSelected`].
     SelectGitHubIssue {
         /// If provided, only issues with this label will be included
         labels: Option<Vec<String>>,
+        /// Let the user select any number of issues, for steps which work on a batch of them.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        multiple: bool,
     },
     /// Attempt to parse issue info from the current branch name and change the workflow's state to
     /// [`State::IssueSelected`].
-    SelectIssueFromBranch,
+    SelectIssueFromBranch {
+        /// Get the issue's summary from GitHub or Jira instead of using the one in the branch name.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        fetch: bool,
+    },
     /// Uses the name of the currently selected issue to checkout an existing or create a new
     /// branch for development. If an existing branch is not found, the user will be prompted to
     /// select an existing local branch to base the new branch off of. Remote branches are not
@@ -60,6 +73,13 @@ pub(crate) enum Step {
         /// A map of value-to-replace to [Variable][`crate::command::Variable`] to replace
         /// it with.
         variables: Option<HashMap<String, command::Variable>>,
+        /// The shell to run `command` in, if not the default for this platform.
+        shell: Option<command::Shell>,
+        /// A different command (and shell) to run on Windows.
+        windows: Option<command::WindowsCommand>,
+        /// Stop the command (and everything it started) if it's still running after this many
+        /// seconds.
+        timeout: Option<u64>,
     },
     /// This will look through all commits since the last tag and parse any
     /// [Conventional Commits](https://www.conventionalcommits.org/en/v1.0.0/) it finds. It will
@@ -70,37 +90,203 @@ pub(crate) enum Step {
     /// This will create a new release on GitHub using the current project version.
     ///
     /// Requires that GitHub details be configured.
-    Release,
+    Release(ReleaseOptions),
+    /// Create a pull request on GitHub from the current branch, with a title and body rendered
+    /// from templates (e.g., to embed the changelog from [`Step::PrepareRelease`]).
+    ///
+    /// Requires that GitHub details be configured.
+    CreatePullRequest(CreatePullRequest),
+    /// Send an email with a subject and body rendered from templates (e.g., to announce a
+    /// release on a mailing list).
+    ///
+    /// Requires that SMTP details be configured.
+    SendEmail(SendEmail),
+    /// Run a command which prints the changes to the public API of each package released by
+    /// [`Step::PrepareRelease`], and add them to its release notes.
+    ApiDiff(ApiDiff),
+    /// Check that the versioned files of every package agree on its version, without changing
+    /// anything.
+    VerifyVersions,
+    /// Verify the signature of the latest release tag of every package against a list of
+    /// allowed signers, before building on top of it.
+    VerifyTag(VerifyTag),
+    /// Register each release from [`Step::PrepareRelease`] with Sentry, associating the commits
+    /// it contains and marking it as released.
+    ///
+    /// Requires that Sentry details be configured.
+    CreateSentryRelease(CreateSentryRelease),
+    /// On a pull request, post a comment previewing what [`Step::PrepareRelease`] would do if the
+    /// pull request were merged. The same comment is updated every time this runs.
+    ///
+    /// Requires that GitHub details be configured.
+    CommentReleasePreview,
+    /// Sign the tag of each release from [`Step::Release`] (and any other assets) with Sigstore
+    /// keyless signing, attaching the signatures to any GitHub release.
+    SignRelease(SignRelease),
+    /// Render a template file (with things like the new version) to a path, staging the result.
+    GenerateFile(GenerateFile),
+    /// Bump the `PKGBUILD` in a clone of an AUR repository to the new version, then push it.
+    PublishAur(PublishAur),
+    /// Update a Flatpak manifest or `snapcraft.yaml` in a clone of a packaging repository to the
+    /// new version, then push it or open a pull request for it.
+    UpdateManifest(UpdateManifest),
+}
+
+/// A [`Step`] as it's defined in a [`crate::Workflow`], along with options that any step can set.
+#[derive(Deserialize, Debug, Serialize)]
+pub(crate) struct ConfiguredStep {
+    #[serde(flatten)]
+    pub(crate) step: Step,
+    /// Identifies this step for the `--skip` and `--only` options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    /// Like `name`, but any number of steps can share a tag, so they can be skipped together.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    /// Only pretend to run this step, even if the rest of the workflow is running for real.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) dry_run: bool,
+    /// Environment variables (like credentials) this step needs, which are checked before any
+    /// step in the workflow runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) requires: Vec<String>,
+    /// If not empty, this step only runs on these platforms and is skipped everywhere else.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) platforms: Vec<Platform>,
+}
+
+/// An operating system (or family of them) that a [`ConfiguredStep`] can be limited to.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Platform {
+    Windows,
+    Macos,
+    Linux,
+    /// Any Unix-like system, including macOS and Linux.
+    Unix,
+}
+
+impl Platform {
+    /// Whether knope is running on this platform.
+    fn is_current(self) -> bool {
+        match self {
+            Self::Windows => cfg!(windows),
+            Self::Macos => cfg!(target_os = "macos"),
+            Self::Linux => cfg!(target_os = "linux"),
+            Self::Unix => cfg!(unix),
+        }
+    }
+}
+
+impl From<Step> for ConfiguredStep {
+    fn from(step: Step) -> Self {
+        Self {
+            step,
+            name: None,
+            tags: Vec::new(),
+            dry_run: false,
+            requires: Vec::new(),
+            platforms: Vec::new(),
+        }
+    }
+}
+
+impl ConfiguredStep {
+    /// Whether `name_or_tag` is the `name` or one of the `tags` of this step.
+    pub(crate) fn matches(&self, name_or_tag: &str) -> bool {
+        self.name.as_deref() == Some(name_or_tag) || self.tags.iter().any(|tag| tag == name_or_tag)
+    }
+
+    /// Whether this step runs on the current platform.
+    pub(crate) fn runs_here(&self) -> bool {
+        self.platforms.is_empty() || self.platforms.iter().any(|platform| platform.is_current())
+    }
+
+    /// Run the inner [`Step`], switching to a dry run just for this step if it's configured that way.
+    ///
+    /// A dry-run step works on a copy of the state which is thrown away afterward, so later steps
+    /// never act on changes (like a prepared release) which weren't really made.
+    pub(crate) fn run(self, run_type: RunType) -> Result<RunType, StepError> {
+        match run_type {
+            RunType::Real(state) if self.dry_run => {
+                let dry_run = RunType::DryRun {
+                    state: state.clone(),
+                    stdout: Box::new(stdout()),
+                };
+                self.step.run(dry_run)?;
+                Ok(RunType::Real(state))
+            }
+            run_type => self.step.run(run_type),
+        }
+    }
 }
 
 impl Step {
     pub(crate) fn run(self, run_type: RunType) -> Result<RunType, StepError> {
         match self {
-            Step::SelectJiraIssue { status } => issues::select_jira_issue(&status, run_type),
-            Step::TransitionJiraIssue { status } => {
-                issues::transition_jira_issue(&status, run_type)
+            Step::SelectJiraIssue { status, multiple } => {
+                issues::select_jira_issue(&status, multiple, run_type)
             }
-            Step::SelectGitHubIssue { labels } => {
-                issues::select_github_issue(labels.as_deref(), run_type)
+            Step::TransitionJiraIssue(transition) => {
+                issues::transition_jira_issue(transition, run_type)
+            }
+            Step::TransitionReleasedIssues(transition) => {
+                issues::transition_released_issues(run_type, &transition)
+            }
+            Step::SelectGitHubIssue { labels, multiple } => {
+                issues::select_github_issue(labels.as_deref(), multiple, run_type)
             }
             Step::SwitchBranches => git::switch_branches(run_type),
             Step::RebaseBranch { to } => git::rebase_branch(&to, run_type),
             Step::BumpVersion(rule) => releases::bump_version(run_type, &rule),
-            Step::Command { command, variables } => {
-                command::run_command(run_type, command, variables)
+            Step::Command {
+                command,
+                variables,
+                shell,
+                windows,
+                timeout,
+            } => {
+                let (command, shell) = match windows {
+                    Some(windows) if cfg!(windows) => (windows.command, windows.shell),
+                    _ => (command, shell),
+                };
+                command::run_command(run_type, &command, variables, shell, timeout)
             }
             Step::PrepareRelease(prepare_release) => {
                 releases::prepare_release(run_type, &prepare_release)
             }
-            Step::SelectIssueFromBranch => git::select_issue_from_current_branch(run_type),
-            Step::Release => releases::release(run_type),
+            Step::SelectIssueFromBranch { fetch } => {
+                git::select_issue_from_current_branch(run_type, fetch)
+            }
+            Step::Release(options) => releases::release(run_type, &options),
+            Step::CreatePullRequest(create_pull_request) => {
+                pull_request::create_pull_request(run_type, create_pull_request)
+            }
+            Step::SendEmail(send_email) => email::send_email(run_type, send_email),
+            Step::ApiDiff(api_diff) => releases::api_diff(run_type, &api_diff),
+            Step::VerifyVersions => releases::verify_versions(run_type),
+            Step::VerifyTag(verify_tag) => releases::verify_tag(run_type, &verify_tag),
+            Step::CreateSentryRelease(create_sentry_release) => {
+                sentry::create_release(run_type, &create_sentry_release)
+            }
+            Step::CommentReleasePreview => pull_request::comment_release_preview(run_type),
+            Step::SignRelease(sign_release) => releases::sign_release(run_type, &sign_release),
+            Step::GenerateFile(generate_file) => {
+                generate_file::generate_file(run_type, generate_file)
+            }
+            Step::PublishAur(publish_aur) => releases::publish_aur(run_type, &publish_aur),
+            Step::UpdateManifest(update_manifest) => {
+                releases::update_manifest(run_type, &update_manifest)
+            }
         }
     }
 
-    /// Set `prerelease_label` if `self` is `PrepareRelease`.
+    /// Set `prerelease_label` if `self` is `PrepareRelease`. An empty label removes it, for a stable
+    /// release.
     pub(crate) fn set_prerelease_label(&mut self, prerelease_label: &str) {
         if let Step::PrepareRelease(prepare_release) = self {
-            prepare_release.prerelease_label = Some(String::from(prerelease_label));
+            prepare_release.prerelease_label = (!prerelease_label.is_empty())
+                .then(|| PrereleaseLabel::All(String::from(prerelease_label)));
         }
     }
 }
@@ -113,6 +299,12 @@ pub(super) enum StepError {
         help("You must call SelectJiraIssue or SelectGitHubIssue before calling this step")
     )]
     NoIssueSelected,
+    #[error("More than one issue is selected")]
+    #[diagnostic(
+        code(step::multiple_issues_selected),
+        help("This step works on a single issue, so select one without the `multiple` option of SelectJiraIssue or SelectGitHubIssue")
+    )]
+    MultipleIssuesSelected,
     #[error("Jira is not configured")]
     #[diagnostic(
         code(step::jira_not_configured),
@@ -134,6 +326,22 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/github.html")
     )]
     GitHubNotConfigured,
+    #[error("No issue tracker is configured")]
+    #[diagnostic(
+        code(step::issue_tracker_not_configured),
+        help(
+            "Jira or GitHub must be configured in order to fetch the issue for the current branch"
+        ),
+        url("https://knope-dev.github.io/knope/config/step/SelectIssueFromBranch.html")
+    )]
+    IssueTrackerNotConfigured,
+    #[error("Could not find a pull request for the current branch")]
+    #[diagnostic(
+        code(step::pull_request_not_found),
+        help("This step must run on a pull request, either in GitHub Actions or on a branch with an open pull request"),
+        url("https://knope-dev.github.io/knope/config/step/CommentReleasePreview.html")
+    )]
+    PullRequestNotFound,
     #[error("Could not increment pre-release version {0}")]
     #[diagnostic(
         code(step::invalid_pre_release_version),
@@ -144,6 +352,23 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/step/BumpVersion.html#pre")
     )]
     InvalidPreReleaseVersion(String),
+    #[error("The pre-release label {label} is not one of the prerelease_channels")]
+    #[diagnostic(
+        code(step::unknown_prerelease_channel),
+        help("The prerelease_channels of the package are: {channels}"),
+        url("https://knope-dev.github.io/knope/config/packages.html#pre-release-channels")
+    )]
+    UnknownPrereleaseChannel { label: String, channels: String },
+    #[error("Invalid build metadata {0}")]
+    #[diagnostic(
+        code(step::invalid_build_metadata),
+        help(
+            "Build metadata can only contain letters, numbers, `-`, and `.` (like `42` or `abc123`). \
+            Without a `metadata`, the existing build metadata must be a number so it can be incremented."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/BumpVersion.html#build")
+    )]
+    InvalidBuildMetadata(String),
     #[error("No packages are ready to release")]
     #[diagnostic(
         code(step::no_release),
@@ -158,13 +383,60 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/packages.html#versioned_files")
     )]
     InvalidSemanticVersion(String),
-    #[error("Versioned files within the same package must have the same version. Found {0} which does not match {1}")]
+    #[error("The version_scheme {template} is invalid: {reason}")]
+    #[diagnostic(
+        code(step::invalid_version_scheme),
+        help("The template needs a `{{name}}` for each component, separated by something like `.`, including the components which the major, minor, and patch rules bump."),
+        url("https://knope-dev.github.io/knope/config/packages.html#version-schemes")
+    )]
+    InvalidVersionScheme { template: String, reason: String },
+    #[error("The version {version} doesn't match the version_scheme {template}")]
+    #[diagnostic(
+        code(step::version_scheme_mismatch),
+        help("Every versioned file of a package with a version_scheme must write the version the same way, like `1.2.3.0` for `{{major}}.{{minor}}.{{patch}}.{{build}}`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#version-schemes")
+    )]
+    VersionSchemeMismatch { version: String, template: String },
+    #[error("The versioned file {0} can't be written with a version_scheme")]
+    #[diagnostic(
+        code(step::version_scheme_unsupported),
+        help("This format only allows semantic versions (like `1.2.3`). Remove the version_scheme, or put this file in a package without one."),
+        url("https://knope-dev.github.io/knope/config/packages.html#version-schemes")
+    )]
+    VersionSchemeUnsupported(PathBuf),
+    #[error("Versioned files within the same package must have the same version. Found {0}")]
     #[diagnostic(
         code(step::inconsistent_versions),
         help("Manually update all versioned_files to have the correct version"),
-        url("https://knope-dev.github.io/knope/config/step/BumpVersion.html")
+        url("https://knope-dev.github.io/knope/config/step/VerifyVersions.html")
+    )]
+    InconsistentVersions(String),
+    #[error("Invalid template expression `{0}`: {1}")]
+    #[diagnostic(
+        code(step::invalid_template_expression),
+        help("Expressions look like {{{{ $variable | slug }}}}, see the docs for every available function"),
+        url("https://knope-dev.github.io/knope/config/templates.html")
+    )]
+    InvalidTemplateExpression(String, String),
+    #[error("Packages must all have the same version when using fixed versioning. {first} is at {first_version} but {second} is at {second_version}")]
+    #[diagnostic(
+        code(step::inconsistent_package_versions),
+        help("Manually update the versioned_files of every package to the same version, or use independent versioning"),
+        url("https://knope-dev.github.io/knope/config/packages.html#fixed-versioning")
+    )]
+    InconsistentPackageVersions {
+        first: String,
+        first_version: String,
+        second: String,
+        second_version: String,
+    },
+    #[error("Packages must all have the same pre-release label when using fixed versioning")]
+    #[diagnostic(
+        code(step::inconsistent_prerelease_labels),
+        help("Every package shares one version, so give them all the same prerelease_label (or none), or use independent versioning"),
+        url("https://knope-dev.github.io/knope/config/step/PrepareRelease.html#per-package-pre-release-labels")
     )]
-    InconsistentVersions(String, String),
+    InconsistentPrereleaseLabels,
     #[error("The versioned file {0} is not a supported format")]
     #[diagnostic(
         code(step::versioned_file_format),
@@ -172,6 +444,13 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
     )]
     VersionedFileFormat(PathBuf),
+    #[error("{0} is not the name of a supported format")]
+    #[diagnostic(
+        code(step::unknown_versioned_file_format),
+        help("The format of a versioned file must be the name of one of the supported files, like `VERSION` or `package.json`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    UnknownVersionedFileFormat(String),
     #[error("The file {0} was an incorrect format")]
     #[diagnostic(
         code(step::invalid_package_json),
@@ -189,13 +468,270 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
     )]
     InvalidPyProject(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_gradle_properties),
+        help("knope expects the gradle.properties file to have a `version` property, like `version=1.2.3`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidGradleProperties(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_build_gradle),
+        help("knope expects the Gradle build file to assign a string literal to `version` at the start of a line, like `version = \"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidBuildGradle(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_chart_yaml),
+        help("knope expects the Chart.yaml file to have a top-level `version` field."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidChartYaml(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_pom_xml),
+        help("knope expects the pom.xml file to have a `<version>` directly inside `<project>`. Modules which inherit their version from a parent are bumped along with the root pom.xml which lists them in `<modules>`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidPomXml(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_vcpkg_json),
+        help("knope expects the vcpkg.json file to have a `\"version\"`, `\"version-semver\"`, or `\"version-string\"` with a string value."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidVcpkgJson(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_conanfile),
+        help("knope expects the conanfile.py file to assign a string to the `version` attribute of the recipe, like `version = \"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidConanfile(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_deno_json),
+        help("knope expects deno.json, deno.jsonc, and jsr.json files to have a top-level `\"version\"` with a string value, like `\"version\": \"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidDenoJson(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_cmake_lists),
+        help("knope expects the CMakeLists.txt file to have a `project()` command with a `VERSION` argument, like `project(my_app VERSION 1.2.3)`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidCMakeLists(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_mix_exs),
+        help("knope expects the mix.exs file to have a `version: \"1.2.3\"` (or `version: @version` with `@version \"1.2.3\"`) in its `project` function."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidMixExs(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_pubspec),
+        help("knope expects the pubspec.yaml file to have a top-level `version` field."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidPubspec(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_setup_cfg),
+        help(
+            "knope expects the setup.cfg file to have a `version` in its `[metadata]` section. \
+            Versions read from somewhere else (like `attr:` or `file:`) are not supported."
+        ),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidSetupCfg(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_setup_py),
+        help("knope expects the setup.py file to pass a string literal as the `version` argument, like `version=\"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidSetupPy(PathBuf),
+    #[error("Could not find a __version__ in {0}")]
+    #[diagnostic(
+        code(step::invalid_python_version),
+        help("knope expects Python files in versioned_files (other than setup.py) to assign a string literal to `__version__`, like `__version__ = \"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidPythonVersion(PathBuf),
+    #[error("Could not find a VERSION in {0}")]
+    #[diagnostic(
+        code(step::invalid_version_rb),
+        help("knope expects version.rb files to assign a string literal to the `VERSION` constant, like `VERSION = \"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidVersionRb(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_version_file),
+        help("knope expects VERSION files to contain only the version (like `1.2.3`), optionally followed by a newline."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidVersionFile(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_dockerfile),
+        help("knope expects Dockerfiles to have a build argument like `ARG VERSION=1.2.3` or a label like `LABEL org.opencontainers.image.version=\"1.2.3\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidDockerfile(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_pkgbuild),
+        help("knope expects a PKGBUILD to assign the version to `pkgver` at the start of a line, like `pkgver=1.2.3`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidPkgbuild(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_rpm_spec),
+        help("knope expects an RPM spec file to have a `Version` tag with the version itself (not a macro), like `Version: 1.2.3`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidRpmSpec(PathBuf),
+    #[error("Could not find a packager for the %changelog of {0}")]
+    #[diagnostic(
+        code(step::missing_rpm_packager),
+        help("Every %changelog entry needs a packager, which knope copies from the latest entry. Add a `Packager` tag (like `Packager: Jane Doe <jane@example.com>`) for the first one."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    MissingRpmPackager(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_nix),
+        help("knope expects a Nix file to bind the version to a string, like `version = \"1.2.3\";`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidNix(PathBuf),
+    #[error("Could not add a release to {0}")]
+    #[diagnostic(
+        code(step::invalid_metainfo),
+        help("knope expects an AppStream metainfo file with a <component> element, which new releases are added to (in its <releases>)."),
+        url("https://knope-dev.github.io/knope/config/packages.html#appstream-metainfo")
+    )]
+    InvalidMetainfo(PathBuf),
+    #[error("Could not create an entry for {0}")]
+    #[diagnostic(
+        code(step::invalid_debian_changelog),
+        help("Every entry of a Debian changelog needs the name of the source package and a maintainer. Set `package` and `maintainer` in `debian_changelog`, or make sure the latest entry in the file has them."),
+        url("https://knope-dev.github.io/knope/config/packages.html#debian-changelogs")
+    )]
+    InvalidDebianChangelog(PathBuf),
+    #[error("Could not find a version in {0}")]
+    #[diagnostic(
+        code(step::invalid_gemspec),
+        help("knope expects the gemspec to assign a string literal to the `version` of the specification, like `spec.version = \"1.2.3\"`. If the version comes from a `VERSION` constant, add the version.rb file which defines it to versioned_files instead."),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidGemspec(PathBuf),
+    #[error("The version pattern {0} must contain {{version}} exactly once")]
+    #[diagnostic(
+        code(step::invalid_version_pattern),
+        help("Put {{version}} where the version goes, like `pattern = \"version: {{version}}\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html#custom-version-patterns")
+    )]
+    InvalidVersionPattern(String),
+    #[error("Could not find a version matching the pattern {1} in {0}")]
+    #[diagnostic(
+        code(step::version_pattern_not_found),
+        help("The text around {{version}} in the pattern must match the file exactly, and the version must be a semantic version."),
+        url("https://knope-dev.github.io/knope/config/packages.html#custom-version-patterns")
+    )]
+    VersionPatternNotFound(PathBuf, String),
+    #[error("The versioned file {0} can't be searched by key")]
+    #[diagnostic(
+        code(step::version_key_format),
+        help("Only TOML (`.toml`), JSON (`.json` or `.jsonc`), and YAML (`.yaml` or `.yml`) files can have a `key`. Use a `pattern` for other files."),
+        url("https://knope-dev.github.io/knope/config/packages.html#versions-by-key")
+    )]
+    VersionKeyFormat(PathBuf),
+    #[error("Could not find a version at the key {1} in {0}")]
+    #[diagnostic(
+        code(step::version_key_not_found),
+        help("Each part of the key (separated by `.`) must be a table, object, or mapping inside the one before it, and the last part must be a string."),
+        url("https://knope-dev.github.io/knope/config/packages.html#versions-by-key")
+    )]
+    VersionKeyNotFound(PathBuf, String),
+    #[error("Could not find a version between {1} and {2} in {0}")]
+    #[diagnostic(
+        code(step::version_markers_not_found),
+        help("Every start marker needs an end marker after it, and there must be a semantic version between them."),
+        url("https://knope-dev.github.io/knope/config/packages.html#versions-between-markers")
+    )]
+    VersionMarkersNotFound(PathBuf, String, String),
     #[error("The file {0} was an incorrect format")]
     #[diagnostic(
         code(step::invalid_cargo_toml),
-        help("knope expects the Cargo.toml file to have a `package.version` property. Workspace support is coming soon!"),
+        help("knope expects the Cargo.toml file to have a `package.version` property, or a `workspace.package.version` property for a workspace root"),
         url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
     )]
     InvalidCargoToml(PathBuf),
+    #[error("The file {0} was an incorrect format")]
+    #[diagnostic(
+        code(step::invalid_cargo_lock),
+        help("knope expects the Cargo.lock file to be generated by Cargo, with a `[[package]]` entry for each package"),
+        url("https://knope-dev.github.io/knope/config/packages.html#cargo_lock")
+    )]
+    InvalidCargoLock(PathBuf),
+    #[error("The file {0} was an incorrect format")]
+    #[diagnostic(
+        code(step::invalid_package_lock),
+        help("knope expects npm lockfiles next to a package.json to be JSON objects generated by npm"),
+        url("https://knope-dev.github.io/knope/config/packages.html#supported-formats-for-versioning")
+    )]
+    InvalidPackageLock(PathBuf),
+    #[error("{0} inherits its version from a Cargo workspace, but no workspace root was found")]
+    #[diagnostic(
+        code(step::cargo_workspace_not_found),
+        help("A `Cargo.toml` with `version.workspace = true` must be in a subdirectory of the workspace root, whose `Cargo.toml` has a `[workspace]` table"),
+        url("https://knope-dev.github.io/knope/config/packages.html#cargo-workspaces")
+    )]
+    CargoWorkspaceNotFound(PathBuf),
+    #[error("SMTP is not configured")]
+    #[diagnostic(
+        code(step::smtp_not_configured),
+        help("SMTP must be configured in order to call this step"),
+        url("https://knope-dev.github.io/knope/config/smtp.html")
+    )]
+    SmtpNotConfigured,
+    #[error("Sentry is not configured")]
+    #[diagnostic(
+        code(step::sentry_not_configured),
+        help("Sentry must be configured in order to call this step"),
+        url("https://knope-dev.github.io/knope/config/sentry.html")
+    )]
+    SentryNotConfigured,
+    #[error("Invalid email address {0}")]
+    #[diagnostic(
+        code(step::invalid_email_address),
+        help("Email addresses must be like `user@example.com` or `Name <user@example.com>`"),
+        url("https://knope-dev.github.io/knope/config/step/SendEmail.html")
+    )]
+    InvalidEmailAddress(String),
+    #[error("Could not build email")]
+    #[diagnostic(
+        code(step::email_error),
+        help("The email could not be constructed, this is probably a bug.")
+    )]
+    EmailError(#[source] lettre::error::Error),
+    #[error("Trouble communicating with the SMTP server")]
+    #[diagnostic(
+        code(step::smtp_error),
+        help(
+            "This occurred while sending an email. The problem could be invalid SMTP config, \
+            an incorrect password, or a network issue."
+        ),
+        url("https://knope-dev.github.io/knope/config/smtp.html")
+    )]
+    SmtpError(#[from] lettre::transport::smtp::Error),
     #[error("Trouble communicating with a remote API")]
     #[diagnostic(
         code(step::api_request_error),
@@ -245,6 +781,43 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/step/SelectIssueFromBranch.html")
     )]
     BadGitBranchName,
+    #[error("{0} is a protected branch")]
+    #[diagnostic(
+        code(step::protected_branch),
+        help("This branch matches one of the protected_branches in knope.toml, so it can only be modified after confirming, which isn't possible when running non-interactively."),
+        url("https://knope-dev.github.io/knope/config/config.html#protected-branches")
+    )]
+    ProtectedBranch(String),
+    #[error("Could not find the branch {0}")]
+    #[diagnostic(
+        code(step::branch_not_found),
+        help("Fetch the branch (like `git fetch origin {0}`) so knope can find which files the pull request changes."),
+        url("https://knope-dev.github.io/knope/config/step/CreatePullRequest.html#code-owners")
+    )]
+    BranchNotFound(String),
+    #[error("Releases are frozen: {0}")]
+    #[diagnostic(
+        code(step::release_frozen),
+        help("Today is in one of the release_freezes in knope.toml. Wait until it's over, or pass --override-freeze to release anyway."),
+        url("https://knope-dev.github.io/knope/config/config.html#release-freezes")
+    )]
+    ReleaseFrozen(String),
+    #[error("Missing required environment variables: {0}")]
+    #[diagnostic(
+        code(step::missing_environment_variables),
+        help(
+            "Steps in this workflow list these in `requires`, so they must be set before it runs."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/step.html#requires")
+    )]
+    MissingEnvironmentVariables(String),
+    #[error("Invalid release freeze: {0}")]
+    #[diagnostic(
+        code(step::invalid_release_freeze),
+        help("Dates must be like 2023-12-20 (for one year) or 12-20 (for every year), and a freeze needs both a start and an end date to be a range."),
+        url("https://knope-dev.github.io/knope/config/config.html#release-freezes")
+    )]
+    InvalidReleaseFreeze(String),
     #[error("Uncommitted changes")]
     #[diagnostic(
         code(step::uncommitted_changes),
@@ -286,6 +859,83 @@ pub(super) enum StepError {
         help("The command failed to execute. Try running it manually to get more information.")
     )]
     CommandError(std::process::ExitStatus),
+    #[error("Command did not finish within {0} seconds")]
+    #[diagnostic(
+        code(step::command_timeout),
+        help("The command and everything it started were stopped. Increase the `timeout` if it needs longer."),
+        url("https://knope-dev.github.io/knope/config/step/Command.html")
+    )]
+    CommandTimeout(u64),
+    #[error("Command was interrupted")]
+    #[diagnostic(
+        code(step::command_interrupted),
+        help("knope was asked to stop while running the command, so the command and everything it started were stopped too.")
+    )]
+    CommandInterrupted,
+    #[error("Could not verify the signature of tag {tag}")]
+    #[diagnostic(
+        code(step::unverified_tag),
+        help(
+            "git verify-tag failed with: {reason}\n\
+            Make sure the tag is signed with an SSH key listed in the allowed signers file."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/VerifyTag.html")
+    )]
+    UnverifiedTag { tag: String, reason: String },
+    #[error("Tag {0} does not exist")]
+    #[diagnostic(
+        code(step::tag_not_found),
+        help("Make sure the version is right, and fetch the tags from the remote with `git fetch --tags`."),
+        url("https://knope-dev.github.io/knope/introduction.html#yanking-a-release")
+    )]
+    TagNotFound(String),
+    #[error("Could not sign {path} with Sigstore")]
+    #[diagnostic(
+        code(step::signing_failed),
+        help(
+            "cosign failed with: {reason}\n\
+            Make sure cosign is installed and, in CI, that the job is allowed to request an identity token (like `id-token: write` on GitHub Actions)."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/SignRelease.html")
+    )]
+    SigningFailed { path: PathBuf, reason: String },
+    #[error("Could not publish to the AUR, `{command}` failed")]
+    #[diagnostic(
+        code(step::aur_command_failed),
+        help(
+            "The command failed with: {reason}\n\
+            Make sure makepkg and updpkgsums (from pacman-contrib) are installed, and that the repository can be pushed to the AUR."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/PublishAur.html")
+    )]
+    AurCommandFailed { command: String, reason: String },
+    #[error("Could not find anything to update in {0}")]
+    #[diagnostic(
+        code(step::invalid_manifest),
+        help(
+            "knope updates the tag of any source which has the tag of the previous release (like `tag: v1.2.3`) in a Flatpak manifest, \
+            and the `version` of a snapcraft.yaml. The manifest must be a JSON or YAML file."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/UpdateManifest.html")
+    )]
+    InvalidManifest(PathBuf),
+    #[error("Could not update the manifest, `{command}` failed")]
+    #[diagnostic(
+        code(step::manifest_command_failed),
+        help(
+            "The command failed with: {reason}\n\
+            Make sure the repository is a Git clone which can be pushed to."
+        ),
+        url("https://knope-dev.github.io/knope/config/step/UpdateManifest.html")
+    )]
+    ManifestCommandFailed { command: String, reason: String },
+    #[error("Tag {tag} already exists on {remote}")]
+    #[diagnostic(
+        code(step::tag_exists_on_remote),
+        help("Someone else released this version while this workflow was running. Pull the latest changes and tags (like `git pull --tags`), then run the workflow (including PrepareRelease) again to get the next version."),
+        url("https://knope-dev.github.io/knope/config/step/Release.html#checking-the-remote")
+    )]
+    TagExistsOnRemote { tag: String, remote: String },
     #[error("Failed to peel tag, could not proceed with processing commits.")]
     #[diagnostic(
         code(step::peel_tag_error),
@@ -310,6 +960,27 @@ pub(super) enum StepError {
     help("This step requires user input, but no user input was provided. Try running the step again."),
     )]
     UserInput(#[source] Option<std::io::Error>),
+    #[error("Cannot prompt for input in CI: {0}")]
+    #[diagnostic(
+        code(step::non_interactive),
+        help("Knope detected that it's running in CI, where nobody can answer prompts. Provide the value another way, like with an environment variable or by naming the workflow to run."),
+        url("https://knope-dev.github.io/knope/ci.html")
+    )]
+    NonInteractive(String),
+    #[error(
+        "Step {number} ({step}) can never succeed because there is no {missing} step before it"
+    )]
+    #[diagnostic(
+        code(step::misordered_step),
+        help("Add a {missing} step before the {step} step, or move the {step} step after one."),
+        url("https://knope-dev.github.io/knope/config/workflow.html")
+    )]
+    MisorderedStep {
+        /// The position of the step in the workflow, starting at 1.
+        number: usize,
+        step: &'static str,
+        missing: &'static str,
+    },
     #[error("PrepareRelease needs to occur before this step")]
     #[diagnostic(
         code(step::release_not_prepared),
@@ -317,6 +988,13 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/step/PrepareRelease.html")
     )]
     ReleaseNotPrepared,
+    #[error("The file {0} is not a valid record of prepared releases")]
+    #[diagnostic(
+        code(step::invalid_record),
+        help("The file should be written by a PrepareRelease step with `record` set, and not edited by hand."),
+        url("https://knope-dev.github.io/knope/config/step/Release.html")
+    )]
+    InvalidRecord(PathBuf),
     #[error("No packages are defined")]
     #[diagnostic(
         code(step::no_defined_packages),
@@ -330,6 +1008,34 @@ pub(super) enum StepError {
         help("Only one package in [package] is currently supported for this step.")
     )]
     TooManyPackages,
+    #[error("No package has a changelog")]
+    #[diagnostic(
+        code(step::no_changelog),
+        help("Set `changelog` for a package in knope.toml, like `changelog = \"CHANGELOG.md\"`."),
+        url("https://knope-dev.github.io/knope/config/packages.html")
+    )]
+    NoChangelog,
+    #[error("No package named {0}")]
+    #[diagnostic(
+        code(step::unknown_package),
+        help("The name must match one of the [packages.<name>] sections in knope.toml."),
+        url("https://knope-dev.github.io/knope/config/packages.html")
+    )]
+    UnknownPackage(String),
+    #[error("No package was selected")]
+    #[diagnostic(
+        code(step::package_not_selected),
+        help("There are multiple packages, so pick one with `--package <name>`."),
+        url("https://knope-dev.github.io/knope/introduction.html#yanking-a-release")
+    )]
+    PackageNotSelected,
+    #[error("Package has no name")]
+    #[diagnostic(
+        code(step::unnamed_package),
+        help("The PackageName variable requires packages to be defined as [packages.<name>]."),
+        url("https://knope-dev.github.io/knope/config/packages.html")
+    )]
+    UnnamedPackage,
     #[error("Conflicting packages definition")]
     #[diagnostic(
         code(step::conflicting_packages),
@@ -337,6 +1043,13 @@ pub(super) enum StepError {
         url("https://knope-dev.github.io/knope/config/packages.html")
     )]
     ConflictingPackages,
+    #[error("Could not read the project graph: {0}")]
+    #[diagnostic(
+        code(step::project_graph),
+        help("Packages are read from the project graph in the [project_graph] section of knope.toml. Make sure the tool can be run with npx, or point `file` at a graph which it exported."),
+        url("https://knope-dev.github.io/knope/config/packages.html#project-graphs")
+    )]
+    ProjectGraph(String),
     #[error("File {0} does not exist")]
     #[diagnostic(
         code(step::file_not_found),
@@ -369,5 +1082,206 @@ impl StepError {
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct PrepareRelease {
     /// If set, the user wants to create a pre-release version using the selected label.
-    pub(crate) prerelease_label: Option<String>,
+    pub(crate) prerelease_label: Option<PrereleaseLabel>,
+    /// The counter to use for the first pre-release of a new version (e.g., `1` for `rc.1`).
+    /// Defaults to 0.
+    pub(crate) prerelease_start: Option<u64>,
+    /// Only follow the first parent of merge commits when looking for commits, leaving out the
+    /// commits from merged branches. Defaults to false.
+    pub(crate) first_parent: Option<bool>,
+    /// Write the prepared releases, including the commits they were prepared from, to this file
+    /// so that a later [`Step::Release`] can use them.
+    pub(crate) record: Option<PathBuf>,
+}
+
+/// The inner content of a [`Step::Release`] step.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct ReleaseOptions {
+    /// Write a provenance statement for each release, attaching it to any GitHub release.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) provenance: bool,
+    /// Add an "Issues resolved" section to each GitHub release, linking to every Jira issue
+    /// referenced by its commits, grouped by issue type.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) jira_issues: bool,
+    /// A file written by [`Step::PrepareRelease`] to release from when there is no
+    /// `PrepareRelease` earlier in the workflow.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) record: Option<PathBuf>,
+    /// When release notes are too long for GitHub (and so are truncated), attach the full
+    /// notes to the GitHub release.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) full_notes_asset: bool,
+    /// Before creating any tags, check this Git remote (like `origin`) for a tag with the same
+    /// name, which means someone else already released that version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) remote: Option<String>,
+}
+
+/// The label of the pre-release versions created by [`PrepareRelease`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(untagged)]
+pub(crate) enum PrereleaseLabel {
+    /// Every package gets a pre-release with the same label.
+    All(String),
+    /// Each listed package (by name) gets a pre-release with its own label, while every other
+    /// package gets a stable release.
+    PerPackage(HashMap<String, String>),
+}
+
+impl PrereleaseLabel {
+    /// The label for the package named `name`, or `None` if it should get a stable release.
+    pub(crate) fn for_package(&self, name: Option<&str>) -> Option<&str> {
+        match self {
+            PrereleaseLabel::All(label) => Some(label),
+            PrereleaseLabel::PerPackage(labels) => {
+                name.and_then(|name| labels.get(name)).map(String::as_str)
+            }
+        }
+    }
+}
+
+/// The inner content of a [`Step::TransitionJiraIssue`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct TransitionJiraIssue {
+    /// The status to transition the current issue to.
+    pub(crate) status: String,
+    /// Values for any fields the transition requires (e.g., `resolution`), in the same shape
+    /// that the Jira API expects them.
+    pub(crate) fields: Option<serde_json::Map<String, serde_json::Value>>,
+    /// A comment to add to the issue as part of the transition.
+    pub(crate) comment: Option<String>,
+    /// A map of value-to-replace to [Variable][`crate::command::Variable`] to replace it with in
+    /// every string of `fields` and `comment`.
+    pub(crate) variables: Option<HashMap<String, command::Variable>>,
+}
+
+/// The inner content of a [`Step::CreatePullRequest`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CreatePullRequest {
+    /// The branch that the pull request should be merged into.
+    pub(crate) base: String,
+    /// The title of the pull request.
+    pub(crate) title: Template,
+    /// The body of the pull request.
+    pub(crate) body: Template,
+    /// GitHub users to request reviews from.
+    pub(crate) reviewers: Option<Vec<String>>,
+    /// GitHub teams (by slug) to request reviews from.
+    pub(crate) team_reviewers: Option<Vec<String>>,
+    /// GitHub users to assign to the pull request.
+    pub(crate) assignees: Option<Vec<String>>,
+    /// Labels to add to the pull request.
+    pub(crate) labels: Option<Vec<String>>,
+    /// Also request reviews from the owners (in CODEOWNERS) of every file the pull request changes.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) codeowners: bool,
+}
+
+/// The inner content of a [`Step::SendEmail`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SendEmail {
+    /// The addresses to send the email to.
+    pub(crate) to: Vec<String>,
+    /// The subject of the email.
+    pub(crate) subject: Template,
+    /// The plain-text body of the email.
+    pub(crate) body: Template,
+}
+
+/// The inner content of a [`Step::ApiDiff`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ApiDiff {
+    /// The command which prints the public API changes (e.g., `cargo public-api diff`), run once
+    /// for each prepared release.
+    pub(crate) command: String,
+    /// A map of value-to-replace to [Variable][`crate::command::Variable`] to replace it with,
+    /// where package-specific variables refer to the released package.
+    pub(crate) variables: Option<HashMap<String, command::Variable>>,
+    /// The shell to run `command` in, if not the default for this platform.
+    pub(crate) shell: Option<command::Shell>,
+    /// The title of the section added to the release notes, defaults to "Public API changes".
+    pub(crate) section: Option<String>,
+}
+
+/// The inner content of a [`Step::SignRelease`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct SignRelease {
+    /// Files (like built binaries) to sign and attach to every release, along with the tag.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) assets: Vec<PathBuf>,
+}
+
+/// The inner content of a [`Step::GenerateFile`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct GenerateFile {
+    /// The file to write, which is created or replaced.
+    pub(crate) path: PathBuf,
+    /// The file containing the template to render into `path`.
+    pub(crate) template: PathBuf,
+    /// A map of value-to-replace to [Variable][`crate::command::Variable`] to replace it with in
+    /// the template.
+    pub(crate) variables: Option<HashMap<String, command::Variable>>,
+}
+
+/// The inner content of a [`Step::PublishAur`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PublishAur {
+    /// A clone of the AUR repository (like `ssh://aur@aur.archlinux.org/my-tool.git`) to publish.
+    pub(crate) repository: PathBuf,
+    /// The package whose new version is published, only needed if there are multiple releases.
+    pub(crate) package: Option<String>,
+}
+
+/// The inner content of a [`Step::UpdateManifest`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct UpdateManifest {
+    /// A clone of the packaging repository (like one on Flathub) containing the manifest.
+    pub(crate) repository: PathBuf,
+    /// The path of the manifest within `repository`.
+    pub(crate) manifest: PathBuf,
+    /// The package whose new version is published, only needed if there are multiple releases.
+    pub(crate) package: Option<String>,
+    /// Open a pull request for the update instead of pushing it to the current branch.
+    pub(crate) pull_request: Option<ManifestPullRequest>,
+}
+
+/// Where [`Step::UpdateManifest`] opens a pull request.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ManifestPullRequest {
+    /// The user or organization that owns the packaging repository on GitHub.
+    pub(crate) owner: String,
+    /// The name of the packaging repository on GitHub.
+    pub(crate) repo: String,
+    /// The branch that the pull request should be merged into.
+    pub(crate) base: String,
+}
+
+/// The inner content of a [`Step::TransitionReleasedIssues`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct TransitionReleasedIssues {
+    /// The status to transition every referenced Jira issue to.
+    pub(crate) status: Option<String>,
+    /// Close every referenced GitHub issue.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) close_github_issues: bool,
+    /// How many issues to transition before pausing, defaults to 10.
+    pub(crate) batch_size: Option<usize>,
+    /// How many seconds to pause between batches, defaults to 1.
+    pub(crate) delay: Option<u64>,
+}
+
+/// The inner content of a [`Step::VerifyTag`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct VerifyTag {
+    /// The path to an SSH allowed signers file (the format used by `ssh-keygen -Y verify`).
+    pub(crate) allowed_signers: PathBuf,
+}
+
+/// The inner content of a [`Step::CreateSentryRelease`] step.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct CreateSentryRelease {
+    /// The name of the release in Sentry, defaults to `<package name>@<version>` (using the
+    /// project name for a single package).
+    pub(crate) version: Option<Template>,
 }
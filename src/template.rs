@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::command::{replace_variables, replace_variables_for_package, Variable};
+use crate::releases::{head_commit_id, Package};
+use crate::step::StepError;
+use crate::State;
+
+/// A string in config which can have [`Variable`]s substituted into it, just like the `command`
+/// of a [`crate::step::Step::Command`]. Any `{{ expression }}` in it is evaluated too, see
+/// [`render_expressions`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct Template {
+    /// The text to render, containing any variable keys you wish to replace.
+    pub(crate) template: String,
+    /// A map of value-to-replace to [`Variable`] to replace it with.
+    pub(crate) variables: Option<HashMap<String, Variable>>,
+}
+
+impl Template {
+    /// Substitute all `variables` into `template` using the current `state`.
+    pub(crate) fn render(self, state: &State) -> Result<String, StepError> {
+        match self.variables {
+            Some(variables) => replace_variables(&self.template, variables, state),
+            None => render_expressions(&self.template, &HashMap::new()),
+        }
+    }
+
+    /// Like [`Template::render`], but any package-specific variables refer to `package`.
+    pub(crate) fn render_for_package(
+        self,
+        state: &State,
+        package: &Package,
+    ) -> Result<String, StepError> {
+        match self.variables {
+            Some(variables) => {
+                replace_variables_for_package(&self.template, variables, state, Some(package))
+            }
+            None => render_expressions(&self.template, &HashMap::new()),
+        }
+    }
+}
+
+/// Render `text`, evaluating every `{{ expression }}` in it and replacing every other occurrence of
+/// a key of `values` with its value.
+///
+/// An expression is a key of `values`, a quoted string, or a function call like `date("%Y.%m")`,
+/// optionally followed by filters (separated by `|`) like `slug`, `upper`, `lower`, or
+/// `truncate(n)`. For example, `{{ $branch | slug | truncate(20) }}`.
+///
+/// Other tools use `{{ }}` too (e.g., `${{ secrets.TOKEN }}` in GitHub Actions or
+/// `docker inspect -f '{{.Id}}'`), so any `{{` which isn't closed or whose contents aren't a valid
+/// expression is left as-is (apart from replacing keys of `values`) instead of being an error.
+pub(crate) fn render_expressions(
+    text: &str,
+    values: &HashMap<String, String>,
+) -> Result<String, StepError> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&replace_keys(&rest[..start], values));
+        let after_start = &rest[start + 2..];
+        let end = if let Some(end) = after_start.find("}}") {
+            end
+        } else {
+            rest = &rest[start..];
+            break;
+        };
+        match evaluate(after_start[..end].trim(), values) {
+            Ok(value) => rendered.push_str(&value),
+            Err(StepError::InvalidTemplateExpression(..)) => {
+                rendered.push_str(&replace_keys(&rest[start..start + end + 4], values));
+            }
+            Err(err) => return Err(err),
+        }
+        rest = &after_start[end + 2..];
+    }
+    rendered.push_str(&replace_keys(rest, values));
+    Ok(rendered)
+}
+
+/// The bare key replacement used outside of expressions.
+fn replace_keys(text: &str, values: &HashMap<String, String>) -> String {
+    values.iter().fold(text.to_string(), |text, (key, value)| {
+        text.replace(key, value)
+    })
+}
+
+fn evaluate(expression: &str, values: &HashMap<String, String>) -> Result<String, StepError> {
+    let invalid =
+        |reason: String| StepError::InvalidTemplateExpression(expression.to_string(), reason);
+    let mut stages = split_outside_quotes(expression, '|').into_iter();
+    let first = stages.next().unwrap_or_default();
+    let mut value = if let Some(value) = values.get(first) {
+        value.clone()
+    } else if let Some(text) = quoted(first) {
+        text.to_string()
+    } else {
+        let (name, arguments) = parse_call(first).map_err(invalid)?;
+        match (name, arguments.as_slice()) {
+            ("date", [Argument::Text(format)]) => format_date(now(), format),
+            ("date", _) => return Err(invalid(String::from("`date` takes one format string"))),
+            ("commit", []) => head_commit_id()?,
+            ("commit", _) => return Err(invalid(String::from("`commit` takes no arguments"))),
+            _ => {
+                return Err(invalid(format!(
+                    "`{first}` is not a variable or a function"
+                )))
+            }
+        }
+    };
+    for stage in stages {
+        let (name, arguments) = parse_call(stage).map_err(invalid)?;
+        value = match (name, arguments.as_slice()) {
+            ("slug", []) => slug(&value),
+            ("upper", []) => value.to_uppercase(),
+            ("lower", []) => value.to_lowercase(),
+            ("truncate", [Argument::Number(length)]) => value.chars().take(*length).collect(),
+            ("truncate", _) => {
+                return Err(invalid(String::from("`truncate` takes one whole number")));
+            }
+            _ => return Err(invalid(format!("`{stage}` is not a filter"))),
+        };
+    }
+    Ok(value)
+}
+
+/// An argument to a function in an expression.
+#[derive(Debug, Eq, PartialEq)]
+enum Argument<'a> {
+    Text(&'a str),
+    Number(usize),
+}
+
+/// Parse something like `truncate(10)` into its name and arguments. The parentheses are optional
+/// when there are no arguments.
+fn parse_call(call: &str) -> Result<(&str, Vec<Argument<'_>>), String> {
+    let (name, arguments) = match call.split_once('(') {
+        Some((name, arguments)) => {
+            let arguments = arguments
+                .strip_suffix(')')
+                .ok_or_else(|| format!("`{call}` is missing a closing `)`"))?;
+            (name.trim(), arguments)
+        }
+        None => (call, ""),
+    };
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+        return Err(format!("`{call}` is not a variable or a function"));
+    }
+    let arguments = split_outside_quotes(arguments, ',')
+        .into_iter()
+        .filter(|argument| !argument.is_empty())
+        .map(|argument| {
+            if let Some(text) = quoted(argument) {
+                Ok(Argument::Text(text))
+            } else {
+                argument
+                    .parse()
+                    .map(Argument::Number)
+                    .map_err(|_| format!("`{argument}` is not a quoted string or a number"))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((name, arguments))
+}
+
+/// Split `text` on `separator` (ignoring any inside of double quotes), trimming each part.
+fn split_outside_quotes(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut part_start = 0;
+    for (index, c) in text.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == separator && !in_quotes {
+            parts.push(text[part_start..index].trim());
+            part_start = index + c.len_utf8();
+        }
+    }
+    parts.push(text[part_start..].trim());
+    parts
+}
+
+fn quoted(text: &str) -> Option<&str> {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+}
+
+/// Lowercase `text` and replace every run of characters other than letters and numbers with `-`.
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Format `seconds` since the Unix epoch (in UTC) using `format`, which supports `%Y`, `%m`,
+/// `%d`, `%H`, `%M`, `%S`, `%a` (like `Mon`), `%b` (like `Jan`), and `%%`. Anything else is left
+/// as-is.
+pub(crate) fn format_date(seconds: u64, format: &str) -> String {
+    let (days, seconds_of_day) = (seconds / 86_400, seconds % 86_400);
+    let (year, month, day) = civil_date(days);
+
+    let mut formatted = String::with_capacity(format.len() * 2);
+
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            formatted.push(c);
+            continue;
+        }
+        let (value, width) = match chars.next() {
+            Some('Y') => (year, 4),
+            Some('m') => (month, 2),
+            Some('d') => (day, 2),
+            Some('H') => (seconds_of_day / 3600, 2),
+            Some('M') => (seconds_of_day % 3600 / 60, 2),
+            Some('S') => (seconds_of_day % 60, 2),
+            Some('a') => {
+                #[allow(clippy::cast_possible_truncation)]
+                formatted.push_str(WEEKDAYS[(days % 7) as usize]);
+                continue;
+            }
+            Some('b') => {
+                #[allow(clippy::cast_possible_truncation)]
+                formatted.push_str(MONTHS[(month - 1) as usize]);
+                continue;
+            }
+            Some('%') | None => {
+                formatted.push('%');
+                continue;
+            }
+            Some(other) => {
+                formatted.push('%');
+                formatted.push(other);
+                continue;
+            }
+        };
+        // Writing to a `String` can't fail
+        write!(formatted, "{value:0width$}").ok();
+    }
+    formatted
+}
+
+/// Convert `days` since the Unix epoch to a civil `(year, month, day)`, see
+/// <http://howardhinnant.github.io/date_algorithms.html>
+pub(crate) fn civil_date(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test_render_expressions {
+    use super::*;
+
+    fn values() -> HashMap<String, String> {
+        let mut values = HashMap::new();
+        values.insert(String::from("$branch"), String::from("42-Fix the Thing!"));
+        values.insert(String::from("$version"), String::from("1.2.3"));
+        values
+    }
+
+    #[test]
+    fn bare_keys() {
+        let rendered = render_expressions("Release $version", &values()).unwrap();
+        assert_eq!(rendered, "Release 1.2.3");
+    }
+
+    #[test]
+    fn filters() {
+        let rendered = render_expressions(
+            "{{ $branch | slug }} {{$branch|upper|truncate(5)}} {{ \"A|B\" | lower }}",
+            &values(),
+        )
+        .unwrap();
+        assert_eq!(rendered, "42-fix-the-thing 42-FI a|b");
+    }
+
+    #[test]
+    fn keys_outside_of_expressions() {
+        let rendered = render_expressions("v$version-{{ $branch | slug }}", &values()).unwrap();
+        assert_eq!(rendered, "v1.2.3-42-fix-the-thing");
+    }
+
+    #[test]
+    fn date() {
+        let rendered = render_expressions("{{ date(\"%Y\") }}", &HashMap::new()).unwrap();
+        assert_eq!(rendered, format_date(now(), "%Y"));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            format_date(1_709_210_096, "%Y.%m.%d %H:%M:%S %% %q"),
+            "2024.02.29 12:34:56 % %q"
+        );
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(format_date(1_709_210_096, "%a, %d %b"), "Thu, 29 Feb");
+    }
+
+    #[test]
+    fn keeps_unknown_function() {
+        let rendered = render_expressions("{{ $version | reverse }}", &values()).unwrap();
+        assert_eq!(rendered, "{{ 1.2.3 | reverse }}");
+    }
+
+    #[test]
+    fn keeps_other_braces() {
+        let rendered =
+            render_expressions("docker inspect -f '{{.Id}}' app:$version", &values()).unwrap();
+        assert_eq!(rendered, "docker inspect -f '{{.Id}}' app:1.2.3");
+    }
+
+    #[test]
+    fn keeps_unclosed() {
+        let rendered =
+            render_expressions("echo {{ $version | upper }} {{ $version", &values()).unwrap();
+        assert_eq!(rendered, "echo 1.2.3 {{ 1.2.3");
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use semver::Version;
+use serde::Deserialize;
+
+/// The latest release of knope itself, which is public so no token is needed.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/knope-dev/knope/releases/latest";
+
+/// How long to wait for GitHub, since a slow update check shouldn't hold up a finished run.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize)]
+struct LatestRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Print a hint if there's a newer release of knope than the one running. This is the only
+/// request made, and any problem with it (like being offline) is silently ignored.
+pub(crate) fn check() {
+    let latest: LatestRelease = match ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .get(LATEST_RELEASE_URL)
+        .call()
+        .ok()
+        .and_then(|response| response.into_json().ok())
+    {
+        Some(latest) => latest,
+        None => return,
+    };
+    if let Some(version) = newer_version(&latest.tag_name, env!("CARGO_PKG_VERSION")) {
+        println!(
+            "knope {version} is available (you have {current}), see {url}",
+            current = env!("CARGO_PKG_VERSION"),
+            url = latest.html_url
+        );
+    }
+}
+
+/// The version in `tag_name` (like `v0.7.0`), if it's newer than `current`.
+fn newer_version(tag_name: &str, current: &str) -> Option<Version> {
+    let latest = Version::parse(tag_name.trim_start_matches(|c: char| !c.is_ascii_digit())).ok()?;
+    let current = Version::parse(current).ok()?;
+    (latest > current).then_some(latest)
+}
+
+#[cfg(test)]
+mod test_newer_version {
+    use super::*;
+
+    #[test]
+    fn newer() {
+        assert_eq!(
+            newer_version("v0.7.0", "0.6.0"),
+            Some(Version::new(0, 7, 0))
+        );
+        assert_eq!(
+            newer_version("knope/v1.0.0", "0.6.0"),
+            Some(Version::new(1, 0, 0))
+        );
+    }
+
+    #[test]
+    fn not_newer() {
+        assert_eq!(newer_version("v0.6.0", "0.6.0"), None);
+        assert_eq!(newer_version("v0.5.1", "0.6.0"), None);
+        assert_eq!(newer_version("v0.7.0-rc.0", "0.7.0"), None);
+    }
+
+    #[test]
+    fn not_a_version() {
+        assert_eq!(newer_version("nightly", "0.6.0"), None);
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::state::RunType;
-use crate::step::{Step, StepError};
+use crate::step::{ConfiguredStep, ReleaseOptions, Step, StepError};
 use crate::State;
 
 /// A workflow is basically the state machine to run for a single execution of knope.
@@ -15,17 +15,138 @@ use crate::State;
 pub(crate) struct Workflow {
     /// The display name of this Workflow. This is what you'll see when you go to select it.
     pub(crate) name: String,
-    /// A list of [`Step`]s to execute in order, stopping if any step fails.
-    pub(crate) steps: Vec<Step>,
+    /// A list of [`crate::step::Step`]s to execute in order, stopping if any step fails.
+    pub(crate) steps: Vec<ConfiguredStep>,
 }
 
 impl Workflow {
     /// Set `prerelease_label` for any steps that are `PrepareRelease` steps.
     pub(crate) fn set_prerelease_label(&mut self, prerelease_label: &str) {
         for step in &mut self.steps {
-            step.set_prerelease_label(prerelease_label);
+            step.step.set_prerelease_label(prerelease_label);
         }
     }
+
+    /// Remove every step matching one of `skip` and, if `only` is not empty, every step which
+    /// doesn't match one of `only`. Steps match by their `name` or any of their `tags`.
+    ///
+    /// # Errors
+    ///
+    /// If any of `skip` or `only` doesn't match any step, it is returned, since it's probably a
+    /// typo.
+    pub(crate) fn select_steps(&mut self, skip: &[String], only: &[String]) -> Result<(), String> {
+        if let Some(unknown) = skip
+            .iter()
+            .chain(only)
+            .find(|name_or_tag| !self.steps.iter().any(|step| step.matches(name_or_tag)))
+        {
+            return Err(unknown.clone());
+        }
+        self.steps.retain(|step| {
+            !skip.iter().any(|name_or_tag| step.matches(name_or_tag))
+                && (only.is_empty() || only.iter().any(|name_or_tag| step.matches(name_or_tag)))
+        });
+        Ok(())
+    }
+
+    /// Remove every step whose `platforms` don't include the current one.
+    pub(crate) fn remove_other_platforms(&mut self) {
+        self.steps.retain(ConfiguredStep::runs_here);
+    }
+
+    /// Whether any step releases something, and so can't run during a release freeze.
+    pub(crate) fn releases(&self) -> bool {
+        self.steps.iter().any(|step| {
+            matches!(
+                step.step,
+                Step::Release(_)
+                    | Step::CreateSentryRelease(_)
+                    | Step::SignRelease(_)
+                    | Step::PublishAur(_)
+                    | Step::UpdateManifest(_)
+            )
+        })
+    }
+
+    /// Check that every environment variable in the `requires` of any step is set, so that a
+    /// workflow doesn't fail halfway through for want of a credential.
+    ///
+    /// # Errors
+    ///
+    /// [`StepError::MissingEnvironmentVariables`] listing every one which isn't set.
+    pub(crate) fn check_requirements(&self) -> Result<(), StepError> {
+        let missing = self
+            .steps
+            .iter()
+            .flat_map(|step| &step.requires)
+            .unique()
+            .filter(|variable| std::env::var_os(variable).map_or(true, |value| value.is_empty()))
+            .join(", ");
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(StepError::MissingEnvironmentVariables(missing))
+        }
+    }
+
+    /// Check that every step comes after the steps it depends on, like `SwitchBranches` after
+    /// selecting an issue, without running anything.
+    fn lint(&self) -> Result<(), StepError> {
+        const SELECT_ISSUE: &str = "SelectJiraIssue, SelectGitHubIssue, or SelectIssueFromBranch";
+        let mut issue_selected = false;
+        let mut release_prepared = false;
+        let mut released = false;
+        for (index, configured_step) in self.steps.iter().enumerate() {
+            let misordered = match &configured_step.step {
+                Step::SelectJiraIssue { .. }
+                | Step::SelectGitHubIssue { .. }
+                | Step::SelectIssueFromBranch { .. } => {
+                    issue_selected = true;
+                    None
+                }
+                Step::PrepareRelease(_) => {
+                    release_prepared = true;
+                    None
+                }
+                Step::TransitionJiraIssue(_) if !issue_selected => {
+                    Some(("TransitionJiraIssue", SELECT_ISSUE))
+                }
+                Step::SwitchBranches if !issue_selected => Some(("SwitchBranches", SELECT_ISSUE)),
+                Step::Release(ReleaseOptions { record: None, .. }) if !release_prepared => {
+                    Some(("Release", "PrepareRelease"))
+                }
+                Step::Release(_) => {
+                    released = true;
+                    None
+                }
+                Step::SignRelease(_) if !released => Some(("SignRelease", "Release")),
+                Step::CreateSentryRelease(_) if !release_prepared => {
+                    Some(("CreateSentryRelease", "PrepareRelease"))
+                }
+                Step::TransitionReleasedIssues(_) if !release_prepared => {
+                    Some(("TransitionReleasedIssues", "PrepareRelease"))
+                }
+                _ => None,
+            };
+            if let Some((step, missing)) = misordered {
+                return Err(StepError::MisorderedStep {
+                    number: index + 1,
+                    step,
+                    missing,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check `workflow` for steps which can never succeed because of the order they're in, so that
+/// problems are reported before any step runs.
+pub(crate) fn lint(workflow: &Workflow) -> Result<(), Error> {
+    workflow.lint().map_err(|err| Error {
+        name: workflow.name.clone(),
+        inner: [err],
+    })
 }
 
 /// A collection of errors from running with the `--validate` option.
@@ -45,7 +166,7 @@ pub struct Error {
     inner: [StepError; 1],
 }
 
-/// Run a series of [`Step`], each of which updates `state`.
+/// Run a series of [`ConfiguredStep`], each of which updates `state`.
 pub(crate) fn run(workflow: Workflow, mut state: RunType) -> Result<(), Error> {
     for step in workflow.steps {
         state = match step.run(state) {
@@ -69,6 +190,12 @@ pub(crate) fn validate(
     let errors = workflows
         .into_iter()
         .filter_map(|workflow| {
+            if let Err(err) = workflow.lint() {
+                return Some(Error {
+                    name: workflow.name,
+                    inner: [err],
+                });
+            }
             run(
                 workflow,
                 RunType::DryRun {
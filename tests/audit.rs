@@ -0,0 +1,93 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Set up a repo with two releases, referencing some Jira issues along the way.
+fn arrange(temp_path: &Path, source_path: &Path) {
+    init(temp_path);
+    commit(temp_path, "feat: First feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "fix: PROJ-12 A bug");
+    commit(
+        temp_path,
+        "feat: Another feature\n\nRefs PROJ-12 and PROJ-13",
+    );
+    tag(temp_path, "v1.1.0");
+    commit(temp_path, "feat: Unreleased feature PROJ-14");
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+}
+
+/// List what went into a release since the previous one, as Markdown.
+#[test]
+fn audit_markdown() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/audit");
+    arrange(temp_path, source_path);
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("audit")
+        .arg("1.1.0")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_matches_path(source_path.join("output.md"));
+}
+
+/// List what went into the first release, as JSON.
+#[test]
+fn audit_json() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/audit");
+    arrange(temp_path, source_path);
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("audit")
+        .arg("1.0.0")
+        .arg("--json")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_matches_path(source_path.join("output.json"));
+}
+
+/// A version that was never released can't be audited.
+#[test]
+fn audit_unknown_version() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/audit");
+    arrange(temp_path, source_path);
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("audit")
+        .arg("2.0.0")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_matches_path(source_path.join("unknown_version_output.txt"));
+}
@@ -17,9 +17,14 @@ mod git_repo_helpers;
 #[case("bump-pre", "1.2.3-rc.0", "1.2.4-rc.0")]
 #[case("bump-pre", "1.2.4-rc.0", "1.2.4-rc.1")]
 #[case("bump-release", "1.2.4-rc.0", "1.2.4")]
+#[case("bump-promote", "1.2.4-rc.0", "1.2.4")]
 #[case("bump-patch", "1.2.3", "1.2.4")]
 #[case("bump-minor", "1.2.3", "1.3.0")]
 #[case("bump-major", "1.2.3", "2.0.0")]
+#[case("bump-build", "1.2.3", "1.2.3+1")]
+#[case("bump-build", "1.2.3+41", "1.2.3+42")]
+#[case("bump-patch", "1.2.3+41", "1.2.4")]
+#[case("set-version", "1.2.3", "1.1.0")]
 fn bump_version(
     #[case] workflow: &str,
     #[case] current_version: &str,
@@ -164,3 +169,883 @@ fn multiple_packages_pre(#[case] workflow: &str) {
         );
     }
 }
+
+/// Bump the version of a package along with its entry in `Cargo.lock`.
+#[test]
+fn cargo_lock() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/cargo_lock");
+
+    for file in ["knope.toml", "Cargo.toml", "Cargo.lock"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+
+    for file in ["Cargo.toml", "Cargo.lock"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a JavaScript package along with its `package-lock.json`.
+#[test]
+fn package_lock() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/package_lock");
+
+    for file in ["knope.toml", "package.json", "package-lock.json"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+
+    for file in ["package.json", "package-lock.json"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a Python package which uses setuptools instead of `pyproject.toml`.
+#[test]
+fn setuptools() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/setuptools");
+
+    for file in ["knope.toml", "setup.cfg", "setup.py"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+
+    for file in ["setup.cfg", "setup.py"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the `__version__` in a Python module along with `pyproject.toml`.
+#[test]
+fn python_module() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/python_module");
+
+    std::fs::create_dir(temp_path.join("mypkg")).unwrap();
+    for file in ["knope.toml", "pyproject.toml", "mypkg/__init__.py"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+
+    assert_eq_path(
+        source_path.join("EXPECTED_pyproject.toml"),
+        read_to_string(temp_path.join("pyproject.toml")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("EXPECTED___init__.py"),
+        read_to_string(temp_path.join("mypkg/__init__.py")).unwrap(),
+    );
+}
+
+/// Bump the version of a Helm chart, along with its `appVersion`.
+#[test]
+fn helm_chart() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/helm_chart");
+
+    for file in ["knope.toml", "Chart.yaml"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_Chart.yaml"),
+        read_to_string(temp_path.join("Chart.yaml")).unwrap(),
+    );
+}
+
+/// Bump the version of a Flutter app's `pubspec.yaml`, incrementing its build number.
+#[test]
+fn pubspec() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/pubspec");
+
+    for file in ["knope.toml", "pubspec.yaml"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_pubspec.yaml"),
+        read_to_string(temp_path.join("pubspec.yaml")).unwrap(),
+    );
+}
+
+/// Bump the version of an Elixir project, which is set from a module attribute in `mix.exs`.
+#[test]
+fn mix_exs() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/mix_exs");
+
+    for file in ["knope.toml", "mix.exs"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_mix.exs"),
+        read_to_string(temp_path.join("mix.exs")).unwrap(),
+    );
+}
+
+/// Bump the `VERSION` of the `project()` in a `CMakeLists.txt`.
+#[test]
+fn cmake() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/cmake");
+
+    for file in ["knope.toml", "CMakeLists.txt"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_CMakeLists.txt"),
+        read_to_string(temp_path.join("CMakeLists.txt")).unwrap(),
+    );
+}
+
+/// Bump the version of a Gradle project in both `gradle.properties` and `build.gradle.kts`, without
+/// touching the versions of plugins.
+#[test]
+fn gradle() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/gradle");
+
+    for file in ["knope.toml", "gradle.properties", "build.gradle.kts"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["gradle.properties", "build.gradle.kts"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a Deno package in both `deno.jsonc` (keeping its comments) and `jsr.json`.
+#[test]
+fn deno() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/deno");
+
+    for file in ["knope.toml", "deno.jsonc", "jsr.json"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["deno.jsonc", "jsr.json"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a C++ library in both `vcpkg.json` (resetting its `port-version`) and
+/// `conanfile.py`.
+#[test]
+fn vcpkg_and_conan() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/vcpkg_and_conan");
+
+    for file in ["knope.toml", "vcpkg.json", "conanfile.py"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["vcpkg.json", "conanfile.py"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a Ruby gem in both `version.rb` and its gemspec.
+#[test]
+fn ruby() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/ruby");
+
+    std::fs::create_dir_all(temp_path.join("lib/my_gem")).unwrap();
+    for file in ["knope.toml", "my_gem.gemspec", "lib/my_gem/version.rb"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_version.rb"),
+        read_to_string(temp_path.join("lib/my_gem/version.rb")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("EXPECTED_my_gem.gemspec"),
+        read_to_string(temp_path.join("my_gem.gemspec")).unwrap(),
+    );
+}
+
+/// Bump the version of a multi-module Maven project, including the parent version of each module.
+#[test]
+fn maven() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/maven");
+
+    for dir in ["core", "cli"] {
+        std::fs::create_dir(temp_path.join(dir)).unwrap();
+    }
+    for file in ["knope.toml", "pom.xml", "core/pom.xml", "cli/pom.xml"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for dir in ["", "core", "cli"] {
+        assert_eq_path(
+            source_path.join(dir).join("EXPECTED_pom.xml"),
+            read_to_string(temp_path.join(dir).join("pom.xml")).unwrap(),
+        );
+    }
+}
+
+/// Bump the version in a `VERSION` file, and in a file with another name which has the same format.
+#[test]
+fn plain() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/plain");
+
+    for file in ["knope.toml", "VERSION", "version.txt"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["VERSION", "version.txt"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version wherever custom patterns match in a file which isn't a supported format, leaving
+/// other versions in it alone.
+#[test]
+fn pattern() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/pattern");
+
+    for file in ["knope.toml", "Cargo.toml", "README.md"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["Cargo.toml", "README.md"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version at a key in TOML, JSON, and YAML files which aren't otherwise supported,
+/// keeping the rest of each file as-is.
+#[test]
+fn key() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/key");
+
+    for file in ["knope.toml", "config.toml", "manifest.json", "values.yaml"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["config.toml", "manifest.json", "values.yaml"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump every version between markers in a documentation file, leaving other versions in it alone.
+#[test]
+fn markers() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/markers");
+
+    for file in ["knope.toml", "VERSION", "README.md"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["VERSION", "README.md"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the version of a `Dockerfile`, in both a build argument and a label.
+#[test]
+fn dockerfile() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/dockerfile");
+
+    for file in ["knope.toml", "Dockerfile", "Dockerfile.prod"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    for file in ["Dockerfile", "Dockerfile.prod"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{file}")),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Bump the `pkgver` of an Arch Linux `PKGBUILD`, which resets the `pkgrel`.
+#[test]
+fn pkgbuild() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/pkgbuild");
+
+    for file in ["knope.toml", "PKGBUILD"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_PKGBUILD"),
+        read_to_string(temp_path.join("PKGBUILD")).unwrap(),
+    );
+}
+
+/// Bump the `version` binding of a Nix flake.
+#[test]
+fn nix() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/nix");
+
+    for file in ["knope.toml", "flake.nix"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_flake.nix"),
+        read_to_string(temp_path.join("flake.nix")).unwrap(),
+    );
+}
+
+/// Bump a four-part version, which is written using the `version_scheme` of the package.
+#[test]
+fn version_scheme() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/version_scheme");
+
+    for file in ["knope.toml", "VERSION"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"))
+        .stderr_eq("");
+    actual_assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_VERSION"),
+        read_to_string(temp_path.join("VERSION")).unwrap(),
+    );
+}
+
+/// A `version_scheme` can't be used with a format that only allows semantic versions, like
+/// `Cargo.toml`.
+#[test]
+fn version_scheme_semver_only_format() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.2.3");
+    let source_path = Path::new("tests/bump_version/version_scheme");
+
+    std::fs::copy(
+        source_path.join("semver_only_knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    for file in ["VERSION", "Cargo.toml"] {
+        std::fs::copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("bump-minor")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_matches_path(source_path.join("semver_only_output.txt"));
+    assert_eq_path(
+        source_path.join("Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+}
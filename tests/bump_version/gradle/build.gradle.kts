@@ -0,0 +1,11 @@
+plugins {
+    id("org.jetbrains.kotlin.jvm") version "1.7.20"
+    application
+}
+
+group = "org.example"
+version = "1.2.3"
+
+repositories {
+    mavenCentral()
+}
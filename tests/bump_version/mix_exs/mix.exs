@@ -0,0 +1,20 @@
+defmodule MyApp.MixProject do
+  use Mix.Project
+
+  @version "1.2.3"
+
+  def project do
+    [
+      app: :my_app,
+      version: @version,
+      elixir: "~> 1.14",
+      deps: deps()
+    ]
+  end
+
+  defp deps do
+    [
+      {:jason, "~> 1.4"}
+    ]
+  end
+end
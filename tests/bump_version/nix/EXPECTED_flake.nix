@@ -0,0 +1,18 @@
+{
+  description = "A tool";
+
+  inputs.nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+
+  outputs = { self, nixpkgs }:
+    let
+      pkgs = nixpkgs.legacyPackages.x86_64-linux;
+    in
+    {
+      packages.x86_64-linux.default = pkgs.rustPlatform.buildRustPackage {
+        pname = "my-tool";
+        version = "1.3.0";
+        src = ./.;
+        cargoLock.lockFile = ./Cargo.lock;
+      };
+    };
+}
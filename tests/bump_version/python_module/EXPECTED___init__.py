@@ -0,0 +1,3 @@
+"""My package."""
+
+__version__ = "1.3.0"
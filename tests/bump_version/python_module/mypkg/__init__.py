@@ -0,0 +1,3 @@
+"""My package."""
+
+__version__ = "1.2.3"
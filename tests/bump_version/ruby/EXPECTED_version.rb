@@ -0,0 +1,5 @@
+# frozen_string_literal: true
+
+module MyGem
+  VERSION = "1.3.0"
+end
@@ -0,0 +1,6 @@
+from setuptools import setup
+
+setup(
+    name="mypkg",
+    version="1.3.0",
+)
@@ -0,0 +1,8 @@
+from conan import ConanFile
+
+
+class MyLibConan(ConanFile):
+    name = "my-lib"
+    version = "1.2.3"
+    settings = "os", "compiler", "build_type", "arch"
+    requires = "fmt/9.1.0"
@@ -0,0 +1,169 @@
+use std::fs::{copy, create_dir};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run a `Command` step using `PackageName` and `PackagePath`, which runs once for each package.
+#[test]
+fn per_package_variables() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/command");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+    for file in ["first/Cargo.toml", "second/pyproject.toml"] {
+        let path = temp_path.join(file);
+        create_dir(path.parent().unwrap()).unwrap();
+        copy(source_path.join(file), path).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("publish")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("publish")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+}
+
+/// A `Command` which runs for longer than its `timeout` should be stopped, along with anything it
+/// started in the background.
+#[cfg(unix)]
+#[test]
+fn timeout() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/command");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("timeout.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    // Act.
+    let start = Instant::now();
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("build")
+        .current_dir(temp_path)
+        .assert();
+    let elapsed = start.elapsed();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_eq_path(source_path.join("timeout_output.txt"));
+    assert!(elapsed < Duration::from_secs(5));
+    sleep(Duration::from_secs(2));
+    assert!(!temp_path.join("orphan").exists());
+}
+
+/// A `Command` with a `shell` runs in that shell, and its `windows` variant is only for Windows.
+#[cfg(unix)]
+#[test]
+fn shell() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/command");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("shell.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("build")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("shell_output.txt"));
+}
+
+/// `{{ }}` in a `Command` which isn't a Knope expression (like a Go template for `docker inspect`)
+/// is passed to the shell as-is.
+#[cfg(unix)]
+#[test]
+fn other_braces() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/command");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("other_braces.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("build")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("other_braces_output.txt"));
+}
+
+/// A `Command` without a `timeout` stays in knope's process group and can read what's typed into
+/// the terminal, like an editor or a password prompt would.
+#[cfg(unix)]
+#[test]
+fn interactive() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/command");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("interactive.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("build")
+        .stdin("yes\n")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("interactive_output.txt"));
+}
@@ -0,0 +1,44 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Preview the release of multiple packages without changing any files.
+#[test]
+fn comment_release_preview() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/comment_release_preview");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+    commit(temp_path, "fix(second): A fix for second");
+
+    for file in ["knope.toml", "Cargo.toml", "pyproject.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("preview")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    for file in ["Cargo.toml", "pyproject.toml"] {
+        assert_eq!(
+            std::fs::read_to_string(temp_path.join(file)).unwrap(),
+            std::fs::read_to_string(source_path.join(file)).unwrap()
+        );
+    }
+    assert!(!temp_path.join("FIRST_CHANGELOG.md").exists());
+}
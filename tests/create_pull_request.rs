@@ -0,0 +1,79 @@
+use std::fs::{copy, create_dir};
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run `CreatePullRequest` after `PrepareRelease` for multiple packages, rendering the body from a
+/// template with the version table and changelog entries. The `${{ }}` of GitHub Actions in the
+/// template isn't a Knope expression, so it's kept as-is.
+#[test]
+fn create_pull_request_from_template() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/create_pull_request");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "Cargo.toml", "pyproject.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// Request reviews from the code owners of the files changed since the base branch.
+#[test]
+fn codeowners() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/create_pull_request/codeowners");
+
+    init(temp_path);
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+    create_dir(temp_path.join(".github")).unwrap();
+    copy(
+        source_path.join(".github/CODEOWNERS"),
+        temp_path.join(".github/CODEOWNERS"),
+    )
+    .unwrap();
+    add_all(temp_path);
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "main");
+    create_dir(temp_path.join("docs")).unwrap();
+    copy(
+        source_path.join("docs/index.md"),
+        temp_path.join("docs/index.md"),
+    )
+    .unwrap();
+    add_all(temp_path);
+    commit(temp_path, "docs: Add docs");
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("pr")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
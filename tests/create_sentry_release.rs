@@ -0,0 +1,74 @@
+use std::fs::copy;
+use std::path::Path;
+
+use rstest::rstest;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run `CreateSentryRelease` after `PrepareRelease`, with the default and a templated version.
+#[rstest]
+#[case("release", "dry_run_output.txt")]
+#[case("templated", "templated_dry_run_output.txt")]
+fn create_sentry_release_dry_run(#[case] workflow: &str, #[case] expected_output: &str) {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/create_sentry_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+    commit(temp_path, "fix: A bug");
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg(workflow)
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join(expected_output));
+}
+
+/// `CreateSentryRelease` can't run without `[sentry]` config.
+#[test]
+fn create_sentry_release_without_sentry_config() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/create_sentry_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+    let config = std::fs::read_to_string(source_path.join("knope.toml")).unwrap();
+    let config = config.replace(
+        "[sentry]\norganization = \"knope-dev\"\nproject = \"knope\"\n\n",
+        "",
+    );
+    std::fs::write(temp_path.join("knope.toml"), config).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_eq_path(source_path.join("no_sentry_config_output.txt"));
+}
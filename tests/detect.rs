@@ -0,0 +1,81 @@
+use std::fs::{copy, create_dir, write};
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run `detect` in a repo with versioned files scattered around. Only files in the same directory
+/// (the same package) which disagree are warned about.
+#[test]
+fn detect_mismatched_versions() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/detect");
+    init(temp_path);
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+    copy(
+        source_path.join("package.json"),
+        temp_path.join("package.json"),
+    )
+    .unwrap();
+    for (dir, file) in [("web", "package.json"), ("bindings", "pyproject.toml")] {
+        create_dir(temp_path.join(dir)).unwrap();
+        copy(source_path.join(file), temp_path.join(dir).join(file)).unwrap();
+    }
+    create_dir(temp_path.join("legacy")).unwrap();
+    write(
+        temp_path.join("legacy").join("Cargo.toml"),
+        "[package]\nversion = \"9.9.9\"\n",
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("detect")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+}
+
+/// Files ignored by Git should not be reported by `detect`.
+#[test]
+fn detect_skips_ignored_files() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/detect");
+    init(temp_path);
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+    create_dir(temp_path.join("target")).unwrap();
+    copy(
+        source_path.join("package.json"),
+        temp_path.join("target").join("package.json"),
+    )
+    .unwrap();
+    create_dir(temp_path.join("vendor")).unwrap();
+    copy(
+        source_path.join("package.json"),
+        temp_path.join("vendor").join("package.json"),
+    )
+    .unwrap();
+    write(temp_path.join(".gitignore"), "vendor/\n").unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("detect")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq("Found the following versioned files:\n  Cargo.toml: 1.2.3\n");
+}
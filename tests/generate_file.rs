@@ -0,0 +1,56 @@
+use std::fs::{copy, create_dir, read_to_string};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Render a `CITATION.cff` with the new version while preparing a release.
+#[test]
+fn generate_file() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/generate_file");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+    create_dir(temp_path.join(".knope")).unwrap();
+    copy(
+        source_path.join(".knope/CITATION.cff"),
+        temp_path.join(".knope/CITATION.cff"),
+    )
+    .unwrap();
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_CITATION.cff"),
+        read_to_string(temp_path.join("CITATION.cff")).unwrap(),
+    );
+    let status = status(temp_path);
+    assert!(status.contains(&String::from("A  CITATION.cff")));
+}
@@ -1,5 +1,6 @@
 use std::fs::{copy, read_to_string};
 use std::path::Path;
+use std::process::Command as StdCommand;
 
 use snapbox::assert_eq_path;
 use snapbox::cmd::{cargo_bin, Command};
@@ -118,3 +119,450 @@ fn multiple_packages() {
     assert_eq!(describe(temp_path, Some("first/*")), "first/v2.0.0");
     assert_eq!(describe(temp_path, Some("second/*")), "second/v0.5.0");
 }
+
+/// Verify that a package's `tag_prefix` is used to find its last release and to tag the new one
+#[test]
+fn tag_prefix() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/tag_prefix");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "first/v1.2.3");
+    tag(temp_path, "js-v0.4.6");
+    commit(temp_path, "feat!: New breaking feature");
+
+    for file in [
+        "knope.toml",
+        "FIRST_CHANGELOG.md",
+        "Cargo.toml",
+        "pyproject.toml",
+        "SECOND_CHANGELOG.md",
+        "package.json",
+    ] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .success()
+        .stdout_matches_path(source_path.join("output.txt"));
+
+    for file in [
+        "FIRST_CHANGELOG.md",
+        "SECOND_CHANGELOG.md",
+        "Cargo.toml",
+        "pyproject.toml",
+        "package.json",
+    ] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+    assert_eq!(describe(temp_path, Some("first/*")), "first/v2.0.0");
+    assert_eq!(describe(temp_path, Some("js-v*")), "js-v0.5.0");
+}
+
+/// Verify that with fixed versioning every package is bumped together and released with one tag
+#[test]
+fn fixed_versioning() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/fixed_versioning");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.2.3");
+    commit(temp_path, "feat(first): New feature");
+
+    for file in [
+        "knope.toml",
+        "FIRST_CHANGELOG.md",
+        "Cargo.toml",
+        "SECOND_CHANGELOG.md",
+        "pyproject.toml",
+    ] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .success()
+        .stdout_matches_path(source_path.join("output.txt"));
+
+    for file in [
+        "FIRST_CHANGELOG.md",
+        "SECOND_CHANGELOG.md",
+        "Cargo.toml",
+        "pyproject.toml",
+    ] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+    assert_eq!(describe(temp_path, None), "v1.3.0");
+}
+
+/// Run a `PrepareRelease` for real, but only pretend to run the `Release` step by setting
+/// `dry_run` on it.
+///
+/// # Expected
+///
+/// Version should be bumped, but no tag should be created.
+#[test]
+fn step_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    copy(
+        source_path.join("step_dry_run/knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    actual_assert
+        .success()
+        .stdout_eq_path(source_path.join("step_dry_run/output.txt"));
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("Expected_Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+    let tag = describe(temp_path, None);
+    assert!(tag.starts_with("v1.0.0-"), "Unexpected tag {tag}");
+}
+
+/// A dry-run `PrepareRelease` followed by a real `Release` must not release the version which was
+/// only pretended.
+#[test]
+fn step_dry_run_prepare() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    copy(
+        source_path.join("step_dry_run_prepare/knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    actual_assert
+        .success()
+        .stdout_eq_path(source_path.join("step_dry_run_prepare/output.txt"));
+    assert_eq_path(
+        source_path.join("CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+    assert_eq!(git(temp_path, &["tag", "--list"]).trim(), "v1.0.0");
+}
+
+/// Run a `PrepareRelease` with `record` set in one workflow, then a `Release` from that record in
+/// another after more commits were added.
+#[test]
+fn record() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/record");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+    Command::new(cargo_bin!("knope"))
+        .arg("prepare")
+        .current_dir(temp_dir.path())
+        .assert()
+        .success();
+    commit(temp_path, "feat!: Unreleased breaking change");
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    let record: serde_json::Value =
+        serde_json::from_str(&read_to_string(temp_path.join("release.json")).unwrap()).unwrap();
+    assert_eq!(record["releases"][0]["version"], "1.1.0");
+    assert!(record["releases"][0]["commits"]["from"].is_string());
+    let tag = describe(temp_path, None);
+    assert_eq!(tag, "v1.1.0");
+}
+
+/// Run a `Release` with `provenance` set, which writes a provenance statement for the new tag.
+#[test]
+fn provenance() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/provenance");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .success()
+        .stdout_matches_path(source_path.join("output.txt"));
+    let statement: serde_json::Value =
+        serde_json::from_str(&read_to_string(temp_path.join("v1.1.0.intoto.json")).unwrap())
+            .unwrap();
+    assert_eq!(statement["subject"][0]["name"], "v1.1.0");
+    let materials = statement["predicate"]["materials"].as_array().unwrap();
+    assert_eq!(materials.len(), 2);
+    assert_eq!(materials[0]["uri"], "git+.@refs/tags/v1.0.0");
+    assert_eq!(materials[1]["uri"], "git+.@HEAD");
+    assert_eq!(
+        materials[1]["digest"]["sha1"],
+        statement["subject"][0]["digest"]["sha1"]
+    );
+}
+
+/// Run a `Release` with `jira_issues` set, which links the Jira issues referenced by the release
+/// in its GitHub release.
+#[test]
+fn jira_issues() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/jira_issues");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: PROJ-1 New feature");
+    commit(temp_path, "fix: A bug\n\nFixes PROJ-2 and OTHER-3");
+    commit(temp_path, "docs: Explain PROJ-1");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// Refuse to release during a release freeze, unless `--override-freeze` is passed.
+#[test]
+fn release_freeze() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/git_release/release_freeze");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let frozen_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+    let frozen_tags = git(temp_path, &["tag", "--list"]);
+    let override_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--override-freeze")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    frozen_assert
+        .failure()
+        .stderr_matches_path(source_path.join("frozen_output.txt"));
+    assert_eq!(frozen_tags.trim(), "v1.0.0");
+    override_assert.success();
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("Expected_Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+    let tag = describe(temp_path, None);
+    assert_eq!(tag, "v1.1.0");
+}
+
+/// Nothing is released when someone else already pushed the same tag to the remote.
+#[test]
+fn remote_tag_exists() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let remote_dir = tempfile::tempdir().unwrap();
+    let remote_path = remote_dir.path();
+    let source_path = Path::new("tests/git_release/remote_tag");
+
+    git(remote_path, &["init", "--bare"]);
+    init(temp_path);
+    add_remote(temp_path, &remote_path.display().to_string());
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+    // Someone else releases the same version first.
+    tag(temp_path, "v1.1.0");
+    git(temp_path, &["push", "origin", "HEAD", "v1.1.0"]);
+    git(temp_path, &["tag", "--delete", "v1.1.0"]);
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .failure()
+        .stderr_matches_path(source_path.join("output.txt"));
+    assert_eq!(git(temp_path, &["tag", "--list", "v1.1.0"]), "");
+}
+
+/// Run `git` with `args` in `path`, returning its stdout.
+fn git(path: &Path, args: &[&str]) -> String {
+    let output = StdCommand::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
@@ -91,6 +91,55 @@ pub fn tag(path: &Path, label: &str) {
     );
 }
 
+/// Create a new branch called `name` at the current commit without switching to it.
+pub fn create_branch(path: &Path, name: &str) {
+    let output = Command::new("git")
+        .arg("branch")
+        .arg(name)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Switch to the existing branch called `name`.
+pub fn switch_branch(path: &Path, name: &str) {
+    let output = Command::new("git")
+        .arg("switch")
+        .arg(name)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Merge the branch called `name` into the current branch, always creating a merge commit with
+/// `message`.
+pub fn merge_branch(path: &Path, name: &str, message: &str) {
+    let output = Command::new("git")
+        .arg("merge")
+        .arg("--no-ff")
+        .arg("-m")
+        .arg(message)
+        .arg(name)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
 /// Get the current tag, panicking if there is no tag.
 pub fn describe(path: &Path, pattern: Option<&str>) -> String {
     let mut cmd = Command::new("git");
@@ -0,0 +1,61 @@
+use std::fs::{copy, read_to_string, write};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::cargo_bin;
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Merge two branches which both added a version to the changelog, using `knope merge-changelog`
+/// as the Git merge driver so there's no conflict.
+#[test]
+fn merge_driver() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/merge_changelog");
+    let changelog = temp_path.join("CHANGELOG.md");
+
+    init(temp_path);
+    let output = std::process::Command::new("git")
+        .arg("config")
+        .arg("merge.knope-changelog.driver")
+        .arg(format!(
+            "{} merge-changelog %O %A %B",
+            cargo_bin!("knope").display()
+        ))
+        .current_dir(temp_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    write(
+        temp_path.join(".gitattributes"),
+        "CHANGELOG.md merge=knope-changelog\n",
+    )
+    .unwrap();
+    copy(source_path.join("base_CHANGELOG.md"), &changelog).unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    create_branch(temp_path, "maintenance");
+
+    copy(source_path.join("ours_CHANGELOG.md"), &changelog).unwrap();
+    add_all(temp_path);
+    commit(temp_path, "chore: Release 1.1.0");
+
+    switch_branch(temp_path, "maintenance");
+    copy(source_path.join("theirs_CHANGELOG.md"), &changelog).unwrap();
+    add_all(temp_path);
+    commit(temp_path, "chore: Release 1.0.1");
+    switch_branch(temp_path, "-");
+
+    // Act.
+    merge_branch(temp_path, "maintenance", "Merge maintenance");
+
+    // Assert.
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(changelog).unwrap(),
+    );
+}
@@ -0,0 +1,56 @@
+use std::fs::{copy, create_dir, read_to_string};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Print the next version of every package without changing any files.
+#[test]
+fn next_version() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/next_version");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "first/v1.2.3");
+    tag(temp_path, "second/v0.4.0");
+    commit(temp_path, "feat(first): New feature");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+    for package in ["first", "second"] {
+        create_dir(temp_path.join(package)).unwrap();
+        copy(
+            source_path.join(format!("{package}_Cargo.toml")),
+            temp_path.join(package).join("Cargo.toml"),
+        )
+        .unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("next-version")
+        .current_dir(temp_path)
+        .assert();
+    let prerelease_assert = Command::new(cargo_bin!("knope"))
+        .arg("next-version")
+        .arg("--prerelease-label=rc")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+    prerelease_assert
+        .success()
+        .stdout_eq_path(source_path.join("prerelease_output.txt"));
+    assert_eq_path(
+        source_path.join("first_Cargo.toml"),
+        read_to_string(temp_path.join("first/Cargo.toml")).unwrap(),
+    );
+}
@@ -0,0 +1,70 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Override the computed version of every package, and of one package in particular.
+#[test]
+fn override_version() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/override_version");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "Cargo.toml", "pyproject.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .arg("--override-version")
+        .arg("second=1.0.0-rc.0")
+        .arg("--override-version")
+        .arg("2.0.0")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// Overriding the version of a package which doesn't exist is an error.
+#[test]
+fn unknown_package() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/override_version");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "Cargo.toml", "pyproject.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .arg("--override-version")
+        .arg("third=1.0.0")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_matches_path(source_path.join("unknown_package.txt"));
+}
@@ -0,0 +1,35 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Steps for other platforms should be skipped.
+#[test]
+fn platforms() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/platforms");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    let expected = if cfg!(windows) {
+        "windows_output.txt"
+    } else {
+        "unix_output.txt"
+    };
+    assert.success().stdout_eq_path(source_path.join(expected));
+}
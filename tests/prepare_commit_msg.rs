@@ -0,0 +1,106 @@
+use std::fs::{copy, read_to_string};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Add the Jira issue key from the branch name to the commit message as a trailer.
+#[test]
+fn jira_issue() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_commit_msg");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "PROJ-123-add-a-thing");
+    switch_branch(temp_path, "PROJ-123-add-a-thing");
+    let message_file = temp_path.join("COMMIT_EDITMSG");
+    copy(source_path.join("COMMIT_EDITMSG"), &message_file).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("prepare-commit-msg")
+        .arg("COMMIT_EDITMSG")
+        .arg("message")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_jira_COMMIT_EDITMSG"),
+        read_to_string(message_file).unwrap(),
+    );
+}
+
+/// Refer to a GitHub issue by its number, with a custom trailer key.
+#[test]
+fn github_issue() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_commit_msg");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    create_branch(temp_path, "42-add-a-thing");
+    switch_branch(temp_path, "42-add-a-thing");
+    let message_file = temp_path.join("COMMIT_EDITMSG");
+    copy(source_path.join("COMMIT_EDITMSG"), &message_file).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("prepare-commit-msg")
+        .arg("COMMIT_EDITMSG")
+        .arg("--trailer")
+        .arg("Refs")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_github_COMMIT_EDITMSG"),
+        read_to_string(message_file).unwrap(),
+    );
+}
+
+/// Leave the message alone on a branch which isn't for an issue, or for a merge commit.
+#[test]
+fn no_issue() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_commit_msg");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    let message_file = temp_path.join("COMMIT_EDITMSG");
+    copy(source_path.join("COMMIT_EDITMSG"), &message_file).unwrap();
+    create_branch(temp_path, "PROJ-123-add-a-thing");
+
+    // Act.
+    let not_on_issue_branch = Command::new(cargo_bin!("knope"))
+        .arg("prepare-commit-msg")
+        .arg("COMMIT_EDITMSG")
+        .current_dir(temp_path)
+        .assert();
+    switch_branch(temp_path, "PROJ-123-add-a-thing");
+    let merge = Command::new(cargo_bin!("knope"))
+        .arg("prepare-commit-msg")
+        .arg("COMMIT_EDITMSG")
+        .arg("merge")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    not_on_issue_branch.success().stdout_eq("").stderr_eq("");
+    merge.success().stdout_eq("").stderr_eq("");
+    assert_eq_path(
+        source_path.join("COMMIT_EDITMSG"),
+        read_to_string(message_file).unwrap(),
+    );
+}
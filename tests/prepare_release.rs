@@ -1,9 +1,9 @@
-use std::fs::{copy, read_to_string, write};
+use std::fs::{copy, create_dir_all, read_to_string, write};
 use std::path::Path;
 
 use rstest::rstest;
-use snapbox::assert_eq_path;
 use snapbox::cmd::{cargo_bin, Command};
+use snapbox::{assert_eq_path, assert_matches_path};
 
 use git_repo_helpers::*;
 
@@ -108,6 +108,84 @@ fn override_prerelease_label_with_option() {
     );
 }
 
+/// Run a `PrepareRelease` which is configured as a pre-release, but release a stable version by
+/// passing an empty `--prerelease-label`.
+#[test]
+fn clear_prerelease_label_with_option() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/clear_prerelease_label");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature in existing release");
+    tag(temp_path, "v1.1.0");
+    commit(temp_path, "feat!: Breaking feature in new RC");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("prerelease")
+        .arg("--prerelease-label=")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("Expected_Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+}
+
+/// A breaking change in a 0.x package releases 1.0.0 when `major_version_zero` is `Standard`.
+#[test]
+fn major_version_zero() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/major_version_zero");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v0.1.2");
+    commit(temp_path, "feat!: Stable API");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("Expected_Cargo.toml"),
+        read_to_string(temp_path.join("Cargo.toml")).unwrap(),
+    );
+}
+
 /// Run a `PrepareRelease` as a pre-release in a repo which already contains a release, but change
 /// the configured `prerelease_label` at runtime using the `KNOPE_PRERELEASE_LABEL` environment variable.
 #[test]
@@ -660,7 +738,7 @@ fn test_prepare_release_invalid_versioned_file_format() {
         "Cargo.toml",
         "pyproject.toml",
         "package.json",
-        "setup.py",
+        "composer.json",
     ] {
         copy(source_path.join(file), temp_path.join(file)).unwrap();
     }
@@ -828,6 +906,51 @@ fn no_versioned_files() {
     assert_eq!(expected_tag, actual_tag);
 }
 
+/// When running on a maintenance branch, tags for newer versions on other branches should be
+/// ignored.
+#[test]
+fn maintenance_branch() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/maintenance_branch");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    create_branch(temp_path, "v1");
+    commit(temp_path, "feat!: Breaking change for 2.x");
+    tag(temp_path, "v2.0.0");
+    switch_branch(temp_path, "v1");
+    commit(temp_path, "fix: Fix for 1.x");
+
+    for file in ["knope.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+    assert_eq!(describe(temp_path, Some("v1.*")), "v1.0.1");
+}
+
 /// If `PrepareRelease` is run with no `prerelease_label`, it should skip any prerelease tags
 /// when parsing commits, as well as determine the next version from the previous released version
 /// (not from the pre-release version).
@@ -952,6 +1075,52 @@ fn go_modules() {
     assert_eq!("v2.0.0", tag);
 }
 
+/// Verify that a `go.mod` in a subdirectory is tagged with that directory, as Go requires.
+#[test]
+fn nested_go_module() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/nested_go_module");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "sub/v1.0.0");
+    commit(temp_path, "feat!: Breaking change");
+
+    create_dir_all(temp_path.join("sub")).unwrap();
+    for file in ["knope.toml", "sub/CHANGELOG.md", "sub/go.mod"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("sub/CHANGELOG.md")).unwrap(),
+    );
+    assert_eq_path(
+        source_path.join("EXPECTED_go.mod"),
+        read_to_string(temp_path.join("sub/go.mod")).unwrap(),
+    );
+    let tag = describe(temp_path, None);
+    assert_eq!("sub/v2.0.0", tag);
+}
+
 /// Verify that PrepareRelease will operate on all defined packages independently
 #[test]
 fn multiple_packages() {
@@ -1008,6 +1177,40 @@ fn multiple_packages() {
     }
 }
 
+/// Give one package a pre-release while another gets a stable release, with the pre-release
+/// counter coming from the tags of that package alone.
+#[test]
+fn per_package_prerelease_labels() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/per_package_prerelease_labels");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "first/v1.2.3");
+    tag(temp_path, "second/v0.4.6");
+    commit(temp_path, "feat: First release candidate");
+    tag(temp_path, "first/v1.3.0-rc.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "Cargo.toml", "package.json"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
 /// When no scopes are defined, all commits must apply to all packages
 #[test]
 fn no_scopes_defined() {
@@ -1223,3 +1426,804 @@ fn no_version_change() {
         .failure()
         .stderr_eq_path(source_path.join("actual_output.txt"));
 }
+
+/// Run a `PrepareRelease` with `changelog_locales` and verify that each localized changelog gets a
+/// placeholder entry for translators.
+#[test]
+fn changelog_locales() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/changelog_locales");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in [
+        "knope.toml",
+        "Cargo.toml",
+        "CHANGELOG.md",
+        "CHANGELOG.de.md",
+    ] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in ["CHANGELOG.md", "CHANGELOG.de.md", "Cargo.toml"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` with commit types for the default and configured `changelog_sections`.
+#[test]
+fn changelog_sections() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/changelog_sections");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "deprecate: Old option");
+    commit(temp_path, "remove: Unused option");
+    commit(temp_path, "sec: Patch a vulnerability");
+    commit(temp_path, "docs: Explain the new option");
+    commit(temp_path, "fix: A bug");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` with cherry-picked commits, which should only be listed once and not
+/// repeat notes from the previous release.
+#[test]
+fn cherry_picks() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/cherry_picks");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "fix: A bug from the maintenance branch");
+    commit(
+        temp_path,
+        "fix: A new bug\n\n(cherry picked from commit 1234)\n\nChange-Id: I1234",
+    );
+    commit(temp_path, "fix: A new bug, take two\n\nChange-Id: I1234");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+}
+
+/// Run a `PrepareRelease` with `changelog_format` set, so long notes are wrapped and truncated.
+#[test]
+fn changelog_format() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/changelog_format");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(
+        temp_path,
+        "feat: A new feature with a description long enough that it needs to be wrapped",
+    );
+    commit(
+        temp_path,
+        "fix: A fix with a description which is so long that it has to be cut off before the end so that the changelog stays readable",
+    );
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` with `icons` in `changelog_format`, so each note starts with the icon
+/// for its commit type.
+#[test]
+fn changelog_icons() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/changelog_icons");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: A new feature");
+    commit(temp_path, "fix: A bug fix");
+    commit(temp_path, "refactor!: Removed the old API");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file)),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` against the root of a Cargo workspace, which should bump every member
+/// and the dependencies between them.
+#[test]
+fn cargo_workspace() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/cargo_workspace");
+    let manifests = [
+        "Cargo.toml",
+        "crates/first/Cargo.toml",
+        "crates/second/Cargo.toml",
+    ];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in manifests.iter().chain(&["knope.toml", "CHANGELOG.md"]) {
+        let destination = temp_path.join(file);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        copy(source_path.join(file), destination).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in manifests.iter().chain(&["CHANGELOG.md"]) {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` for a Cargo workspace member which inherits its version, which should
+/// bump the version of the workspace root instead.
+#[test]
+fn inherited_version() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/inherited_version");
+    let manifests = ["Cargo.toml", "crates/member/Cargo.toml"];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in manifests.iter().chain(&["knope.toml", "CHANGELOG.md"]) {
+        let destination = temp_path.join(file);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        copy(source_path.join(file), destination).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in manifests.iter().chain(&["CHANGELOG.md"]) {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Run a `PrepareRelease` for two packages which inherit their version from the same Cargo
+/// workspace, which should both be bumped by the biggest change to either of them and share a tag.
+#[test]
+fn shared_inherited_version() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/shared_inherited_version");
+    let files = [
+        "Cargo.toml",
+        "crates/first/Cargo.toml",
+        "crates/first/CHANGELOG.md",
+        "crates/second/Cargo.toml",
+        "crates/second/CHANGELOG.md",
+    ];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    create_dir_all(temp_path.join("crates/first")).unwrap();
+    create_dir_all(temp_path.join("crates/second")).unwrap();
+    write(temp_path.join("crates/first/lib.rs"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: First feature");
+    write(temp_path.join("crates/second/lib.rs"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "fix: Second fix");
+
+    for file in files.iter().chain(&["knope.toml"]) {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in [
+        "Cargo.toml",
+        "crates/first/CHANGELOG.md",
+        "crates/second/CHANGELOG.md",
+    ] {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Only consider the commits which change a package's `paths` for that package.
+#[test]
+fn paths() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/paths");
+    let files = [
+        "first/Cargo.toml",
+        "first/CHANGELOG.md",
+        "second/package.json",
+        "second/CHANGELOG.md",
+    ];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "first/v1.0.0");
+    tag(temp_path, "second/v1.0.0");
+    create_dir_all(temp_path.join("first")).unwrap();
+    create_dir_all(temp_path.join("second")).unwrap();
+    write(temp_path.join("first/lib.rs"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: First feature");
+    write(temp_path.join("second/index.js"), "").unwrap();
+    write(temp_path.join("second/README.md"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "fix: Second fix");
+    write(temp_path.join("second/README.md"), "Docs").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: Unrelated feature");
+
+    for file in files.iter().chain(&["knope.toml"]) {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in files {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Commits which only change files matching `exclude_paths` don't apply to the package.
+#[test]
+fn exclude_paths() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/exclude_paths");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    create_dir_all(temp_path.join("docs")).unwrap();
+    write(temp_path.join("docs/guide.txt"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: Documented feature");
+    create_dir_all(temp_path.join("src")).unwrap();
+    write(temp_path.join("src/lib.rs"), "").unwrap();
+    write(temp_path.join("README.md"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "fix: Code fix");
+    write(temp_path.join("README.md"), "Docs").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: README feature");
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// Packages which depend on a released package should require its new version.
+#[test]
+fn internal_dependencies() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/internal_dependencies");
+    let files = [
+        "core/Cargo.toml",
+        "core/package.json",
+        "core/CHANGELOG.md",
+        "cli/Cargo.toml",
+        "cli/CHANGELOG.md",
+        "web/package.json",
+        "web/CHANGELOG.md",
+    ];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "core/v1.0.0");
+    tag(temp_path, "cli/v2.0.0");
+    tag(temp_path, "web/v3.0.0");
+    commit(temp_path, "feat(core): New feature");
+
+    for dir in ["core", "cli", "web"] {
+        create_dir_all(temp_path.join(dir)).unwrap();
+    }
+    for file in files.iter().chain(&["knope.toml"]) {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in files {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Packages, their paths, and the dependencies between them can come from an Nx project graph.
+#[test]
+fn project_graph() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/project_graph");
+    let files = [
+        "apps/app/package.json",
+        "libs/ui/package.json",
+        "libs/ui/CHANGELOG.md",
+    ];
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "app/v1.0.0");
+    tag(temp_path, "ui/v1.0.0");
+    create_dir_all(temp_path.join("apps/app")).unwrap();
+    create_dir_all(temp_path.join("libs/ui")).unwrap();
+    write(temp_path.join("libs/ui/button.js"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "feat: New button");
+    write(temp_path.join("apps/app/main.js"), "").unwrap();
+    add_all(temp_path);
+    commit(temp_path, "fix: Crash on start");
+
+    for file in files.iter().chain(&["knope.toml", "project-graph.json"]) {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+
+    for file in files {
+        assert_eq_path(
+            source_path.join(format!("EXPECTED_{}", file.replace('/', "_"))),
+            read_to_string(temp_path.join(file)).unwrap(),
+        );
+    }
+}
+
+/// Add a release with the new changelog entry to an AppStream metainfo file.
+#[test]
+fn metainfo() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/metainfo");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+    commit(temp_path, "fix: A fix");
+
+    for file in [
+        "knope.toml",
+        "Cargo.toml",
+        "CHANGELOG.md",
+        "com.example.App.metainfo.xml",
+    ] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_matches_path(
+        source_path.join("EXPECTED_com.example.App.metainfo.xml"),
+        read_to_string(temp_path.join("com.example.App.metainfo.xml")).unwrap(),
+    );
+}
+
+#[test]
+fn debian_changelog() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/debian_changelog");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+    commit(temp_path, "fix: A fix");
+
+    create_dir_all(temp_path.join("debian")).unwrap();
+    for file in [
+        "knope.toml",
+        "Cargo.toml",
+        "CHANGELOG.md",
+        "debian/changelog",
+    ] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_matches_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_matches_path(
+        source_path.join("EXPECTED_changelog"),
+        read_to_string(temp_path.join("debian/changelog")).unwrap(),
+    );
+}
+
+#[test]
+fn rpm_spec() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/rpm_spec");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+    commit(temp_path, "fix: A fix");
+
+    for file in ["knope.toml", "CHANGELOG.md", "my-tool.spec"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_dir.path())
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_dir.path())
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_matches_path(source_path.join("dry_run_output.txt"));
+    actual_assert.success().stdout_eq("");
+    assert_matches_path(
+        source_path.join("EXPECTED_my-tool.spec"),
+        read_to_string(temp_path.join("my-tool.spec")).unwrap(),
+    );
+}
+
+/// Commits from merged branches are only included when not following the first parent.
+#[rstest]
+#[case("release", "all_parents_dry_run_output.txt")]
+#[case("first-parent", "first_parent_dry_run_output.txt")]
+fn merge_commits(#[case] workflow: &str, #[case] expected_output: &str) {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/merge_commits");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    create_branch(temp_path, "feature");
+    commit(temp_path, "fix: Mainline fix");
+    switch_branch(temp_path, "feature");
+    commit(temp_path, "feat: Branch feature");
+    switch_branch(temp_path, "-");
+    merge_branch(temp_path, "feature", "fix: Merge the feature branch");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg(workflow)
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join(expected_output));
+}
+
+#[test]
+fn reverted_commits() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/prepare_release/reverted_commits");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: Temporary feature");
+    commit(temp_path, "fix: A fix");
+    commit(temp_path, "Revert \"feat: Temporary feature\"");
+    commit(temp_path, "Revert \"feat: Existing feature\"");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
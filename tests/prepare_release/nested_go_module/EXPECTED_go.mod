@@ -0,0 +1,3 @@
+module github.com/knope-dev/knope/sub/v2
+
+go 1.19
@@ -0,0 +1,3 @@
+module github.com/knope-dev/knope/sub
+
+go 1.19
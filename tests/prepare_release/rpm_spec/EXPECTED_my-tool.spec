@@ -0,0 +1,16 @@
+Name:           my-tool
+Version:        1.1.0
+Release:        1%{?dist}
+Summary:        A tool
+License:        MIT
+
+%description
+A tool.
+
+%changelog
+* [..] Jane Doe <jane@example.com> - 1.1.0-1
+- New feature
+- A fix
+
+* Sun Jan 01 2023 Jane Doe <jane@example.com> - 1.0.0-2
+- Initial release
@@ -0,0 +1,12 @@
+Name:           my-tool
+Version:        1.0.0
+Release:        2%{?dist}
+Summary:        A tool
+License:        MIT
+
+%description
+A tool.
+
+%changelog
+* Sun Jan 01 2023 Jane Doe <jane@example.com> - 1.0.0-2
+- Initial release
@@ -0,0 +1,100 @@
+use std::fs::{copy, create_dir, read_to_string};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Publish a release to the AUR, without actually running `makepkg` or pushing.
+#[test]
+fn publish_aur_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/publish_aur");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+    create_dir(temp_path.join("aur")).unwrap();
+    copy(
+        source_path.join("aur/PKGBUILD"),
+        temp_path.join("aur/PKGBUILD"),
+    )
+    .unwrap();
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// Don't commit to the AUR repository if it's on a protected branch, since nobody can be asked
+/// to confirm when running non-interactively.
+#[test]
+fn publish_aur_protected_branch() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/publish_aur");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    copy(
+        source_path.join("protected_branch_knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    for file in ["CHANGELOG.md", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+    let aur_path = temp_path.join("aur");
+    create_dir(&aur_path).unwrap();
+    copy(source_path.join("aur/PKGBUILD"), aur_path.join("PKGBUILD")).unwrap();
+    init(&aur_path);
+    commit(&aur_path, "Initial commit");
+    create_branch(&aur_path, "main");
+    switch_branch(&aur_path, "main");
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .env("CI", "true")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("protected_branch_dry_run_output.txt"));
+    actual_assert
+        .failure()
+        .stderr_eq_path(source_path.join("protected_branch_output.txt"));
+    assert_eq_path(
+        source_path.join("aur/PKGBUILD"),
+        read_to_string(aur_path.join("PKGBUILD")).unwrap(),
+    );
+}
@@ -0,0 +1,56 @@
+use std::fs::{copy, read_to_string};
+use std::path::Path;
+
+use snapbox::assert_eq_path;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Rebuild the changelog from every stable version tag, keeping its title but leaving out
+/// pre-releases and unreleased commits.
+#[test]
+fn regenerate_changelog() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/regenerate_changelog");
+
+    init(temp_path);
+    commit(temp_path, "feat: First feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "fix: A bug");
+    commit(temp_path, "feat: Pre-release feature");
+    tag(temp_path, "v1.1.0-rc.0");
+    commit(temp_path, "docs: Not in the changelog");
+    tag(temp_path, "v1.1.0");
+    commit(temp_path, "feat: Unreleased feature");
+
+    for file in ["knope.toml", "Cargo.toml", "CHANGELOG.md"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("regenerate-changelog")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let dry_run_changelog = read_to_string(temp_path.join("CHANGELOG.md")).unwrap();
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("regenerate-changelog")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert.success();
+    assert_eq_path(source_path.join("CHANGELOG.md"), dry_run_changelog);
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("output.txt"));
+    assert_eq_path(
+        source_path.join("EXPECTED_CHANGELOG.md"),
+        read_to_string(temp_path.join("CHANGELOG.md")).unwrap(),
+    );
+}
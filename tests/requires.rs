@@ -0,0 +1,88 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Every missing environment variable should be reported before any step runs.
+#[test]
+fn missing_variables() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/requires");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .env_remove("CRATES_TOKEN")
+        .env_remove("NPM_TOKEN")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stdout_eq("")
+        .stderr_matches_path(source_path.join("missing_output.txt"));
+}
+
+/// Only the steps which are going to run need their environment variables.
+#[test]
+fn skipped_steps() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/requires");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--only")
+        .arg("build")
+        .env_remove("CRATES_TOKEN")
+        .env_remove("NPM_TOKEN")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("skipped_output.txt"));
+}
+
+#[test]
+fn all_set() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/requires");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .env("CRATES_TOKEN", "crates")
+        .env("NPM_TOKEN", "npm")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("all_set_output.txt"));
+}
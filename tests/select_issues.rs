@@ -0,0 +1,33 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Select several Jira issues, then transition and list all of them.
+#[test]
+fn select_multiple_issues() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/select_issues");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("triage")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
@@ -0,0 +1,68 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run `SendEmail` after `PrepareRelease`, rendering the subject and body from templates.
+#[test]
+fn send_email_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/send_email");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// `SendEmail` can't run without `[smtp]` config.
+#[test]
+fn send_email_without_smtp_config() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/send_email");
+
+    init(temp_path);
+    commit(temp_path, "feat: New feature");
+
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+    let config = std::fs::read_to_string(source_path.join("knope.toml")).unwrap();
+    let config = config.replace(
+        "[smtp]\nhost = \"smtp.knope.dev\"\nfrom = \"Release Bot <bot@knope.dev>\"\n\n",
+        "",
+    );
+    std::fs::write(temp_path.join("knope.toml"), config).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_eq_path(source_path.join("no_smtp_config_output.txt"));
+}
@@ -0,0 +1,67 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Sign a release and an extra asset, without actually running `cosign`.
+#[test]
+fn sign_release_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/sign_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature");
+
+    for file in ["knope.toml", "CHANGELOG.md", "Cargo.toml", "my-tool.tar.gz"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
+
+/// `SignRelease` can only sign a release which was created earlier in the workflow.
+#[test]
+fn sign_release_before_release() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/sign_release");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    copy(
+        source_path.join("misordered_knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_matches_path(source_path.join("misordered_output.txt"));
+}
@@ -0,0 +1,65 @@
+use std::fs::copy;
+use std::path::Path;
+
+use rstest::rstest;
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run only some of the steps in a workflow, selected by their `name` or `tags`.
+#[rstest]
+#[case(&["--skip", "publish"], "skip_tag_output.txt")]
+#[case(&["--skip", "build", "--skip", "npm"], "skip_names_output.txt")]
+#[case(&["--only", "npm"], "only_name_output.txt")]
+#[case(&["--only", "publish", "--skip", "crates-io"], "only_and_skip_output.txt")]
+fn select_steps(#[case] args: &[&str], #[case] output_file: &str) {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/skip_steps");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .args(args)
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join(output_file));
+}
+
+/// A step name which doesn't match anything is probably a typo, so nothing should run.
+#[test]
+fn unknown_step() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/skip_steps");
+
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(source_path.join("knope.toml"), temp_path.join("knope.toml")).unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--skip")
+        .arg("publsh")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stdout_eq("")
+        .stderr_eq_path(source_path.join("unknown_step_output.txt"));
+}
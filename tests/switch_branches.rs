@@ -0,0 +1,164 @@
+use std::fs::{copy, write};
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Switching branches with nothing changed in the working tree.
+#[test]
+fn clean_working_tree() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    setup(temp_path);
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("switch")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path("tests/switch_branches/output.txt");
+}
+
+/// Changes to tracked files would be thrown away by switching branches, so they stop the step.
+#[test]
+fn uncommitted_changes() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    setup(temp_path);
+    write(temp_path.join("README.md"), "Changed\n").unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("switch")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stdout_eq_path("tests/switch_branches/output.txt")
+        .stderr_eq_path("tests/switch_branches/uncommitted_changes_output.txt");
+}
+
+/// A file tracked with Git LFS looks modified to libgit2 once it's checked out, because libgit2
+/// can't run the LFS filter. That isn't an uncommitted change as long as `git status` agrees.
+#[test]
+fn checked_out_lfs_file() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    setup(temp_path);
+    // Rewrite the checked out file so its modification time no longer matches the index, and its
+    // contents (not the pointer Git stored) are compared.
+    write(temp_path.join("asset.bin"), "contents\n").unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("switch")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path("tests/switch_branches/output.txt");
+}
+
+/// A real change to a file tracked with Git LFS is still an uncommitted change.
+#[test]
+fn changed_lfs_file() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    setup(temp_path);
+    write(temp_path.join("asset.bin"), "changed\n").unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("switch")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stdout_eq_path("tests/switch_branches/output.txt")
+        .stderr_eq_path("tests/switch_branches/uncommitted_changes_output.txt");
+}
+
+/// Switching to an existing branch which is protected has to be confirmed, which isn't possible
+/// in CI.
+#[test]
+fn protected_branch() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    setup(temp_path);
+    copy(
+        "tests/switch_branches/protected_knope.toml",
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("switch")
+        .env("CI", "true")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stdout_eq_path("tests/switch_branches/protected_output.txt")
+        .stderr_eq_path("tests/switch_branches/protected_error_output.txt");
+}
+
+/// Create a repo on a branch for issue 42, with a committed `README.md` and an `asset.bin` tracked
+/// by a stand-in for Git LFS: its filter stores `pointer` in place of `contents`, like LFS stores a
+/// pointer in place of the real file.
+fn setup(path: &Path) {
+    init(path);
+    git(
+        path,
+        &["config", "filter.lfs.clean", "sed s/contents/pointer/"],
+    );
+    git(
+        path,
+        &["config", "filter.lfs.smudge", "sed s/pointer/contents/"],
+    );
+    git(path, &["config", "filter.lfs.required", "true"]);
+    copy("tests/switch_branches/knope.toml", path.join("knope.toml")).unwrap();
+    write(path.join(".gitattributes"), "*.bin filter=lfs\n").unwrap();
+    write(path.join("asset.bin"), "contents\n").unwrap();
+    write(path.join("README.md"), "Readme\n").unwrap();
+    add_all(path);
+    commit(path, "Initial commit");
+    assert_eq!(git(path, &["show", "HEAD:asset.bin"]), "pointer\n");
+    git(path, &["switch", "--create", "42-a-test-issue"]);
+}
+
+/// Run `git` with `args` in `path`, returning its stdout.
+fn git(path: &Path, args: &[&str]) -> String {
+    let output = StdCommand::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
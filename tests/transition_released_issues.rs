@@ -0,0 +1,42 @@
+use std::fs::copy;
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Every issue referenced since the last release is transitioned once, and nothing from before it.
+#[test]
+fn transition_released_issues_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/transition_released_issues");
+
+    init(temp_path);
+    commit(temp_path, "feat: PROJ-1 Existing feature");
+    tag(temp_path, "v1.0.0");
+    commit(temp_path, "feat: New feature (#12)\n\nIssue: PROJ-2");
+    commit(
+        temp_path,
+        "fix: PROJ-3 A bug, also in PROJ-2 and owner/repo#4",
+    );
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("release")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+}
@@ -1,4 +1,4 @@
-use std::fs::copy;
+use std::fs::{copy, create_dir, write};
 use std::path::Path;
 
 use snapbox::cmd::{cargo_bin, Command};
@@ -69,3 +69,82 @@ fn validate_conflicting_packages() {
         .failure()
         .stderr_eq_path("tests/validate/multiple_package_formats.txt");
 }
+
+/// Running a workflow with steps in an order that can never work should fail before any step runs.
+#[test]
+fn misordered_steps() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/validate");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("misordered_steps.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("start")
+        .current_dir(temp_path)
+        .assert();
+    assert
+        .failure()
+        .stdout_eq("")
+        .stderr_eq_path("tests/validate/misordered_steps.txt");
+}
+
+/// A workflow with misordered steps shouldn't stop other workflows from running.
+#[test]
+fn misordered_steps_in_other_workflow() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/validate");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("misordered_steps.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("greet")
+        .current_dir(temp_path)
+        .assert();
+    assert.success().stdout_eq("Hello\n");
+}
+
+/// `--validate` should warn about packages which would create the same tags.
+#[test]
+fn tag_prefix_collision() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/validate");
+    init(temp_path);
+    commit(temp_path, "Initial commit");
+    copy(
+        source_path.join("tag_prefix_collision.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+    for package in ["first", "second"] {
+        create_dir(temp_path.join(package)).unwrap();
+        write(
+            temp_path.join(package).join("Cargo.toml"),
+            format!("[package]\nname = \"{package}\"\nversion = \"1.0.0\"\n"),
+        )
+        .unwrap();
+    }
+
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("--validate")
+        .current_dir(temp_path)
+        .assert();
+    assert
+        .success()
+        .stdout_eq_path("tests/validate/tag_prefix_collision.txt");
+}
@@ -0,0 +1,133 @@
+use std::fs::{copy, read_to_string, write};
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Run `VerifyTag` on an unsigned release tag, which must fail.
+#[test]
+fn unsigned_tag() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/verify_tag");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    tag(temp_path, "v1.0.0");
+
+    for file in ["knope.toml", "Cargo.toml", "allowed_signers"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .success()
+        .stdout_eq_path(source_path.join("dry_run_output.txt"));
+    actual_assert
+        .failure()
+        .stderr_matches_path(source_path.join("unsigned_output.txt"));
+}
+
+/// `VerifyTag` has nothing to check if a package hasn't been released yet.
+#[test]
+fn no_release_tag() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/verify_tag");
+
+    init(temp_path);
+    commit(temp_path, "feat: Initial feature");
+
+    for file in ["knope.toml", "Cargo.toml", "allowed_signers"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    actual_assert.success().stdout_eq("");
+}
+
+/// Run `VerifyTag` on a tag signed by an SSH key in the allowed signers file.
+#[test]
+fn signed_tag() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/verify_tag");
+
+    init(temp_path);
+    commit(temp_path, "feat: Existing feature");
+    let status = StdCommand::new("ssh-keygen")
+        .args([
+            "-q",
+            "-t",
+            "ed25519",
+            "-N",
+            "",
+            "-C",
+            "",
+            "-f",
+            "signing_key",
+        ])
+        .current_dir(temp_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let public_key = read_to_string(temp_path.join("signing_key.pub")).unwrap();
+    write(
+        temp_path.join("allowed_signers"),
+        format!("test@knope.dev {public_key}"),
+    )
+    .unwrap();
+    let status = StdCommand::new("git")
+        .args([
+            "-c",
+            "gpg.format=ssh",
+            "-c",
+            "user.signingkey=signing_key",
+            "tag",
+            "-s",
+            "-m",
+            "v1.0.0",
+            "v1.0.0",
+        ])
+        .current_dir(temp_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    actual_assert.success().stdout_eq("");
+}
@@ -0,0 +1,81 @@
+use std::fs::{copy, create_dir_all};
+use std::path::Path;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+const FILES: [&str; 4] = [
+    "first/Cargo.toml",
+    "first/package.json",
+    "second/Cargo.toml",
+    "second/package.json",
+];
+
+/// Run `VerifyVersions` when the versioned files of a package don't agree, which must fail and
+/// list the version in each file of that package.
+#[test]
+fn inconsistent_versions() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/verify_versions");
+
+    init(temp_path);
+    for file in FILES.iter().chain(&["knope.toml"]) {
+        let destination = temp_path.join(file);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        copy(source_path.join(file), destination).unwrap();
+    }
+
+    // Act.
+    let dry_run_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+    let actual_assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    dry_run_assert
+        .failure()
+        .stderr_eq_path(source_path.join("inconsistent_output.txt"));
+    actual_assert
+        .failure()
+        .stderr_eq_path(source_path.join("inconsistent_output.txt"));
+}
+
+/// `VerifyVersions` succeeds without any output when every package's files agree.
+#[test]
+fn consistent_versions() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/verify_versions");
+
+    init(temp_path);
+    for file in FILES.iter().chain(&["knope.toml"]) {
+        let destination = temp_path.join(file);
+        create_dir_all(destination.parent().unwrap()).unwrap();
+        copy(source_path.join(file), destination).unwrap();
+    }
+    copy(
+        source_path.join("first/fixed_package.json"),
+        temp_path.join("first/package.json"),
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("verify")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert.success().stdout_eq("").stderr_eq("");
+}
@@ -0,0 +1,127 @@
+use std::fs::copy;
+use std::path::Path;
+use std::process::Command as StdCommand;
+
+use snapbox::cmd::{cargo_bin, Command};
+
+use git_repo_helpers::*;
+
+mod git_repo_helpers;
+
+/// Yank a release which has a GitHub release, without actually changing anything.
+#[test]
+fn github_dry_run() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/yank");
+
+    init(temp_path);
+    commit(temp_path, "chore: Release 1.2.3");
+    tag(temp_path, "v1.2.3");
+    copy(source_path.join("Cargo.toml"), temp_path.join("Cargo.toml")).unwrap();
+    copy(
+        source_path.join("github/knope.toml"),
+        temp_path.join("knope.toml"),
+    )
+    .unwrap();
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("yank")
+        .arg("1.2.3")
+        .arg("--draft")
+        .arg("--revert")
+        .arg("--dry-run")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .success()
+        .stdout_eq_path(source_path.join("github/dry_run_output.txt"));
+}
+
+/// Yank a release by deleting its tag from the repo and the remote, then reverting its commit.
+#[test]
+fn delete_tag_and_revert() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let remote_dir = tempfile::tempdir().unwrap();
+    let remote_path = remote_dir.path();
+    let source_path = Path::new("tests/yank");
+
+    git(remote_path, &["init", "--bare"]);
+    init(temp_path);
+    add_remote(temp_path, &remote_path.display().to_string());
+    commit(temp_path, "feat: Existing feature");
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+    add_all(temp_path);
+    commit(temp_path, "chore: Release 1.2.3");
+    tag(temp_path, "v1.2.3");
+    git(temp_path, &["push", "origin", "HEAD", "v1.2.3"]);
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("yank")
+        .arg("1.2.3")
+        .arg("--revert")
+        .arg("--yes")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert.success();
+    assert_eq!(git(temp_path, &["tag", "--list"]), "");
+    assert_eq!(git(remote_path, &["tag", "--list"]), "");
+    assert_eq!(
+        git(temp_path, &["log", "-1", "--format=%s"]),
+        "Revert \"chore: Release 1.2.3\"\n"
+    );
+}
+
+/// A tag which doesn't exist can't be yanked.
+#[test]
+fn missing_tag() {
+    // Arrange.
+    let temp_dir = tempfile::tempdir().unwrap();
+    let temp_path = temp_dir.path();
+    let source_path = Path::new("tests/yank");
+
+    init(temp_path);
+    commit(temp_path, "chore: Release 1.2.3");
+    for file in ["knope.toml", "Cargo.toml"] {
+        copy(source_path.join(file), temp_path.join(file)).unwrap();
+    }
+
+    // Act.
+    let assert = Command::new(cargo_bin!("knope"))
+        .arg("yank")
+        .arg("1.2.3")
+        .arg("--yes")
+        .current_dir(temp_path)
+        .assert();
+
+    // Assert.
+    assert
+        .failure()
+        .stderr_matches_path(source_path.join("missing_tag_output.txt"));
+}
+
+/// Run `git` with `args` in `path`, returning its stdout.
+fn git(path: &Path, args: &[&str]) -> String {
+    let output = StdCommand::new("git")
+        .args(args)
+        .current_dir(path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).to_string()
+}